@@ -53,7 +53,7 @@ async fn main() {
     //                     0 => {
     //                         app.offer_service(ServiceID::from(0x1234), InstanceID::from(1), InterfaceVersion::make_version(2, 1));
     //                         app.offer_event_seg(ServiceID::from(0x1234), InstanceID::from(1), MethodID::from(0x8001),
-    //                             EventGroupID::from(2), true, None, false, true);
+    //                             EventGroupID::from(2), EventKind::Field, None, false, true, Reliability::Unknown);
     //                         println!("0x8001: 0");
     //                         app.notify(ServiceID::from(0x1234), InstanceID::from(1), MethodID::from(0x8001),
     //                             &Bytes::from("0"), false);
@@ -15,9 +15,9 @@
 // }
 //
 // async fn provider() {
-//     let mut app = vsomeiprs::VSomeipApplication::create("sc1p").expect("Cannot create sc1p");
+//     let app = vsomeiprs::VSomeipApplication::create_detached("sc1p").expect("Cannot create sc1p");
 //     let (sender, mut recv) = tokio::sync::mpsc::unbounded_channel();
-//     app.setup_channel_callbacks(sender);
+//     app.attach_sink(sender).expect("sink already attached");
 //
 //     loop {
 //         tokio::select! {
@@ -59,9 +59,9 @@
 //     let mut counter = 0u32;
 //     let mut svc_available = false;
 //     let mut interval = time::interval(Duration::from_millis(100));
-//     let mut app = vsomeiprs::VSomeipApplication::create("sc1c").expect("Cannot create sc1c");
+//     let app = vsomeiprs::VSomeipApplication::create_detached("sc1c").expect("Cannot create sc1c");
 //     let (sender, mut recv) = tokio::sync::mpsc::unbounded_channel();
-//     app.setup_channel_callbacks(sender);
+//     app.attach_sink(sender).expect("sink already attached");
 //
 //     loop {
 //         tokio::select! {
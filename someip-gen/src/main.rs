@@ -0,0 +1,142 @@
+// SPDX-License-Identifier: MPL-2.0
+//
+// Copyright (C) 2024 Alexander Seifarth
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Generates a Rust module of typed SOME/IP proxies and skeletons from ARXML, Franca IDL and
+//! JSON/YAML descriptions for projects not using a build script, e.g.
+//! ```text
+//! someip-gen vehicle.fidl vehicle.fdepl --module vehicle --proxies --string-length 16 -o src/vehicle.rs
+//! ```
+
+use std::fs;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use clap::{Parser, ValueEnum};
+use vsomeiprs_codegen::{generate_rust_with, parse_arxml_documents, parse_franca, parse_json, parse_yaml,
+                        CodegenError, GenerateOptions, Model, ProfileOptions};
+
+#[derive(Parser)]
+#[command(name = "someip-gen", version, about = "Generates typed SOME/IP proxies and skeletons for vsomeiprs")]
+struct Args {
+    /// Descriptions by extension: .arxml, .fidl with the .fdepl deployments, .json, .yaml or .yml.
+    /// References are resolved in all files of the same format.
+    #[arg(required = true)]
+    inputs: Vec<PathBuf>,
+    /// Writes the code to the file instead of stdout.
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+    /// Wraps the code into `pub mod MODULE`.
+    #[arg(long)]
+    module: Option<String>,
+    /// Generates the proxies, with --skeletons both (the default).
+    #[arg(long)]
+    proxies: bool,
+    /// Generates the service traits and skeletons, with --proxies both (the default).
+    #[arg(long)]
+    skeletons: bool,
+    /// Length field of strings in bits (0, 8, 16, 32), 32 by default.
+    #[arg(long, value_name = "BITS")]
+    string_length: Option<u8>,
+    /// Length field of dynamic arrays in bits (0, 8, 16, 32), 32 by default.
+    #[arg(long, value_name = "BITS")]
+    array_length: Option<u8>,
+    /// Length field before structs in bits (0, 8, 16, 32), 0 by default.
+    #[arg(long, value_name = "BITS")]
+    struct_length: Option<u8>,
+    /// Alignment of struct members in bytes, 1 by default.
+    #[arg(long, value_name = "BYTES")]
+    alignment: Option<usize>,
+    #[arg(long, value_enum)]
+    byte_order: Option<ByteOrder>,
+}
+
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+enum ByteOrder {
+    Big,
+    Little,
+}
+
+impl Args {
+    fn options(&self) -> GenerateOptions {
+        let profile = (self.string_length.is_some() || self.array_length.is_some() || self.struct_length.is_some()
+            || self.alignment.is_some() || self.byte_order.is_some())
+            .then(|| {
+                let default = ProfileOptions::default();
+                ProfileOptions {
+                    string_length: self.string_length.unwrap_or(default.string_length),
+                    array_length: self.array_length.unwrap_or(default.array_length),
+                    struct_length: self.struct_length.unwrap_or(default.struct_length),
+                    alignment: self.alignment.unwrap_or(default.alignment),
+                    little_endian: self.byte_order == Some(ByteOrder::Little),
+                }
+            });
+        let both = !self.proxies && !self.skeletons;
+        GenerateOptions {
+            module: self.module.clone(),
+            proxies: self.proxies || both,
+            skeletons: self.skeletons || both,
+            profile,
+        }
+    }
+
+    /// Reads the inputs grouped by format into one model.
+    fn model(&self) -> Result<Model, CodegenError> {
+        let mut texts: [Vec<String>; 5] = Default::default();
+        for input in &self.inputs {
+            let format = match input.extension().and_then(|extension| extension.to_str()) {
+                Some("arxml") => 0,
+                Some("fidl") => 1,
+                Some("fdepl") => 2,
+                Some("json") => 3,
+                Some("yaml" | "yml") => 4,
+                _ => return Err(CodegenError::InvalidValue { element: "input", value: input.display().to_string() }),
+            };
+            let text = fs::read_to_string(input)
+                .map_err(|e| CodegenError::Io(format!("{}: {}", input.display(), e)))?;
+            texts[format].push(text);
+        }
+        let [arxml, fidl, fdepl, json, yaml] = texts.each_ref().map(|texts| texts.iter().map(String::as_str)
+            .collect::<Vec<_>>());
+        let mut models = vec![parse_arxml_documents(&arxml)?, parse_franca(&fidl, &fdepl)?];
+        for text in json {
+            models.push(parse_json(text)?);
+        }
+        for text in yaml {
+            models.push(parse_yaml(text)?);
+        }
+        let mut model = Model::default();
+        for part in models {
+            model.interfaces.extend(part.interfaces);
+            model.data_types.extend(part.data_types);
+        }
+        Ok(model)
+    }
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    let code = match args.model().and_then(|model| generate_rust_with(&model, &args.options())) {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("someip-gen: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    let written = match &args.output {
+        Some(output) => fs::write(output, code).map_err(|e| format!("{}: {}", output.display(), e)),
+        None => {
+            print!("{}", code);
+            Ok(())
+        }
+    };
+    match written {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("someip-gen: cannot write {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
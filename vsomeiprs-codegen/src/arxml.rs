@@ -0,0 +1,315 @@
+// SPDX-License-Identifier: MPL-2.0
+//
+// Copyright (C) 2024 Alexander Seifarth
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::collections::HashMap;
+use roxmltree::{Document, Node};
+use super::{Argument, CodegenError, DataType, DataTypeKind, Enumerator, Event, EventGroup, Field, Member, Method,
+            Model, ServiceInterface, TypeRef};
+
+/// Reads the service interfaces with a SOME/IP deployment and all C++ implementation data types
+/// of an ARXML document.
+pub fn parse_arxml(text: &str) -> Result<Model, CodegenError> {
+    parse_arxml_documents(&[text])
+}
+
+/// Reads several ARXML documents into one model, references may point into other documents,
+/// e.g. from a deployment to a service interface.
+pub fn parse_arxml_documents(texts: &[&str]) -> Result<Model, CodegenError> {
+    let mut model = Model::default();
+    for document in parse_each(texts)? {
+        model.interfaces.extend(document.interfaces);
+        model.data_types.extend(document.data_types);
+    }
+    Ok(model)
+}
+
+/// Reads a model per ARXML document with the elements declared in it, references are resolved
+/// in all documents.
+pub(crate) fn parse_each(texts: &[&str]) -> Result<Vec<Model>, CodegenError> {
+    let documents = texts.iter()
+        .map(|text| Document::parse(text).map_err(|e| CodegenError::Xml(e.to_string())))
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut index = Index { paths: HashMap::new() };
+    for document in &documents {
+        index.add(document.root_element(), "");
+    }
+
+    let mut models = Vec::new();
+    for document in &documents {
+        let mut model = Model::default();
+        for node in document.descendants() {
+            if is(node, "STD-CPP-IMPLEMENTATION-DATA-TYPE") {
+                if let Some(data_type) = index.data_type(node)? {
+                    model.data_types.push(data_type);
+                }
+            } else if is(node, "SOMEIP-SERVICE-INTERFACE-DEPLOYMENT") {
+                model.interfaces.push(index.interface(node)?);
+            }
+        }
+        models.push(model);
+    }
+    Ok(models)
+}
+
+/// Elements with a short name by their absolute path, e.g. `/vehicle/types/Speed`.
+struct Index<'a, 'input> {
+    paths: HashMap<String, Node<'a, 'input>>,
+}
+
+impl<'a, 'input> Index<'a, 'input> {
+    fn add(&mut self, node: Node<'a, 'input>, path: &str) {
+        let path = match short_name(node) {
+            Some(name) => {
+                let path = format!("{}/{}", path, name);
+                self.paths.insert(path.clone(), node);
+                path
+            }
+            None => path.to_string(),
+        };
+        for child in node.children().filter(Node::is_element) {
+            self.add(child, &path);
+        }
+    }
+
+    /// Returns the element referenced by the child element `reference` of the node.
+    fn resolve(&self, node: Node<'a, 'input>, reference: &'static str) -> Result<Node<'a, 'input>, CodegenError> {
+        let path = text(node, reference).ok_or_else(|| missing(reference, node))?;
+        self.paths.get(path).copied().ok_or_else(|| CodegenError::UnresolvedReference(path.to_string()))
+    }
+
+    /// Returns the type referenced by the first type reference below the node.
+    fn type_ref(&self, node: Node<'a, 'input>) -> Result<TypeRef, CodegenError> {
+        let reference = node.descendants()
+            .find(|child| ["TYPE-TREF", "TYPE-REFERENCE-REF", "TEMPLATE-TYPE-REF"].iter().any(|tag| is(*child, tag)))
+            .ok_or_else(|| missing("TYPE-TREF", node))?;
+        let path = reference.text().unwrap_or_default().trim();
+        match self.paths.get(path) {
+            Some(data_type) if is_primitive(*data_type) => primitive(*data_type),
+            Some(data_type) => Ok(TypeRef::Named(name(*data_type)?.to_string())),
+            // standard types are often not part of the description
+            None => path.rsplit('/').next().and_then(TypeRef::primitive)
+                .ok_or_else(|| CodegenError::UnresolvedReference(path.to_string())),
+        }
+    }
+
+    /// Returns the data type or `None` for a primitive type.
+    fn data_type(&self, node: Node<'a, 'input>) -> Result<Option<DataType>, CodegenError> {
+        if is_primitive(node) {
+            return primitive(node).map(|_| None);
+        }
+        let name = name(node)?.to_string();
+        let category = text(node, "CATEGORY").unwrap_or_default();
+        let kind = match category {
+            "STRUCTURE" => {
+                let members = elements(node, "SUB-ELEMENTS")
+                    .map(|member| {
+                        Ok(Member { name: self::name(member)?.to_string(), type_ref: self.type_ref(member)? })
+                    })
+                    .collect::<Result<_, CodegenError>>()?;
+                DataTypeKind::Struct(members)
+            }
+            "VECTOR" => DataTypeKind::Alias(TypeRef::Vector(Box::new(self.type_ref(node)?))),
+            "ARRAY" => {
+                let size = number(node, "ARRAY-SIZE")?;
+                DataTypeKind::Alias(TypeRef::Array(Box::new(self.type_ref(node)?), size))
+            }
+            "STRING" => DataTypeKind::Alias(TypeRef::String),
+            "TYPE_REFERENCE" => DataTypeKind::Alias(self.type_ref(node)?),
+            "VALUE" if child(node, "COMPU-METHOD-REF").is_some() => {
+                let base = self.type_ref(node)?;
+                if !matches!(base, TypeRef::U8 | TypeRef::U16 | TypeRef::U32 | TypeRef::U64 | TypeRef::I8
+                    | TypeRef::I16 | TypeRef::I32 | TypeRef::I64) {
+                    return Err(CodegenError::UnsupportedType { name, category: category.to_string() });
+                }
+                let compu_method = self.resolve(node, "COMPU-METHOD-REF")?;
+                let enumerators = compu_method.descendants().filter(|scale| is(*scale, "COMPU-SCALE"))
+                    .map(|scale| {
+                        let name = scale.descendants().find(|vt| is(*vt, "VT") || is(*vt, "SYMBOL"))
+                            .and_then(|vt| vt.text())
+                            .ok_or_else(|| missing("VT", scale))?;
+                        Ok(Enumerator { name: name.trim().to_string(), value: number(scale, "LOWER-LIMIT")? })
+                    })
+                    .collect::<Result<_, CodegenError>>()?;
+                DataTypeKind::Enum { base, enumerators }
+            }
+            "VALUE" => DataTypeKind::Alias(self.type_ref(node)?),
+            _ => return Err(CodegenError::UnsupportedType { name, category: category.to_string() }),
+        };
+        Ok(Some(DataType { name, kind }))
+    }
+
+    /// Returns the service interface of the deployment with the IDs of the deployment.
+    fn interface(&self, deployment: Node<'a, 'input>) -> Result<ServiceInterface, CodegenError> {
+        let interface = self.resolve(deployment, "SERVICE-INTERFACE-TREF")?;
+        let version = child(deployment, "SERVICE-INTERFACE-VERSION")
+            .ok_or_else(|| missing("SERVICE-INTERFACE-VERSION", deployment))?;
+
+        let mut methods = Vec::new();
+        for operation in elements(interface, "METHODS") {
+            let method_deployment = self.deployment(deployment, "METHOD-DEPLOYMENTS", "METHOD-REF", operation)?;
+            let mut in_args = Vec::new();
+            let mut out_args = Vec::new();
+            for argument in elements(operation, "ARGUMENTS") {
+                let arg = Argument { name: name(argument)?.to_string(), type_ref: self.type_ref(argument)? };
+                match text(argument, "DIRECTION").unwrap_or("IN") {
+                    "IN" => in_args.push(arg),
+                    "OUT" => out_args.push(arg),
+                    "INOUT" => {
+                        in_args.push(arg.clone());
+                        out_args.push(arg);
+                    }
+                    direction => return Err(CodegenError::InvalidValue { element: "DIRECTION",
+                        value: direction.to_string() }),
+                }
+            }
+            methods.push(Method {
+                name: name(operation)?.to_string(),
+                method_id: number(method_deployment, "METHOD-ID")?,
+                fire_and_forget: text(operation, "FIRE-AND-FORGET") == Some("true"),
+                reliable: reliable(method_deployment),
+                in_args,
+                out_args,
+            });
+        }
+
+        let mut events = Vec::new();
+        for event in elements(interface, "EVENTS") {
+            let event_deployment = self.deployment(deployment, "EVENT-DEPLOYMENTS", "EVENT-REF", event)?;
+            events.push(Event {
+                name: name(event)?.to_string(),
+                event_id: number(event_deployment, "EVENT-ID")?,
+                reliable: reliable(event_deployment),
+                type_ref: self.type_ref(event)?,
+            });
+        }
+
+        let mut fields = Vec::new();
+        for field in elements(interface, "FIELDS") {
+            let field_deployment = self.deployment(deployment, "FIELD-DEPLOYMENTS", "FIELD-REF", field)?;
+            let id = |element, id| child(field_deployment, element).map(|node| number(node, id)).transpose();
+            fields.push(Field {
+                name: name(field)?.to_string(),
+                type_ref: self.type_ref(field)?,
+                getter_id: id("GET", "METHOD-ID")?,
+                setter_id: id("SET", "METHOD-ID")?,
+                notifier_id: id("NOTIFIER", "EVENT-ID")?,
+                reliable: reliable(field_deployment),
+            });
+        }
+
+        let mut event_groups = Vec::new();
+        for group in elements(deployment, "EVENT-GROUPS") {
+            let events = child(group, "EVENT-REFS").into_iter()
+                .flat_map(|refs| refs.children().filter(Node::is_element))
+                .map(|reference| self.event_name(reference))
+                .collect::<Result<_, _>>()?;
+            event_groups.push(EventGroup {
+                name: name(group)?.to_string(),
+                event_group_id: number(group, "EVENT-GROUP-ID")?,
+                events,
+            });
+        }
+
+        Ok(ServiceInterface {
+            name: name(interface)?.to_string(),
+            service_id: number(deployment, "SERVICE-INTERFACE-ID")?,
+            major_version: number(version, "MAJOR-VERSION")?,
+            minor_version: number(version, "MINOR-VERSION")?,
+            methods,
+            events,
+            fields,
+            event_groups,
+        })
+    }
+
+    /// Returns the deployment in the list of the service deployment whose `reference` resolves to
+    /// the element of the service interface.
+    fn deployment(&self, deployment: Node<'a, 'input>, list: &'static str, reference: &'static str,
+                  element: Node<'a, 'input>) -> Result<Node<'a, 'input>, CodegenError>
+    {
+        elements(deployment, list)
+            .find(|item| self.resolve(*item, reference).is_ok_and(|target| target == element))
+            .ok_or_else(|| missing(list, element))
+    }
+
+    /// Returns the name of the event or field referenced by an event group, given either by its
+    /// event deployment, the notifier of its field deployment or directly.
+    fn event_name(&self, reference: Node<'a, 'input>) -> Result<String, CodegenError> {
+        let path = reference.text().unwrap_or_default().trim();
+        let target = self.paths.get(path).copied().ok_or_else(|| CodegenError::UnresolvedReference(path.to_string()))?;
+        let target = if is(target, "SOMEIP-EVENT-DEPLOYMENT") {
+            self.resolve(target, "EVENT-REF")?
+        } else if is(target, "NOTIFIER") {
+            let field_deployment = target.parent_element().ok_or_else(|| missing("FIELD-REF", target))?;
+            self.resolve(field_deployment, "FIELD-REF")?
+        } else {
+            target
+        };
+        Ok(name(target)?.to_string())
+    }
+}
+
+fn is(node: Node, tag: &str) -> bool {
+    node.is_element() && node.tag_name().name() == tag
+}
+
+fn child<'a, 'input>(node: Node<'a, 'input>, tag: &str) -> Option<Node<'a, 'input>> {
+    node.children().find(|child| is(*child, tag))
+}
+
+/// Returns the elements of the list element `tag` of the node.
+fn elements<'a, 'input>(node: Node<'a, 'input>, tag: &str) -> impl Iterator<Item = Node<'a, 'input>> {
+    child(node, tag).into_iter().flat_map(|list| list.children().filter(Node::is_element))
+}
+
+fn text<'a>(node: Node<'a, '_>, tag: &str) -> Option<&'a str> {
+    child(node, tag).and_then(|child| child.text()).map(str::trim)
+}
+
+fn short_name<'a>(node: Node<'a, '_>) -> Option<&'a str> {
+    text(node, "SHORT-NAME")
+}
+
+fn name<'a>(node: Node<'a, '_>) -> Result<&'a str, CodegenError> {
+    short_name(node).ok_or_else(|| missing("SHORT-NAME", node))
+}
+
+/// Returns the decimal or hexadecimal (`0x`) number of the child element.
+fn number<T: TryFrom<u64>>(node: Node, tag: &'static str) -> Result<T, CodegenError> {
+    let value = text(node, tag).ok_or_else(|| missing(tag, node))?;
+    let parsed = match value.strip_prefix("0x").or_else(|| value.strip_prefix("0X")) {
+        Some(hex) => u64::from_str_radix(hex, 16),
+        None => value.parse(),
+    };
+    parsed.ok().and_then(|value| T::try_from(value).ok())
+        .ok_or_else(|| CodegenError::InvalidValue { element: tag, value: value.to_string() })
+}
+
+fn reliable(deployment: Node) -> bool {
+    deployment.descendants().any(|node| is(node, "TRANSPORT-PROTOCOL") && node.text().map(str::trim) == Some("TCP"))
+}
+
+/// Returns whether the data type is a primitive type, i.e. a value without base type or the
+/// standard string.
+fn is_primitive(node: Node) -> bool {
+    match text(node, "CATEGORY") {
+        Some("VALUE") => child(node, "COMPU-METHOD-REF").is_none()
+            && !node.descendants().any(|child| is(child, "TYPE-REFERENCE-REF")),
+        Some("STRING") => short_name(node).and_then(TypeRef::primitive) == Some(TypeRef::String),
+        _ => false,
+    }
+}
+
+fn primitive(node: Node) -> Result<TypeRef, CodegenError> {
+    let name = name(node)?;
+    TypeRef::primitive(name)
+        .ok_or_else(|| CodegenError::UnsupportedType { name: name.to_string(), category: "VALUE".to_string() })
+}
+
+fn missing(element: &'static str, parent: Node) -> CodegenError {
+    CodegenError::Missing { element, parent: short_name(parent).unwrap_or(parent.tag_name().name()).to_string() }
+}
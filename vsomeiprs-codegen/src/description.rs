@@ -0,0 +1,314 @@
+// SPDX-License-Identifier: MPL-2.0
+//
+// Copyright (C) 2024 Alexander Seifarth
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use serde::Deserialize;
+use super::{Argument, CodegenError, DataType, DataTypeKind, Enumerator, Event, EventGroup, Field, Member, Method,
+            Model, ServiceInterface, TypeRef};
+
+/// Reads the service interfaces and data types of a JSON description, see the crate
+/// documentation for the schema.
+pub fn parse_json(text: &str) -> Result<Model, CodegenError> {
+    let description: Description = serde_json::from_str(text).map_err(|e| CodegenError::Json(e.to_string()))?;
+    description.into_model()
+}
+
+/// Reads the service interfaces and data types of a YAML description, see the crate
+/// documentation for the schema.
+pub fn parse_yaml(text: &str) -> Result<Model, CodegenError> {
+    let description: Description = serde_yaml::from_str(text).map_err(|e| CodegenError::Yaml(e.to_string()))?;
+    description.into_model()
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Description {
+    #[serde(default)]
+    types: Vec<TypeDescription>,
+    #[serde(default)]
+    interfaces: Vec<InterfaceDescription>,
+}
+
+/// Data type, exactly one of `type` (alias), `struct` and `enum` is given.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TypeDescription {
+    name: String,
+    #[serde(rename = "type")]
+    alias: Option<String>,
+    #[serde(rename = "struct")]
+    members: Option<Vec<Typed>>,
+    #[serde(rename = "enum")]
+    base: Option<String>,
+    #[serde(default)]
+    enumerators: Vec<EnumeratorDescription>,
+}
+
+/// Struct member or method argument.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Typed {
+    name: String,
+    #[serde(rename = "type")]
+    type_name: String,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct EnumeratorDescription {
+    name: String,
+    value: Number,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct InterfaceDescription {
+    name: String,
+    service_id: Number,
+    /// Major and minor version.
+    version: (u8, u32),
+    #[serde(default)]
+    methods: Vec<MethodDescription>,
+    #[serde(default)]
+    events: Vec<EventDescription>,
+    #[serde(default)]
+    fields: Vec<FieldDescription>,
+    #[serde(default)]
+    event_groups: Vec<EventGroupDescription>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct MethodDescription {
+    name: String,
+    id: Number,
+    #[serde(default, rename = "in")]
+    in_args: Vec<Typed>,
+    #[serde(default, rename = "out")]
+    out_args: Vec<Typed>,
+    #[serde(default)]
+    fire_and_forget: bool,
+    #[serde(default)]
+    reliable: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct EventDescription {
+    name: String,
+    id: Number,
+    #[serde(rename = "type")]
+    type_name: String,
+    #[serde(default)]
+    reliable: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FieldDescription {
+    name: String,
+    #[serde(rename = "type")]
+    type_name: String,
+    getter: Option<Number>,
+    setter: Option<Number>,
+    notifier: Option<Number>,
+    #[serde(default)]
+    reliable: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct EventGroupDescription {
+    name: String,
+    id: Number,
+    events: Vec<String>,
+}
+
+/// ID or value, a number or a string with a decimal or `0x` prefixed hexadecimal number.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Number {
+    Integer(u64),
+    Text(String),
+}
+
+impl Number {
+    fn value<T: TryFrom<u64>>(&self, element: &'static str) -> Result<T, CodegenError> {
+        let value = match self {
+            Number::Integer(value) => Some(*value),
+            Number::Text(text) => match text.trim().strip_prefix("0x") {
+                Some(hex) => u64::from_str_radix(hex, 16).ok(),
+                None => text.trim().parse().ok(),
+            },
+        };
+        value.and_then(|value| T::try_from(value).ok()).ok_or_else(|| {
+            let value = match self {
+                Number::Integer(value) => value.to_string(),
+                Number::Text(text) => text.clone(),
+            };
+            CodegenError::InvalidValue { element, value }
+        })
+    }
+}
+
+impl Description {
+    fn into_model(self) -> Result<Model, CodegenError> {
+        let names = self.types.iter().map(|data_type| data_type.name.as_str()).collect::<Vec<_>>();
+        let data_types = self.types.iter()
+            .map(|data_type| data_type.to_model(&names))
+            .collect::<Result<Vec<_>, _>>()?;
+        let interfaces = self.interfaces.iter()
+            .map(|interface| interface.to_model(&names))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Model { interfaces, data_types })
+    }
+}
+
+impl TypeDescription {
+    fn to_model(&self, names: &[&str]) -> Result<DataType, CodegenError> {
+        let kind = match (&self.alias, &self.members, &self.base) {
+            (Some(alias), None, None) => DataTypeKind::Alias(type_ref(alias, names)?),
+            (None, Some(members), None) => DataTypeKind::Struct(members.iter()
+                .map(|member| Ok(Member { name: member.name.clone(), type_ref: type_ref(&member.type_name, names)? }))
+                .collect::<Result<_, CodegenError>>()?),
+            (None, None, Some(base)) => {
+                let base = primitive(base).filter(is_integer)
+                    .ok_or_else(|| CodegenError::InvalidValue { element: "enum", value: base.clone() })?;
+                let enumerators = self.enumerators.iter()
+                    .map(|enumerator| Ok(Enumerator { name: enumerator.name.clone(),
+                                                      value: enumerator.value.value("enumerator value")? }))
+                    .collect::<Result<_, CodegenError>>()?;
+                DataTypeKind::Enum { base, enumerators }
+            }
+            _ => return Err(CodegenError::Missing { element: "type, struct or enum", parent: self.name.clone() }),
+        };
+        Ok(DataType { name: self.name.clone(), kind })
+    }
+}
+
+impl InterfaceDescription {
+    fn to_model(&self, names: &[&str]) -> Result<ServiceInterface, CodegenError> {
+        let args = |args: &[Typed]| args.iter()
+            .map(|arg| Ok(Argument { name: arg.name.clone(), type_ref: type_ref(&arg.type_name, names)? }))
+            .collect::<Result<Vec<_>, CodegenError>>();
+        let methods = self.methods.iter()
+            .map(|method| Ok(Method {
+                name: method.name.clone(),
+                method_id: method.id.value("method id")?,
+                fire_and_forget: method.fire_and_forget,
+                reliable: method.reliable,
+                in_args: args(&method.in_args)?,
+                out_args: args(&method.out_args)?,
+            }))
+            .collect::<Result<Vec<_>, CodegenError>>()?;
+        let events = self.events.iter()
+            .map(|event| Ok(Event {
+                name: event.name.clone(),
+                event_id: event.id.value("event id")?,
+                reliable: event.reliable,
+                type_ref: type_ref(&event.type_name, names)?,
+            }))
+            .collect::<Result<Vec<_>, CodegenError>>()?;
+        let optional_id = |id: &Option<Number>, element| id.as_ref().map(|id| id.value(element)).transpose();
+        let fields = self.fields.iter()
+            .map(|field| Ok(Field {
+                name: field.name.clone(),
+                type_ref: type_ref(&field.type_name, names)?,
+                getter_id: optional_id(&field.getter, "getter id")?,
+                setter_id: optional_id(&field.setter, "setter id")?,
+                notifier_id: optional_id(&field.notifier, "notifier id")?,
+                reliable: field.reliable,
+            }))
+            .collect::<Result<Vec<_>, CodegenError>>()?;
+        let notifiers = events.iter().map(|event| &event.name)
+            .chain(fields.iter().filter(|field| field.notifier_id.is_some()).map(|field| &field.name))
+            .collect::<Vec<_>>();
+        let event_groups = self.event_groups.iter()
+            .map(|group| {
+                if let Some(unknown) = group.events.iter().find(|event| !notifiers.contains(event)) {
+                    return Err(CodegenError::UnresolvedReference(format!("{}/{}", self.name, unknown)));
+                }
+                Ok(EventGroup { name: group.name.clone(), event_group_id: group.id.value("event group id")?,
+                                events: group.events.clone() })
+            })
+            .collect::<Result<Vec<_>, CodegenError>>()?;
+        Ok(ServiceInterface {
+            name: self.name.clone(),
+            service_id: self.service_id.value("service id")?,
+            major_version: self.version.0,
+            minor_version: self.version.1,
+            methods,
+            events,
+            fields,
+            event_groups,
+        })
+    }
+}
+
+/// Returns the type of a name in Rust syntax, e.g. `u16`, `Vec<Speed>` or `[u8; 4]`, AUTOSAR
+/// names of primitives like `uint16` are accepted as well.
+fn type_ref(name: &str, names: &[&str]) -> Result<TypeRef, CodegenError> {
+    let name = name.trim();
+    if let Some(item) = name.strip_prefix("Vec<").and_then(|rest| rest.strip_suffix('>')) {
+        return Ok(TypeRef::Vector(Box::new(type_ref(item, names)?)));
+    }
+    if let Some((item, len)) = name.strip_prefix('[').and_then(|rest| rest.strip_suffix(']'))
+        .and_then(|rest| rest.rsplit_once(';'))
+    {
+        let len = len.trim().parse()
+            .map_err(|_| CodegenError::InvalidValue { element: "array length", value: len.trim().to_string() })?;
+        return Ok(TypeRef::Array(Box::new(type_ref(item, names)?), len));
+    }
+    if let Some(primitive) = primitive(name) {
+        return Ok(primitive);
+    }
+    match names.contains(&name) {
+        true => Ok(TypeRef::Named(name.to_string())),
+        false => Err(CodegenError::UnresolvedReference(name.to_string())),
+    }
+}
+
+fn primitive(name: &str) -> Option<TypeRef> {
+    let type_ref = match name {
+        "u8" => TypeRef::U8,
+        "u16" => TypeRef::U16,
+        "u32" => TypeRef::U32,
+        "u64" => TypeRef::U64,
+        "i8" => TypeRef::I8,
+        "i16" => TypeRef::I16,
+        "i32" => TypeRef::I32,
+        "i64" => TypeRef::I64,
+        "f32" => TypeRef::F32,
+        "f64" => TypeRef::F64,
+        _ => return TypeRef::primitive(name),
+    };
+    Some(type_ref)
+}
+
+fn is_integer(type_ref: &TypeRef) -> bool {
+    matches!(type_ref, TypeRef::U8 | TypeRef::U16 | TypeRef::U32 | TypeRef::U64 | TypeRef::I8 | TypeRef::I16 |
+                       TypeRef::I32 | TypeRef::I64)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn type_ref_test() {
+        let names = ["Speed"];
+        assert_eq!(type_ref("u16", &names), Ok(TypeRef::U16));
+        assert_eq!(type_ref("uint16_t", &names), Ok(TypeRef::U16));
+        assert_eq!(type_ref("String", &names), Ok(TypeRef::String));
+        assert_eq!(type_ref("Vec<Vec<Speed>>", &names),
+                   Ok(TypeRef::Vector(Box::new(TypeRef::Vector(Box::new(TypeRef::Named("Speed".to_string())))))));
+        assert_eq!(type_ref("[u8; 4]", &names), Ok(TypeRef::Array(Box::new(TypeRef::U8), 4)));
+        assert_eq!(type_ref("Distance", &names), Err(CodegenError::UnresolvedReference("Distance".to_string())));
+        assert_eq!(Number::Text("0x8001".to_string()).value::<u16>("id"), Ok(0x8001));
+        assert_eq!(Number::Integer(0x10000).value::<u16>("id"),
+                   Err(CodegenError::InvalidValue { element: "id", value: "65536".to_string() }));
+    }
+}
@@ -0,0 +1,53 @@
+// SPDX-License-Identifier: MPL-2.0
+//
+// Copyright (C) 2024 Alexander Seifarth
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::fmt;
+
+/// Error reading a description or generating code from it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CodegenError {
+    /// The document is not well-formed XML.
+    Xml(String),
+    /// The JSON description is malformed or does not match the schema.
+    Json(String),
+    /// The YAML description is malformed or does not match the schema.
+    Yaml(String),
+    /// The Franca IDL or deployment has a syntax error, the message includes the line.
+    Franca(String),
+    /// A mandatory element is missing in the element with the short name.
+    Missing { element: &'static str, parent: String },
+    /// The element has a value that cannot be used, e.g. an ID out of range.
+    InvalidValue { element: &'static str, value: String },
+    /// A reference does not resolve to an element of the expected kind.
+    UnresolvedReference(String),
+    /// The data type has a category that cannot be mapped to Rust.
+    UnsupportedType { name: String, category: String },
+    /// The generated code is no valid Rust, e.g. due to a short name that is no identifier.
+    InvalidCode(String),
+    /// A description could not be read or the code could not be written.
+    Io(String),
+}
+
+impl fmt::Display for CodegenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CodegenError::Xml(e) => write!(f, "invalid XML: {}", e),
+            CodegenError::Json(e) => write!(f, "invalid JSON description: {}", e),
+            CodegenError::Yaml(e) => write!(f, "invalid YAML description: {}", e),
+            CodegenError::Franca(e) => write!(f, "invalid Franca document: {}", e),
+            CodegenError::Missing { element, parent } => write!(f, "missing {} in {}", element, parent),
+            CodegenError::InvalidValue { element, value } => write!(f, "invalid {} '{}'", element, value),
+            CodegenError::UnresolvedReference(path) => write!(f, "unresolved reference {}", path),
+            CodegenError::UnsupportedType { name, category } =>
+                write!(f, "data type {} has unsupported category {}", name, category),
+            CodegenError::InvalidCode(e) => write!(f, "generated code is invalid: {}", e),
+            CodegenError::Io(e) => write!(f, "I/O error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for CodegenError {}
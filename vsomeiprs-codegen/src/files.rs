@@ -0,0 +1,122 @@
+// SPDX-License-Identifier: MPL-2.0
+//
+// Copyright (C) 2024 Alexander Seifarth
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use super::{arxml, generate_rust, parse_json, parse_yaml, CodegenError};
+
+/// Generates the Rust code of the ARXML files matching the pattern into the directory, to be
+/// called from a build script with its `OUT_DIR`:
+/// ```rust,ignore
+/// fn main() {
+///     let out_dir = std::env::var("OUT_DIR").unwrap();
+///     vsomeiprs_codegen::generate("interfaces/*.arxml", out_dir).unwrap();
+/// }
+/// ```
+/// The file name of the pattern may contain the wildcards `*` and `?`. The code of `name.arxml`
+/// is written to `name.rs` and contains the data types and service interfaces declared in it,
+/// references are resolved in all matched ARXML files. JSON (`.json`) and YAML (`.yaml`, `.yml`)
+/// descriptions are read on their own. The crate includes it by
+/// `vsomeiprs::include_generated!("name")`, files referencing each other must be included into
+/// the same module. Files whose code did not change are not written again, so cargo does not
+/// rebuild the crate.
+///
+/// Prints `cargo:rerun-if-changed` for the matched files and their directory. Returns the paths
+/// of the generated files in the order of the file names.
+pub fn generate(pattern: impl AsRef<Path>, out_dir: impl AsRef<Path>) -> Result<Vec<PathBuf>, CodegenError> {
+    let inputs = expand(pattern.as_ref())?;
+    let texts = inputs.iter()
+        .map(|input| fs::read_to_string(input).map_err(|e| io_error(input, e)))
+        .collect::<Result<Vec<_>, _>>()?;
+    let extensions = inputs.iter()
+        .map(|input| input.extension().and_then(|extension| extension.to_str()).unwrap_or_default())
+        .collect::<Vec<_>>();
+    let arxml_texts = texts.iter().zip(&extensions)
+        .filter(|(_, extension)| !is_description(extension))
+        .map(|(text, _)| text.as_str())
+        .collect::<Vec<_>>();
+    let mut arxml_models = arxml::parse_each(&arxml_texts)?.into_iter();
+    let models = texts.iter().zip(&extensions)
+        .map(|(text, extension)| match *extension {
+            "json" => parse_json(text),
+            "yaml" | "yml" => parse_yaml(text),
+            _ => Ok(arxml_models.next().unwrap_or_default()),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut outputs = Vec::new();
+    for (input, model) in inputs.iter().zip(models) {
+        println!("cargo:rerun-if-changed={}", input.display());
+        let stem = input.file_stem().unwrap_or_default();
+        let output = out_dir.as_ref().join(stem).with_extension("rs");
+        let code = generate_rust(&model)?;
+        if fs::read_to_string(&output).ok().as_ref() != Some(&code) {
+            fs::write(&output, code).map_err(|e| io_error(&output, e))?;
+        }
+        outputs.push(output);
+    }
+    Ok(outputs)
+}
+
+/// Returns the files matching the pattern sorted by name. Only the file name may contain
+/// wildcards, the directory is watched for new files.
+fn expand(pattern: &Path) -> Result<Vec<PathBuf>, CodegenError> {
+    let file_pattern = pattern.file_name().and_then(|name| name.to_str())
+        .ok_or_else(|| CodegenError::InvalidValue { element: "pattern", value: pattern.display().to_string() })?;
+    if !file_pattern.contains(['*', '?']) {
+        return Ok(vec![pattern.to_path_buf()]);
+    }
+    let dir = match pattern.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    println!("cargo:rerun-if-changed={}", dir.display());
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir).map_err(|e| io_error(dir, e))? {
+        let path = entry.map_err(|e| io_error(dir, e))?.path();
+        let matched = path.file_name().and_then(|name| name.to_str())
+            .is_some_and(|name| matches(file_pattern.as_bytes(), name.as_bytes()));
+        if matched && path.is_file() {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+fn is_description(extension: &str) -> bool {
+    matches!(extension, "json" | "yaml" | "yml")
+}
+
+/// Returns whether the name matches the pattern with the wildcards `*` and `?`.
+fn matches(pattern: &[u8], name: &[u8]) -> bool {
+    match (pattern.first(), name.first()) {
+        (None, None) => true,
+        (Some(b'*'), _) => matches(&pattern[1..], name) || (!name.is_empty() && matches(pattern, &name[1..])),
+        (Some(b'?'), Some(_)) => matches(&pattern[1..], &name[1..]),
+        (Some(p), Some(n)) if p == n => matches(&pattern[1..], &name[1..]),
+        _ => false,
+    }
+}
+
+fn io_error(path: &Path, error: std::io::Error) -> CodegenError {
+    CodegenError::Io(format!("{}: {}", path.display(), error))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn matches_test() {
+        assert!(matches(b"*.arxml", b"vehicle.arxml"));
+        assert!(matches(b"v?hicle*", b"vehicle.arxml"));
+        assert!(matches(b"*", b""));
+        assert!(!matches(b"*.arxml", b"vehicle.xml"));
+        assert!(!matches(b"?", b""));
+    }
+}
@@ -0,0 +1,713 @@
+// SPDX-License-Identifier: MPL-2.0
+//
+// Copyright (C) 2024 Alexander Seifarth
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use super::{Argument, CodegenError, DataType, DataTypeKind, Enumerator, Event, EventGroup, Field, Member, Method,
+            Model, ServiceInterface, TypeRef};
+
+/// Reads the interfaces and types of Franca IDL documents (`.fidl`) with the SOME/IP IDs of the
+/// CommonAPI SOME/IP deployments (`.fdepl`) of the interfaces.
+///
+/// Type collections and the types of interfaces provide the data types: typedefs, arrays,
+/// enumerations (with the `EnumBackingType` of their deployment, `UInt8` by default) and structs.
+/// Interfaces without a deployment are skipped. Their methods, broadcasts with a single argument
+/// and attributes turn into methods, events and fields, whose IDs, event groups and transport
+/// are given by the deployment properties `SomeIpServiceID`, `SomeIpMethodID`, `SomeIpEventID`,
+/// `SomeIpEventGroups`, `SomeIpGetterID`, `SomeIpSetterID`, `SomeIpNotifierID`,
+/// `SomeIpNotifierEventGroups`, `SomeIpReliable` and `SomeIpAttributeReliable`. Event groups are
+/// named by their ID, e.g. `EventGroup1`.
+///
+/// References are resolved in all documents by the last segment of the type name, imports are
+/// not followed.
+pub fn parse_franca(fidl_texts: &[&str], fdepl_texts: &[&str]) -> Result<Model, CodegenError> {
+    let mut idl = Idl::default();
+    for text in fidl_texts {
+        Parser::new(text)?.idl(&mut idl)?;
+    }
+    let mut deployments = Vec::new();
+    for text in fdepl_texts {
+        deployments.extend(Parser::new(text)?.entries()?);
+    }
+    let defines = deployments.iter().filter_map(|entry| match entry {
+        Entry::Block { header, entries } if header.first().is_some_and(|word| word == "define") =>
+            Some((header, entries.as_slice())),
+        _ => None,
+    }).collect::<Vec<_>>();
+    let deployment = |kind: &str, name: &str| defines.iter()
+        .find(|(header, _)| header.windows(2).any(|pair| pair[0] == kind && last_segment(&pair[1]) == name))
+        .map(|(_, entries)| *entries);
+
+    let names = idl.types.iter().map(|idl_type| idl_type.name.clone()).collect::<Vec<_>>();
+    let check = |type_ref: &TypeRef| check_type(type_ref, &names);
+    let mut data_types = Vec::new();
+    for idl_type in &idl.types {
+        let entries = deployment(idl_type.scope.0, &idl_type.scope.1).unwrap_or_default();
+        data_types.push(idl_type.to_model(entries, &idl.types, check)?);
+    }
+    let mut interfaces = Vec::new();
+    for interface in &idl.interfaces {
+        if let Some(entries) = deployment("interface", &interface.name) {
+            interfaces.push(interface.to_model(entries, check)?);
+        }
+    }
+    Ok(Model { interfaces, data_types })
+}
+
+#[derive(Default)]
+struct Idl {
+    types: Vec<IdlType>,
+    interfaces: Vec<IdlInterface>,
+}
+
+struct IdlType {
+    name: String,
+    /// Kind (`typeCollection` or `interface`) and name of the enclosing element, the scope of
+    /// the type's deployment.
+    scope: (&'static str, String),
+    kind: IdlTypeKind,
+}
+
+enum IdlTypeKind {
+    Alias(TypeRef),
+    Enum(Vec<(String, Option<u64>)>),
+    Struct { base: Option<String>, members: Vec<Member> },
+}
+
+#[derive(Default)]
+struct IdlInterface {
+    name: String,
+    version: (u8, u32),
+    methods: Vec<IdlMethod>,
+    attributes: Vec<IdlAttribute>,
+    broadcasts: Vec<(String, Vec<Argument>)>,
+}
+
+struct IdlMethod {
+    name: String,
+    fire_and_forget: bool,
+    in_args: Vec<Argument>,
+    out_args: Vec<Argument>,
+}
+
+struct IdlAttribute {
+    name: String,
+    type_ref: TypeRef,
+    readonly: bool,
+    no_subscriptions: bool,
+}
+
+impl IdlType {
+    fn to_model(&self, deployment: &[Entry], types: &[IdlType], check: impl Fn(&TypeRef) -> Result<(), CodegenError>)
+        -> Result<DataType, CodegenError>
+    {
+        let kind = match &self.kind {
+            IdlTypeKind::Alias(type_ref) => {
+                check(type_ref)?;
+                DataTypeKind::Alias(type_ref.clone())
+            }
+            IdlTypeKind::Enum(values) => {
+                let backing_type = block(deployment, "enumeration", &self.name)
+                    .and_then(|entries| property(entries, "EnumBackingType"));
+                let base = match backing_type {
+                    Some(Value::Word(base)) => franca_primitive(base).filter(is_integer)
+                        .ok_or_else(|| CodegenError::InvalidValue { element: "EnumBackingType", value: base.clone() })?,
+                    _ => TypeRef::U8,
+                };
+                let mut next = 0;
+                let enumerators = values.iter().map(|(name, value)| {
+                    let value = value.unwrap_or(next);
+                    next = value + 1;
+                    Enumerator { name: name.clone(), value }
+                }).collect();
+                DataTypeKind::Enum { base, enumerators }
+            }
+            IdlTypeKind::Struct { .. } => DataTypeKind::Struct(self.members(types, 0)?),
+        };
+        Ok(DataType { name: self.name.clone(), kind })
+    }
+
+    /// Returns the members of a struct, those of its base first.
+    fn members(&self, types: &[IdlType], depth: usize) -> Result<Vec<Member>, CodegenError> {
+        let IdlTypeKind::Struct { base, members } = &self.kind else {
+            return Err(CodegenError::UnsupportedType { name: self.name.clone(), category: "base".to_string() });
+        };
+        let mut all = match base {
+            Some(base) if depth < types.len() => types.iter().find(|idl_type| idl_type.name == *base)
+                .ok_or_else(|| CodegenError::UnresolvedReference(base.clone()))?
+                .members(types, depth + 1)?,
+            Some(base) => return Err(CodegenError::InvalidValue { element: "extends", value: base.clone() }),
+            None => Vec::new(),
+        };
+        all.extend(members.iter().cloned());
+        Ok(all)
+    }
+}
+
+impl IdlInterface {
+    fn to_model(&self, deployment: &[Entry], check: impl Fn(&TypeRef) -> Result<(), CodegenError>)
+        -> Result<ServiceInterface, CodegenError>
+    {
+        let id = |entries: &[Entry], name: &'static str, parent: &str| property(entries, name)
+            .ok_or_else(|| CodegenError::Missing { element: name, parent: parent.to_string() })
+            .and_then(|value| value.id(name));
+        let optional_id = |entries: &[Entry], name: &'static str| property(entries, name).map(|value| value.id(name))
+            .transpose();
+        let flag = |entries: &[Entry], name| matches!(property(entries, name),
+                                                      Some(Value::Word(word)) if word == "true");
+        let mut groups: Vec<EventGroup> = Vec::new();
+        let mut add_to_groups = |entries: &[Entry], property_name: &'static str, member: &str| {
+            for value in list(entries, property_name) {
+                let event_group_id = value.id(property_name)?;
+                match groups.iter_mut().find(|group| group.event_group_id == event_group_id) {
+                    Some(group) => group.events.push(member.to_string()),
+                    None => groups.push(EventGroup { name: format!("EventGroup{}", event_group_id), event_group_id,
+                                                     events: vec![member.to_string()] }),
+                }
+            }
+            Ok::<_, CodegenError>(())
+        };
+
+        let mut methods = Vec::new();
+        for method in &self.methods {
+            let entries = block(deployment, "method", &method.name).unwrap_or_default();
+            for arg in method.in_args.iter().chain(&method.out_args) {
+                check(&arg.type_ref)?;
+            }
+            methods.push(Method {
+                name: method.name.clone(),
+                method_id: id(entries, "SomeIpMethodID", &method.name)?,
+                fire_and_forget: method.fire_and_forget,
+                reliable: flag(entries, "SomeIpReliable"),
+                in_args: method.in_args.clone(),
+                out_args: method.out_args.clone(),
+            });
+        }
+        let mut events = Vec::new();
+        for (name, args) in &self.broadcasts {
+            let [arg] = args.as_slice() else {
+                let category = "broadcast without exactly one argument".to_string();
+                return Err(CodegenError::UnsupportedType { name: name.clone(), category });
+            };
+            check(&arg.type_ref)?;
+            let entries = block(deployment, "broadcast", name).unwrap_or_default();
+            events.push(Event {
+                name: name.clone(),
+                event_id: id(entries, "SomeIpEventID", name)?,
+                reliable: flag(entries, "SomeIpReliable"),
+                type_ref: arg.type_ref.clone(),
+            });
+            add_to_groups(entries, "SomeIpEventGroups", name)?;
+        }
+        let mut fields = Vec::new();
+        for attribute in &self.attributes {
+            check(&attribute.type_ref)?;
+            let entries = block(deployment, "attribute", &attribute.name).unwrap_or_default();
+            let notifier_id = match attribute.no_subscriptions {
+                true => None,
+                false => optional_id(entries, "SomeIpNotifierID")?,
+            };
+            if notifier_id.is_some() {
+                add_to_groups(entries, "SomeIpNotifierEventGroups", &attribute.name)?;
+            }
+            fields.push(Field {
+                name: attribute.name.clone(),
+                type_ref: attribute.type_ref.clone(),
+                getter_id: optional_id(entries, "SomeIpGetterID")?,
+                setter_id: match attribute.readonly {
+                    true => None,
+                    false => optional_id(entries, "SomeIpSetterID")?,
+                },
+                notifier_id,
+                reliable: flag(entries, "SomeIpAttributeReliable") || flag(entries, "SomeIpGetterReliable"),
+            });
+        }
+        Ok(ServiceInterface {
+            name: self.name.clone(),
+            service_id: id(deployment, "SomeIpServiceID", &self.name)?,
+            major_version: self.version.0,
+            minor_version: self.version.1,
+            methods,
+            events,
+            fields,
+            event_groups: groups,
+        })
+    }
+}
+
+/// Property or block of a deployment, e.g. `SomeIpMethodID = 1` or `method set { .. }`.
+enum Entry {
+    Property(String, Value),
+    Block { header: Vec<String>, entries: Vec<Entry> },
+}
+
+enum Value {
+    Number(u64),
+    Word(String),
+    Text,
+    List(Vec<Value>),
+}
+
+impl Value {
+    fn id<T: TryFrom<u64>>(&self, element: &'static str) -> Result<T, CodegenError> {
+        match self {
+            Value::Number(value) => T::try_from(*value).ok(),
+            _ => None,
+        }.ok_or_else(|| CodegenError::InvalidValue { element, value: self.to_string() })
+    }
+}
+
+impl std::fmt::Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::Number(value) => write!(f, "{}", value),
+            Value::Word(word) => write!(f, "{}", word),
+            Value::Text => write!(f, "\"..\""),
+            Value::List(values) => write!(f, "{{ {} }}", values.iter().map(Value::to_string)
+                .collect::<Vec<_>>().join(", ")),
+        }
+    }
+}
+
+fn property<'a>(entries: &'a [Entry], name: &str) -> Option<&'a Value> {
+    entries.iter().find_map(|entry| match entry {
+        Entry::Property(property, value) if property == name => Some(value),
+        _ => None,
+    })
+}
+
+/// Returns the values of a list property, a single value as a list of it.
+fn list<'a>(entries: &'a [Entry], name: &str) -> &'a [Value] {
+    match property(entries, name) {
+        Some(Value::List(values)) => values,
+        Some(value) => std::slice::from_ref(value),
+        None => &[],
+    }
+}
+
+fn block<'a>(entries: &'a [Entry], kind: &str, name: &str) -> Option<&'a [Entry]> {
+    entries.iter().find_map(|entry| match entry {
+        Entry::Block { header, entries } if header.len() == 2 && header[0] == kind
+            && last_segment(&header[1]) == name => Some(entries.as_slice()),
+        _ => None,
+    })
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    Number(u64),
+    Text,
+    Symbol(char),
+}
+
+/// Parser of Franca IDL and deployment documents.
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    position: usize,
+}
+
+impl Parser {
+    fn new(text: &str) -> Result<Self, CodegenError> {
+        let mut tokens = Vec::new();
+        let mut chars = text.char_indices().peekable();
+        let mut line = 1;
+        while let Some((start, c)) = chars.next() {
+            let rest = &text[start..];
+            let token = if c == '\n' {
+                line += 1;
+                continue;
+            } else if c.is_whitespace() {
+                continue;
+            } else if let Some(end) = comment_end(rest) {
+                let comment = &rest[..end];
+                line += comment.matches('\n').count();
+                while chars.peek().is_some_and(|(i, _)| *i < start + end) {
+                    chars.next();
+                }
+                continue;
+            } else if c == '"' {
+                for (_, c) in chars.by_ref() {
+                    match c {
+                        '"' => break,
+                        '\n' => line += 1,
+                        _ => {}
+                    }
+                }
+                Token::Text
+            } else if c.is_ascii_alphanumeric() || c == '_' {
+                let mut end = start + c.len_utf8();
+                while let Some((i, c)) = chars.peek().copied() {
+                    if !(c.is_ascii_alphanumeric() || c == '_' || c == '.') {
+                        break;
+                    }
+                    end = i + c.len_utf8();
+                    chars.next();
+                }
+                let word = &text[start..end];
+                match c.is_ascii_digit() {
+                    true => Token::Number(number(word).ok_or_else(|| syntax_error(line, word))?),
+                    false => Token::Word(word.to_string()),
+                }
+            } else {
+                Token::Symbol(c)
+            };
+            tokens.push((token, line));
+        }
+        Ok(Parser { tokens, position: 0 })
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position).map(|(token, _)| token)
+    }
+
+    fn next(&mut self) -> Result<Token, CodegenError> {
+        let token = self.tokens.get(self.position).map(|(token, _)| token.clone())
+            .ok_or_else(|| self.error("end of document"))?;
+        self.position += 1;
+        Ok(token)
+    }
+
+    fn error(&self, found: &str) -> CodegenError {
+        let line = self.tokens.get(self.position).or(self.tokens.last()).map_or(1, |(_, line)| *line);
+        syntax_error(line, found)
+    }
+
+    fn word(&mut self) -> Result<String, CodegenError> {
+        match self.next()? {
+            Token::Word(word) => Ok(word),
+            token => {
+                self.position -= 1;
+                Err(self.error(&format!("{:?}", token)))
+            }
+        }
+    }
+
+    fn eat(&mut self, expected: &Token) -> bool {
+        let matched = self.peek() == Some(expected);
+        if matched {
+            self.position += 1;
+        }
+        matched
+    }
+
+    fn eat_word(&mut self, word: &str) -> bool {
+        self.eat(&Token::Word(word.to_string()))
+    }
+
+    fn expect(&mut self, symbol: char) -> Result<(), CodegenError> {
+        match self.eat(&Token::Symbol(symbol)) {
+            true => Ok(()),
+            false => Err(self.error(&format!("{:?} instead of '{}'", self.peek(), symbol))),
+        }
+    }
+
+    fn idl(&mut self, idl: &mut Idl) -> Result<(), CodegenError> {
+        while let Some(token) = self.peek().cloned() {
+            match token {
+                Token::Word(word) if word == "package" => {
+                    self.next()?;
+                    self.word()?;
+                }
+                Token::Word(word) if word == "import" => {
+                    while self.next()? != Token::Text {}
+                }
+                Token::Word(word) if word == "typeCollection" => {
+                    self.next()?;
+                    let name = last_segment(&self.word()?).to_string();
+                    self.expect('{')?;
+                    while !self.eat(&Token::Symbol('}')) {
+                        if self.eat_word("version") {
+                            self.version()?;
+                        } else {
+                            idl.types.push(self.idl_type(("typeCollection", name.clone()))?);
+                        }
+                    }
+                }
+                Token::Word(word) if word == "interface" => {
+                    self.next()?;
+                    let interface = self.interface(idl)?;
+                    idl.interfaces.push(interface);
+                }
+                _ => return Err(self.error(&format!("{:?}", token))),
+            }
+        }
+        Ok(())
+    }
+
+    fn version(&mut self) -> Result<(u8, u32), CodegenError> {
+        self.expect('{')?;
+        let mut version = (0, 0);
+        while !self.eat(&Token::Symbol('}')) {
+            let part = self.word()?;
+            let value = match self.next()? {
+                Token::Number(value) => value,
+                token => return Err(self.error(&format!("{:?}", token))),
+            };
+            let invalid = || CodegenError::InvalidValue { element: "version", value: value.to_string() };
+            match part.as_str() {
+                "major" => version.0 = u8::try_from(value).map_err(|_| invalid())?,
+                "minor" => version.1 = u32::try_from(value).map_err(|_| invalid())?,
+                _ => return Err(self.error(&part)),
+            }
+        }
+        Ok(version)
+    }
+
+    fn interface(&mut self, idl: &mut Idl) -> Result<IdlInterface, CodegenError> {
+        let mut interface = IdlInterface { name: last_segment(&self.word()?).to_string(), ..Default::default() };
+        while !self.eat(&Token::Symbol('{')) {
+            // extends and manages clauses
+            self.next()?;
+        }
+        while !self.eat(&Token::Symbol('}')) {
+            let keyword = self.word()?;
+            match keyword.as_str() {
+                "version" => interface.version = self.version()?,
+                "attribute" => {
+                    let type_ref = self.type_ref()?;
+                    let name = self.word()?;
+                    let mut attribute = IdlAttribute { name, type_ref, readonly: false, no_subscriptions: false };
+                    loop {
+                        if self.eat_word("readonly") {
+                            attribute.readonly = true;
+                        } else if self.eat_word("noSubscriptions") {
+                            attribute.no_subscriptions = true;
+                        } else {
+                            break;
+                        }
+                    }
+                    interface.attributes.push(attribute);
+                }
+                "method" => {
+                    let name = self.word()?;
+                    let fire_and_forget = self.eat_word("fireAndForget");
+                    let mut method = IdlMethod { name, fire_and_forget, in_args: Vec::new(), out_args: Vec::new() };
+                    self.expect('{')?;
+                    while !self.eat(&Token::Symbol('}')) {
+                        match self.word()?.as_str() {
+                            "in" => method.in_args = self.arguments()?,
+                            "out" => method.out_args = self.arguments()?,
+                            "error" => match self.eat(&Token::Symbol('{')) {
+                                true => while self.next()? != Token::Symbol('}') {},
+                                false => {
+                                    self.word()?;
+                                }
+                            },
+                            word => return Err(self.error(word)),
+                        }
+                    }
+                    interface.methods.push(method);
+                }
+                "broadcast" => {
+                    let name = self.word()?;
+                    self.eat_word("selective");
+                    self.expect('{')?;
+                    let mut args = Vec::new();
+                    while !self.eat(&Token::Symbol('}')) {
+                        match self.word()?.as_str() {
+                            "out" => args = self.arguments()?,
+                            word => return Err(self.error(word)),
+                        }
+                    }
+                    interface.broadcasts.push((name, args));
+                }
+                _ => {
+                    self.position -= 1;
+                    idl.types.push(self.idl_type(("interface", interface.name.clone()))?);
+                }
+            }
+        }
+        Ok(interface)
+    }
+
+    fn idl_type(&mut self, scope: (&'static str, String)) -> Result<IdlType, CodegenError> {
+        let keyword = self.word()?;
+        let name = self.word()?;
+        let kind = match keyword.as_str() {
+            "typedef" => {
+                if !self.eat_word("is") {
+                    return Err(self.error("typedef without 'is'"));
+                }
+                IdlTypeKind::Alias(self.type_ref()?)
+            }
+            "array" => {
+                if !self.eat_word("of") {
+                    return Err(self.error("array without 'of'"));
+                }
+                IdlTypeKind::Alias(TypeRef::Vector(Box::new(self.type_ref()?)))
+            }
+            "enumeration" => {
+                if self.eat_word("extends") {
+                    return Err(CodegenError::UnsupportedType { name, category: "extended enumeration".to_string() });
+                }
+                self.expect('{')?;
+                let mut values = Vec::new();
+                while !self.eat(&Token::Symbol('}')) {
+                    let enumerator = self.word()?;
+                    let value = match self.eat(&Token::Symbol('=')) {
+                        true => match self.next()? {
+                            Token::Number(value) => Some(value),
+                            token => return Err(self.error(&format!("{:?}", token))),
+                        },
+                        false => None,
+                    };
+                    self.eat(&Token::Symbol(','));
+                    values.push((enumerator, value));
+                }
+                IdlTypeKind::Enum(values)
+            }
+            "struct" => {
+                let base = match self.eat_word("extends") {
+                    true => Some(last_segment(&self.word()?).to_string()),
+                    false => None,
+                };
+                self.eat_word("polymorphic");
+                let members = self.arguments()?.into_iter()
+                    .map(|Argument { name, type_ref }| Member { name, type_ref })
+                    .collect();
+                IdlTypeKind::Struct { base, members }
+            }
+            _ => return Err(CodegenError::UnsupportedType { name, category: keyword }),
+        };
+        Ok(IdlType { name, scope, kind })
+    }
+
+    /// Reads `{ Type name .. }`.
+    fn arguments(&mut self) -> Result<Vec<Argument>, CodegenError> {
+        self.expect('{')?;
+        let mut args = Vec::new();
+        while !self.eat(&Token::Symbol('}')) {
+            let type_ref = self.type_ref()?;
+            args.push(Argument { type_ref, name: self.word()? });
+        }
+        Ok(args)
+    }
+
+    fn type_ref(&mut self) -> Result<TypeRef, CodegenError> {
+        let name = self.word()?;
+        let name = last_segment(&name);
+        let type_ref = franca_primitive(name).unwrap_or_else(|| TypeRef::Named(name.to_string()));
+        match self.eat(&Token::Symbol('[')) {
+            true => {
+                self.expect(']')?;
+                Ok(TypeRef::Vector(Box::new(type_ref)))
+            }
+            false => Ok(type_ref),
+        }
+    }
+
+    /// Reads the properties and blocks of a deployment until the end of the document or block.
+    fn entries(&mut self) -> Result<Vec<Entry>, CodegenError> {
+        let mut entries = Vec::new();
+        while let Some(token) = self.peek().cloned() {
+            match token {
+                Token::Symbol('}') => break,
+                Token::Word(word) if word == "import" => {
+                    while self.next()? != Token::Text {}
+                }
+                Token::Word(word) => {
+                    self.next()?;
+                    if self.eat(&Token::Symbol('=')) {
+                        entries.push(Entry::Property(word, self.value()?));
+                        continue;
+                    }
+                    let mut header = vec![word];
+                    while !self.eat(&Token::Symbol('{')) {
+                        header.push(self.word()?);
+                    }
+                    let block = self.entries()?;
+                    self.expect('}')?;
+                    entries.push(Entry::Block { header, entries: block });
+                }
+                token => return Err(self.error(&format!("{:?}", token))),
+            }
+        }
+        Ok(entries)
+    }
+
+    fn value(&mut self) -> Result<Value, CodegenError> {
+        Ok(match self.next()? {
+            Token::Number(value) => Value::Number(value),
+            Token::Word(word) => Value::Word(word),
+            Token::Text => Value::Text,
+            Token::Symbol('{') => {
+                let mut values = Vec::new();
+                while !self.eat(&Token::Symbol('}')) {
+                    values.push(self.value()?);
+                    self.eat(&Token::Symbol(','));
+                }
+                Value::List(values)
+            }
+            token => return Err(self.error(&format!("{:?}", token))),
+        })
+    }
+}
+
+/// Returns the length of the comment or annotation at the start of the text.
+fn comment_end(text: &str) -> Option<usize> {
+    let (end, len) = if text.starts_with("//") {
+        ("\n", 0)
+    } else if text.starts_with("/*") {
+        ("*/", 2)
+    } else if text.starts_with("<**") {
+        ("**>", 3)
+    } else {
+        return None;
+    };
+    Some(text[2..].find(end).map_or(text.len(), |i| i + 2 + len))
+}
+
+fn number(word: &str) -> Option<u64> {
+    match word.strip_prefix("0x").or_else(|| word.strip_prefix("0X")) {
+        Some(hex) => u64::from_str_radix(hex, 16).ok(),
+        None => word.parse().ok(),
+    }
+}
+
+fn syntax_error(line: usize, found: &str) -> CodegenError {
+    CodegenError::Franca(format!("line {}: unexpected {}", line, found))
+}
+
+fn last_segment(name: &str) -> &str {
+    name.rsplit('.').next().unwrap_or(name)
+}
+
+fn franca_primitive(name: &str) -> Option<TypeRef> {
+    match name {
+        "ByteBuffer" => Some(TypeRef::Vector(Box::new(TypeRef::U8))),
+        _ => TypeRef::primitive(name),
+    }
+}
+
+fn is_integer(type_ref: &TypeRef) -> bool {
+    matches!(type_ref, TypeRef::U8 | TypeRef::U16 | TypeRef::U32 | TypeRef::U64 | TypeRef::I8 | TypeRef::I16 |
+                       TypeRef::I32 | TypeRef::I64)
+}
+
+fn check_type(type_ref: &TypeRef, names: &[String]) -> Result<(), CodegenError> {
+    match type_ref {
+        TypeRef::Vector(item) | TypeRef::Array(item, _) => check_type(item, names),
+        TypeRef::Named(name) if !names.contains(name) => Err(CodegenError::UnresolvedReference(name.clone())),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn tokens_test() {
+        let text = "<** @description: x **> method set { // y\n in { UInt8[] a } }\n/* z */ 0x10";
+        let parser = Parser::new(text).unwrap();
+        let tokens = parser.tokens.iter().map(|(token, _)| token.clone()).collect::<Vec<_>>();
+        let word = |word: &str| Token::Word(word.to_string());
+        assert_eq!(tokens, [word("method"), word("set"), Token::Symbol('{'), word("in"), Token::Symbol('{'),
+                            word("UInt8"), Token::Symbol('['), Token::Symbol(']'), word("a"), Token::Symbol('}'),
+                            Token::Symbol('}'), Token::Number(0x10)]);
+        assert_eq!(parser.tokens.last().unwrap().1, 3);
+    }
+}
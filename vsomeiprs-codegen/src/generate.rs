@@ -0,0 +1,779 @@
+// SPDX-License-Identifier: MPL-2.0
+//
+// Copyright (C) 2024 Alexander Seifarth
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use proc_macro2::{Ident, Literal, Span, TokenStream};
+use quote::{format_ident, quote};
+use super::{Argument, CodegenError, DataType, DataTypeKind, Field, Method, Model, ServiceInterface, TypeRef};
+
+/// Options of [generate_rust_with()], by default the code of proxies and skeletons with the
+/// default deployment profile at the top level of the file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GenerateOptions {
+    /// Module wrapping the code, e.g. `vehicle` for `pub mod vehicle { .. }`.
+    pub module: Option<String>,
+    /// Generates the `<Interface>Proxy`s and `<Interface>Event`s.
+    pub proxies: bool,
+    /// Generates the `<Interface>Service` traits and `<Interface>Skeleton`s.
+    pub skeletons: bool,
+    /// Deployment profile of the proxies and skeletons, `vsomeiprs::codec::DeploymentProfile`'s
+    /// default if `None`.
+    pub profile: Option<ProfileOptions>,
+}
+
+impl Default for GenerateOptions {
+    fn default() -> Self {
+        GenerateOptions { module: None, proxies: true, skeletons: true, profile: None }
+    }
+}
+
+/// Deployment profile of the generated code, returned by its `deployment_profile()` and set
+/// by the constructors of the proxies and skeletons.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProfileOptions {
+    /// Length field of strings in bits, 0, 8, 16 or 32.
+    pub string_length: u8,
+    /// Length field of dynamic arrays in bits, 0, 8, 16 or 32.
+    pub array_length: u8,
+    /// Length field before structs in bits, 0, 8, 16 or 32.
+    pub struct_length: u8,
+    /// Alignment in bytes of struct members.
+    pub alignment: usize,
+    pub little_endian: bool,
+}
+
+impl Default for ProfileOptions {
+    fn default() -> Self {
+        ProfileOptions { string_length: 32, array_length: 32, struct_length: 0, alignment: 1, little_endian: false }
+    }
+}
+
+/// Returns the Rust source of the data types and of a module per service interface with the
+/// typed proxy, service trait and skeleton, see the crate documentation.
+pub fn generate_rust(model: &Model) -> Result<String, CodegenError> {
+    generate_rust_with(model, &GenerateOptions::default())
+}
+
+/// Returns the Rust source of the model like [generate_rust()] with the module, parts and
+/// deployment profile of the options.
+pub fn generate_rust_with(model: &Model, options: &GenerateOptions) -> Result<String, CodegenError> {
+    let data_types = model.data_types.iter().map(data_type);
+    let interfaces = model.interfaces.iter().map(|service| interface(service, options));
+    let profile = options.profile.as_ref().map(deployment_profile).transpose()?;
+    let mut tokens = quote! {
+        #profile
+        #(#data_types)*
+        #(#interfaces)*
+    };
+    if let Some(module) = &options.module {
+        let module = ident(&snake_case(module));
+        tokens = quote! {
+            pub mod #module {
+                #tokens
+            }
+        };
+    }
+    let file = syn::parse2(tokens).map_err(|e| CodegenError::InvalidCode(e.to_string()))?;
+    Ok(format!("// Generated by vsomeiprs-codegen, do not edit.\n\n{}", prettyplease::unparse(&file)))
+}
+
+fn data_type(data_type: &DataType) -> TokenStream {
+    let name = ident(&upper_camel_case(&data_type.name));
+    match &data_type.kind {
+        DataTypeKind::Struct(members) => {
+            let names = members.iter().map(|member| ident(&snake_case(&member.name)));
+            let types = members.iter().map(|member| rust_type(&member.type_ref));
+            quote! {
+                #[derive(Debug, Clone, PartialEq, ::vsomeiprs::codec::SomeipSerialize,
+                         ::vsomeiprs::codec::SomeipDeserialize)]
+                pub struct #name {
+                    #(pub #names: #types,)*
+                }
+            }
+        }
+        DataTypeKind::Enum { base, enumerators } => {
+            let repr = rust_type(base);
+            let names = enumerators.iter().map(|enumerator| ident(&upper_camel_case(&enumerator.name)));
+            let values = enumerators.iter().map(|enumerator| Literal::u64_unsuffixed(enumerator.value));
+            quote! {
+                #[derive(Debug, Clone, Copy, PartialEq, Eq, ::vsomeiprs::codec::SomeipSerialize,
+                         ::vsomeiprs::codec::SomeipDeserialize)]
+                #[repr(#repr)]
+                pub enum #name {
+                    #(#names = #values,)*
+                }
+            }
+        }
+        DataTypeKind::Alias(type_ref) => {
+            let rust_type = rust_type(type_ref);
+            quote!(pub type #name = #rust_type;)
+        }
+    }
+}
+
+/// Arguments of a method or field accessor as parameters, payload expression and type.
+struct Args {
+    names: Vec<Ident>,
+    types: Vec<TokenStream>,
+    /// Struct collecting more than one argument.
+    wrapper: Option<Ident>,
+}
+
+impl Args {
+    fn new(args: &[Argument], wrapper: Ident) -> Self {
+        Args {
+            names: args.iter().map(|arg| ident(&snake_case(&arg.name))).collect(),
+            types: args.iter().map(|arg| rust_type(&arg.type_ref)).collect(),
+            wrapper: (args.len() > 1).then_some(wrapper),
+        }
+    }
+
+    fn none() -> Self {
+        Args { names: Vec::new(), types: Vec::new(), wrapper: None }
+    }
+
+    fn value(type_ref: &TypeRef) -> Self {
+        Args { names: vec![format_ident!("value")], types: vec![rust_type(type_ref)], wrapper: None }
+    }
+
+    fn params(&self) -> TokenStream {
+        let Args { names, types, .. } = self;
+        quote!(#(#names: #types),*)
+    }
+
+    /// Expression or pattern of the payload.
+    fn payload(&self) -> TokenStream {
+        let names = &self.names;
+        match (&self.wrapper, names.as_slice()) {
+            (Some(wrapper), _) => quote!(#wrapper { #(#names),* }),
+            (None, [name]) => quote!(#name),
+            (None, _) => quote!(()),
+        }
+    }
+
+    fn payload_type(&self) -> TokenStream {
+        match (&self.wrapper, self.types.as_slice()) {
+            (Some(wrapper), _) => quote!(#wrapper),
+            (None, [rust_type]) => rust_type.clone(),
+            (None, _) => quote!(()),
+        }
+    }
+
+    fn wrapper_struct(&self) -> TokenStream {
+        let Args { names, types, wrapper } = self;
+        match wrapper {
+            Some(wrapper) => quote! {
+                #[derive(Debug, Clone, PartialEq, SomeipSerialize, SomeipDeserialize)]
+                #[someip(length_width = 0)]
+                pub struct #wrapper {
+                    #(pub #names: #types,)*
+                }
+            },
+            None => quote!(),
+        }
+    }
+}
+
+/// Request of the proxy and handler of the service trait, a method or a field getter or setter.
+struct Operation {
+    name: Ident,
+    id: Ident,
+    fire_and_forget: bool,
+    reliable: bool,
+    input: Args,
+    output: Args,
+}
+
+impl Operation {
+    fn method(method: &Method) -> Self {
+        let name = upper_camel_case(&method.name);
+        Operation {
+            name: ident(&snake_case(&method.name)),
+            id: ident(&constant_case(&method.name)),
+            fire_and_forget: method.fire_and_forget,
+            reliable: method.reliable,
+            input: Args::new(&method.in_args, format_ident!("{}In", name)),
+            output: Args::new(&method.out_args, format_ident!("{}Out", name)),
+        }
+    }
+
+    fn getter(field: &Field) -> Self {
+        Operation {
+            name: ident(&format!("get_{}", snake_case(&field.name))),
+            id: ident(&format!("GET_{}", constant_case(&field.name))),
+            fire_and_forget: false,
+            reliable: field.reliable,
+            input: Args::none(),
+            output: Args::value(&field.type_ref),
+        }
+    }
+
+    fn setter(field: &Field) -> Self {
+        Operation {
+            name: ident(&format!("set_{}", snake_case(&field.name))),
+            id: ident(&format!("SET_{}", constant_case(&field.name))),
+            fire_and_forget: false,
+            reliable: field.reliable,
+            input: Args::value(&field.type_ref),
+            output: Args::value(&field.type_ref),
+        }
+    }
+
+    fn proxy_fn(&self) -> TokenStream {
+        let Operation { name, id, .. } = self;
+        let params = self.input.params();
+        let payload = self.input.payload();
+        let reliability = reliability(self.reliable);
+        if self.fire_and_forget {
+            quote! {
+                pub fn #name(&self, #params) -> Result<(), CallError> {
+                    self.proxy.call_no_return(method_ids::#id, &#payload, #reliability)
+                }
+            }
+        } else {
+            let output = self.output.payload_type();
+            quote! {
+                pub async fn #name(&self, #params) -> Result<#output, CallError> {
+                    self.proxy.call(method_ids::#id, &#payload, #reliability).await
+                }
+            }
+        }
+    }
+
+    fn service_fn(&self) -> TokenStream {
+        let name = &self.name;
+        let params = self.input.params();
+        let output = match self.fire_and_forget {
+            true => quote!(()),
+            false => {
+                let output = self.output.payload_type();
+                quote!(Result<#output, ReturnCode>)
+            }
+        };
+        quote!(fn #name(&self, #params) -> impl Future<Output = #output> + Send;)
+    }
+
+    fn dispatch_arm(&self) -> TokenStream {
+        let Operation { name, id, .. } = self;
+        let names = &self.input.names;
+        let pattern = self.input.payload();
+        let input = self.input.payload_type();
+        if self.fire_and_forget {
+            quote! {
+                method_ids::#id => {
+                    if let Ok(#pattern) = self.skeleton.decode::<#input>(data) {
+                        service.#name(#(#names),*).await;
+                    }
+                }
+            }
+        } else {
+            quote! {
+                method_ids::#id => {
+                    let result = match self.skeleton.decode::<#input>(data) {
+                        Ok(#pattern) => service.#name(#(#names),*).await,
+                        Err(_) => Err(ReturnCode::MalformedMessage),
+                    };
+                    if expects_response {
+                        self.skeleton.respond(header, result)?;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Event of the proxy and notification of the skeleton, an event or a field notifier.
+struct Notification {
+    name: String,
+    id: Ident,
+    is_field: bool,
+    reliable: bool,
+    rust_type: TokenStream,
+    event_groups: Vec<Ident>,
+}
+
+impl Notification {
+    fn descriptor(&self) -> TokenStream {
+        let Notification { id, event_groups, .. } = self;
+        let constructor = match self.is_field {
+            true => quote!(field),
+            false => quote!(new),
+        };
+        let reliability = reliability(self.reliable);
+        quote! {
+            EventDescriptor {
+                reliability: #reliability,
+                ..EventDescriptor::#constructor(event_ids::#id, Vec::from([#(event_group_ids::#event_groups),*]))
+            }
+        }
+    }
+}
+
+/// Returns the function `deployment_profile()`.
+fn deployment_profile(profile: &ProfileOptions) -> Result<TokenStream, CodegenError> {
+    let width = |bits: u8| match bits {
+        0 => Ok(quote!(None)),
+        8 => Ok(quote!(U8)),
+        16 => Ok(quote!(U16)),
+        32 => Ok(quote!(U32)),
+        _ => Err(CodegenError::InvalidValue { element: "length width", value: bits.to_string() }),
+    };
+    let string_length = width(profile.string_length)?;
+    let array_length = width(profile.array_length)?;
+    let struct_length = width(profile.struct_length)?;
+    if profile.alignment == 0 {
+        return Err(CodegenError::InvalidValue { element: "alignment", value: "0".to_string() });
+    }
+    let alignment = Literal::usize_unsuffixed(profile.alignment);
+    let byte_order = match profile.little_endian {
+        true => quote!(LittleEndian),
+        false => quote!(BigEndian),
+    };
+    Ok(quote! {
+        /// Deployment profile of the proxies and skeletons.
+        pub fn deployment_profile() -> ::vsomeiprs::codec::DeploymentProfile {
+            use ::vsomeiprs::codec::{ByteOrder, DeploymentProfile, LengthWidth};
+            DeploymentProfile {
+                string_length: LengthWidth::#string_length,
+                array_length: LengthWidth::#array_length,
+                struct_length: LengthWidth::#struct_length,
+                alignment: #alignment,
+                byte_order: ByteOrder::#byte_order,
+                ..DeploymentProfile::default()
+            }
+        }
+    })
+}
+
+fn interface(interface: &ServiceInterface, options: &GenerateOptions) -> TokenStream {
+    let name = upper_camel_case(&interface.name);
+    let module = ident(&snake_case(&interface.name));
+    let proxy = format_ident!("{}Proxy", name);
+    let service = format_ident!("{}Service", name);
+    let skeleton = format_ident!("{}Skeleton", name);
+    let event = format_ident!("{}Event", name);
+    let service_id = hex(interface.service_id);
+    let major_version = Literal::u8_unsuffixed(interface.major_version);
+    let minor_version = Literal::u32_unsuffixed(interface.minor_version);
+
+    let mut operations = interface.methods.iter().map(Operation::method).collect::<Vec<_>>();
+    let mut method_ids = interface.methods.iter().map(|method| method.method_id).collect::<Vec<_>>();
+    for field in &interface.fields {
+        if let Some(getter_id) = field.getter_id {
+            operations.push(Operation::getter(field));
+            method_ids.push(getter_id);
+        }
+        if let Some(setter_id) = field.setter_id {
+            operations.push(Operation::setter(field));
+            method_ids.push(setter_id);
+        }
+    }
+    let method_id_names = operations.iter().map(|operation| &operation.id);
+    let method_ids = method_ids.into_iter().map(hex);
+    let wrappers = operations.iter().flat_map(|operation| [operation.input.wrapper_struct(),
+                                                                 operation.output.wrapper_struct()]);
+    let proxy_fns = operations.iter().map(Operation::proxy_fn);
+    let service_fns = operations.iter().map(Operation::service_fn);
+    let dispatch_arms = operations.iter().map(Operation::dispatch_arm);
+
+    let group_names = |name: &str| interface.event_groups_of(name)
+        .map(|group| ident(&constant_case(&group.name))).collect::<Vec<_>>();
+    let mut notifications = interface.events.iter()
+        .map(|event| Notification {
+            name: event.name.clone(),
+            id: ident(&constant_case(&event.name)),
+            is_field: false,
+            reliable: event.reliable,
+            rust_type: rust_type(&event.type_ref),
+            event_groups: group_names(&event.name),
+        })
+        .collect::<Vec<_>>();
+    let mut event_ids = interface.events.iter().map(|event| event.event_id).collect::<Vec<_>>();
+    for field in &interface.fields {
+        if let Some(notifier_id) = field.notifier_id {
+            notifications.push(Notification {
+                name: field.name.clone(),
+                id: ident(&constant_case(&field.name)),
+                is_field: true,
+                reliable: field.reliable,
+                rust_type: rust_type(&field.type_ref),
+                event_groups: group_names(&field.name),
+            });
+            event_ids.push(notifier_id);
+        }
+    }
+    let event_id_names = notifications.iter().map(|notification| &notification.id);
+    let event_ids = event_ids.into_iter().map(hex);
+    let descriptors = notifications.iter().map(Notification::descriptor);
+    let notify_fns = notifications.iter().map(|notification| {
+        let Notification { id, rust_type, .. } = notification;
+        let name = format_ident!("notify_{}", snake_case(&notification.name));
+        quote! {
+            pub fn #name(&self, value: &#rust_type) -> Result<(), ServiceError> {
+                self.skeleton.notify(event_ids::#id, value)
+            }
+        }
+    });
+
+    let (proxy_field_fns, skeleton_field_fns): (Vec<_>, Vec<_>) = interface.fields.iter().map(|field| {
+        let name = format_ident!("{}_field", snake_case(&field.name));
+        let id = ident(&constant_case(&field.name));
+        let rust_type = rust_type(&field.type_ref);
+        let method_id = |method_id: Option<u16>, prefix: &str| match method_id {
+            Some(_) => {
+                let id = format_ident!("{}_{}", prefix, id);
+                quote!(Some(method_ids::#id))
+            }
+            None => quote!(None),
+        };
+        let getter = method_id(field.getter_id, "GET");
+        let setter = method_id(field.setter_id, "SET");
+        let notifier = match field.notifier_id {
+            Some(_) => {
+                let event_groups = group_names(&field.name);
+                quote!(Some((event_ids::#id, Vec::from([#(event_group_ids::#event_groups),*]))))
+            }
+            None => quote!(None),
+        };
+        let reliable = field.reliable;
+        let deployment = quote! {
+            FieldDeployment { getter: #getter, setter: #setter, notifier: #notifier, reliable: #reliable,
+                              cycle: None }
+        };
+        let proxy_doc = format!(" Returns the field {} to read, write and subscribe to it.", field.name);
+        let skeleton_doc = format!(" Returns a provider storing the value of the field {}.", field.name);
+        (quote! {
+            #[doc = #proxy_doc]
+            pub fn #name(&self) -> Field<#rust_type> {
+                Field::new(self.proxy.clone(), #deployment)
+            }
+        }, quote! {
+            #[doc = #skeleton_doc]
+            pub fn #name(&self, initial: #rust_type) -> FieldProvider<#rust_type> {
+                FieldProvider::new(self.skeleton.clone(), #deployment, initial)
+            }
+        })
+    }).unzip();
+
+    let group_ids = interface.event_groups.iter().map(|group| ident(&constant_case(&group.name)));
+    let group_id_values = interface.event_groups.iter().map(|group| hex(group.event_group_id));
+    let subscribe_fns = interface.event_groups.iter().map(|group| {
+        let name = format_ident!("subscribe_{}", snake_case(&group.name));
+        let group_id = ident(&constant_case(&group.name));
+        let members = notifications.iter().filter(|notification| group.events.contains(&notification.name));
+        let reliability = reliability(members.clone().any(|notification| notification.reliable));
+        let events = members.map(|Notification { id, is_field, .. }| quote!((event_ids::#id, #is_field)));
+        quote! {
+            pub fn #name(&self) -> Result<(), SendError> {
+                self.proxy.subscribe(event_group_ids::#group_id, &[#(#events),*], #reliability)
+            }
+        }
+    });
+
+    let (event_enum, decode_event) = match notifications.is_empty() {
+        true => (quote!(), quote!()),
+        false => {
+            let variants = notifications.iter().map(|notification| ident(&upper_camel_case(&notification.name)))
+                .collect::<Vec<_>>();
+            let types = notifications.iter().map(|notification| &notification.rust_type);
+            let ids = notifications.iter().map(|notification| &notification.id);
+            (quote! {
+                /// Notification of an event or field.
+                #[derive(Debug, Clone, PartialEq)]
+                pub enum #event {
+                    #(#variants(#types),)*
+                }
+            }, quote! {
+                /// Returns the event if the message is a notification of the service instance.
+                pub fn decode_event(&self, msg: &MessageType)
+                    -> Option<Result<#event, CodecError>>
+                {
+                    let (event_id, data) = self.proxy.notification(msg)?;
+                    match event_id {
+                        #(event_ids::#ids => Some(self.proxy.decode(data).map(#event::#variants)),)*
+                        _ => None,
+                    }
+                }
+            })
+        }
+    };
+
+    let proxy_doc = format!(" Proxy of the service interface {}.", interface.name);
+    let service_doc = format!(" Implementation of the service interface {}, called by [{}::run()].",
+                           interface.name, skeleton);
+    let skeleton_doc = format!(" Skeleton offering the service interface {}.", interface.name);
+    let with_profile = options.profile.as_ref().map(|_| quote!(.with_profile(deployment_profile())));
+    let proxy_part = options.proxies.then(|| quote! {
+        #event_enum
+
+        #[doc = #proxy_doc]
+        pub struct #proxy {
+            proxy: ServiceProxy,
+        }
+
+        impl #proxy {
+            pub fn new(app: Arc<VSomeipApplication>, instance_id: InstanceID)
+                -> Self
+            {
+                let address = Address::new(SERVICE_ID, instance_id, VERSION);
+                #proxy { proxy: ServiceProxy::new(app, address)#with_profile }
+            }
+
+            pub fn with_profile(self, profile: DeploymentProfile) -> Self {
+                #proxy { proxy: self.proxy.with_profile(profile) }
+            }
+
+            /// Sets the timeout and retransmissions of the method calls.
+            pub fn with_options(self, options: RequestOptions) -> Self {
+                #proxy { proxy: self.proxy.with_options(options) }
+            }
+
+            /// Appends a layer wrapping the method calls.
+            pub fn with_layer(self, layer: Arc<dyn Layer>) -> Self {
+                #proxy { proxy: self.proxy.with_layer(layer) }
+            }
+
+            pub fn proxy(&self) -> &ServiceProxy {
+                &self.proxy
+            }
+
+            #(#proxy_fns)*
+
+            #(#proxy_field_fns)*
+
+            #(#subscribe_fns)*
+
+            #decode_event
+        }
+    });
+    let skeleton_part = options.skeletons.then(|| quote! {
+        #[doc = #service_doc]
+        pub trait #service {
+            #(#service_fns)*
+        }
+
+        #[doc = #skeleton_doc]
+        pub struct #skeleton {
+            skeleton: ServiceSkeleton,
+        }
+
+        impl #skeleton {
+            pub fn new(app: Arc<VSomeipApplication>, instance_id: InstanceID)
+                -> Self
+            {
+                let address = Address::new(SERVICE_ID, instance_id, VERSION);
+                #skeleton { skeleton: ServiceSkeleton::new(app, address)#with_profile }
+            }
+
+            pub fn with_profile(self, profile: DeploymentProfile) -> Self {
+                #skeleton { skeleton: self.skeleton.with_profile(profile) }
+            }
+
+            pub fn skeleton(&self) -> &ServiceSkeleton {
+                &self.skeleton
+            }
+
+            /// Offers the service instance with its events and fields.
+            pub fn offer(&self) -> Result<(), SendError> {
+                self.skeleton.offer(Vec::from([#(#descriptors),*]))
+            }
+
+            pub fn stop_offer(&self) {
+                self.skeleton.stop_offer()
+            }
+
+            #(#notify_fns)*
+
+            #(#skeleton_field_fns)*
+
+            /// Answers the requests to the service instance received from the channel, e.g.
+            /// `TypedReceivers::requests`, by the service until the channel is closed.
+            pub async fn run<S: #service>(&self, service: &S, requests: &mut RequestReceiver) {
+                self.skeleton.run(requests, |msg| async move { self.dispatch(service, &msg).await }).await
+            }
+
+            /// Calls the service for a request to the service instance and sends its response,
+            /// [ReturnCode::UnknownMethod] for an unknown method. Requests without return are not
+            /// answered. Returns `false` if the message is no request to the instance.
+            pub async fn dispatch<S: #service>(&self, service: &S, msg: &MessageType)
+                -> Result<bool, ServiceError>
+            {
+                let Some((header, data)) = self.skeleton.request(msg) else {
+                    return Ok(false);
+                };
+                let expects_response = matches!(msg, MessageType::Request { .. });
+                match header.method_id {
+                    #(#dispatch_arms)*
+                    _ if expects_response => {
+                        self.skeleton.respond::<()>(header, Err(ReturnCode::UnknownMethod))?;
+                    }
+                    _ => {}
+                }
+                Ok(true)
+            }
+        }
+    });
+    quote! {
+        pub mod #module {
+            #![allow(unused_imports)]
+            use std::future::Future;
+            use std::sync::Arc;
+            use ::vsomeiprs::codec::{CodecError, DeploymentProfile, SomeipDeserialize, SomeipSerialize};
+            use ::vsomeiprs::commonapi::Address;
+            use ::vsomeiprs::service::{CallError, Field, FieldDeployment, FieldProvider, Layer, RequestReceiver,
+                                       ServiceError, ServiceProxy, ServiceSkeleton};
+            use ::vsomeiprs::{EventDescriptor, EventGroupID, InstanceID, InterfaceVersion, MessageType, MethodID,
+                              Reliability, RequestOptions, ReturnCode, SendError, ServiceID, VSomeipApplication};
+            use super::*;
+
+            pub const SERVICE_ID: ServiceID = ServiceID::new(#service_id);
+            pub const VERSION: InterfaceVersion =
+                InterfaceVersion::make_version(#major_version, #minor_version);
+
+            pub mod method_ids {
+                use super::*;
+
+                #(pub const #method_id_names: MethodID = MethodID::new(#method_ids);)*
+            }
+
+            pub mod event_ids {
+                use super::*;
+
+                #(pub const #event_id_names: MethodID = MethodID::new(#event_ids);)*
+            }
+
+            pub mod event_group_ids {
+                use super::*;
+
+                #(pub const #group_ids: EventGroupID =
+                    EventGroupID::new(#group_id_values);)*
+            }
+
+            #(#wrappers)*
+
+            #proxy_part
+
+            #skeleton_part
+        }
+    }
+}
+
+fn rust_type(type_ref: &TypeRef) -> TokenStream {
+    match type_ref {
+        TypeRef::Bool => quote!(bool),
+        TypeRef::U8 => quote!(u8),
+        TypeRef::U16 => quote!(u16),
+        TypeRef::U32 => quote!(u32),
+        TypeRef::U64 => quote!(u64),
+        TypeRef::I8 => quote!(i8),
+        TypeRef::I16 => quote!(i16),
+        TypeRef::I32 => quote!(i32),
+        TypeRef::I64 => quote!(i64),
+        TypeRef::F32 => quote!(f32),
+        TypeRef::F64 => quote!(f64),
+        TypeRef::String => quote!(String),
+        TypeRef::Vector(item) => {
+            let item = rust_type(item);
+            quote!(Vec<#item>)
+        }
+        TypeRef::Array(item, size) => {
+            let item = rust_type(item);
+            let size = Literal::usize_unsuffixed(*size);
+            quote!([#item; #size])
+        }
+        TypeRef::Named(name) => {
+            let name = ident(&upper_camel_case(name));
+            quote!(#name)
+        }
+    }
+}
+
+fn reliability(reliable: bool) -> TokenStream {
+    match reliable {
+        true => quote!(Reliability::Reliable),
+        false => quote!(Reliability::Unreliable),
+    }
+}
+
+fn hex(id: u16) -> syn::LitInt {
+    syn::LitInt::new(&format!("0x{:04x}", id), Span::call_site())
+}
+
+/// Returns the identifier, a raw identifier for Rust keywords.
+fn ident(name: &str) -> Ident {
+    let name = match name.starts_with(|c: char| c.is_ascii_digit()) {
+        true => format!("_{}", name),
+        false => name.to_string(),
+    };
+    match syn::parse_str::<Ident>(&name) {
+        Ok(ident) => ident,
+        Err(_) => Ident::new_raw(&name, Span::call_site()),
+    }
+}
+
+/// Returns the words of a name in camel case, snake case or upper case, e.g. `SetSpeed`,
+/// `vehicleSpeed`, `HTTPServer` or `ECO_MODE`.
+fn words(name: &str) -> Vec<String> {
+    let chars = name.chars().collect::<Vec<_>>();
+    let mut words = Vec::new();
+    let mut word = String::new();
+    for (i, c) in chars.iter().enumerate() {
+        if !c.is_ascii_alphanumeric() {
+            if !word.is_empty() {
+                words.push(std::mem::take(&mut word));
+            }
+            continue;
+        }
+        let prev = i.checked_sub(1).map(|i| chars[i]);
+        let next = chars.get(i + 1);
+        let boundary = c.is_ascii_uppercase() && !word.is_empty()
+            && (prev.is_some_and(|prev| prev.is_ascii_lowercase() || prev.is_ascii_digit())
+                || next.is_some_and(|next| next.is_ascii_lowercase()));
+        if boundary {
+            words.push(std::mem::take(&mut word));
+        }
+        word.push(*c);
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+    words
+}
+
+fn snake_case(name: &str) -> String {
+    words(name).iter().map(|word| word.to_ascii_lowercase()).collect::<Vec<_>>().join("_")
+}
+
+fn constant_case(name: &str) -> String {
+    snake_case(name).to_ascii_uppercase()
+}
+
+fn upper_camel_case(name: &str) -> String {
+    words(name).iter()
+        .map(|word| {
+            let word = word.to_ascii_lowercase();
+            let mut chars = word.chars();
+            chars.next().map(|first| first.to_ascii_uppercase().to_string() + chars.as_str()).unwrap_or_default()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_names() {
+        assert_eq!(snake_case("SetSpeed"), "set_speed");
+        assert_eq!(snake_case("vehicleSpeed"), "vehicle_speed");
+        assert_eq!(snake_case("HTTPServer2"), "http_server2");
+        assert_eq!(constant_case("SpeedChanged"), "SPEED_CHANGED");
+        assert_eq!(upper_camel_case("ECO_MODE"), "EcoMode");
+        assert_eq!(upper_camel_case("Speedometer"), "Speedometer");
+        assert_eq!(ident("type").to_string(), "r#type");
+        assert_eq!(ident("1st").to_string(), "_1st");
+    }
+}
@@ -0,0 +1,112 @@
+// SPDX-License-Identifier: MPL-2.0
+//
+// Copyright (C) 2024 Alexander Seifarth
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Code generation of typed SOME/IP proxies and skeletons from AUTOSAR ARXML, Franca IDL or
+//! JSON/YAML descriptions.
+//!
+//! [parse_arxml()] reads the `SERVICE-INTERFACE`s with a `SOMEIP-SERVICE-INTERFACE-DEPLOYMENT`
+//! (service ID, version, method, event and field IDs, event groups and transport protocol) and
+//! the `STD-CPP-IMPLEMENTATION-DATA-TYPE`s (structures, vectors, arrays, strings, type
+//! references and enumerations given by a `TEXTTABLE` compu method) into a [Model].
+//! References to standard types that are not part of the description, e.g. `/std/uint16_t`, map
+//! to the primitive type named by the last path segment.
+//!
+//! [generate_rust()] turns the model into Rust source for `vsomeiprs` with the `derive` feature:
+//! - a struct, enum or type alias per data type deriving the `vsomeiprs::codec` traits,
+//! - a module per service interface with the constants `SERVICE_ID` and `VERSION`, the modules
+//!   `method_ids`, `event_ids` and `event_group_ids` and
+//!   - `<Interface>Proxy` with an async function per method and field getter and setter
+//!     returning the output or a `vsomeiprs::service::CallError` of the failed call, a
+//!     `<field>_field()` function per field returning a `vsomeiprs::service::Field`, a
+//!     `subscribe_<group>()` function per event group and `decode_event()` returning the
+//!     `<Interface>Event` of a notification, see `vsomeiprs::service::ServiceProxy`,
+//!   - the `<Interface>Service` trait with a function per method, getter and setter returning a
+//!     future of the output or of an error return code, to be implemented by `async fn`s,
+//!   - `<Interface>Skeleton` offering the instance, sending `notify_<event>()` notifications,
+//!     returning a `vsomeiprs::service::FieldProvider` storing the value by `<field>_field()`
+//!     and answering the requests received from a channel by `run()`, which `dispatch()`es each
+//!     to the service trait. A request that cannot be deserialized is answered with
+//!     `E_MALFORMED_MESSAGE`, one of an unknown method with `E_UNKNOWN_METHOD`, see
+//!     `vsomeiprs::service::ServiceSkeleton`.
+//!
+//! Methods with more than one input or output argument use the structs `<Method>In` and
+//! `<Method>Out`. Names are converted to Rust conventions, e.g. `SetSpeed` to `set_speed()`.
+//!
+//! [parse_json()] and [parse_yaml()] read a description of the same content for teams without
+//! ARXML tooling. IDs are numbers or strings like `"0x8001"`, types are given in Rust syntax,
+//! e.g. `u16`, `String`, `Vec<Speed>` or `[u16; 4]`, or by the name of a type of the description.
+//! A type has exactly one of `type` (an alias), `struct` and `enum`:
+//! ```yaml
+//! types:
+//!   - { name: Speed, type: u16 }
+//!   - name: DriveMode
+//!     enum: u8
+//!     enumerators: [ { name: ECO, value: 0 }, { name: SPORT, value: 1 } ]
+//!   - name: Trip
+//!     struct: [ { name: name, type: String }, { name: speeds, type: "Vec<Speed>" } ]
+//! interfaces:
+//!   - name: Speedometer
+//!     service_id: 0x1234
+//!     version: [ 1, 2 ]  # major and minor
+//!     methods:
+//!       - { name: SetSpeedLimit, id: 1, in: [ { name: limit, type: Speed } ] }
+//!       - name: StartTrip
+//!         id: 2
+//!         reliable: true  # TCP, default false
+//!         in: [ { name: mode, type: DriveMode } ]
+//!         out: [ { name: trip, type: Trip } ]
+//!       - { name: Reset, id: 3, fire_and_forget: true }
+//!     events:
+//!       - { name: SpeedChanged, id: 0x8001, type: Speed }
+//!     fields:  # getter, setter and notifier are optional
+//!       - { name: DriveMode, type: DriveMode, getter: 0x10, setter: 0x11, notifier: 0x8002 }
+//!     event_groups:
+//!       - { name: Driving, id: 1, events: [ SpeedChanged, DriveMode ] }
+//! ```
+//!
+//! [parse_franca()] reads Franca IDL documents with the CommonAPI SOME/IP deployments of their
+//! interfaces, [generate_rust_with()] generates only the proxies or skeletons, wraps the code
+//! into a module or sets a deployment profile. The binary of the `someip-gen` crate generates a
+//! module from the command line:
+//! ```text
+//! someip-gen vehicle.fidl vehicle.fdepl --module vehicle --proxies --string-length 16 -o src/vehicle.rs
+//! ```
+//!
+//! A build script generates the code of all descriptions by [generate()], the crate includes it
+//! by `vsomeiprs::include_generated!()`:
+//! ```rust,ignore
+//! // build.rs
+//! fn main() {
+//!     vsomeiprs_codegen::generate("interfaces/*.arxml", std::env::var("OUT_DIR").unwrap()).unwrap();
+//! }
+//!
+//! // src/lib.rs, the types and interfaces of interfaces/vehicle.arxml
+//! pub mod vehicle {
+//!     vsomeiprs::include_generated!("vehicle");
+//! }
+//! ```
+
+mod arxml;
+pub use arxml::*;
+
+mod description;
+pub use description::*;
+
+mod error;
+pub use error::*;
+
+mod files;
+pub use files::*;
+
+mod franca;
+pub use franca::*;
+
+mod generate;
+pub use generate::*;
+
+mod model;
+pub use model::*;
@@ -0,0 +1,160 @@
+// SPDX-License-Identifier: MPL-2.0
+//
+// Copyright (C) 2024 Alexander Seifarth
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+/// Service interfaces and data types of one or more ARXML files.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Model {
+    pub interfaces: Vec<ServiceInterface>,
+    /// Data types except the primitive ones, in document order.
+    pub data_types: Vec<DataType>,
+}
+
+impl Model {
+    pub fn interface(&self, name: &str) -> Option<&ServiceInterface> {
+        self.interfaces.iter().find(|interface| interface.name == name)
+    }
+
+    pub fn data_type(&self, name: &str) -> Option<&DataType> {
+        self.data_types.iter().find(|data_type| data_type.name == name)
+    }
+}
+
+/// Service interface together with its SOME/IP deployment.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServiceInterface {
+    pub name: String,
+    pub service_id: u16,
+    pub major_version: u8,
+    pub minor_version: u32,
+    pub methods: Vec<Method>,
+    pub events: Vec<Event>,
+    pub fields: Vec<Field>,
+    pub event_groups: Vec<EventGroup>,
+}
+
+impl ServiceInterface {
+    /// Returns the event groups containing the event or field notifier.
+    pub fn event_groups_of(&self, name: &str) -> impl Iterator<Item = &EventGroup> {
+        let name = name.to_string();
+        self.event_groups.iter().filter(move |group| group.events.contains(&name))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Method {
+    pub name: String,
+    pub method_id: u16,
+    /// Method without response (REQUEST_NO_RETURN).
+    pub fire_and_forget: bool,
+    /// Transported by TCP instead of UDP.
+    pub reliable: bool,
+    pub in_args: Vec<Argument>,
+    pub out_args: Vec<Argument>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Argument {
+    pub name: String,
+    pub type_ref: TypeRef,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Event {
+    pub name: String,
+    pub event_id: u16,
+    pub reliable: bool,
+    pub type_ref: TypeRef,
+}
+
+/// Field with the IDs of its getter, setter and notifier, `None` if the field has none.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Field {
+    pub name: String,
+    pub type_ref: TypeRef,
+    pub getter_id: Option<u16>,
+    pub setter_id: Option<u16>,
+    pub notifier_id: Option<u16>,
+    pub reliable: bool,
+}
+
+/// Event group with the names of its events and field notifiers.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EventGroup {
+    pub name: String,
+    pub event_group_id: u16,
+    pub events: Vec<String>,
+}
+
+/// Type of an argument, event, field or member.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TypeRef {
+    Bool,
+    U8,
+    U16,
+    U32,
+    U64,
+    I8,
+    I16,
+    I32,
+    I64,
+    F32,
+    F64,
+    String,
+    Vector(Box<TypeRef>),
+    Array(Box<TypeRef>, usize),
+    /// Data type of the [Model] with the name.
+    Named(String),
+}
+
+impl TypeRef {
+    /// Returns the primitive type of an AUTOSAR or C++ type name, e.g. `uint16_t` or `UInt16`.
+    pub fn primitive(name: &str) -> Option<TypeRef> {
+        let type_ref = match name.to_ascii_lowercase().as_str() {
+            "bool" | "boolean" => TypeRef::Bool,
+            "uint8" | "uint8_t" => TypeRef::U8,
+            "uint16" | "uint16_t" => TypeRef::U16,
+            "uint32" | "uint32_t" => TypeRef::U32,
+            "uint64" | "uint64_t" => TypeRef::U64,
+            "sint8" | "int8" | "int8_t" => TypeRef::I8,
+            "sint16" | "int16" | "int16_t" => TypeRef::I16,
+            "sint32" | "int32" | "int32_t" => TypeRef::I32,
+            "sint64" | "int64" | "int64_t" => TypeRef::I64,
+            "float" | "float32" | "float32_t" => TypeRef::F32,
+            "double" | "float64" | "float64_t" => TypeRef::F64,
+            "string" => TypeRef::String,
+            _ => return None,
+        };
+        Some(type_ref)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DataType {
+    pub name: String,
+    pub kind: DataTypeKind,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DataTypeKind {
+    Struct(Vec<Member>),
+    /// Enumeration with an integer base type.
+    Enum { base: TypeRef, enumerators: Vec<Enumerator> },
+    /// Other name of a type, e.g. of a vector.
+    Alias(TypeRef),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Member {
+    pub name: String,
+    pub type_ref: TypeRef,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Enumerator {
+    pub name: String,
+    pub value: u64,
+}
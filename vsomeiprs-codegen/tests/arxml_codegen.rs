@@ -0,0 +1,72 @@
+// SPDX-License-Identifier: MPL-2.0
+//
+// Copyright (C) 2024 Alexander Seifarth
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use vsomeiprs_codegen::{generate_rust, parse_arxml, CodegenError, DataTypeKind, Enumerator, TypeRef};
+
+/// Test: arxml-codegen
+///
+/// Reads the service interface and data types of `vehicle.arxml`, checks the model and that the
+/// generated code is valid Rust with the expected proxy, service trait and skeleton items.
+#[test]
+pub fn main() {
+    let model = parse_arxml(include_str!("vehicle.arxml")).unwrap();
+
+    let names = model.data_types.iter().map(|data_type| data_type.name.as_str()).collect::<Vec<_>>();
+    assert_eq!(names, ["DriveMode", "Speed", "Wheels", "Trip", "SpeedList"]);
+    assert_eq!(model.data_type("DriveMode").unwrap().kind, DataTypeKind::Enum {
+        base: TypeRef::U8,
+        enumerators: vec![
+            Enumerator { name: "ECO".to_string(), value: 0 },
+            Enumerator { name: "SPORT".to_string(), value: 1 },
+            Enumerator { name: "OFF_ROAD".to_string(), value: 0x10 },
+        ],
+    });
+    assert_eq!(model.data_type("Wheels").unwrap().kind, DataTypeKind::Alias(TypeRef::Array(Box::new(TypeRef::U16), 4)));
+    match &model.data_type("Trip").unwrap().kind {
+        DataTypeKind::Struct(members) => {
+            let types = members.iter().map(|member| &member.type_ref).collect::<Vec<_>>();
+            assert_eq!(types, [&TypeRef::String, &TypeRef::U32, &TypeRef::Named("SpeedList".to_string())]);
+        }
+        kind => panic!("unexpected {:?}", kind),
+    }
+
+    let interface = model.interface("Speedometer").unwrap();
+    assert_eq!((interface.service_id, interface.major_version, interface.minor_version), (0x1234, 1, 2));
+    let methods = interface.methods.iter()
+        .map(|method| (method.name.as_str(), method.method_id, method.fire_and_forget, method.reliable,
+                       method.in_args.len(), method.out_args.len()))
+        .collect::<Vec<_>>();
+    assert_eq!(methods, [("SetSpeedLimit", 1, false, false, 1, 0), ("StartTrip", 2, false, true, 2, 1),
+                         ("Reset", 3, true, false, 0, 0)]);
+    assert_eq!(interface.events[0].event_id, 0x8001);
+    assert_eq!(interface.events[0].type_ref, TypeRef::Named("Speed".to_string()));
+    let field = &interface.fields[0];
+    assert_eq!((field.getter_id, field.setter_id, field.notifier_id, field.reliable),
+               (Some(0x10), Some(0x11), Some(0x8002), true));
+    assert_eq!(interface.event_groups[0].event_group_id, 1);
+    assert_eq!(interface.event_groups[0].events, ["SpeedChanged", "DriveMode"]);
+
+    let code = generate_rust(&model).unwrap();
+    syn::parse_file(&code).unwrap();
+    for item in ["pub enum DriveMode", "OffRoad = 16", "pub type Speed = u16;", "pub type Wheels = [u16; 4];",
+                 "pub distance_km: u32", "pub mod speedometer", "pub struct StartTripIn",
+                 "pub const SET_SPEED_LIMIT: MethodID = MethodID::new(0x0001);",
+                 "pub const DRIVE_MODE: MethodID = MethodID::new(0x8002);",
+                 "pub async fn start_trip(", "-> Result<Trip, CallError>", "pub fn reset(&self)",
+                 "pub async fn set_drive_mode(", "pub fn with_options(self, options: RequestOptions)",
+                 "pub fn subscribe_driving(&self)", "pub trait SpeedometerService", "pub struct SpeedometerSkeleton",
+                 "pub fn notify_speed_changed(",
+                 "pub async fn run<S: SpeedometerService>(", "pub enum SpeedometerEvent",
+                 "pub fn drive_mode_field(&self) -> Field<DriveMode>",
+                 "pub fn drive_mode_field(&self, initial: DriveMode) -> FieldProvider<DriveMode>"] {
+        assert!(code.contains(item), "missing {} in\n{}", item, code);
+    }
+
+    assert!(matches!(parse_arxml("<AUTOSAR>"), Err(CodegenError::Xml(_))));
+    let unresolved = include_str!("vehicle.arxml").replace("/vehicle/types/SpeedList", "/vehicle/types/Missing");
+    assert_eq!(parse_arxml(&unresolved), Err(CodegenError::UnresolvedReference("/vehicle/types/Missing".to_string())));
+}
@@ -0,0 +1,41 @@
+// SPDX-License-Identifier: MPL-2.0
+//
+// Copyright (C) 2024 Alexander Seifarth
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::fs;
+use std::path::Path;
+use vsomeiprs_codegen::{generate, generate_rust, parse_arxml, parse_json, parse_yaml, CodegenError};
+
+/// Test: description
+///
+/// Reads the JSON and YAML descriptions of the interface of `vehicle.arxml`, checks that they
+/// result in the same model and code and the errors of invalid descriptions.
+#[test]
+pub fn main() {
+    let model = parse_arxml(include_str!("vehicle.arxml")).unwrap();
+    assert_eq!(parse_json(include_str!("vehicle.json")).unwrap(), model);
+    assert_eq!(parse_yaml(include_str!("vehicle.yaml")).unwrap(), model);
+
+    let out_dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("description");
+    let _ = fs::remove_dir_all(&out_dir);
+    fs::create_dir_all(&out_dir).unwrap();
+    let outputs = generate(Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/vehicle.*"), &out_dir).unwrap();
+    assert_eq!(outputs.len(), 3);
+    for output in outputs {
+        assert_eq!(fs::read_to_string(output).unwrap(), generate_rust(&model).unwrap());
+    }
+
+    assert!(matches!(parse_json("{ \"types\": [ { \"name\": \"Speed\" } ] }"), Err(CodegenError::Missing { .. })));
+    assert!(matches!(parse_json("{ \"interface\": [] }"), Err(CodegenError::Json(_))));
+    assert!(matches!(parse_yaml("types: [ { name: Speed, type: u16"), Err(CodegenError::Yaml(_))));
+    assert_eq!(parse_yaml("types: [ { name: Trip, struct: [ { name: speed, type: Speed } ] } ]"),
+               Err(CodegenError::UnresolvedReference("Speed".to_string())));
+    assert_eq!(parse_yaml("types: [ { name: Mode, enum: f32 } ]"),
+               Err(CodegenError::InvalidValue { element: "enum", value: "f32".to_string() }));
+    let unknown_event = include_str!("vehicle.yaml").replace("events: [ SpeedChanged, DriveMode ]",
+                                                              "events: [ SpeedChanged, Gear ]");
+    assert_eq!(parse_yaml(&unknown_event), Err(CodegenError::UnresolvedReference("Speedometer/Gear".to_string())));
+}
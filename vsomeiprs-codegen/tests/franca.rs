@@ -0,0 +1,58 @@
+// SPDX-License-Identifier: MPL-2.0
+//
+// Copyright (C) 2024 Alexander Seifarth
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use vsomeiprs_codegen::{generate_rust_with, parse_arxml, parse_franca, CodegenError, GenerateOptions,
+                        ProfileOptions};
+
+const FIDL: &str = include_str!("franca/vehicle.fidl");
+const FDEPL: &str = include_str!("franca/vehicle.fdepl");
+
+/// Test: franca
+///
+/// Reads the Franca IDL and deployment of the interface of `vehicle.arxml`, checks that they
+/// result in the same model apart from the array `Wheels` and the event group name, generates
+/// code with options and checks the errors of invalid documents.
+#[test]
+pub fn main() {
+    let mut model = parse_arxml(include_str!("vehicle.arxml")).unwrap();
+    model.data_types.retain(|data_type| data_type.name != "Wheels");
+    model.interfaces[0].event_groups[0].name = "EventGroup1".to_string();
+    assert_eq!(parse_franca(&[FIDL], &[FDEPL]).unwrap(), model);
+    assert!(parse_franca(&[FIDL], &[]).unwrap().interfaces.is_empty());
+
+    let options = GenerateOptions {
+        module: Some("vehicle".to_string()),
+        skeletons: false,
+        profile: Some(ProfileOptions { string_length: 16, little_endian: true, ..Default::default() }),
+        ..Default::default()
+    };
+    let code = generate_rust_with(&model, &options).unwrap();
+    assert!(code.contains("pub mod vehicle {"));
+    assert!(code.contains("pub fn deployment_profile() -> ::vsomeiprs::codec::DeploymentProfile"));
+    assert!(code.contains("string_length: LengthWidth::U16"));
+    assert!(code.contains("byte_order: ByteOrder::LittleEndian"));
+    assert!(code.contains(".with_profile(deployment_profile())"));
+    assert!(code.contains("pub struct SpeedometerProxy"));
+    assert!(!code.contains("SpeedometerSkeleton"));
+    let options = GenerateOptions { proxies: false, ..Default::default() };
+    let code = generate_rust_with(&model, &options).unwrap();
+    assert!(!code.contains("SpeedometerProxy") && code.contains("pub struct SpeedometerSkeleton"));
+    assert!(!code.contains("deployment_profile"));
+    let options = GenerateOptions { profile: Some(ProfileOptions { array_length: 12, ..Default::default() }),
+                                    ..Default::default() };
+    assert_eq!(generate_rust_with(&model, &options),
+               Err(CodegenError::InvalidValue { element: "length width", value: "12".to_string() }));
+
+    assert_eq!(parse_franca(&["package vehicle\ninterface Speedometer {\n    attribute UInt8\n}"], &[]),
+               Err(CodegenError::Franca("line 4: unexpected Symbol('}')".to_string())));
+    assert_eq!(parse_franca(&[&FIDL.replace("Speed limit", "Distance limit")], &[FDEPL]),
+               Err(CodegenError::UnresolvedReference("Distance".to_string())));
+    assert_eq!(parse_franca(&[FIDL], &[&FDEPL.replace("SomeIpMethodID = 3", "")]),
+               Err(CodegenError::Missing { element: "SomeIpMethodID", parent: "Reset".to_string() }));
+    assert_eq!(parse_franca(&[FIDL], &[&FDEPL.replace("EnumBackingType = UInt8", "EnumBackingType = Float")]),
+               Err(CodegenError::InvalidValue { element: "EnumBackingType", value: "Float".to_string() }));
+}
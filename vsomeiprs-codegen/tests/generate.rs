@@ -0,0 +1,37 @@
+// SPDX-License-Identifier: MPL-2.0
+//
+// Copyright (C) 2024 Alexander Seifarth
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::fs;
+use std::path::Path;
+use vsomeiprs_codegen::{generate, generate_rust, parse_arxml, CodegenError};
+
+/// Test: generate
+///
+/// Generates the code of the ARXML files of the test directory as a build script would and checks
+/// the file naming, that unchanged files are not written again and the errors of missing files.
+#[test]
+pub fn main() {
+    let out_dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("generate");
+    let _ = fs::remove_dir_all(&out_dir);
+    fs::create_dir_all(&out_dir).unwrap();
+    let pattern = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/*.arxml");
+
+    let outputs = generate(&pattern, &out_dir).unwrap();
+    assert_eq!(outputs, [out_dir.join("vehicle.rs")]);
+    let expected = generate_rust(&parse_arxml(include_str!("vehicle.arxml")).unwrap()).unwrap();
+    assert_eq!(fs::read_to_string(&outputs[0]).unwrap(), expected);
+
+    let modified = fs::metadata(&outputs[0]).unwrap().modified().unwrap();
+    std::thread::sleep(std::time::Duration::from_millis(20));
+    assert_eq!(generate(&pattern, &out_dir).unwrap(), outputs);
+    assert_eq!(fs::metadata(&outputs[0]).unwrap().modified().unwrap(), modified);
+
+    let no_match = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/*.xml");
+    assert_eq!(generate(no_match, &out_dir), Ok(vec![]));
+    let missing = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/missing.arxml");
+    assert!(matches!(generate(missing, &out_dir), Err(CodegenError::Io(_))));
+}
@@ -0,0 +1,597 @@
+// SPDX-License-Identifier: MPL-2.0
+//
+// Copyright (C) 2024 Alexander Seifarth
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Derive macros for the traits of `vsomeiprs::codec`, re-exported there with the `derive`
+//! feature. See `vsomeiprs::codec` for the supported types and `#[someip(..)]` attributes.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::{parse_macro_input, parse_quote, Attribute, Data, DataEnum, DeriveInput, Error, Fields, GenericArgument,
+          Generics, Ident, LitBool, LitInt, LitStr, PathArguments, Result, Type};
+
+#[proc_macro_derive(SomeipSerialize, attributes(someip))]
+pub fn derive_serialize(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_serialize(&input).unwrap_or_else(Error::into_compile_error).into()
+}
+
+#[proc_macro_derive(SomeipDeserialize, attributes(someip))]
+pub fn derive_deserialize(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_deserialize(&input).unwrap_or_else(Error::into_compile_error).into()
+}
+
+/// Deployment given by the `#[someip(..)]` attributes of a type or member.
+#[derive(Default)]
+struct Attrs {
+    length_width: Option<TokenStream2>,
+    align: Option<usize>,
+    tlv: bool,
+    id: Option<u16>,
+    encoding: Option<TokenStream2>,
+    byte_order: Option<TokenStream2>,
+    bom: Option<bool>,
+    terminator: Option<bool>,
+    selector: Option<u32>,
+    unknown: bool,
+    selector_width: Option<TokenStream2>,
+    bitfield: Option<u32>,
+    bits: Option<u32>,
+    offset: Option<u32>,
+}
+
+impl Attrs {
+    /// Returns the `StringDeployment` expression if the member has a string deployment, taking
+    /// the settings not given from the profile of `codec` (the encoder or decoder).
+    fn string_deployment(&self, codec: &Ident) -> Option<TokenStream2> {
+        if self.encoding.is_none() && self.bom.is_none() && self.terminator.is_none() {
+            return None;
+        }
+        let encoding = self.encoding.as_ref().map(|encoding| quote!(encoding: #encoding,));
+        let bom = self.bom.map(|bom| quote!(bom: #bom,));
+        let terminator = self.terminator.map(|terminator| quote!(terminator: #terminator,));
+        Some(quote!(::vsomeiprs::codec::StringDeployment { #encoding #bom #terminator ..#codec.profile().string }))
+    }
+}
+
+fn parse_attrs(attrs: &[Attribute]) -> Result<Attrs> {
+    let mut result = Attrs::default();
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("someip")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("length_width") {
+                let width = match meta.value()?.parse::<LitInt>()?.base10_parse::<u8>()? {
+                    0 => quote!(None),
+                    1 => quote!(U8),
+                    2 => quote!(U16),
+                    4 => quote!(U32),
+                    _ => return Err(meta.error("length_width must be 0, 1, 2 or 4")),
+                };
+                result.length_width = Some(quote!(::vsomeiprs::codec::LengthWidth::#width));
+                Ok(())
+            } else if meta.path.is_ident("align") {
+                result.align = Some(meta.value()?.parse::<LitInt>()?.base10_parse()?);
+                Ok(())
+            } else if meta.path.is_ident("encoding") {
+                let encoding = match meta.value()?.parse::<LitStr>()?.value().as_str() {
+                    "utf8" => quote!(Utf8),
+                    "utf16le" => quote!(Utf16Le),
+                    "utf16be" => quote!(Utf16Be),
+                    _ => return Err(meta.error("encoding must be \"utf8\", \"utf16le\" or \"utf16be\"")),
+                };
+                result.encoding = Some(quote!(::vsomeiprs::codec::StringEncoding::#encoding));
+                Ok(())
+            } else if meta.path.is_ident("byte_order") {
+                let byte_order = match meta.value()?.parse::<LitStr>()?.value().as_str() {
+                    "big_endian" => quote!(BigEndian),
+                    "little_endian" => quote!(LittleEndian),
+                    _ => return Err(meta.error("byte_order must be \"big_endian\" or \"little_endian\"")),
+                };
+                result.byte_order = Some(quote!(::vsomeiprs::codec::ByteOrder::#byte_order));
+                Ok(())
+            } else if meta.path.is_ident("bom") {
+                result.bom = Some(meta.value()?.parse::<LitBool>()?.value);
+                Ok(())
+            } else if meta.path.is_ident("terminator") {
+                result.terminator = Some(meta.value()?.parse::<LitBool>()?.value);
+                Ok(())
+            } else if meta.path.is_ident("selector") {
+                result.selector = Some(meta.value()?.parse::<LitInt>()?.base10_parse()?);
+                Ok(())
+            } else if meta.path.is_ident("selector_width") {
+                let width = match meta.value()?.parse::<LitInt>()?.base10_parse::<u8>()? {
+                    1 => quote!(U8),
+                    2 => quote!(U16),
+                    4 => quote!(U32),
+                    _ => return Err(meta.error("selector_width must be 1, 2 or 4")),
+                };
+                result.selector_width = Some(quote!(::vsomeiprs::codec::LengthWidth::#width));
+                Ok(())
+            } else if meta.path.is_ident("bitfield") {
+                let bits = meta.value()?.parse::<LitInt>()?.base10_parse()?;
+                if ![8, 16, 32, 64].contains(&bits) {
+                    return Err(meta.error("bitfield must be 8, 16, 32 or 64"));
+                }
+                result.bitfield = Some(bits);
+                Ok(())
+            } else if meta.path.is_ident("bits") {
+                result.bits = Some(meta.value()?.parse::<LitInt>()?.base10_parse()?);
+                Ok(())
+            } else if meta.path.is_ident("offset") {
+                result.offset = Some(meta.value()?.parse::<LitInt>()?.base10_parse()?);
+                Ok(())
+            } else if meta.path.is_ident("unknown") {
+                result.unknown = true;
+                Ok(())
+            } else if meta.path.is_ident("tlv") {
+                result.tlv = true;
+                Ok(())
+            } else if meta.path.is_ident("id") {
+                let id = meta.value()?.parse::<LitInt>()?.base10_parse()?;
+                if id > MAX_DATA_ID {
+                    return Err(meta.error("data ID must not exceed 0xfff"));
+                }
+                result.id = Some(id);
+                Ok(())
+            } else {
+                Err(meta.error("unknown someip attribute"))
+            }
+        })?;
+    }
+    Ok(result)
+}
+
+/// Largest data ID of a TLV member.
+const MAX_DATA_ID: u16 = 0x0fff;
+
+/// Returns the `#[someip(..)]` attributes of the members of a TLV encoded struct, checking the
+/// data IDs.
+fn tlv_member_attrs(fields: &Fields) -> Result<Vec<Attrs>> {
+    let mut result: Vec<Attrs> = Vec::new();
+    for field in fields {
+        let attrs = parse_attrs(&field.attrs)?;
+        match attrs.id {
+            None => return Err(Error::new_spanned(field, "members of tlv structs need an id")),
+            Some(id) if result.iter().any(|other| other.id == Some(id)) =>
+                return Err(Error::new_spanned(field, "duplicate data ID")),
+            Some(_) => {}
+        }
+        if attrs.align.is_some() || attrs.byte_order.is_some() {
+            return Err(Error::new_spanned(field, "align and byte_order are not supported in tlv members"));
+        }
+        result.push(attrs);
+    }
+    Ok(result)
+}
+
+/// Returns the offset and width of the members of a bitfield struct with a carrier of the given
+/// bits, checking that they fit into it without overlapping.
+fn bitfield_members(fields: &Fields, carrier_bits: u32) -> Result<Vec<(u32, u32)>> {
+    let mut result: Vec<(u32, u32)> = Vec::new();
+    let mut next = 0;
+    for field in fields {
+        let attrs = parse_attrs(&field.attrs)?;
+        let is_bool = matches!(&field.ty, Type::Path(path) if path.path.is_ident("bool"));
+        let width = match attrs.bits {
+            Some(width) if width > 0 => width,
+            None if is_bool => 1,
+            _ => return Err(Error::new_spanned(field, "bitfield members need bits of at least 1")),
+        };
+        let offset = attrs.offset.unwrap_or(next);
+        if offset + width > carrier_bits {
+            return Err(Error::new_spanned(field, "member exceeds the bitfield"));
+        }
+        if result.iter().any(|(other, other_width)| offset < other + other_width && *other < offset + width) {
+            return Err(Error::new_spanned(field, "member overlaps another member"));
+        }
+        result.push((offset, width));
+        next = offset + width;
+    }
+    Ok(result)
+}
+
+/// Returns `T` if the type is `Option<T>`.
+fn option_inner(ty: &Type) -> Option<&Type> {
+    let Type::Path(path) = ty else { return None };
+    let segment = path.path.segments.last()?;
+    if segment.ident != "Option" {
+        return None;
+    }
+    let PathArguments::AngleBracketed(arguments) = &segment.arguments else { return None };
+    match arguments.args.first()? {
+        GenericArgument::Type(inner) => Some(inner),
+        _ => None,
+    }
+}
+
+/// Variant of a union with the type of its member (`None` for fieldless variants) and selector.
+struct UnionVariant<'a> {
+    ident: &'a Ident,
+    ty: Option<&'a Type>,
+    selector: u32,
+}
+
+/// Returns the variants of a union, `None` if the enum is fieldless.
+fn union_variants(data: &DataEnum) -> Result<Option<Vec<UnionVariant<'_>>>> {
+    let mut fieldless = true;
+    for variant in &data.variants {
+        fieldless &= matches!(variant.fields, Fields::Unit) || parse_attrs(&variant.attrs)?.unknown;
+    }
+    if fieldless {
+        return Ok(None);
+    }
+    let mut result: Vec<UnionVariant> = Vec::new();
+    for (index, variant) in data.variants.iter().enumerate() {
+        let ty = match &variant.fields {
+            Fields::Unit => None,
+            Fields::Unnamed(fields) if fields.unnamed.len() == 1 => Some(&fields.unnamed[0].ty),
+            _ => return Err(Error::new_spanned(variant, "union variants need a single unnamed member or none")),
+        };
+        let selector = parse_attrs(&variant.attrs)?.selector.unwrap_or(index as u32 + 1);
+        if result.iter().any(|other| other.selector == selector) {
+            return Err(Error::new_spanned(variant, "duplicate selector"));
+        }
+        result.push(UnionVariant { ident: &variant.ident, ty, selector });
+    }
+    Ok(Some(result))
+}
+
+/// Enumerator of a fieldless enum with the expression of its value.
+struct Enumerator<'a> {
+    ident: &'a Ident,
+    value: TokenStream2,
+}
+
+/// Returns the enumerators of a fieldless enum and the variant keeping unknown values, if any.
+/// The values follow the discriminants of the variants.
+fn enumerators(data: &DataEnum) -> Result<(Vec<Enumerator<'_>>, Option<&Ident>)> {
+    let mut result = Vec::new();
+    let mut unknown = None;
+    let mut next = quote!(0);
+    for variant in &data.variants {
+        let value = match &variant.discriminant {
+            Some((_, discriminant)) => quote!(#discriminant),
+            None => next,
+        };
+        next = quote!((#value) + 1);
+        if !parse_attrs(&variant.attrs)?.unknown {
+            result.push(Enumerator { ident: &variant.ident, value });
+            continue;
+        }
+        match &variant.fields {
+            Fields::Unnamed(fields) if fields.unnamed.len() == 1 && unknown.is_none() => unknown = Some(&variant.ident),
+            Fields::Unnamed(fields) if fields.unnamed.len() == 1 =>
+                return Err(Error::new_spanned(variant, "duplicate unknown variant")),
+            _ => return Err(Error::new_spanned(variant, "the unknown variant needs a single unnamed member")),
+        }
+    }
+    Ok((result, unknown))
+}
+
+/// Returns the generics with `bound` added to all type parameters.
+fn add_bounds(generics: &Generics, bound: TokenStream2) -> Generics {
+    let mut generics = generics.clone();
+    for param in generics.type_params_mut() {
+        param.bounds.push(parse_quote!(#bound));
+    }
+    generics
+}
+
+/// Returns the integer type of the `#[repr]` attribute of a fieldless enum.
+fn enum_repr(input: &DeriveInput) -> Result<Ident> {
+    let mut repr = None;
+    for attr in input.attrs.iter().filter(|attr| attr.path().is_ident("repr")) {
+        attr.parse_nested_meta(|meta| {
+            if let Some(ident) = meta.path.get_ident() {
+                if ["u8", "u16", "u32", "u64", "i8", "i16", "i32", "i64"].contains(&ident.to_string().as_str()) {
+                    repr = Some(ident.clone());
+                }
+            }
+            Ok(())
+        })?;
+    }
+    repr.ok_or_else(|| Error::new_spanned(&input.ident, "enums need a #[repr] integer type"))
+}
+
+fn expand_serialize(input: &DeriveInput) -> Result<TokenStream2> {
+    let attrs = parse_attrs(&input.attrs)?;
+    if attrs.align.is_some() {
+        return Err(Error::new_spanned(&input.ident, "align is supported on members only"));
+    }
+    let mut items = TokenStream2::new();
+    let mut length_width = attrs.length_width.clone();
+    // structs without own length field take it from the profile
+    let profile_length = matches!(input.data, Data::Struct(_)) && attrs.bitfield.is_none() && length_width.is_none();
+    let name = &input.ident;
+    let body = match &input.data {
+        Data::Struct(data) if attrs.bitfield.is_some() => {
+            let carrier_bits = attrs.bitfield.expect("checked by the match guard");
+            let carrier = format_ident!("u{}", carrier_bits);
+            if attrs.length_width.is_none() {
+                items = quote!(const BASE_SIZE: Option<usize> = Some(#carrier_bits as usize / 8););
+            }
+            let members = data.fields.members();
+            let (offsets, widths): (Vec<_>, Vec<_>) = bitfield_members(&data.fields, carrier_bits)?.into_iter().unzip();
+            quote!(
+                let mut carrier = 0u64;
+                #(carrier |= ::vsomeiprs::codec::pack_bits(stringify!(#name), &self.#members, #offsets, #widths)?;)*
+                ::vsomeiprs::codec::SomeipSerialize::serialize(&(carrier as #carrier), encoder)
+            )
+        }
+        Data::Struct(data) if attrs.tlv => {
+            let mut statements = Vec::new();
+            let member_attrs = tlv_member_attrs(&data.fields)?;
+            for ((field, field_attrs), member) in data.fields.iter().zip(member_attrs).zip(data.fields.members()) {
+                let id = field_attrs.id.expect("checked by tlv_member_attrs()");
+                let width = field_attrs.length_width.clone()
+                    .unwrap_or(quote!(::vsomeiprs::codec::LengthWidth::None));
+                let write = |value| match field_attrs.string_deployment(&format_ident!("encoder")) {
+                    Some(deployment) => quote!(encoder.put_tagged_string(#id, #value, &#deployment, #width)?;),
+                    None => quote!(encoder.put_tagged(#id, #value, #width)?;),
+                };
+                statements.push(match option_inner(&field.ty) {
+                    Some(_) => {
+                        let write = write(quote!(value));
+                        quote!(if let Some(value) = &self.#member { #write })
+                    }
+                    None => write(quote!(&self.#member)),
+                });
+            }
+            quote!(#(#statements)* Ok(()))
+        }
+        Data::Struct(data) => {
+            let mut statements = Vec::new();
+            for (field, member) in data.fields.iter().zip(data.fields.members()) {
+                if option_inner(&field.ty).is_some() {
+                    return Err(Error::new_spanned(field, "optional members require a tlv struct"));
+                }
+                let field_attrs = parse_attrs(&field.attrs)?;
+                let align = field_attrs.align.map(|align| quote!(#align))
+                    .unwrap_or(quote!(encoder.profile().alignment));
+                statements.push(quote!(encoder.align(#align);));
+                let deployment = field_attrs.string_deployment(&format_ident!("encoder"));
+                let write = match (deployment, field_attrs.length_width) {
+                    (Some(deployment), width) => {
+                        let width = width.unwrap_or(quote!(encoder.profile().string_length));
+                        quote!(encoder.put_string(&self.#member, &#deployment, #width))
+                    }
+                    (None, Some(width)) => quote!(
+                        ::vsomeiprs::codec::SomeipSerialize::serialize_with_length(&self.#member, encoder, #width)),
+                    (None, None) => quote!(::vsomeiprs::codec::SomeipSerialize::serialize(&self.#member, encoder)),
+                };
+                statements.push(match field_attrs.byte_order {
+                    Some(byte_order) => quote!(encoder.with_byte_order(#byte_order, |encoder| #write)?;),
+                    None => quote!(#write?;),
+                });
+            }
+            quote!(#(#statements)* Ok(()))
+        }
+        Data::Enum(data) if union_variants(data)?.is_some() => {
+            let variants = union_variants(data)?.expect("checked by the match guard");
+            let selector_width = attrs.selector_width.clone().unwrap_or(quote!(encoder.profile().union_selector));
+            let width = length_width.take().unwrap_or(quote!(encoder.profile().union_length));
+            let arms = variants.iter().map(|UnionVariant { ident, ty, selector }| match ty {
+                Some(_) => quote!(Self::#ident(value) => encoder.put_union(#selector, #selector_width, #width,
+                    |encoder| ::vsomeiprs::codec::SomeipSerialize::serialize(value, encoder)),),
+                None => quote!(Self::#ident => encoder.put_union(#selector, #selector_width, #width, |_| Ok(())),),
+            });
+            quote!(match self { #(#arms)* })
+        }
+        Data::Enum(data) => {
+            let repr = enum_repr(input)?;
+            let (enumerators, unknown) = enumerators(data)?;
+            let (variants, values): (Vec<_>, Vec<_>) = enumerators.into_iter()
+                .map(|Enumerator { ident, value }| (ident, value))
+                .unzip();
+            let unknown = unknown.map(|unknown| quote!(Self::#unknown(value) => *value,));
+            if attrs.length_width.is_none() {
+                items = quote!(const BASE_SIZE: Option<usize> = Some(::core::mem::size_of::<#repr>()););
+            }
+            quote!(
+                let value: #repr = match self { #(Self::#variants => #values,)* #unknown };
+                ::vsomeiprs::codec::SomeipSerialize::serialize(&value, encoder)
+            )
+        }
+        Data::Union(_) => return Err(Error::new_spanned(&input.ident, "unions are not supported")),
+    };
+    let body = match &attrs.byte_order {
+        Some(byte_order) => quote!(encoder.with_byte_order(#byte_order, |encoder| { #body })),
+        None => body,
+    };
+    let body = match length_width {
+        Some(width) => quote!(encoder.with_length(#width, |encoder| { #body })),
+        None if profile_length => {
+            // a length field given by the containing type replaces the one of the profile
+            items = quote!(
+                fn serialize_with_length(&self, encoder: &mut ::vsomeiprs::codec::Encoder,
+                                         width: ::vsomeiprs::codec::LengthWidth)
+                    -> ::core::result::Result<(), ::vsomeiprs::codec::CodecError>
+                {
+                    encoder.with_length(width, |encoder| { #body })
+                }
+            );
+            quote!(
+                let width = encoder.profile().struct_length;
+                ::vsomeiprs::codec::SomeipSerialize::serialize_with_length(self, encoder, width)
+            )
+        }
+        None => body,
+    };
+
+    let generics = add_bounds(&input.generics, quote!(::vsomeiprs::codec::SomeipSerialize));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::vsomeiprs::codec::SomeipSerialize for #name #ty_generics #where_clause {
+            #items
+
+            fn serialize(&self, encoder: &mut ::vsomeiprs::codec::Encoder)
+                -> ::core::result::Result<(), ::vsomeiprs::codec::CodecError>
+            {
+                #body
+            }
+        }
+    })
+}
+
+fn expand_deserialize(input: &DeriveInput) -> Result<TokenStream2> {
+    let attrs = parse_attrs(&input.attrs)?;
+    if attrs.align.is_some() {
+        return Err(Error::new_spanned(&input.ident, "align is supported on members only"));
+    }
+    let name = &input.ident;
+    let mut length_width = attrs.length_width.clone();
+    let profile_length = matches!(input.data, Data::Struct(_)) && attrs.bitfield.is_none() && length_width.is_none();
+    let mut items = TokenStream2::new();
+    let body = match &input.data {
+        Data::Struct(data) if attrs.bitfield.is_some() => {
+            let carrier_bits = attrs.bitfield.expect("checked by the match guard");
+            let carrier = format_ident!("u{}", carrier_bits);
+            let members = data.fields.members();
+            let (offsets, widths): (Vec<_>, Vec<_>) = bitfield_members(&data.fields, carrier_bits)?.into_iter().unzip();
+            quote!(
+                let carrier = <#carrier as ::vsomeiprs::codec::SomeipDeserialize>::deserialize(decoder)? as u64;
+                Ok(Self { #(#members: ::vsomeiprs::codec::unpack_bits(carrier, #offsets, #widths)),* })
+            )
+        }
+        Data::Struct(data) if attrs.tlv => {
+            let member_attrs = tlv_member_attrs(&data.fields)?;
+            let ids: Vec<_> = member_attrs.iter()
+                .map(|attrs| attrs.id.expect("checked by tlv_member_attrs()"))
+                .collect();
+            let values: Vec<_> = (0..ids.len()).map(|index| format_ident!("field{}", index)).collect();
+            let reads = data.fields.iter().zip(&member_attrs).map(|(field, attrs)| {
+                let ty = option_inner(&field.ty).unwrap_or(&field.ty);
+                match attrs.string_deployment(&format_ident!("decoder")) {
+                    Some(deployment) => quote!(decoder.get_tagged_string(tag, &#deployment)?),
+                    None => quote!(decoder.get_tagged::<#ty>(tag)?),
+                }
+            });
+            let members = data.fields.members();
+            let results = data.fields.iter().zip(&ids).zip(&values).map(|((field, id), value)| {
+                match option_inner(&field.ty) {
+                    Some(_) => quote!(#value),
+                    None => quote!(#value.ok_or(::vsomeiprs::codec::CodecError::MissingMember {
+                        type_name: stringify!(#name), data_id: #id })?),
+                }
+            });
+            quote!(
+                #(let mut #values = None;)*
+                while !decoder.is_empty() {
+                    let tag = decoder.get_tag()?;
+                    match tag.data_id {
+                        #(#ids => #values = Some(#reads),)*
+                        _ => decoder.skip_tagged(tag)?,
+                    }
+                }
+                Ok(Self { #(#members: #results),* })
+            )
+        }
+        Data::Struct(data) => {
+            let mut statements = Vec::new();
+            let mut values = Vec::new();
+            for (index, field) in data.fields.iter().enumerate() {
+                if option_inner(&field.ty).is_some() {
+                    return Err(Error::new_spanned(field, "optional members require a tlv struct"));
+                }
+                let field_attrs = parse_attrs(&field.attrs)?;
+                let align = field_attrs.align.map(|align| quote!(#align))
+                    .unwrap_or(quote!(decoder.profile().alignment));
+                statements.push(quote!(decoder.align(#align)?;));
+                let value = format_ident!("field{}", index);
+                let ty = &field.ty;
+                let deployment = field_attrs.string_deployment(&format_ident!("decoder"));
+                let read = match (deployment, field_attrs.length_width) {
+                    (Some(deployment), width) => {
+                        let width = width.unwrap_or(quote!(decoder.profile().string_length));
+                        quote!(decoder.get_string(&#deployment, #width))
+                    }
+                    (None, Some(width)) => quote!(
+                        <#ty as ::vsomeiprs::codec::SomeipDeserialize>::deserialize_with_length(decoder, #width)),
+                    (None, None) => quote!(<#ty as ::vsomeiprs::codec::SomeipDeserialize>::deserialize(decoder)),
+                };
+                statements.push(match field_attrs.byte_order {
+                    Some(byte_order) => quote!(let #value = decoder.with_byte_order(#byte_order, |decoder| #read)?;),
+                    None => quote!(let #value = #read?;),
+                });
+                values.push(value);
+            }
+            let members = data.fields.members();
+            quote!(#(#statements)* Ok(Self { #(#members: #values),* }))
+        }
+        Data::Enum(data) if union_variants(data)?.is_some() => {
+            let variants = union_variants(data)?.expect("checked by the match guard");
+            let selector_width = attrs.selector_width.clone().unwrap_or(quote!(decoder.profile().union_selector));
+            let width = length_width.take().unwrap_or(quote!(decoder.profile().union_length));
+            let arms = variants.iter().map(|UnionVariant { ident, ty, selector }| match ty {
+                Some(ty) => quote!(#selector =>
+                    Ok(Self::#ident(<#ty as ::vsomeiprs::codec::SomeipDeserialize>::deserialize(decoder)?)),),
+                None => quote!(#selector => Ok(Self::#ident),),
+            });
+            quote!(
+                decoder.get_union(#selector_width, #width, |selector, decoder| match selector {
+                    #(#arms)*
+                    selector => Err(::vsomeiprs::codec::CodecError::InvalidValue {
+                        type_name: stringify!(#name), value: selector as u64 }),
+                })
+            )
+        }
+        Data::Enum(data) => {
+            let repr = enum_repr(input)?;
+            let (enumerators, unknown) = enumerators(data)?;
+            let (variants, values): (Vec<_>, Vec<_>) = enumerators.into_iter()
+                .map(|Enumerator { ident, value }| (ident, value))
+                .unzip();
+            let otherwise = match unknown {
+                Some(unknown) => quote!(Ok(Self::#unknown(value))),
+                None => quote!(Err(::vsomeiprs::codec::CodecError::InvalidValue {
+                    type_name: stringify!(#name), value: value as u64 })),
+            };
+            quote!(
+                let value = <#repr as ::vsomeiprs::codec::SomeipDeserialize>::deserialize(decoder)?;
+                #(if value == #values { return Ok(Self::#variants); })*
+                #otherwise
+            )
+        }
+        Data::Union(_) => return Err(Error::new_spanned(name, "unions are not supported")),
+    };
+    let body = match &attrs.byte_order {
+        Some(byte_order) => quote!(decoder.with_byte_order(#byte_order, |decoder| { #body })),
+        None => body,
+    };
+    let body = match length_width {
+        Some(width) => quote!(decoder.with_length(#width, |decoder| { #body })),
+        None if profile_length => {
+            items = quote!(
+                fn deserialize_with_length(decoder: &mut ::vsomeiprs::codec::Decoder<'_>,
+                                           width: ::vsomeiprs::codec::LengthWidth)
+                    -> ::core::result::Result<Self, ::vsomeiprs::codec::CodecError>
+                {
+                    decoder.with_length(width, |decoder| { #body })
+                }
+            );
+            quote!(
+                let width = decoder.profile().struct_length;
+                <Self as ::vsomeiprs::codec::SomeipDeserialize>::deserialize_with_length(decoder, width)
+            )
+        }
+        None => body,
+    };
+
+    let generics = add_bounds(&input.generics, quote!(::vsomeiprs::codec::SomeipDeserialize));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::vsomeiprs::codec::SomeipDeserialize for #name #ty_generics #where_clause {
+            #items
+
+            fn deserialize(decoder: &mut ::vsomeiprs::codec::Decoder<'_>)
+                -> ::core::result::Result<Self, ::vsomeiprs::codec::CodecError>
+            {
+                #body
+            }
+        }
+    })
+}
@@ -0,0 +1,38 @@
+// SPDX-License-Identifier: MPL-2.0
+//
+// Copyright (C) 2024 Alexander Seifarth
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Benchmark: receiving payloads with and without copying
+//!
+//! Converts received payloads into [Payload]s, once sharing the received data as the receive
+//! path does and once copying it as it did before. The received payloads are backed by `Bytes`
+//! instead of vsomeip payload objects, so no routing manager is needed.
+//! Run with `cargo bench --bench receive`.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+use bytes::Bytes;
+use vsomeiprs::{Payload, VSomeipPayload};
+
+const ITERATIONS: u32 = 100_000;
+
+fn main() {
+    for size in [64, 1024, 64 * 1024] {
+        let received = VSomeipPayload::from(Bytes::from(vec![0x5a; size]));
+        let shared = measure(|| Payload::from(&received));
+        let copied = measure(|| Payload::from(Bytes::copy_from_slice(received.as_bytes_ref())));
+        println!("{:>6} bytes: shared {:>10?} copied {:>10?} per payload", size, shared, copied);
+    }
+}
+
+/// Returns the mean duration of the conversion.
+fn measure(mut convert: impl FnMut() -> Payload) -> Duration {
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        black_box(convert());
+    }
+    start.elapsed() / ITERATIONS
+}
@@ -0,0 +1,134 @@
+// SPDX-License-Identifier: MPL-2.0
+//
+// Copyright (C) 2024 Alexander Seifarth
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use tokio::sync::{broadcast, watch};
+use super::{Availability, InstanceID, ServiceID, ANY_INSTANCE};
+
+/// Number of availability changes buffered for each broadcast receiver.
+const BROADCAST_CAPACITY: usize = 64;
+
+/// Availability watch and broadcast channels of an application, shared with its callbacks.
+pub(crate) struct AvailabilityWatches {
+    state: Mutex<WatchState>,
+    events: broadcast::Sender<Availability>,
+}
+
+#[derive(Default)]
+struct WatchState {
+    watches: HashMap<(ServiceID, InstanceID), watch::Sender<bool>>,
+    // available instances per service, the [ANY_INSTANCE] watch is up while one of them is
+    available: HashMap<ServiceID, HashSet<InstanceID>>,
+}
+
+impl WatchState {
+    fn is_available(&self, service_id: ServiceID, instance_id: InstanceID) -> bool {
+        self.available.get(&service_id)
+            .is_some_and(|instances| instance_id == ANY_INSTANCE || instances.contains(&instance_id))
+    }
+}
+
+impl Default for AvailabilityWatches {
+    fn default() -> Self {
+        AvailabilityWatches { state: Mutex::default(), events: broadcast::channel(BROADCAST_CAPACITY).0 }
+    }
+}
+
+impl AvailabilityWatches {
+    /// Returns a receiver of the availability of the service instance. Receivers of the same
+    /// instance share one channel.
+    pub(crate) fn watch(&self, service_id: ServiceID, instance_id: InstanceID) -> watch::Receiver<bool> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let avail = state.is_available(service_id, instance_id);
+        state.watches.entry((service_id, instance_id))
+            .or_insert_with(|| watch::channel(avail).0)
+            .subscribe()
+    }
+
+    /// Returns a receiver of all availability changes reported after the call.
+    pub(crate) fn subscribe(&self) -> broadcast::Receiver<Availability> {
+        self.events.subscribe()
+    }
+
+    /// Updates the watches of the instance and of the service's [ANY_INSTANCE] watch and
+    /// broadcasts the change. The [ANY_INSTANCE] watch stays up while any instance is available.
+    /// Channels without receivers left are removed.
+    pub(crate) fn update(&self, availability: Availability) {
+        let Availability { service_id, instance_id, avail, .. } = availability;
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let instances = state.available.entry(service_id).or_default();
+        if avail {
+            instances.insert(instance_id);
+        } else {
+            instances.remove(&instance_id);
+        }
+        let any = !instances.is_empty();
+        if !any {
+            state.available.remove(&service_id);
+        }
+        for (key, avail) in [((service_id, instance_id), avail), ((service_id, ANY_INSTANCE), any)] {
+            if let Some(sender) = state.watches.get(&key) {
+                if sender.send(avail).is_err() {
+                    state.watches.remove(&key);
+                }
+            }
+        }
+        // fails only if there is no receiver
+        let _ = self.events.send(availability);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::InterfaceVersion;
+
+    fn availability(instance: u16, avail: bool) -> Availability {
+        Availability { service_id: ServiceID(1), instance_id: InstanceID(instance), avail,
+            version: InterfaceVersion::make_any() }
+    }
+
+    #[test]
+    fn watch_test() {
+        let watches = AvailabilityWatches::default();
+        let instance = watches.watch(ServiceID(1), InstanceID(1));
+        let any = watches.watch(ServiceID(1), ANY_INSTANCE);
+        let other = watches.watch(ServiceID(1), InstanceID(2));
+        assert!(!*instance.borrow());
+
+        let mut events = watches.subscribe();
+        watches.update(availability(1, true));
+        assert!(*instance.borrow());
+        assert!(*any.borrow());
+        assert!(!*other.borrow());
+        assert_eq!(events.try_recv(), Ok(availability(1, true)));
+
+        drop(other);
+        watches.update(availability(2, true));
+        assert!(!watches.state.lock().unwrap().watches.contains_key(&(ServiceID(1), InstanceID(2))));
+    }
+
+    #[test]
+    fn any_instance_test() {
+        let watches = AvailabilityWatches::default();
+        let any = watches.watch(ServiceID(1), ANY_INSTANCE);
+        watches.update(availability(1, true));
+        watches.update(availability(2, true));
+        assert!(*any.borrow());
+
+        // instance 1 is still up
+        watches.update(availability(2, false));
+        assert!(*any.borrow());
+        assert!(*watches.watch(ServiceID(1), InstanceID(1)).borrow());
+        assert!(!*watches.watch(ServiceID(1), InstanceID(2)).borrow());
+
+        watches.update(availability(1, false));
+        assert!(!*any.borrow());
+        assert!(!*watches.watch(ServiceID(1), ANY_INSTANCE).borrow());
+    }
+}
@@ -0,0 +1,179 @@
+// SPDX-License-Identifier: MPL-2.0
+//
+// Copyright (C) 2024 Alexander Seifarth
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+use tokio::sync::mpsc::{Receiver, UnboundedReceiver};
+use tokio::time::timeout;
+use super::{Configuration, SinkSender, VSomeipApplication, VSomeipError, VSomeipMessage};
+
+/// Number of configurations generated by the process, keeps their file names unique.
+static GENERATED: AtomicUsize = AtomicUsize::new(0);
+
+/// Source of the vsomeip configuration of the application.
+enum ConfigSource {
+    /// vsomeip's default lookup (environment, default paths)
+    Default,
+    Generated(Configuration),
+    File(PathBuf),
+}
+
+/// Builder for a [VSomeipApplication] with creation options, see [VSomeipApplication::builder()].
+///
+/// The configuration options only affect the application being built, the configuration is
+/// passed to vsomeip when the application is created, not via the environment.
+/// [ApplicationBuilder::build_bounded()] delivers the messages into a channel of limited capacity.
+/// ```rust,no_run
+/// use std::time::Duration;
+/// use vsomeiprs::{Configuration, VSomeipApplication};
+///
+/// async fn setup() {
+///     let (app, recv) = VSomeipApplication::builder("client")
+///         .configuration(Configuration::new().logging("warning", true))
+///         .io_threads(2)
+///         .wait_registered(Duration::from_secs(5))
+///         .build_bounded(1024).await
+///         .expect("application registered");
+/// }
+/// ```
+pub struct ApplicationBuilder {
+    name: String,
+    config: ConfigSource,
+    io_threads: Option<u8>,
+    wait_registered: Option<Duration>,
+}
+
+impl ApplicationBuilder {
+    pub(crate) fn new(name: &str) -> Self {
+        ApplicationBuilder { name: name.to_string(), config: ConfigSource::Default, io_threads: None,
+            wait_registered: None }
+    }
+
+    /// Uses the given configuration, it is written to a temporary file while the application is
+    /// created. Replaces a configuration file set before.
+    pub fn configuration(mut self, configuration: Configuration) -> Self {
+        self.config = ConfigSource::Generated(configuration);
+        self
+    }
+
+    /// Uses the configuration file (or directory) `path`.
+    /// Replaces a configuration set before.
+    pub fn configuration_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.config = ConfigSource::File(path.into());
+        self
+    }
+
+    /// Sets the number of vsomeip i/o threads of the application.
+    /// This is part of the generated configuration, so it requires
+    /// [ApplicationBuilder::configuration()], building fails otherwise.
+    pub fn io_threads(mut self, threads: u8) -> Self {
+        self.io_threads = Some(threads);
+        self
+    }
+
+    /// Waits in [ApplicationBuilder::build()] until the application is registered at the routing
+    /// manager, failing with [VSomeipError::RegistrationTimeout] after `timeout`.
+    /// The channel is not consumed, the `RegistrationState(true)` message stays in it.
+    pub fn wait_registered(mut self, timeout: Duration) -> Self {
+        self.wait_registered = Some(timeout);
+        self
+    }
+
+    /// Creates the application with the configuration, see [VSomeipApplication::create()].
+    pub async fn build(self) -> Result<(VSomeipApplication, UnboundedReceiver<VSomeipMessage>), VSomeipError> {
+        let (sender, recv) = tokio::sync::mpsc::unbounded_channel();
+        let app = self.create(SinkSender::Channel(sender)).await?;
+        Ok((app, recv))
+    }
+
+    /// Creates the application like [ApplicationBuilder::build()], delivering its messages into
+    /// a channel of `capacity` messages. Messages arriving while it is full are dropped, so a slow
+    /// receiver does not block vsomeip's threads.
+    pub async fn build_bounded(self, capacity: usize)
+        -> Result<(VSomeipApplication, Receiver<VSomeipMessage>), VSomeipError>
+    {
+        if capacity == 0 {
+            return Err(VSomeipError::Configuration("channel capacity must not be zero".into()));
+        }
+        let (sender, recv) = tokio::sync::mpsc::channel(capacity);
+        let app = self.create(SinkSender::Bounded(sender)).await?;
+        Ok((app, recv))
+    }
+
+    async fn create(self, sink: SinkSender) -> Result<VSomeipApplication, VSomeipError> {
+        let app = match resolve_configuration(&self.name, self.config, self.io_threads)? {
+            ConfigSource::Default => VSomeipApplication::create_detached(&self.name)?,
+            ConfigSource::File(path) => VSomeipApplication::create_configured(&self.name, Some(&path))?,
+            ConfigSource::Generated(config) => {
+                let path = configuration_path();
+                config.write_to(&path)
+                    .map_err(|e| VSomeipError::Configuration(format!("{}: {}", path.display(), e)))?;
+                let app = VSomeipApplication::create_configured(&self.name, Some(&path));
+                // vsomeip reads the configuration when the application is created
+                if let Err(e) = std::fs::remove_file(&path) {
+                    log::warn!("Cannot remove configuration {}: {}", path.display(), e);
+                }
+                app?
+            }
+        };
+        app.context.attach(sink).expect("new application has no sink");
+        if let Some(timeout_time) = self.wait_registered {
+            let mut registered = app.watch_registration();
+            if !matches!(timeout(timeout_time, registered.wait_for(|registered| *registered)).await, Ok(Ok(_))) {
+                // tears the application down on a blocking task instead of dropping it here
+                app.shutdown().await;
+                return Err(VSomeipError::RegistrationTimeout);
+            }
+        }
+        Ok(app)
+    }
+}
+
+/// Returns a path for a generated configuration, unique within the system. The application name
+/// is not part of it, it may contain characters not allowed in file names.
+fn configuration_path() -> PathBuf {
+    std::env::temp_dir().join(format!("vsomeiprs-{}-{}.json", std::process::id(),
+                                      GENERATED.fetch_add(1, Ordering::Relaxed)))
+}
+
+/// Adds the i/o threads to the configuration, they can only be set in a generated one.
+fn resolve_configuration(name: &str, config: ConfigSource, io_threads: Option<u8>)
+    -> Result<ConfigSource, VSomeipError>
+{
+    match (config, io_threads) {
+        (config, None) => Ok(config),
+        (ConfigSource::Generated(config), Some(threads)) =>
+            Ok(ConfigSource::Generated(config.application_threads(name, threads))),
+        (_, Some(_)) =>
+            Err(VSomeipError::Configuration("i/o threads can only be set with a generated configuration".into())),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn resolve_configuration_test() {
+        assert!(matches!(resolve_configuration("app", ConfigSource::Default, None), Ok(ConfigSource::Default)));
+        assert!(matches!(resolve_configuration("app", ConfigSource::Generated(Configuration::new()), Some(2)),
+                         Ok(ConfigSource::Generated(_))));
+        // the default lookup or a file would be replaced by a configuration of the threads only
+        assert!(matches!(resolve_configuration("app", ConfigSource::Default, Some(2)),
+                         Err(VSomeipError::Configuration(_))));
+        assert!(matches!(resolve_configuration("app", ConfigSource::File("vsomeip.json".into()), Some(2)),
+                         Err(VSomeipError::Configuration(_))));
+    }
+
+    #[test]
+    fn configuration_path_test() {
+        let (first, second) = (configuration_path(), configuration_path());
+        assert_ne!(first, second);
+        assert_eq!(first.parent(), Some(std::env::temp_dir().as_path()));
+    }
+}
@@ -0,0 +1,135 @@
+// SPDX-License-Identifier: MPL-2.0
+//
+// Copyright (C) 2024 Alexander Seifarth
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard, RwLock};
+use bytes::Bytes;
+use super::{InstanceID, MethodID, ServiceID};
+
+type IsChanged = dyn Fn(&Bytes, &Bytes) -> bool + Send + Sync;
+
+/// Change predicates of the events of an application with the last notified values, see
+/// [crate::VSomeipApplication::set_change_filter()].
+#[derive(Default)]
+pub(crate) struct ChangeFilters {
+    filters: RwLock<HashMap<(ServiceID, InstanceID, MethodID), Arc<ChangeFilter>>>,
+}
+
+struct ChangeFilter {
+    is_changed: Box<IsChanged>,
+    last: Mutex<Option<Bytes>>,
+}
+
+impl ChangeFilters {
+    pub(crate) fn insert(&self, service_id: ServiceID, instance_id: InstanceID, notifier_id: MethodID,
+                         is_changed: Box<IsChanged>)
+    {
+        self.filters.write().unwrap().insert((service_id, instance_id, notifier_id),
+                                             Arc::new(ChangeFilter { is_changed, last: Mutex::new(None) }));
+    }
+
+    pub(crate) fn remove(&self, service_id: ServiceID, instance_id: InstanceID, notifier_id: MethodID) {
+        self.filters.write().unwrap().remove(&(service_id, instance_id, notifier_id));
+    }
+
+    pub(crate) fn contains(&self, service_id: ServiceID, instance_id: InstanceID, notifier_id: MethodID) -> bool {
+        self.filters.read().unwrap().contains_key(&(service_id, instance_id, notifier_id))
+    }
+
+    /// Calls `f` with the filters of the events locked, so that comparing a payload with the last
+    /// notified value, sending it and recording it cannot interleave with another notification of
+    /// the same events. `f` must not notify these events itself.
+    pub(crate) fn locked<R>(&self, service_id: ServiceID, instance_id: InstanceID, notifier_ids: &[MethodID],
+                            f: impl FnOnce(&mut LockedFilters<'_>) -> R) -> R
+    {
+        // locked in the order of the notifier ids, so that batches of the same events cannot deadlock
+        let mut notifier_ids = notifier_ids.to_vec();
+        notifier_ids.sort();
+        notifier_ids.dedup();
+        let filters: Vec<_> = notifier_ids.into_iter()
+            .filter_map(|notifier_id| Some((notifier_id, self.get(service_id, instance_id, notifier_id)?)))
+            .collect();
+        let mut locked = LockedFilters { locked: filters.iter()
+            .map(|(notifier_id, filter)| (*notifier_id, &*filter.is_changed, filter.last.lock().unwrap()))
+            .collect() };
+        f(&mut locked)
+    }
+
+    /// Forgets the last notified value, e.g. when the event is no longer offered.
+    pub(crate) fn reset(&self, service_id: ServiceID, instance_id: InstanceID, notifier_id: MethodID) {
+        if let Some(filter) = self.get(service_id, instance_id, notifier_id) {
+            *filter.last.lock().unwrap() = None;
+        }
+    }
+
+    fn get(&self, service_id: ServiceID, instance_id: InstanceID, notifier_id: MethodID) -> Option<Arc<ChangeFilter>> {
+        self.filters.read().unwrap().get(&(service_id, instance_id, notifier_id)).cloned()
+    }
+}
+
+/// Filters of events locked by [ChangeFilters::locked()] with their last notified values.
+pub(crate) struct LockedFilters<'a> {
+    locked: Vec<(MethodID, &'a IsChanged, MutexGuard<'a, Option<Bytes>>)>,
+}
+
+impl LockedFilters<'_> {
+    /// Returns whether the payload is to be notified: always if the event has no filter, no value
+    /// was notified yet or `force` is set, otherwise if its predicate reports a change to the last
+    /// notified value.
+    pub(crate) fn is_changed(&self, notifier_id: MethodID, payload: &Bytes, force: bool) -> bool {
+        let Some((_, is_changed, last)) = self.locked.iter().find(|(id, _, _)| *id == notifier_id) else {
+            return true;
+        };
+        match &**last {
+            Some(last) if !force => is_changed(last, payload),
+            _ => true,
+        }
+    }
+
+    /// Stores the notified payload as the value further ones are compared with.
+    pub(crate) fn notified(&mut self, notifier_id: MethodID, payload: Bytes) {
+        if let Some((_, _, last)) = self.locked.iter_mut().find(|(id, _, _)| *id == notifier_id) {
+            **last = Some(payload);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn change_filters_test() {
+        let filters = ChangeFilters::default();
+        let (service_id, instance_id, notifier_id) = (ServiceID(1), InstanceID(1), MethodID(0x8001));
+        let value = |v: u8| Bytes::from(vec![v]);
+        let is_changed = |notifier_id, v, force| {
+            filters.locked(service_id, instance_id, &[notifier_id], |locked| {
+                locked.is_changed(notifier_id, &value(v), force)
+            })
+        };
+        assert!(is_changed(notifier_id, 1, false));
+
+        // changes by more than 2 are significant
+        filters.insert(service_id, instance_id, notifier_id, Box::new(|old, new| old[0].abs_diff(new[0]) > 2));
+        assert!(filters.contains(service_id, instance_id, notifier_id));
+        filters.locked(service_id, instance_id, &[notifier_id, MethodID(0x8002), notifier_id], |locked| {
+            assert!(locked.is_changed(notifier_id, &value(10), false));
+            locked.notified(notifier_id, value(10));
+            assert!(!locked.is_changed(notifier_id, &value(12), false));
+        });
+        assert!(!is_changed(notifier_id, 12, false));
+        assert!(is_changed(notifier_id, 12, true));
+        assert!(is_changed(notifier_id, 13, false));
+        assert!(is_changed(MethodID(0x8002), 10, false));
+
+        filters.reset(service_id, instance_id, notifier_id);
+        assert!(is_changed(notifier_id, 10, false));
+        filters.remove(service_id, instance_id, notifier_id);
+        assert!(!filters.contains(service_id, instance_id, notifier_id));
+    }
+}
@@ -0,0 +1,1088 @@
+// SPDX-License-Identifier: MPL-2.0
+//
+// Copyright (C) 2024 Alexander Seifarth
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Serialization of payloads in the SOME/IP wire format.
+//!
+//! Types implementing [SomeipSerialize] and [SomeipDeserialize] are written to an [Encoder] and
+//! read from a [Decoder]:
+//! - integers and floats in big endian byte order by default, `bool` as one byte, `()` as nothing,
+//! - tuples of up to 8 members as the sequence of their members,
+//! - fixed size arrays `[T; N]` without length field,
+//! - dynamic arrays `Vec<T>` and strings with a 32 bit length field by default, strings in UTF-8
+//!   with byte order mark and terminating zero by default, see [StringDeployment],
+//! - structs as the sequence of their members, optionally with a length field, or with
+//!   tag-length-value (TLV) encoded members, see [Tag],
+//! - unions, see [Encoder::put_union()].
+//!
+//! The defaults are given by the [DeploymentProfile] of the encoder or decoder, which sets the
+//! byte order, the length fields, the string deployment and the alignment of struct members of a
+//! project. The `#[someip(..)]` attributes below override the profile.
+//!
+//! With the `derive` feature structs and fieldless enums with a `#[repr]` integer type can derive
+//! the traits. The `#[someip(..)]` attribute sets the deployment:
+//! - `length_width = 0|1|2|4` on a struct puts a length field of that many bytes before it
+//!   instead of the one of the profile, on a member it sets the width of the array, string or
+//!   struct length field or puts a length field before another member,
+//! - `align = N` on a member pads the payload with zeros to a multiple of N bytes before it
+//!   instead of the profile's alignment,
+//! - `byte_order = "big_endian"|"little_endian"` on a member sets the byte order of the member
+//!   including its length field, on a type the byte order of its members, instead of the
+//!   profile's,
+//! - `encoding = "utf8"|"utf16le"|"utf16be"`, `bom = true|false` and `terminator = true|false`
+//!   on a `String` member set its [StringDeployment],
+//! - `tlv` on a struct encodes its members with tags, `id = N` on each member sets its data ID.
+//!   Members with unknown data IDs are skipped when deserializing, so a struct may be extended
+//!   by newer interface versions. Members of type `Option<T>` are optional, they are left out
+//!   when `None` and `None` when missing.
+//!
+//! `bitfield = 8|16|32|64` on a struct packs its members into an unsigned integer of that many
+//! bits, see [BitfieldMember]. `bits = N` on a member sets its width (1 for `bool`), `offset = N`
+//! its lowest bit, by default the bit after the previous member starting with bit 0.
+//!
+//! Fieldless enums are written as their discriminant. An enum of a newer interface version may
+//! have additional enumerators, `unknown` on a variant with a single member of the `#[repr]` type
+//! keeps such values instead of failing with [CodecError::InvalidValue]. The value of an unknown
+//! variant is written as is, a known value is read as its enumerator:
+//! ```rust,ignore
+//! #[derive(SomeipSerialize, SomeipDeserialize)]
+//! #[repr(u8)]
+//! enum Mode {
+//!     Eco = 1,
+//!     Sport = 2,
+//!     #[someip(unknown)]
+//!     Unknown(u8),
+//! }
+//! ```
+//!
+//! Enums with variants of a single unnamed member are unions, written with a length field
+//! (the profile's unless set by `length_width`), the type selector and the member.
+//! `selector_width = 1|2|4` on the enum sets the selector width (the profile's by default),
+//! `selector = N` on a variant its selector (its position starting with 1 by default). Fieldless
+//! variants are written without member.
+//!
+//! With the `serde` feature types implementing `serde::Serialize` and `serde::Deserialize` are
+//! written by [to_bytes()] and read by [from_bytes()] with the length fields of a
+//! [DeploymentProfile]. Structs, tuples and fixed size arrays are written as the sequence of
+//! their members, sequences and maps as dynamic arrays (a map as array of key value pairs),
+//! enums as unions with the variant's position starting with 1 as selector and `Option` as union
+//! of selector 0 for `None` and 1 for `Some`. Newtype structs are written as their member.
+//! ```rust,ignore
+//! use vsomeiprs::codec::{self, SomeipDeserialize, SomeipSerialize};
+//!
+//! #[derive(SomeipSerialize, SomeipDeserialize, Debug, PartialEq)]
+//! struct Position {
+//!     latitude: f64,
+//!     longitude: f64,
+//!     #[someip(length_width = 1)]
+//!     name: String,
+//! }
+//!
+//! let position = Position { latitude: 48.1, longitude: 11.6, name: "home".to_string() };
+//! let payload = codec::encode(&position).unwrap();
+//! assert_eq!(codec::decode::<Position>(&payload).unwrap(), position);
+//! ```
+
+use std::fmt;
+use bytes::{BufMut, Bytes, BytesMut};
+
+#[cfg(feature = "derive")]
+pub use vsomeiprs_derive::{SomeipDeserialize, SomeipSerialize};
+
+#[cfg(feature = "serde")]
+mod serde_codec;
+#[cfg(feature = "serde")]
+pub use serde_codec::{from_bytes, to_bytes, Deserializer, Serializer};
+
+/// Error of serializing or deserializing a payload.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CodecError {
+    /// The data ended before the value was complete.
+    Truncated { needed: usize, remaining: usize },
+    /// A length does not fit into its length field.
+    LengthOverflow { length: usize, width: LengthWidth },
+    /// A string is not valid in its encoding.
+    InvalidString,
+    /// A value is out of the range of its type, e.g. an unknown enumerator.
+    InvalidValue { type_name: &'static str, value: u64 },
+    /// A TLV encoded struct lacks the member with the data ID.
+    MissingMember { type_name: &'static str, data_id: u16 },
+    /// A type of the serde data model without representation in the wire format.
+    Unsupported(&'static str),
+    /// Error raised by a serde `Serialize` or `Deserialize` implementation.
+    Custom(String),
+}
+
+impl fmt::Display for CodecError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CodecError::Truncated { needed, remaining } =>
+                write!(f, "data truncated: {} bytes needed, {} remaining", needed, remaining),
+            CodecError::LengthOverflow { length, width } =>
+                write!(f, "length {} exceeds the length field of {} bytes", length, width.size()),
+            CodecError::InvalidString => write!(f, "invalid string encoding"),
+            CodecError::InvalidValue { type_name, value } => write!(f, "invalid value {} for {}", value, type_name),
+            CodecError::MissingMember { type_name, data_id } =>
+                write!(f, "member with data ID {} missing in {}", data_id, type_name),
+            CodecError::Unsupported(what) => write!(f, "{} not supported by the SOME/IP wire format", what),
+            CodecError::Custom(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+impl std::error::Error for CodecError {}
+
+/// Byte order of numbers, length fields and union selectors.
+#[derive(Eq, PartialEq, Debug, Clone, Copy, Default)]
+pub enum ByteOrder {
+    #[default]
+    BigEndian,
+    LittleEndian,
+}
+
+/// Deployment settings of a project, applying to all values without own deployment by
+/// `#[someip(..)]` attributes.
+///
+/// The profile is given to [Encoder::with_profile()] and [Decoder::with_profile()], so that one
+/// type definition serves projects with different deployments:
+/// ```rust
+/// use vsomeiprs::codec::{self, ByteOrder, DeploymentProfile, LengthWidth};
+///
+/// let profile = DeploymentProfile { array_length: LengthWidth::U8, byte_order: ByteOrder::LittleEndian,
+///     ..DeploymentProfile::default() };
+/// let payload = codec::encode_with(&vec![1u16, 2], &profile).unwrap();
+/// assert_eq!(payload.as_ref(), [4, 1, 0, 2, 0]);
+/// assert_eq!(codec::decode_with::<Vec<u16>>(&payload, &profile).unwrap(), [1, 2]);
+/// ```
+#[derive(Eq, PartialEq, Debug, Clone, Copy)]
+pub struct DeploymentProfile {
+    /// Length field of strings, 32 bit by default.
+    pub string_length: LengthWidth,
+    /// Length field of dynamic arrays, byte arrays and maps, 32 bit by default.
+    pub array_length: LengthWidth,
+    /// Length field before structs, none by default.
+    pub struct_length: LengthWidth,
+    /// Length field of unions, 32 bit by default.
+    pub union_length: LengthWidth,
+    /// Width of the union type selector, 32 bit by default.
+    pub union_selector: LengthWidth,
+    pub string: StringDeployment,
+    /// Alignment in bytes of struct members, 1 (the default) for no padding.
+    pub alignment: usize,
+    /// Big endian by default.
+    pub byte_order: ByteOrder,
+}
+
+impl Default for DeploymentProfile {
+    fn default() -> Self {
+        DeploymentProfile {
+            string_length: LengthWidth::U32,
+            array_length: LengthWidth::U32,
+            struct_length: LengthWidth::None,
+            union_length: LengthWidth::U32,
+            union_selector: LengthWidth::U32,
+            string: StringDeployment::default(),
+            alignment: 1,
+            byte_order: ByteOrder::BigEndian,
+        }
+    }
+}
+
+/// Width of a length field.
+#[derive(Eq, PartialEq, Debug, Clone, Copy)]
+pub enum LengthWidth {
+    /// No length field, the value extends to the end of the enclosing data.
+    None,
+    U8,
+    U16,
+    U32,
+}
+
+impl LengthWidth {
+    /// Returns the width in bytes.
+    pub fn size(self) -> usize {
+        match self {
+            LengthWidth::None => 0,
+            LengthWidth::U8 => 1,
+            LengthWidth::U16 => 2,
+            LengthWidth::U32 => 4,
+        }
+    }
+
+    fn write(self, target: &mut [u8], length: usize, byte_order: ByteOrder) -> Result<(), CodecError> {
+        let overflow = CodecError::LengthOverflow { length, width: self };
+        let length = match self {
+            LengthWidth::None => return Ok(()),
+            LengthWidth::U8 => u8::try_from(length).map_err(|_| overflow)? as u32,
+            LengthWidth::U16 => u16::try_from(length).map_err(|_| overflow)? as u32,
+            LengthWidth::U32 => u32::try_from(length).map_err(|_| overflow)?,
+        };
+        let bytes = match byte_order {
+            ByteOrder::BigEndian => &length.to_be_bytes()[4 - self.size()..],
+            ByteOrder::LittleEndian => &length.to_le_bytes()[..self.size()],
+        };
+        target.copy_from_slice(bytes);
+        Ok(())
+    }
+}
+
+/// Wire type of a TLV tag, telling the size of the member's data.
+#[derive(Eq, PartialEq, Debug, Clone, Copy)]
+pub enum WireType {
+    /// Base type of 8 bit.
+    Bits8,
+    Bits16,
+    Bits32,
+    Bits64,
+    /// Data preceded by a length field of the size given by the deployment.
+    StaticLength,
+    /// Data preceded by a length field of 8 bit.
+    Length8,
+    Length16,
+    Length32,
+}
+
+impl WireType {
+    /// Returns the wire type of a member with the given base size, see
+    /// [SomeipSerialize::BASE_SIZE], or otherwise with the given length field width, 32 bit for
+    /// [LengthWidth::None]. [WireType::StaticLength] is never returned.
+    pub fn of(base_size: Option<usize>, width: LengthWidth) -> Self {
+        match (base_size, width) {
+            (Some(1), _) => WireType::Bits8,
+            (Some(2), _) => WireType::Bits16,
+            (Some(4), _) => WireType::Bits32,
+            (Some(8), _) => WireType::Bits64,
+            (_, LengthWidth::U8) => WireType::Length8,
+            (_, LengthWidth::U16) => WireType::Length16,
+            (_, LengthWidth::U32 | LengthWidth::None) => WireType::Length32,
+        }
+    }
+
+    /// Returns the width of the length field, [LengthWidth::None] for base types and the given
+    /// `static_width` for [WireType::StaticLength].
+    pub fn length_width(self, static_width: LengthWidth) -> LengthWidth {
+        match self {
+            WireType::Bits8 | WireType::Bits16 | WireType::Bits32 | WireType::Bits64 => LengthWidth::None,
+            WireType::StaticLength => static_width,
+            WireType::Length8 => LengthWidth::U8,
+            WireType::Length16 => LengthWidth::U16,
+            WireType::Length32 => LengthWidth::U32,
+        }
+    }
+}
+
+/// Tag of a TLV encoded struct member, 16 bit with the wire type in bits 12 to 14 and the data ID
+/// in bits 0 to 11.
+#[derive(Eq, PartialEq, Debug, Clone, Copy)]
+pub struct Tag {
+    pub wire_type: WireType,
+    pub data_id: u16,
+}
+
+/// Largest data ID of a [Tag].
+pub const MAX_DATA_ID: u16 = 0x0fff;
+
+impl Tag {
+    fn to_wire(self) -> u16 {
+        let wire_type = match self.wire_type {
+            WireType::Bits8 => 0,
+            WireType::Bits16 => 1,
+            WireType::Bits32 => 2,
+            WireType::Bits64 => 3,
+            WireType::StaticLength => 4,
+            WireType::Length8 => 5,
+            WireType::Length16 => 6,
+            WireType::Length32 => 7,
+        };
+        wire_type << 12 | self.data_id & MAX_DATA_ID
+    }
+
+    fn from_wire(value: u16) -> Self {
+        let wire_type = match value >> 12 & 0x7 {
+            0 => WireType::Bits8,
+            1 => WireType::Bits16,
+            2 => WireType::Bits32,
+            3 => WireType::Bits64,
+            4 => WireType::StaticLength,
+            5 => WireType::Length8,
+            6 => WireType::Length16,
+            _ => WireType::Length32,
+        };
+        Tag { wire_type, data_id: value & MAX_DATA_ID }
+    }
+}
+
+fn selector_or_default(selector_width: LengthWidth) -> LengthWidth {
+    match selector_width {
+        LengthWidth::None => LengthWidth::U32,
+        width => width,
+    }
+}
+
+/// Writer of a serialized payload.
+#[derive(Debug, Default)]
+pub struct Encoder {
+    buf: BytesMut,
+    profile: DeploymentProfile,
+}
+
+impl Encoder {
+    /// Returns an encoder with the default [DeploymentProfile].
+    pub fn new() -> Self {
+        Encoder::default()
+    }
+
+    pub fn with_profile(profile: DeploymentProfile) -> Self {
+        Encoder { buf: BytesMut::new(), profile }
+    }
+
+    pub fn profile(&self) -> &DeploymentProfile {
+        &self.profile
+    }
+
+    /// Returns the number of bytes written, i.e. the offset from the start of the payload.
+    pub fn position(&self) -> usize {
+        self.buf.len()
+    }
+
+    pub fn put_slice(&mut self, data: &[u8]) {
+        self.buf.put_slice(data);
+    }
+
+    /// Pads with zeros to a multiple of `alignment` bytes from the start of the payload.
+    pub fn align(&mut self, alignment: usize) {
+        if alignment > 1 {
+            let padding = (alignment - self.position() % alignment) % alignment;
+            self.buf.put_bytes(0, padding);
+        }
+    }
+
+    /// Writes the data written by `f` preceded by a length field of the given width.
+    pub fn with_length<F>(&mut self, width: LengthWidth, f: F) -> Result<(), CodecError>
+        where F: FnOnce(&mut Encoder) -> Result<(), CodecError>
+    {
+        let field = self.begin_length(width);
+        f(self)?;
+        self.end_length(field, field + width.size(), width)
+    }
+
+    /// Writes with the byte order within `f`, e.g. a member deviating from the profile.
+    pub fn with_byte_order<F>(&mut self, byte_order: ByteOrder, f: F) -> Result<(), CodecError>
+        where F: FnOnce(&mut Encoder) -> Result<(), CodecError>
+    {
+        let outer = std::mem::replace(&mut self.profile.byte_order, byte_order);
+        let result = f(self);
+        self.profile.byte_order = outer;
+        result
+    }
+
+    /// Writes a length field placeholder and returns its position.
+    fn begin_length(&mut self, width: LengthWidth) -> usize {
+        let field = self.position();
+        self.buf.put_bytes(0, width.size());
+        field
+    }
+
+    /// Sets the length field at `field` to the length of the data written since `start`.
+    fn end_length(&mut self, field: usize, start: usize, width: LengthWidth) -> Result<(), CodecError> {
+        let length = self.position() - start;
+        width.write(&mut self.buf[field..field + width.size()], length, self.profile.byte_order)
+    }
+
+    /// Writes the selector of a union, see [Encoder::put_union()].
+    fn put_selector(&mut self, selector: u32, selector_width: LengthWidth) -> Result<(), CodecError> {
+        let selector_width = selector_or_default(selector_width);
+        let field = self.begin_length(selector_width);
+        selector_width.write(&mut self.buf[field..], selector as usize, self.profile.byte_order)
+            .map_err(|_| CodecError::InvalidValue { type_name: "union selector", value: selector as u64 })
+    }
+
+    /// Writes a union with the selector of the element's type and the element written by `f`. The
+    /// length field of the given width covers the element only. The selector width
+    /// [LengthWidth::None] is taken as 32 bit, selector 0 denotes an empty union.
+    pub fn put_union<F>(&mut self, selector: u32, selector_width: LengthWidth, width: LengthWidth, f: F)
+        -> Result<(), CodecError>
+        where F: FnOnce(&mut Encoder) -> Result<(), CodecError>
+    {
+        let field = self.begin_length(width);
+        self.put_selector(selector, selector_width)?;
+        let element = self.position();
+        f(self)?;
+        self.end_length(field, element, width)
+    }
+
+    /// Writes a TLV encoded member with the data ID. Base types are written without length field,
+    /// others with a length field of the given width, 32 bit for [LengthWidth::None].
+    pub fn put_tagged<T>(&mut self, data_id: u16, value: &T, width: LengthWidth) -> Result<(), CodecError>
+        where T: SomeipSerialize + ?Sized
+    {
+        let wire_type = WireType::of(T::BASE_SIZE, width);
+        Tag { wire_type, data_id }.to_wire().serialize(self)?;
+        match wire_type.length_width(LengthWidth::U32) {
+            LengthWidth::None => value.serialize(self),
+            width => value.serialize_with_length(self, width),
+        }
+    }
+
+    /// Returns the serialized payload.
+    pub fn finish(self) -> Bytes {
+        self.buf.freeze()
+    }
+}
+
+/// Reader of a serialized payload.
+#[derive(Debug)]
+pub struct Decoder<'a> {
+    data: &'a [u8],
+    pos: usize,
+    /// Offset of `data` from the start of the payload.
+    base: usize,
+    profile: DeploymentProfile,
+}
+
+impl<'a> Decoder<'a> {
+    /// Returns a decoder with the default [DeploymentProfile].
+    pub fn new(data: &'a [u8]) -> Self {
+        Decoder::with_profile(data, DeploymentProfile::default())
+    }
+
+    pub fn with_profile(data: &'a [u8], profile: DeploymentProfile) -> Self {
+        Decoder { data, pos: 0, base: 0, profile }
+    }
+
+    pub fn profile(&self) -> &DeploymentProfile {
+        &self.profile
+    }
+
+    /// Returns the offset from the start of the payload.
+    pub fn position(&self) -> usize {
+        self.base + self.pos
+    }
+
+    /// Returns the number of bytes left, within a length field the bytes left of its data.
+    pub fn remaining(&self) -> usize {
+        self.data.len() - self.pos
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.remaining() == 0
+    }
+
+    /// Reads the next `len` bytes.
+    pub fn take(&mut self, len: usize) -> Result<&'a [u8], CodecError> {
+        if len > self.remaining() {
+            return Err(CodecError::Truncated { needed: len, remaining: self.remaining() });
+        }
+        let data = &self.data[self.pos..self.pos + len];
+        self.pos += len;
+        Ok(data)
+    }
+
+    /// Skips the padding to a multiple of `alignment` bytes from the start of the payload.
+    pub fn align(&mut self, alignment: usize) -> Result<(), CodecError> {
+        if alignment > 1 {
+            let padding = (alignment - self.position() % alignment) % alignment;
+            self.take(padding)?;
+        }
+        Ok(())
+    }
+
+    /// Reads a length field of the given width and lets `f` read the data it covers. Data not
+    /// read by `f` is skipped, e.g. members appended by a newer interface version. Without length
+    /// field `f` reads from this decoder.
+    pub fn with_length<T, F>(&mut self, width: LengthWidth, f: F) -> Result<T, CodecError>
+        where F: FnOnce(&mut Decoder<'a>) -> Result<T, CodecError>
+    {
+        match self.get_length(width)? {
+            Some(length) => f(&mut self.sub(length)?),
+            None => f(self),
+        }
+    }
+
+    /// Reads with the byte order within `f`, the counterpart of [Encoder::with_byte_order()].
+    pub fn with_byte_order<T, F>(&mut self, byte_order: ByteOrder, f: F) -> Result<T, CodecError>
+        where F: FnOnce(&mut Decoder<'a>) -> Result<T, CodecError>
+    {
+        let outer = std::mem::replace(&mut self.profile.byte_order, byte_order);
+        let result = f(self);
+        self.profile.byte_order = outer;
+        result
+    }
+
+    /// Reads a union, the counterpart of [Encoder::put_union()]. `f` reads the element of the type
+    /// given by the selector.
+    pub fn get_union<T, F>(&mut self, selector_width: LengthWidth, width: LengthWidth, f: F) -> Result<T, CodecError>
+        where F: FnOnce(u32, &mut Decoder<'a>) -> Result<T, CodecError>
+    {
+        let length = self.get_length(width)?;
+        let selector = self.get_length(selector_or_default(selector_width))?.unwrap_or_default() as u32;
+        match length {
+            Some(length) => f(selector, &mut self.sub(length)?),
+            None => f(selector, self),
+        }
+    }
+
+    fn get_length(&mut self, width: LengthWidth) -> Result<Option<usize>, CodecError> {
+        Ok(match width {
+            LengthWidth::None => None,
+            LengthWidth::U8 => Some(u8::deserialize(self)? as usize),
+            LengthWidth::U16 => Some(u16::deserialize(self)? as usize),
+            LengthWidth::U32 => Some(u32::deserialize(self)? as usize),
+        })
+    }
+
+    /// Returns a decoder of the next `length` bytes.
+    fn sub(&mut self, length: usize) -> Result<Decoder<'a>, CodecError> {
+        let base = self.position();
+        Ok(Decoder { data: self.take(length)?, pos: 0, base, profile: self.profile })
+    }
+
+    /// Reads the tag of a TLV encoded member.
+    pub fn get_tag(&mut self) -> Result<Tag, CodecError> {
+        Ok(Tag::from_wire(u16::deserialize(self)?))
+    }
+
+    /// Reads the data of a TLV encoded member following its tag, the counterpart of
+    /// [Encoder::put_tagged()]. The length field of [WireType::StaticLength] is taken as 32 bit.
+    pub fn get_tagged<T: SomeipDeserialize>(&mut self, tag: Tag) -> Result<T, CodecError> {
+        match tag.wire_type.length_width(LengthWidth::U32) {
+            LengthWidth::None => T::deserialize(self),
+            width => T::deserialize_with_length(self, width),
+        }
+    }
+
+    /// Skips the data of a TLV encoded member following its tag, e.g. of an unknown member.
+    pub fn skip_tagged(&mut self, tag: Tag) -> Result<(), CodecError> {
+        let size = match tag.wire_type {
+            WireType::Bits8 => 1,
+            WireType::Bits16 => 2,
+            WireType::Bits32 => 4,
+            WireType::Bits64 => 8,
+            wire_type => return self.with_length(wire_type.length_width(LengthWidth::U32), |_| Ok(())),
+        };
+        self.take(size).map(|_| ())
+    }
+}
+
+/// Type that can be serialized into a SOME/IP payload.
+pub trait SomeipSerialize {
+    /// Size in bytes if the type is a base type, which is TLV encoded without length field.
+    const BASE_SIZE: Option<usize> = None;
+
+    fn serialize(&self, encoder: &mut Encoder) -> Result<(), CodecError>;
+
+    /// Serializes with a length field of the given width. Dynamic arrays and strings use it as
+    /// their length field, other types are preceded by it.
+    fn serialize_with_length(&self, encoder: &mut Encoder, width: LengthWidth) -> Result<(), CodecError> {
+        encoder.with_length(width, |encoder| self.serialize(encoder))
+    }
+}
+
+/// Type that can be deserialized from a SOME/IP payload.
+pub trait SomeipDeserialize: Sized {
+    fn deserialize(decoder: &mut Decoder<'_>) -> Result<Self, CodecError>;
+
+    /// Counterpart of [SomeipSerialize::serialize_with_length()].
+    fn deserialize_with_length(decoder: &mut Decoder<'_>, width: LengthWidth) -> Result<Self, CodecError> {
+        decoder.with_length(width, |decoder| Self::deserialize(decoder))
+    }
+}
+
+/// Serializes the value into a payload with the default [DeploymentProfile].
+pub fn encode<T: SomeipSerialize + ?Sized>(value: &T) -> Result<Bytes, CodecError> {
+    encode_with(value, &DeploymentProfile::default())
+}
+
+/// Deserializes a value from the payload with the default [DeploymentProfile]. Trailing data is
+/// ignored.
+pub fn decode<T: SomeipDeserialize>(data: &[u8]) -> Result<T, CodecError> {
+    decode_with(data, &DeploymentProfile::default())
+}
+
+/// Serializes the value into a payload with the deployment profile.
+pub fn encode_with<T: SomeipSerialize + ?Sized>(value: &T, profile: &DeploymentProfile) -> Result<Bytes, CodecError> {
+    let mut encoder = Encoder::with_profile(*profile);
+    value.serialize(&mut encoder)?;
+    Ok(encoder.finish())
+}
+
+/// Deserializes a value from the payload with the deployment profile. Trailing data is ignored.
+pub fn decode_with<T: SomeipDeserialize>(data: &[u8], profile: &DeploymentProfile) -> Result<T, CodecError> {
+    T::deserialize(&mut Decoder::with_profile(data, *profile))
+}
+
+macro_rules! impl_number {
+    ($($t:ty),*) => {
+        $(
+            impl SomeipSerialize for $t {
+                const BASE_SIZE: Option<usize> = Some(std::mem::size_of::<$t>());
+
+                fn serialize(&self, encoder: &mut Encoder) -> Result<(), CodecError> {
+                    match encoder.profile().byte_order {
+                        ByteOrder::BigEndian => encoder.put_slice(&self.to_be_bytes()),
+                        ByteOrder::LittleEndian => encoder.put_slice(&self.to_le_bytes()),
+                    }
+                    Ok(())
+                }
+            }
+
+            impl SomeipDeserialize for $t {
+                fn deserialize(decoder: &mut Decoder<'_>) -> Result<Self, CodecError> {
+                    let byte_order = decoder.profile().byte_order;
+                    let bytes = decoder.take(std::mem::size_of::<$t>())?.try_into().expect("size checked by take()");
+                    Ok(match byte_order {
+                        ByteOrder::BigEndian => <$t>::from_be_bytes(bytes),
+                        ByteOrder::LittleEndian => <$t>::from_le_bytes(bytes),
+                    })
+                }
+            }
+        )*
+    };
+}
+
+impl_number!(u8, u16, u32, u64, i8, i16, i32, i64, f32, f64);
+
+impl SomeipSerialize for bool {
+    const BASE_SIZE: Option<usize> = Some(1);
+
+    fn serialize(&self, encoder: &mut Encoder) -> Result<(), CodecError> {
+        (*self as u8).serialize(encoder)
+    }
+}
+
+impl SomeipDeserialize for bool {
+    fn deserialize(decoder: &mut Decoder<'_>) -> Result<Self, CodecError> {
+        match u8::deserialize(decoder)? {
+            0 => Ok(false),
+            1 => Ok(true),
+            value => Err(CodecError::InvalidValue { type_name: "bool", value: value as u64 }),
+        }
+    }
+}
+
+/// Empty payload, e.g. of a method without arguments.
+impl SomeipSerialize for () {
+    fn serialize(&self, _encoder: &mut Encoder) -> Result<(), CodecError> {
+        Ok(())
+    }
+}
+
+impl SomeipDeserialize for () {
+    fn deserialize(_decoder: &mut Decoder<'_>) -> Result<Self, CodecError> {
+        Ok(())
+    }
+}
+
+/// Tuples are written as the sequence of their members like structs without length field, e.g.
+/// the arguments of a method.
+macro_rules! impl_tuple {
+    ($($member:ident),+) => {
+        impl<$($member: SomeipSerialize),+> SomeipSerialize for ($($member,)+) {
+            #[allow(non_snake_case)]
+            fn serialize(&self, encoder: &mut Encoder) -> Result<(), CodecError> {
+                let ($($member,)+) = self;
+                $($member.serialize(encoder)?;)+
+                Ok(())
+            }
+        }
+
+        impl<$($member: SomeipDeserialize),+> SomeipDeserialize for ($($member,)+) {
+            fn deserialize(decoder: &mut Decoder<'_>) -> Result<Self, CodecError> {
+                Ok(($($member::deserialize(decoder)?,)+))
+            }
+        }
+    };
+}
+
+impl_tuple!(A);
+impl_tuple!(A, B);
+impl_tuple!(A, B, C);
+impl_tuple!(A, B, C, D);
+impl_tuple!(A, B, C, D, E);
+impl_tuple!(A, B, C, D, E, F);
+impl_tuple!(A, B, C, D, E, F, G);
+impl_tuple!(A, B, C, D, E, F, G, H);
+
+impl<T: SomeipSerialize, const N: usize> SomeipSerialize for [T; N] {
+    fn serialize(&self, encoder: &mut Encoder) -> Result<(), CodecError> {
+        self.iter().try_for_each(|item| item.serialize(encoder))
+    }
+}
+
+impl<T: SomeipDeserialize, const N: usize> SomeipDeserialize for [T; N] {
+    fn deserialize(decoder: &mut Decoder<'_>) -> Result<Self, CodecError> {
+        let items = (0..N).map(|_| T::deserialize(decoder)).collect::<Result<Vec<_>, _>>()?;
+        match items.try_into() {
+            Ok(array) => Ok(array),
+            Err(_) => unreachable!("N items collected"),
+        }
+    }
+}
+
+impl<T: SomeipSerialize> SomeipSerialize for Vec<T> {
+    fn serialize(&self, encoder: &mut Encoder) -> Result<(), CodecError> {
+        self.serialize_with_length(encoder, encoder.profile().array_length)
+    }
+
+    fn serialize_with_length(&self, encoder: &mut Encoder, width: LengthWidth) -> Result<(), CodecError> {
+        encoder.with_length(width, |encoder| self.iter().try_for_each(|item| item.serialize(encoder)))
+    }
+}
+
+impl<T: SomeipDeserialize> SomeipDeserialize for Vec<T> {
+    fn deserialize(decoder: &mut Decoder<'_>) -> Result<Self, CodecError> {
+        Self::deserialize_with_length(decoder, decoder.profile().array_length)
+    }
+
+    fn deserialize_with_length(decoder: &mut Decoder<'_>, width: LengthWidth) -> Result<Self, CodecError> {
+        decoder.with_length(width, |decoder| {
+            let mut items = Vec::new();
+            while !decoder.is_empty() {
+                let remaining = decoder.remaining();
+                items.push(T::deserialize(decoder)?);
+                // the length of an array of zero-sized elements does not determine their number
+                if decoder.remaining() == remaining {
+                    return Err(CodecError::Unsupported("array of zero-sized elements"));
+                }
+            }
+            Ok(items)
+        })
+    }
+}
+
+/// Type of a member of a bitfield struct, see the `bitfield` attribute of the derive macros. The
+/// width of a member must not exceed the bits of its type.
+pub trait BitfieldMember: Sized {
+    fn to_bits(&self) -> u64;
+    /// Converts the member's bits, the bits above its width are zero.
+    fn from_bits(bits: u64) -> Self;
+}
+
+impl BitfieldMember for bool {
+    fn to_bits(&self) -> u64 {
+        *self as u64
+    }
+
+    fn from_bits(bits: u64) -> Self {
+        bits != 0
+    }
+}
+
+macro_rules! impl_bitfield_member {
+    ($($t:ty),*) => {
+        $(
+            impl BitfieldMember for $t {
+                fn to_bits(&self) -> u64 {
+                    *self as u64
+                }
+
+                fn from_bits(bits: u64) -> Self {
+                    bits as $t
+                }
+            }
+        )*
+    };
+}
+
+impl_bitfield_member!(u8, u16, u32, u64);
+
+/// Returns the bits of a bitfield member of the given width moved to its offset, failing with
+/// [CodecError::InvalidValue] if the value exceeds the width.
+pub fn pack_bits<T: BitfieldMember>(type_name: &'static str, member: &T, offset: u32, width: u32)
+    -> Result<u64, CodecError>
+{
+    let value = member.to_bits();
+    if width < u64::BITS && value >> width != 0 {
+        return Err(CodecError::InvalidValue { type_name, value });
+    }
+    Ok(value << offset)
+}
+
+/// Returns the bitfield member of the given width at the offset of the carrier.
+pub fn unpack_bits<T: BitfieldMember>(carrier: u64, offset: u32, width: u32) -> T {
+    let mask = if width < u64::BITS { (1 << width) - 1 } else { u64::MAX };
+    T::from_bits(carrier >> offset & mask)
+}
+
+const UTF8_BOM: [u8; 3] = [0xef, 0xbb, 0xbf];
+const UTF16_BOM: u16 = 0xfeff;
+
+/// Encoding of a string.
+#[derive(Eq, PartialEq, Debug, Clone, Copy, Default)]
+pub enum StringEncoding {
+    #[default]
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+}
+
+/// Deployment of a string, by default UTF-8 with byte order mark and terminating zero.
+///
+/// When deserializing a byte order mark takes precedence over the configured encoding, byte order
+/// mark and terminator are removed if present.
+#[derive(Eq, PartialEq, Debug, Clone, Copy)]
+pub struct StringDeployment {
+    pub encoding: StringEncoding,
+    /// Starts the string with a byte order mark.
+    pub bom: bool,
+    /// Ends the string with a zero character.
+    pub terminator: bool,
+}
+
+impl Default for StringDeployment {
+    fn default() -> Self {
+        StringDeployment { encoding: StringEncoding::Utf8, bom: true, terminator: true }
+    }
+}
+
+impl StringDeployment {
+    fn write(&self, encoder: &mut Encoder, value: &str) {
+        let units = self.bom.then_some(UTF16_BOM).into_iter()
+            .chain(value.encode_utf16())
+            .chain(self.terminator.then_some(0));
+        match self.encoding {
+            StringEncoding::Utf8 => {
+                if self.bom {
+                    encoder.put_slice(&UTF8_BOM);
+                }
+                encoder.put_slice(value.as_bytes());
+                if self.terminator {
+                    encoder.put_slice(&[0]);
+                }
+            }
+            StringEncoding::Utf16Le => units.for_each(|unit| encoder.put_slice(&unit.to_le_bytes())),
+            StringEncoding::Utf16Be => units.for_each(|unit| encoder.put_slice(&unit.to_be_bytes())),
+        }
+    }
+
+    fn read(&self, data: &[u8]) -> Result<String, CodecError> {
+        let (encoding, data) = match data {
+            [0xef, 0xbb, 0xbf, rest @ ..] => (StringEncoding::Utf8, rest),
+            [0xff, 0xfe, rest @ ..] => (StringEncoding::Utf16Le, rest),
+            [0xfe, 0xff, rest @ ..] => (StringEncoding::Utf16Be, rest),
+            _ => (self.encoding, data),
+        };
+        let from_bytes = match encoding {
+            StringEncoding::Utf8 => {
+                let data = data.strip_suffix(&[0]).unwrap_or(data);
+                return String::from_utf8(data.to_vec()).map_err(|_| CodecError::InvalidString);
+            }
+            StringEncoding::Utf16Le => u16::from_le_bytes,
+            StringEncoding::Utf16Be => u16::from_be_bytes,
+        };
+        if data.len() % 2 != 0 {
+            return Err(CodecError::InvalidString);
+        }
+        let units: Vec<u16> = data.chunks_exact(2).map(|unit| from_bytes([unit[0], unit[1]])).collect();
+        let units = units.strip_suffix(&[0]).unwrap_or(&units);
+        String::from_utf16(units).map_err(|_| CodecError::InvalidString)
+    }
+}
+
+impl Encoder {
+    /// Writes a string with the deployment and a length field of the given width.
+    pub fn put_string(&mut self, value: &str, deployment: &StringDeployment, width: LengthWidth)
+        -> Result<(), CodecError>
+    {
+        self.with_length(width, |encoder| {
+            deployment.write(encoder, value);
+            Ok(())
+        })
+    }
+
+    /// Writes a TLV encoded string member, see [Encoder::put_tagged()].
+    pub fn put_tagged_string(&mut self, data_id: u16, value: &str, deployment: &StringDeployment,
+                             width: LengthWidth) -> Result<(), CodecError>
+    {
+        let wire_type = WireType::of(None, width);
+        Tag { wire_type, data_id }.to_wire().serialize(self)?;
+        self.put_string(value, deployment, wire_type.length_width(LengthWidth::U32))
+    }
+}
+
+impl Decoder<'_> {
+    /// Reads a string with the deployment and a length field of the given width.
+    pub fn get_string(&mut self, deployment: &StringDeployment, width: LengthWidth) -> Result<String, CodecError> {
+        self.with_length(width, |decoder| {
+            let data = decoder.take(decoder.remaining())?;
+            deployment.read(data)
+        })
+    }
+
+    /// Reads a TLV encoded string member following its tag, see [Decoder::get_tagged()].
+    pub fn get_tagged_string(&mut self, tag: Tag, deployment: &StringDeployment) -> Result<String, CodecError> {
+        self.get_string(deployment, tag.wire_type.length_width(LengthWidth::U32))
+    }
+}
+
+impl SomeipSerialize for String {
+    fn serialize(&self, encoder: &mut Encoder) -> Result<(), CodecError> {
+        self.serialize_with_length(encoder, encoder.profile().string_length)
+    }
+
+    fn serialize_with_length(&self, encoder: &mut Encoder, width: LengthWidth) -> Result<(), CodecError> {
+        let deployment = encoder.profile().string;
+        encoder.put_string(self, &deployment, width)
+    }
+}
+
+impl SomeipDeserialize for String {
+    fn deserialize(decoder: &mut Decoder<'_>) -> Result<Self, CodecError> {
+        Self::deserialize_with_length(decoder, decoder.profile().string_length)
+    }
+
+    fn deserialize_with_length(decoder: &mut Decoder<'_>, width: LengthWidth) -> Result<Self, CodecError> {
+        let deployment = decoder.profile().string;
+        decoder.get_string(&deployment, width)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn number_test() {
+        let mut encoder = Encoder::new();
+        0x12u8.serialize(&mut encoder).unwrap();
+        encoder.align(4);
+        0x3456u16.serialize(&mut encoder).unwrap();
+        (-2i32).serialize(&mut encoder).unwrap();
+        true.serialize(&mut encoder).unwrap();
+        let data = encoder.finish();
+        assert_eq!(data.as_ref(), [0x12, 0, 0, 0, 0x34, 0x56, 0xff, 0xff, 0xff, 0xfe, 1]);
+
+        let mut decoder = Decoder::new(&data);
+        assert_eq!(u8::deserialize(&mut decoder), Ok(0x12));
+        decoder.align(4).unwrap();
+        assert_eq!(u16::deserialize(&mut decoder), Ok(0x3456));
+        assert_eq!(i32::deserialize(&mut decoder), Ok(-2));
+        assert_eq!(bool::deserialize(&mut decoder), Ok(true));
+        assert_eq!(u8::deserialize(&mut decoder), Err(CodecError::Truncated { needed: 1, remaining: 0 }));
+        assert!(decode::<bool>(&[2]).is_err());
+
+        assert_eq!(encode(&(1u8, (), 2u16)).unwrap().as_ref(), [1, 0, 2]);
+        assert_eq!(decode::<(u8, (), u16)>(&[1, 0, 2]), Ok((1, (), 2)));
+    }
+
+    #[test]
+    fn length_test() {
+        let mut encoder = Encoder::new();
+        vec![1u16, 2].serialize_with_length(&mut encoder, LengthWidth::U8).unwrap();
+        "ab".to_string().serialize(&mut encoder).unwrap();
+        [7u8; 2].serialize(&mut encoder).unwrap();
+        let data = encoder.finish();
+        assert_eq!(data.as_ref(), [4, 0, 1, 0, 2, 0, 0, 0, 6, 0xef, 0xbb, 0xbf, b'a', b'b', 0, 7, 7]);
+
+        let mut decoder = Decoder::new(&data);
+        assert_eq!(Vec::<u16>::deserialize_with_length(&mut decoder, LengthWidth::U8), Ok(vec![1, 2]));
+        assert_eq!(String::deserialize(&mut decoder), Ok("ab".to_string()));
+        assert_eq!(<[u8; 2]>::deserialize(&mut decoder), Ok([7, 7]));
+
+        let mut encoder = Encoder::new();
+        assert_eq!(vec![0u8; 256].serialize_with_length(&mut encoder, LengthWidth::U8),
+                   Err(CodecError::LengthOverflow { length: 256, width: LengthWidth::U8 }));
+        // data of a length field not read is skipped
+        assert_eq!(Vec::<()>::deserialize_with_length(&mut Decoder::new(&[1, 0]), LengthWidth::U8),
+                   Err(CodecError::Unsupported("array of zero-sized elements")));
+        assert_eq!(Vec::<()>::deserialize_with_length(&mut Decoder::new(&[0]), LengthWidth::U8), Ok(vec![]));
+        let mut decoder = Decoder::new(&[3, 1, 2, 3, 4]);
+        assert_eq!(decoder.with_length(LengthWidth::U8, |decoder| u8::deserialize(decoder)), Ok(1));
+        assert_eq!(u8::deserialize(&mut decoder), Ok(4));
+    }
+
+    #[test]
+    fn string_test() {
+        let utf16 = StringDeployment { encoding: StringEncoding::Utf16Le, bom: true, terminator: false };
+        let plain = StringDeployment { encoding: StringEncoding::Utf16Be, bom: false, terminator: true };
+        let mut encoder = Encoder::new();
+        encoder.put_string("aä", &utf16, LengthWidth::U8).unwrap();
+        encoder.put_string("a", &plain, LengthWidth::None).unwrap();
+        let data = encoder.finish();
+        assert_eq!(data.as_ref(), [6, 0xff, 0xfe, b'a', 0, 0xe4, 0, 0, b'a', 0, 0]);
+
+        let mut decoder = Decoder::new(&data);
+        // the byte order mark takes precedence
+        assert_eq!(decoder.get_string(&plain, LengthWidth::U8), Ok("aä".to_string()));
+        assert_eq!(decoder.get_string(&plain, LengthWidth::None), Ok("a".to_string()));
+        assert_eq!(Decoder::new(&[0, b'a', 0]).get_string(&plain, LengthWidth::None), Err(CodecError::InvalidString));
+    }
+
+    #[test]
+    fn union_test() {
+        let mut encoder = Encoder::new();
+        encoder.put_union(2, LengthWidth::U8, LengthWidth::U16, |encoder| 0x1234u16.serialize(encoder)).unwrap();
+        encoder.put_union(1, LengthWidth::None, LengthWidth::None, |encoder| 7u8.serialize(encoder)).unwrap();
+        let data = encoder.finish();
+        assert_eq!(data.as_ref(), [0, 2, 2, 0x12, 0x34, 0, 0, 0, 1, 7]);
+        assert!(Encoder::new().put_union(256, LengthWidth::U8, LengthWidth::U8, |_| Ok(())).is_err());
+
+        let mut decoder = Decoder::new(&data);
+        assert_eq!(decoder.get_union(LengthWidth::U8, LengthWidth::U16, |selector, decoder| {
+            assert_eq!(selector, 2);
+            assert_eq!(decoder.remaining(), 2);
+            u8::deserialize(decoder)
+        }), Ok(0x12));
+        assert_eq!(decoder.get_union(LengthWidth::U32, LengthWidth::None, |_, decoder| u8::deserialize(decoder)),
+                   Ok(7));
+    }
+
+    #[test]
+    fn profile_test() {
+        let profile = DeploymentProfile { array_length: LengthWidth::U16, string_length: LengthWidth::U8,
+            byte_order: ByteOrder::LittleEndian, ..DeploymentProfile::default() };
+        let mut encoder = Encoder::with_profile(profile);
+        vec![0x0102u16].serialize(&mut encoder).unwrap();
+        "a".to_string().serialize(&mut encoder).unwrap();
+        encoder.put_union(0x0203, LengthWidth::U16, LengthWidth::U8, |_| Ok(())).unwrap();
+        let data = encoder.finish();
+        assert_eq!(data.as_ref(), [2, 0, 2, 1, 5, 0xef, 0xbb, 0xbf, b'a', 0, 0, 3, 2]);
+
+        let mut decoder = Decoder::with_profile(&data, profile);
+        assert_eq!(Vec::<u16>::deserialize(&mut decoder), Ok(vec![0x0102]));
+        assert_eq!(String::deserialize(&mut decoder), Ok("a".to_string()));
+        assert_eq!(decoder.get_union(LengthWidth::U16, LengthWidth::U8, |selector, _| Ok(selector)), Ok(0x0203));
+        assert_eq!(decode::<u16>(&data), Ok(0x0200));
+    }
+
+    #[test]
+    fn bits_test() {
+        assert_eq!(pack_bits("Status", &true, 3, 1), Ok(0x08));
+        assert_eq!(pack_bits("Status", &5u8, 4, 3), Ok(0x50));
+        assert_eq!(pack_bits("Status", &9u8, 4, 3), Err(CodecError::InvalidValue { type_name: "Status", value: 9 }));
+        assert_eq!(pack_bits("Status", &u64::MAX, 0, 64), Ok(u64::MAX));
+        assert!(unpack_bits::<bool>(0x08, 3, 1));
+        assert_eq!(unpack_bits::<u8>(0xd8, 4, 3), 5);
+    }
+
+    #[test]
+    fn tlv_test() {
+        let mut encoder = Encoder::new();
+        encoder.put_tagged(1, &0x1234u16, LengthWidth::None).unwrap();
+        encoder.put_tagged(0x0fff, &vec![1u8, 2], LengthWidth::U8).unwrap();
+        encoder.put_tagged(2, &"a".to_string(), LengthWidth::None).unwrap();
+        let data = encoder.finish();
+        assert_eq!(data.as_ref(), [0x10, 0x01, 0x12, 0x34, 0x5f, 0xff, 2, 1, 2,
+            0x70, 0x02, 0, 0, 0, 5, 0xef, 0xbb, 0xbf, b'a', 0]);
+
+        let mut decoder = Decoder::new(&data);
+        let tag = decoder.get_tag().unwrap();
+        assert_eq!(tag, Tag { wire_type: WireType::Bits16, data_id: 1 });
+        assert_eq!(decoder.get_tagged::<u16>(tag), Ok(0x1234));
+        let tag = decoder.get_tag().unwrap();
+        decoder.skip_tagged(tag).unwrap();
+        let tag = decoder.get_tag().unwrap();
+        assert_eq!(tag.wire_type, WireType::Length32);
+        assert_eq!(decoder.get_tagged::<String>(tag), Ok("a".to_string()));
+        assert!(decoder.is_empty());
+
+        let mut decoder = Decoder::new(&[0x40, 0x01, 0, 0, 0, 1, 7]);
+        let tag = decoder.get_tag().unwrap();
+        assert_eq!(tag.wire_type, WireType::StaticLength);
+        decoder.skip_tagged(tag).unwrap();
+        assert!(decoder.is_empty());
+    }
+}
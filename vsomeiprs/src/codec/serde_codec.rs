@@ -0,0 +1,715 @@
+// SPDX-License-Identifier: MPL-2.0
+//
+// Copyright (C) 2024 Alexander Seifarth
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::fmt;
+use bytes::Bytes;
+use serde::de::{self, DeserializeSeed, IntoDeserializer, Visitor};
+use serde::ser::{self, Serialize};
+use super::{CodecError, Decoder, DeploymentProfile, Encoder, LengthWidth, SomeipDeserialize, SomeipSerialize};
+
+impl ser::Error for CodecError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        CodecError::Custom(msg.to_string())
+    }
+}
+
+impl de::Error for CodecError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        CodecError::Custom(msg.to_string())
+    }
+}
+
+/// Serializes the value into a payload with the deployment profile.
+/// ```rust
+/// use vsomeiprs::codec::{self, DeploymentProfile, LengthWidth};
+///
+/// #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq)]
+/// struct Position {
+///     latitude: f32,
+///     name: String,
+/// }
+///
+/// let profile = DeploymentProfile { string_length: LengthWidth::U8, ..DeploymentProfile::default() };
+/// let position = Position { latitude: 1.5, name: "a".to_string() };
+/// let payload = codec::to_bytes(&position, &profile).unwrap();
+/// assert_eq!(payload.as_ref(), [0x3f, 0xc0, 0, 0, 5, 0xef, 0xbb, 0xbf, b'a', 0]);
+/// assert_eq!(codec::from_bytes::<Position>(&payload, &profile).unwrap(), position);
+/// ```
+pub fn to_bytes<T: Serialize + ?Sized>(value: &T, profile: &DeploymentProfile) -> Result<Bytes, CodecError> {
+    let mut encoder = Encoder::with_profile(*profile);
+    value.serialize(&mut Serializer::new(&mut encoder))?;
+    Ok(encoder.finish())
+}
+
+/// Deserializes a value from the payload with the deployment profile. Trailing data is ignored.
+pub fn from_bytes<'de, T: de::Deserialize<'de>>(data: &'de [u8], profile: &DeploymentProfile)
+    -> Result<T, CodecError>
+{
+    T::deserialize(&mut Deserializer::new(&mut Decoder::with_profile(data, *profile)))
+}
+
+/// serde serializer writing the SOME/IP wire format to an [Encoder] with the encoder's
+/// [DeploymentProfile].
+pub struct Serializer<'a> {
+    encoder: &'a mut Encoder,
+}
+
+impl<'a> Serializer<'a> {
+    pub fn new(encoder: &'a mut Encoder) -> Self {
+        Serializer { encoder }
+    }
+
+    fn profile(&self) -> DeploymentProfile {
+        *self.encoder.profile()
+    }
+
+    /// Starts a value with a length field, finished by [Compound]. Its elements are aligned to
+    /// `alignment` bytes.
+    fn begin(&mut self, width: LengthWidth, alignment: usize) -> Compound<'_, 'a> {
+        let field = self.encoder.begin_length(width);
+        let start = self.encoder.position();
+        Compound { ser: self, field, start, width, alignment }
+    }
+
+    /// Starts the union of the enum variant.
+    fn begin_variant(&mut self, variant_index: u32) -> Result<Compound<'_, 'a>, CodecError> {
+        let profile = self.profile();
+        let field = self.encoder.begin_length(profile.union_length);
+        self.encoder.put_selector(variant_index + 1, profile.union_selector)?;
+        let start = self.encoder.position();
+        Ok(Compound { ser: self, field, start, width: profile.union_length, alignment: 1 })
+    }
+}
+
+/// Value with a length field being serialized, the length field is set by `end()`.
+pub struct Compound<'b, 'a> {
+    ser: &'b mut Serializer<'a>,
+    field: usize,
+    start: usize,
+    width: LengthWidth,
+    alignment: usize,
+}
+
+impl Compound<'_, '_> {
+    fn element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), CodecError> {
+        self.ser.encoder.align(self.alignment);
+        value.serialize(&mut *self.ser)
+    }
+
+    fn finish(self) -> Result<(), CodecError> {
+        self.ser.encoder.end_length(self.field, self.start, self.width)
+    }
+}
+
+impl<'b, 'a> ser::Serializer for &'b mut Serializer<'a> {
+    type Ok = ();
+    type Error = CodecError;
+    type SerializeSeq = Compound<'b, 'a>;
+    type SerializeTuple = Compound<'b, 'a>;
+    type SerializeTupleStruct = Compound<'b, 'a>;
+    type SerializeTupleVariant = Compound<'b, 'a>;
+    type SerializeMap = Compound<'b, 'a>;
+    type SerializeStruct = Compound<'b, 'a>;
+    type SerializeStructVariant = Compound<'b, 'a>;
+
+    fn serialize_bool(self, v: bool) -> Result<(), CodecError> {
+        SomeipSerialize::serialize(&v, self.encoder)
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<(), CodecError> {
+        SomeipSerialize::serialize(&v, self.encoder)
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<(), CodecError> {
+        SomeipSerialize::serialize(&v, self.encoder)
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<(), CodecError> {
+        SomeipSerialize::serialize(&v, self.encoder)
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<(), CodecError> {
+        SomeipSerialize::serialize(&v, self.encoder)
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<(), CodecError> {
+        SomeipSerialize::serialize(&v, self.encoder)
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<(), CodecError> {
+        SomeipSerialize::serialize(&v, self.encoder)
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<(), CodecError> {
+        SomeipSerialize::serialize(&v, self.encoder)
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<(), CodecError> {
+        SomeipSerialize::serialize(&v, self.encoder)
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<(), CodecError> {
+        SomeipSerialize::serialize(&v, self.encoder)
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<(), CodecError> {
+        SomeipSerialize::serialize(&v, self.encoder)
+    }
+
+    /// Writes the character as 32 bit code point.
+    fn serialize_char(self, v: char) -> Result<(), CodecError> {
+        SomeipSerialize::serialize(&(v as u32), self.encoder)
+    }
+
+    fn serialize_str(self, v: &str) -> Result<(), CodecError> {
+        let profile = self.profile();
+        self.encoder.put_string(v, &profile.string, profile.string_length)
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<(), CodecError> {
+        self.encoder.with_length(self.profile().array_length, |encoder| {
+            encoder.put_slice(v);
+            Ok(())
+        })
+    }
+
+    fn serialize_none(self) -> Result<(), CodecError> {
+        let profile = self.profile();
+        self.encoder.put_union(0, profile.union_selector, profile.union_length, |_| Ok(()))
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), CodecError> {
+        let mut union = self.begin_variant(0)?;
+        union.element(value)?;
+        union.finish()
+    }
+
+    fn serialize_unit(self) -> Result<(), CodecError> {
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<(), CodecError> {
+        Ok(())
+    }
+
+    fn serialize_unit_variant(self, _name: &'static str, variant_index: u32, _variant: &'static str)
+        -> Result<(), CodecError>
+    {
+        self.begin_variant(variant_index)?.finish()
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(self, _name: &'static str, value: &T)
+        -> Result<(), CodecError>
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(self, _name: &'static str, variant_index: u32,
+                                                        _variant: &'static str, value: &T) -> Result<(), CodecError>
+    {
+        let mut union = self.begin_variant(variant_index)?;
+        union.element(value)?;
+        union.finish()
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Compound<'b, 'a>, CodecError> {
+        let width = self.profile().array_length;
+        Ok(self.begin(width, 1))
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Compound<'b, 'a>, CodecError> {
+        Ok(self.begin(LengthWidth::None, 1))
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, _len: usize) -> Result<Compound<'b, 'a>, CodecError> {
+        let profile = self.profile();
+        Ok(self.begin(profile.struct_length, profile.alignment))
+    }
+
+    fn serialize_tuple_variant(self, _name: &'static str, variant_index: u32, _variant: &'static str,
+                               _len: usize) -> Result<Compound<'b, 'a>, CodecError>
+    {
+        self.begin_variant(variant_index)
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Compound<'b, 'a>, CodecError> {
+        let width = self.profile().array_length;
+        Ok(self.begin(width, 1))
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Compound<'b, 'a>, CodecError> {
+        let profile = self.profile();
+        Ok(self.begin(profile.struct_length, profile.alignment))
+    }
+
+    fn serialize_struct_variant(self, _name: &'static str, variant_index: u32, _variant: &'static str,
+                                _len: usize) -> Result<Compound<'b, 'a>, CodecError>
+    {
+        let alignment = self.profile().alignment;
+        let variant = self.begin_variant(variant_index)?;
+        Ok(Compound { alignment, ..variant })
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+impl ser::SerializeSeq for Compound<'_, '_> {
+    type Ok = ();
+    type Error = CodecError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), CodecError> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), CodecError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTuple for Compound<'_, '_> {
+    type Ok = ();
+    type Error = CodecError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), CodecError> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), CodecError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleStruct for Compound<'_, '_> {
+    type Ok = ();
+    type Error = CodecError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), CodecError> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), CodecError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleVariant for Compound<'_, '_> {
+    type Ok = ();
+    type Error = CodecError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), CodecError> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), CodecError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeMap for Compound<'_, '_> {
+    type Ok = ();
+    type Error = CodecError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), CodecError> {
+        self.element(key)
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), CodecError> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), CodecError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeStruct for Compound<'_, '_> {
+    type Ok = ();
+    type Error = CodecError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, _key: &'static str, value: &T) -> Result<(), CodecError> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), CodecError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeStructVariant for Compound<'_, '_> {
+    type Ok = ();
+    type Error = CodecError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, _key: &'static str, value: &T) -> Result<(), CodecError> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), CodecError> {
+        self.finish()
+    }
+}
+
+/// serde deserializer reading the SOME/IP wire format from a [Decoder].
+///
+/// The deserializer reads with the decoder's [DeploymentProfile]. The wire format is not
+/// self-describing, `deserialize_any()` is not supported.
+pub struct Deserializer<'a, 'de> {
+    decoder: &'a mut Decoder<'de>,
+}
+
+impl<'a, 'de> Deserializer<'a, 'de> {
+    pub fn new(decoder: &'a mut Decoder<'de>) -> Self {
+        Deserializer { decoder }
+    }
+
+    fn profile(&self) -> DeploymentProfile {
+        *self.decoder.profile()
+    }
+
+    /// Lets the visitor read the members within a length field of the given width, `count`
+    /// members aligned to `alignment` bytes or, if `None`, up to the end of the data.
+    fn members<V: Visitor<'de>>(&mut self, width: LengthWidth, count: Option<usize>, alignment: usize, visitor: V)
+        -> Result<V::Value, CodecError>
+    {
+        self.decoder.with_length(width, |decoder| {
+            visitor.visit_seq(Members::new(Deserializer::new(decoder), count, alignment))
+        })
+    }
+
+    /// Reads a union and lets `f` read the element of the variant given by the selector.
+    fn union<T, F>(&mut self, f: F) -> Result<T, CodecError>
+        where F: FnOnce(u32, Deserializer<'_, 'de>) -> Result<T, CodecError>
+    {
+        let profile = self.profile();
+        self.decoder.get_union(profile.union_selector, profile.union_length, |selector, decoder| {
+            f(selector, Deserializer::new(decoder))
+        })
+    }
+}
+
+macro_rules! deserialize_number {
+    ($($method:ident => $visit:ident: $t:ty),*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, CodecError> {
+                visitor.$visit(<$t>::deserialize(self.decoder)?)
+            }
+        )*
+    }
+}
+
+impl<'de> de::Deserializer<'de> for &mut Deserializer<'_, 'de> {
+    type Error = CodecError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, CodecError> {
+        Err(CodecError::Unsupported("deserialize_any"))
+    }
+
+    deserialize_number!(deserialize_bool => visit_bool: bool,
+        deserialize_i8 => visit_i8: i8, deserialize_i16 => visit_i16: i16,
+        deserialize_i32 => visit_i32: i32, deserialize_i64 => visit_i64: i64,
+        deserialize_u8 => visit_u8: u8, deserialize_u16 => visit_u16: u16,
+        deserialize_u32 => visit_u32: u32, deserialize_u64 => visit_u64: u64,
+        deserialize_f32 => visit_f32: f32, deserialize_f64 => visit_f64: f64);
+
+    fn deserialize_char<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, CodecError> {
+        let value = u32::deserialize(self.decoder)?;
+        let c = char::from_u32(value).ok_or(CodecError::InvalidValue { type_name: "char", value: value as u64 })?;
+        visitor.visit_char(c)
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, CodecError> {
+        self.deserialize_string(visitor)
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, CodecError> {
+        let profile = self.profile();
+        visitor.visit_string(self.decoder.get_string(&profile.string, profile.string_length)?)
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, CodecError> {
+        let width = self.profile().array_length;
+        let data = self.decoder.with_length(width, |decoder| decoder.take(decoder.remaining()))?;
+        visitor.visit_borrowed_bytes(data)
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, CodecError> {
+        self.deserialize_bytes(visitor)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, CodecError> {
+        self.union(|selector, mut de| match selector {
+            0 => visitor.visit_none(),
+            1 => visitor.visit_some(&mut de),
+            selector => Err(CodecError::InvalidValue { type_name: "Option", value: selector as u64 }),
+        })
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, CodecError> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V)
+        -> Result<V::Value, CodecError>
+    {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V)
+        -> Result<V::Value, CodecError>
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, CodecError> {
+        self.members(self.profile().array_length, None, 1, visitor)
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value, CodecError> {
+        self.members(LengthWidth::None, Some(len), 1, visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(self, _name: &'static str, len: usize, visitor: V)
+        -> Result<V::Value, CodecError>
+    {
+        let profile = self.profile();
+        self.members(profile.struct_length, Some(len), profile.alignment, visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, CodecError> {
+        self.decoder.with_length(self.profile().array_length, |decoder| {
+            visitor.visit_map(Members::new(Deserializer::new(decoder), None, 1))
+        })
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(self, _name: &'static str, fields: &'static [&'static str], visitor: V)
+        -> Result<V::Value, CodecError>
+    {
+        let profile = self.profile();
+        self.members(profile.struct_length, Some(fields.len()), profile.alignment, visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(self, name: &'static str, _variants: &'static [&'static str], visitor: V)
+        -> Result<V::Value, CodecError>
+    {
+        self.union(|selector, de| match selector {
+            0 => Err(CodecError::InvalidValue { type_name: name, value: 0 }),
+            selector => visitor.visit_enum(Variant { de, variant_index: selector - 1 }),
+        })
+    }
+
+    fn deserialize_identifier<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, CodecError> {
+        Err(CodecError::Unsupported("identifiers"))
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, CodecError> {
+        Err(CodecError::Unsupported("deserialize_ignored_any"))
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+/// Access to the members of a struct, tuple, sequence or map.
+struct Members<'a, 'de> {
+    de: Deserializer<'a, 'de>,
+    /// Number of members left, `None` up to the end of the data.
+    count: Option<usize>,
+    alignment: usize,
+    /// Remaining data before the previous member, if the members last up to the end of the data.
+    remaining: Option<usize>,
+}
+
+impl<'a, 'de> Members<'a, 'de> {
+    fn new(de: Deserializer<'a, 'de>, count: Option<usize>, alignment: usize) -> Self {
+        Self { de, count, alignment, remaining: None }
+    }
+
+    /// Returns whether a member follows and skips the padding before it.
+    fn next(&mut self) -> Result<bool, CodecError> {
+        let next = match &mut self.count {
+            Some(0) => false,
+            Some(count) => {
+                *count -= 1;
+                true
+            }
+            None => {
+                let remaining = self.de.decoder.remaining();
+                // the length of a sequence of zero-sized elements does not determine their number
+                if self.remaining.replace(remaining) == Some(remaining) {
+                    return Err(CodecError::Unsupported("array of zero-sized elements"));
+                }
+                remaining > 0
+            }
+        };
+        if next {
+            self.de.decoder.align(self.alignment)?;
+        }
+        Ok(next)
+    }
+}
+
+impl<'de> de::SeqAccess<'de> for Members<'_, 'de> {
+    type Error = CodecError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>, CodecError> {
+        if !self.next()? {
+            return Ok(None);
+        }
+        seed.deserialize(&mut self.de).map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.count
+    }
+}
+
+impl<'de> de::MapAccess<'de> for Members<'_, 'de> {
+    type Error = CodecError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, CodecError> {
+        if !self.next()? {
+            return Ok(None);
+        }
+        seed.deserialize(&mut self.de).map(Some)
+    }
+
+    fn next_value_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<T::Value, CodecError> {
+        seed.deserialize(&mut self.de)
+    }
+}
+
+/// Access to the variant of an enum given by the union selector.
+struct Variant<'a, 'de> {
+    de: Deserializer<'a, 'de>,
+    variant_index: u32,
+}
+
+impl<'a, 'de> de::EnumAccess<'de> for Variant<'a, 'de> {
+    type Error = CodecError;
+    type Variant = Deserializer<'a, 'de>;
+
+    fn variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<(T::Value, Self::Variant), CodecError> {
+        let variant = seed.deserialize(self.variant_index.into_deserializer())?;
+        Ok((variant, self.de))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for Deserializer<'_, 'de> {
+    type Error = CodecError;
+
+    fn unit_variant(self) -> Result<(), CodecError> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(mut self, seed: T) -> Result<T::Value, CodecError> {
+        seed.deserialize(&mut self)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value, CodecError> {
+        visitor.visit_seq(Members::new(self, Some(len), 1))
+    }
+
+    fn struct_variant<V: Visitor<'de>>(self, fields: &'static [&'static str], visitor: V)
+        -> Result<V::Value, CodecError>
+    {
+        let alignment = self.profile().alignment;
+        visitor.visit_seq(Members::new(self, Some(fields.len()), alignment))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::collections::BTreeMap;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    enum Source {
+        Off,
+        Tuner(u16),
+        Media { track: u8, title: String },
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Id(u16);
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Status {
+        id: Id,
+        active: bool,
+        position: (i8, i8),
+        samples: Vec<u16>,
+        volume: Option<u8>,
+        sources: Vec<Source>,
+        names: BTreeMap<u8, String>,
+    }
+
+    #[test]
+    fn round_trip_test() {
+        let profile = DeploymentProfile {
+            string_length: LengthWidth::U8,
+            array_length: LengthWidth::U16,
+            union_length: LengthWidth::U8,
+            union_selector: LengthWidth::U8,
+            ..DeploymentProfile::default()
+        };
+        let status = Status {
+            id: Id(0x0102),
+            active: true,
+            position: (1, -1),
+            samples: vec![3],
+            volume: Some(4),
+            sources: vec![Source::Off, Source::Tuner(5), Source::Media { track: 6, title: "".to_string() }],
+            names: BTreeMap::from([(7, "a".to_string())]),
+        };
+        let payload = to_bytes(&status, &profile).unwrap();
+        assert_eq!(payload.as_ref(), [
+            1, 2, 1, 1, 0xff,                                   // id, active, position
+            0, 2, 0, 3,                                         // samples
+            1, 1, 4,                                            // volume
+            0, 14, 0, 1, 2, 2, 0, 5, 6, 3, 6, 4, 0xef, 0xbb, 0xbf, 0, // sources
+            0, 7, 7, 5, 0xef, 0xbb, 0xbf, b'a', 0,              // names
+        ]);
+        assert_eq!(from_bytes::<Status>(&payload, &profile).unwrap(), status);
+
+        let payload = to_bytes(&None::<u8>, &profile).unwrap();
+        assert_eq!(payload.as_ref(), [0, 0]);
+        assert_eq!(from_bytes::<Option<u8>>(&payload, &profile).unwrap(), None);
+        assert_eq!(from_bytes::<Source>(&[0, 0], &profile),
+                   Err(CodecError::InvalidValue { type_name: "Source", value: 0 }));
+        assert!(matches!(from_bytes::<Source>(&[0, 4], &profile), Err(CodecError::Custom(_))));
+    }
+
+    #[test]
+    fn struct_length_test() {
+        let profile = DeploymentProfile { struct_length: LengthWidth::U16, ..DeploymentProfile::default() };
+        #[derive(Serialize, Deserialize, Debug, PartialEq)]
+        struct Point {
+            x: u8,
+            y: u8,
+        }
+        let payload = to_bytes(&Point { x: 1, y: 2 }, &profile).unwrap();
+        assert_eq!(payload.as_ref(), [0, 2, 1, 2]);
+        // members appended by a newer version are skipped
+        assert_eq!(from_bytes::<Point>(&[0, 3, 1, 2, 3], &profile).unwrap(), Point { x: 1, y: 2 });
+        assert_eq!(to_bytes(&1u128, &profile).map_err(|_| ()), Err(()));
+        assert_eq!(from_bytes::<serde_json::Value>(&payload, &profile),
+                   Err(CodecError::Unsupported("deserialize_any")));
+    }
+    #[test]
+    fn zero_sized_elements_test() {
+        let profile = DeploymentProfile::default();
+        // the array length does not determine the number of zero-sized elements
+        assert_eq!(from_bytes::<Vec<()>>(&[0, 0, 0, 1, 0xaa], &profile),
+                   Err(CodecError::Unsupported("array of zero-sized elements")));
+        assert_eq!(from_bytes::<Vec<()>>(&[0, 0, 0, 0], &profile).unwrap(), vec![]);
+        assert_eq!(from_bytes::<BTreeMap<(), ()>>(&[0, 0, 0, 1, 0xaa], &profile),
+                   Err(CodecError::Unsupported("array of zero-sized elements")));
+    }
+}
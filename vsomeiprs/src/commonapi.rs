@@ -0,0 +1,975 @@
+// SPDX-License-Identifier: MPL-2.0
+//
+// Copyright (C) 2024 Alexander Seifarth
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Compatibility layer mirroring the proxy/stub semantics of CommonAPI (C++).
+//!
+//! The types in this module follow the CommonAPI concepts so that application logic written
+//! against CommonAPI can be ported with little restructuring:
+//! - [Proxy] with availability status ([Proxy::is_available], [Proxy::get_proxy_status_event]),
+//!   asynchronous method calls completing with a [CallStatus], attributes
+//!   (`get_value_async`/`set_value_async`/`get_changed_event`) and broadcasts.
+//! - [FailoverProxy] for services offered as several redundant instances, using the most
+//!   preferred available instance.
+//! - [StubAdapter] which dispatches method calls to a [Stub] implementation, serves attribute
+//!   getters/setters from stored values and fires attribute changes and broadcasts.
+//!
+//! Like CommonAPI callbacks, all listeners are plain closures. They are invoked from
+//! [Proxy::handle] and [StubAdapter::handle] which the application calls for each message
+//! received from its [VSomeipMessage] receiver. Payloads are passed as serialized [Bytes].
+//! ```rust,no_run
+//! use std::sync::Arc;
+//! use bytes::Bytes;
+//! use vsomeiprs::commonapi::{Address, CallStatus, Proxy};
+//! use vsomeiprs::{InstanceID, InterfaceVersion, MethodID, Reliability, ServiceID, VSomeipApplication};
+//!
+//! async fn run() {
+//!     let (app, mut recv) = VSomeipApplication::create("client").unwrap();
+//!     let address = Address::new(ServiceID(0x1234), InstanceID(1), InterfaceVersion::make_major(1));
+//!     let proxy = Proxy::new(Arc::new(app), address);
+//!     proxy.get_proxy_status_event().subscribe(|status| println!("proxy status {:?}", status));
+//!     while let Some(msg) = recv.recv().await {
+//!         proxy.handle(&msg);
+//!         if proxy.is_available() {
+//!             proxy.call_method_async(MethodID(1), &Bytes::new(), Reliability::Unreliable,
+//!                 |status: CallStatus, _response: Bytes| println!("call status {:?}", status));
+//!         }
+//!     }
+//! }
+//! ```
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
+use bytes::Bytes;
+use super::{ClientID, EventGroupID, EventKind, InstanceID, InterfaceVersion, MessageHeader, MessageType, MethodID,
+            Reliability, ReturnCode, SendError, ServiceHandle, ServiceID, SessionID, VSomeipApplication,
+            VSomeipMessage};
+
+/// Result status of a method call or attribute access (CommonAPI `CallStatus`).
+#[derive(Eq, PartialEq, Debug, Clone, Copy)]
+pub enum CallStatus {
+    Success,
+    OutOfMemory,
+    NotAvailable,
+    ConnectionFailed,
+    RemoteError,
+    Unknown,
+    InvalidValue,
+    SubscriptionRefused,
+    SerializationError,
+}
+
+impl From<&ReturnCode> for CallStatus {
+    fn from(rc: &ReturnCode) -> Self {
+        match rc {
+            ReturnCode::Ok => CallStatus::Success,
+            ReturnCode::UnknownService | ReturnCode::NotReachable => CallStatus::NotAvailable,
+            ReturnCode::MalformedMessage => CallStatus::SerializationError,
+            _ => CallStatus::RemoteError,
+        }
+    }
+}
+
+/// Decodes the payload of error messages of a service into an application error, e.g. an
+/// exception string or error struct. Returns `None` if the payload can't be decoded.
+pub type ErrorDecoder = Box<dyn Fn(ReturnCode, &Bytes) -> Option<Box<dyn Error + Send + Sync>> + Send + Sync>;
+
+/// Failed method call or attribute access.
+#[derive(Debug)]
+pub struct CallError {
+    pub status: CallStatus,
+    /// Return code of the error message, `None` if the call failed without error message.
+    pub return_code: Option<ReturnCode>,
+    /// Raw payload of the error message.
+    pub payload: Bytes,
+    /// Application error decoded by the proxy's [ErrorDecoder].
+    pub error: Option<Box<dyn Error + Send + Sync>>,
+}
+
+impl CallError {
+    /// Returns the decoded application error if it is of type `E`.
+    pub fn downcast_ref<E: Error + 'static>(&self) -> Option<&E> {
+        self.error.as_ref().and_then(|e| e.downcast_ref::<E>())
+    }
+}
+
+impl From<CallStatus> for CallError {
+    fn from(status: CallStatus) -> Self {
+        CallError { status, return_code: None, payload: Bytes::new(), error: None }
+    }
+}
+
+impl fmt::Display for CallError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "call failed: {:?}", self.status)?;
+        if let Some(return_code) = &self.return_code {
+            write!(f, " ({})", return_code)?;
+        }
+        if let Some(error) = &self.error {
+            write!(f, ": {}", error)?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for CallError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.error.as_ref().map(|e| e.as_ref() as &(dyn Error + 'static))
+    }
+}
+
+/// Availability of the service instance behind a proxy (CommonAPI `AvailabilityStatus`).
+#[derive(Eq, PartialEq, Debug, Clone, Copy)]
+pub enum AvailabilityStatus {
+    Unknown,
+    Available,
+    NotAvailable,
+}
+
+/// Address of a service instance - the SOME/IP counterpart of a CommonAPI
+/// `domain:interface:instance` address.
+#[derive(Eq, PartialEq, Debug, Clone, Copy)]
+pub struct Address {
+    pub service_id: ServiceID,
+    pub instance_id: InstanceID,
+    pub version: InterfaceVersion,
+}
+
+impl Address {
+    pub fn new(service_id: ServiceID, instance_id: InstanceID, version: InterfaceVersion) -> Self {
+        Address { service_id, instance_id, version }
+    }
+}
+
+impl From<Address> for ServiceHandle {
+    fn from(address: Address) -> Self {
+        ServiceHandle::new(address.service_id, address.instance_id, address.version)
+    }
+}
+
+impl From<ServiceHandle> for Address {
+    fn from(service: ServiceHandle) -> Self {
+        Address::new(service.service_id, service.instance_id, service.version)
+    }
+}
+
+/// SOME/IP deployment of an attribute: the getter and setter methods and the notifier event.
+/// Attributes are identified by their getter.
+#[derive(Eq, PartialEq, Debug, Clone, Copy)]
+pub struct AttributeDeployment {
+    pub getter: MethodID,
+    pub setter: Option<MethodID>,
+    pub notifier: Option<(MethodID, EventGroupID)>,
+}
+
+/// SOME/IP deployment of a broadcast: the event and its event group.
+#[derive(Eq, PartialEq, Debug, Clone, Copy)]
+pub struct BroadcastDeployment {
+    pub event_id: MethodID,
+    pub eventgroup: EventGroupID,
+}
+
+/// Handle of a listener subscribed to an [Event].
+pub type Subscription = u32;
+
+type Listener<T> = Arc<dyn Fn(&T) + Send + Sync>;
+
+/// CommonAPI style event to which listeners can be subscribed.
+///
+/// The listeners are called without holding the event's lock, so a listener may subscribe,
+/// unsubscribe and fire the event itself. Such changes take effect with the next firing.
+pub struct Event<T> {
+    next: AtomicU32,
+    listeners: Mutex<Vec<(Subscription, Listener<T>)>>,
+}
+
+impl<T> Default for Event<T> {
+    fn default() -> Self {
+        Event { next: AtomicU32::new(1), listeners: Mutex::new(Vec::new()) }
+    }
+}
+
+impl<T> Event<T> {
+    /// Adds a listener and returns the handle to unsubscribe it.
+    pub fn subscribe(&self, listener: impl Fn(&T) + Send + Sync + 'static) -> Subscription {
+        let subscription = self.next.fetch_add(1, Ordering::Relaxed);
+        self.listeners.lock().unwrap().push((subscription, Arc::new(listener)));
+        subscription
+    }
+
+    /// Removes a listener.
+    pub fn unsubscribe(&self, subscription: Subscription) {
+        self.listeners.lock().unwrap().retain(|(s, _)| *s != subscription);
+    }
+
+    pub(crate) fn fire(&self, value: &T) {
+        let listeners: Vec<_> = self.listeners.lock().unwrap().iter().map(|(_, l)| l.clone()).collect();
+        for listener in listeners {
+            listener(value);
+        }
+    }
+}
+
+type CallCallback = Box<dyn FnOnce(Result<Bytes, CallError>) + Send>;
+
+/// Requested event: its event group and the listeners.
+type ProxyEvent = (EventGroupID, Arc<Event<Bytes>>);
+
+/// Client side representation of a remote service instance (CommonAPI `Proxy`).
+///
+/// The proxy requests the service on construction and releases it (including all requested
+/// events) when dropped.
+pub struct Proxy {
+    app: Arc<VSomeipApplication>,
+    address: Address,
+    status: Mutex<AvailabilityStatus>,
+    status_event: Event<AvailabilityStatus>,
+    pending: Mutex<HashMap<SessionID, CallCallback>>,
+    events: Mutex<HashMap<MethodID, ProxyEvent>>,
+    error_decoder: Mutex<Option<ErrorDecoder>>,
+}
+
+impl Drop for Proxy {
+    fn drop(&mut self) {
+        let address = self.address;
+        for (event_id, (eventgroup, _)) in self.events.lock().unwrap().iter() {
+            self.app.unsubscribe_event(address.service_id, address.instance_id, *eventgroup, *event_id);
+            self.app.release_event(address.service_id, address.instance_id, *event_id);
+        }
+        self.app.release_service(address);
+    }
+}
+
+impl Proxy {
+    /// Creates the proxy and requests the service instance.
+    pub fn new(app: Arc<VSomeipApplication>, address: Address) -> Self {
+        app.request_service(address);
+        Proxy {
+            app,
+            address,
+            status: Mutex::new(AvailabilityStatus::Unknown),
+            status_event: Event::default(),
+            pending: Mutex::new(HashMap::new()),
+            events: Mutex::new(HashMap::new()),
+            error_decoder: Mutex::new(None),
+        }
+    }
+
+    pub fn get_address(&self) -> &Address {
+        &self.address
+    }
+
+    pub fn is_available(&self) -> bool {
+        *self.status.lock().unwrap() == AvailabilityStatus::Available
+    }
+
+    pub fn get_availability_status(&self) -> AvailabilityStatus {
+        *self.status.lock().unwrap()
+    }
+
+    /// Event fired on every change of the availability status.
+    pub fn get_proxy_status_event(&self) -> &Event<AvailabilityStatus> {
+        &self.status_event
+    }
+
+    /// Sets the decoder for the payload of error messages of the service.
+    pub fn set_error_decoder(&self, decoder: impl Fn(ReturnCode, &Bytes) -> Option<Box<dyn Error + Send + Sync>>
+                                                  + Send + Sync + 'static) {
+        *self.error_decoder.lock().unwrap() = Some(Box::new(decoder));
+    }
+
+    /// Calls a method. The callback is invoked with the call status and the response payload
+    /// once the response or error arrives - or immediately with [CallStatus::NotAvailable] if
+    /// the service is not available.
+    pub fn call_method_async(&self, method_id: MethodID, payload: &Bytes, reliability: Reliability,
+                             callback: impl FnOnce(CallStatus, Bytes) + Send + 'static) {
+        self.call_method(method_id, payload, reliability, move |result| match result {
+            Ok(response) => callback(CallStatus::Success, response),
+            Err(error) => callback(error.status, error.payload),
+        })
+    }
+
+    /// Calls a method like [Proxy::call_method_async()], but errors are reported as [CallError]
+    /// including the application error decoded by the proxy's error decoder.
+    pub fn call_method(&self, method_id: MethodID, payload: &Bytes, reliability: Reliability,
+                       callback: impl FnOnce(Result<Bytes, CallError>) + Send + 'static) {
+        self.send_call(method_id, payload, reliability, Box::new(callback));
+    }
+
+    /// Sends the call, returns the session of the pending call.
+    fn send_call(&self, method_id: MethodID, payload: &Bytes, reliability: Reliability, callback: CallCallback)
+        -> Option<SessionID>
+    {
+        if !self.is_available() {
+            callback(Err(CallError::from(CallStatus::NotAvailable)));
+            return None;
+        }
+        // hold the lock while sending so that the response cannot be handled before the
+        // callback is stored
+        let mut pending = self.pending.lock().unwrap();
+        match self.app.send_request(self.address, method_id, payload, reliability) {
+            Ok(session) => {
+                pending.insert(session, callback);
+                Some(session)
+            }
+            Err(e) => {
+                drop(pending);
+                callback(Err(CallError { error: Some(Box::new(e)), ..CallError::from(CallStatus::InvalidValue) }));
+                None
+            }
+        }
+    }
+
+    /// Drops the callback of a pending call, a later response is ignored.
+    fn cancel(&self, session: SessionID) {
+        self.pending.lock().unwrap().remove(&session);
+    }
+
+    /// Returns the attribute with the given deployment.
+    pub fn attribute(&self, deployment: AttributeDeployment) -> Attribute<'_> {
+        Attribute { proxy: self, deployment }
+    }
+
+    /// Returns the broadcast with the given deployment.
+    pub fn broadcast(&self, deployment: BroadcastDeployment) -> Broadcast<'_> {
+        Broadcast { proxy: self, deployment }
+    }
+
+    /// Processes a message received by the application.
+    /// Returns whether the message belonged to this proxy.
+    pub fn handle(&self, msg: &VSomeipMessage) -> bool {
+        match msg {
+            VSomeipMessage::ServiceAvailability { service_id, instance_id, avail, .. } => {
+                if *service_id != self.address.service_id || *instance_id != self.address.instance_id {
+                    return false;
+                }
+                self.set_status(if *avail { AvailabilityStatus::Available } else { AvailabilityStatus::NotAvailable });
+                true
+            }
+            VSomeipMessage::Message(MessageType::Response { header, data }) if self.matches(header) => {
+                self.complete(header, Ok(data.as_bytes_ref().clone()))
+            }
+            VSomeipMessage::Message(MessageType::Error { header, return_code, data }) if self.matches(header) => {
+                let payload = data.as_bytes_ref().clone();
+                let error = self.error_decoder.lock().unwrap().as_ref()
+                    .and_then(|decode| decode(*return_code, &payload));
+                self.complete(header, Err(CallError { status: CallStatus::from(return_code),
+                    return_code: Some(*return_code), payload, error }))
+            }
+            VSomeipMessage::Message(MessageType::Notification { header, data, .. }) if self.matches(header) => {
+                let event = self.events.lock().unwrap().get(&header.method_id).map(|(_, e)| e.clone());
+                match event {
+                    Some(event) => { event.fire(data.as_bytes_ref()); true }
+                    None => false,
+                }
+            }
+            _ => false,
+        }
+    }
+
+    fn matches(&self, header: &MessageHeader) -> bool {
+        header.service_id == self.address.service_id && header.instance_id == self.address.instance_id
+    }
+
+    fn complete(&self, header: &MessageHeader, result: Result<Bytes, CallError>) -> bool {
+        let callback = self.pending.lock().unwrap().remove(&header.session_id);
+        match callback {
+            Some(callback) => { callback(result); true }
+            None => false,
+        }
+    }
+
+    fn set_status(&self, status: AvailabilityStatus) {
+        {
+            let mut current = self.status.lock().unwrap();
+            if *current == status {
+                return;
+            }
+            *current = status;
+        }
+        if status == AvailabilityStatus::Available {
+            for (event_id, (eventgroup, _)) in self.events.lock().unwrap().iter() {
+                self.subscribe(*eventgroup, *event_id);
+            }
+        } else {
+            // the callbacks may call the proxy again
+            let pending = std::mem::take(&mut *self.pending.lock().unwrap());
+            for (_, callback) in pending {
+                callback(Err(CallError::from(CallStatus::NotAvailable)));
+            }
+        }
+        self.status_event.fire(&status);
+    }
+
+    /// Returns the event for a notifier, requesting the event (and subscribing if available) on
+    /// first use.
+    fn event(&self, event_id: MethodID, eventgroup: EventGroupID, is_field: bool) -> Arc<Event<Bytes>> {
+        let mut events = self.events.lock().unwrap();
+        events.entry(event_id).or_insert_with(|| {
+            let address = self.address;
+            self.app.request_event_seg(address.service_id, address.instance_id, event_id, eventgroup, is_field,
+                                       Reliability::Unknown);
+            if self.is_available() {
+                self.subscribe(eventgroup, event_id);
+            }
+            (eventgroup, Arc::new(Event::default()))
+        }).1.clone()
+    }
+
+    /// Subscribes the event, a failure is only logged as the event's listeners cannot be told.
+    fn subscribe(&self, eventgroup: EventGroupID, event_id: MethodID) {
+        if let Err(e) = self.app.subscribe(self.address, eventgroup, event_id) {
+            log::warn!("Cannot subscribe event {}: {}", event_id, e);
+        }
+    }
+}
+
+/// Calls of a [FailoverProxy::call_method_hedged()], the callbacks of the ones still pending are
+/// dropped when it completes or is dropped.
+struct HedgedCalls<'a> {
+    proxies: &'a [Proxy],
+    sessions: Mutex<Vec<(usize, SessionID)>>,
+}
+
+impl Drop for HedgedCalls<'_> {
+    fn drop(&mut self) {
+        for (index, session) in self.sessions.get_mut().unwrap().drain(..) {
+            self.proxies[index].cancel(session);
+        }
+    }
+}
+
+/// Starts the primary call and, if it has not succeeded within `delay`, the hedge call, which
+/// returns `false` if there is no instance to call. A primary call failing before the delay is
+/// hedged immediately. Returns the first successful response or, if all calls fail, the first
+/// error, which is the primary's unless it was still pending when the hedge failed.
+async fn hedged(delay: Duration, primary: impl FnOnce(CallCallback), hedge: impl FnOnce(CallCallback) -> bool)
+    -> Result<Bytes, CallError>
+{
+    let (sender, mut results) = tokio::sync::mpsc::unbounded_channel();
+    let callback = |sender: &tokio::sync::mpsc::UnboundedSender<_>| -> CallCallback {
+        let sender = sender.clone();
+        Box::new(move |result| { let _ = sender.send(result); })
+    };
+    primary(callback(&sender));
+    let mut outstanding = 1;
+    let mut first_error = None;
+    if let Ok(Some(result)) = tokio::time::timeout(delay, results.recv()).await {
+        match result {
+            Ok(response) => return Ok(response),
+            Err(error) => first_error = Some(error),
+        }
+        outstanding -= 1;
+    }
+    if hedge(callback(&sender)) {
+        outstanding += 1;
+    }
+    drop(sender);
+    while outstanding > 0 {
+        match results.recv().await {
+            Some(Ok(response)) => return Ok(response),
+            Some(Err(error)) => { first_error.get_or_insert(error); }
+            None => break,
+        }
+        outstanding -= 1;
+    }
+    Err(first_error.unwrap_or_else(|| CallError::from(CallStatus::NotAvailable)))
+}
+
+/// Event of a [FailoverProxy] with the listener forwarding the notifications of the active
+/// instance's proxy.
+struct FailoverEvent {
+    eventgroup: EventGroupID,
+    is_field: bool,
+    event: Arc<Event<Bytes>>,
+    forwarder: Option<(usize, Subscription)>,
+}
+
+/// Client side representation of a service offered as several redundant instances.
+///
+/// A [Proxy] is created for each candidate instance, in order of preference. Calls and event
+/// subscriptions use the most preferred available instance (the active one). When it disappears
+/// they are redirected to the next available instance, when a more preferred instance becomes
+/// available again they move back to it. Calls pending at the disappeared instance complete with
+/// [CallStatus::NotAvailable] and are not repeated.
+pub struct FailoverProxy {
+    proxies: Vec<Proxy>,
+    active: Mutex<Option<usize>>,
+    active_event: Event<Option<InstanceID>>,
+    events: Mutex<HashMap<MethodID, FailoverEvent>>,
+}
+
+impl FailoverProxy {
+    /// Creates the proxies of the candidate instances, `instances` in order of preference.
+    pub fn new(app: Arc<VSomeipApplication>, service_id: ServiceID, instances: &[InstanceID],
+               version: InterfaceVersion) -> Self {
+        let proxies = instances.iter()
+            .map(|instance_id| Proxy::new(app.clone(), Address::new(service_id, *instance_id, version)))
+            .collect();
+        FailoverProxy { proxies, active: Mutex::new(None), active_event: Event::default(),
+            events: Mutex::new(HashMap::new()) }
+    }
+
+    /// Returns the proxy of the active instance.
+    pub fn active_proxy(&self) -> Option<&Proxy> {
+        self.active.lock().unwrap().map(|index| &self.proxies[index])
+    }
+
+    pub fn is_available(&self) -> bool {
+        self.active.lock().unwrap().is_some()
+    }
+
+    /// Event fired when the active instance changes, `None` if no instance is available.
+    pub fn get_active_instance_event(&self) -> &Event<Option<InstanceID>> {
+        &self.active_event
+    }
+
+    /// Calls a method at the active instance, see [Proxy::call_method()].
+    pub fn call_method(&self, method_id: MethodID, payload: &Bytes, reliability: Reliability,
+                       callback: impl FnOnce(Result<Bytes, CallError>) + Send + 'static) {
+        match self.active_proxy() {
+            Some(proxy) => proxy.call_method(method_id, payload, reliability, callback),
+            None => callback(Err(CallError::from(CallStatus::NotAvailable))),
+        }
+    }
+
+    /// Calls a method at the active instance, see [Proxy::call_method_async()].
+    pub fn call_method_async(&self, method_id: MethodID, payload: &Bytes, reliability: Reliability,
+                             callback: impl FnOnce(CallStatus, Bytes) + Send + 'static) {
+        self.call_method(method_id, payload, reliability, move |result| match result {
+            Ok(response) => callback(CallStatus::Success, response),
+            Err(error) => callback(error.status, error.payload),
+        })
+    }
+
+    /// Calls a read-only, idempotent method with hedging: if the active instance has not answered
+    /// within `delay` or failed before, the call is sent to the next available instance as well
+    /// and the first successful response is returned. If all calls fail, the first error is
+    /// returned, i.e. the active instance's if it failed before the delay. The response of the
+    /// call that lost is ignored.
+    ///
+    /// The responses are delivered by [FailoverProxy::handle()], which must be called by another
+    /// task while this call is awaited.
+    pub async fn call_method_hedged(&self, method_id: MethodID, payload: &Bytes, reliability: Reliability,
+                                    delay: Duration) -> Result<Bytes, CallError> {
+        let Some(primary) = *self.active.lock().unwrap() else {
+            return Err(CallError::from(CallStatus::NotAvailable));
+        };
+        let calls = HedgedCalls { proxies: &self.proxies, sessions: Mutex::new(Vec::new()) };
+        let call = |index: usize, callback: CallCallback| {
+            if let Some(session) = self.proxies[index].send_call(method_id, payload, reliability, callback) {
+                calls.sessions.lock().unwrap().push((index, session));
+            }
+        };
+        let hedge = |callback| {
+            let hedge = self.proxies.iter().enumerate()
+                .position(|(index, proxy)| index != primary && proxy.is_available());
+            hedge.map(|index| call(index, callback)).is_some()
+        };
+        hedged(delay, |callback| call(primary, callback), hedge).await
+    }
+
+    /// Event fired with each broadcast of the active instance.
+    pub fn get_broadcast_event(&self, deployment: BroadcastDeployment) -> Arc<Event<Bytes>> {
+        self.event(deployment.event_id, deployment.eventgroup, false)
+    }
+
+    /// Event fired with each notification of the attribute by the active instance; `None` for
+    /// attributes without notifier.
+    pub fn get_attribute_changed_event(&self, deployment: AttributeDeployment) -> Option<Arc<Event<Bytes>>> {
+        deployment.notifier.map(|(event_id, eventgroup)| self.event(event_id, eventgroup, true))
+    }
+
+    /// Processes a message received by the application.
+    /// Returns whether the message belonged to one of the candidate instances.
+    pub fn handle(&self, msg: &VSomeipMessage) -> bool {
+        let handled = self.proxies.iter().any(|proxy| proxy.handle(msg));
+        if handled {
+            self.update_active();
+        }
+        handled
+    }
+
+    fn update_active(&self) {
+        let active = self.proxies.iter().position(|proxy| proxy.is_available());
+        {
+            let mut current = self.active.lock().unwrap();
+            if *current == active {
+                return;
+            }
+            *current = active;
+        }
+        for event in self.events.lock().unwrap().iter_mut() {
+            self.redirect(*event.0, event.1, active);
+        }
+        self.active_event.fire(&active.map(|index| self.proxies[index].address.instance_id));
+    }
+
+    /// Moves the forwarding listener of an event to the proxy of the active instance.
+    fn redirect(&self, event_id: MethodID, event: &mut FailoverEvent, active: Option<usize>) {
+        if let Some((index, subscription)) = event.forwarder.take() {
+            self.proxies[index].event(event_id, event.eventgroup, event.is_field).unsubscribe(subscription);
+        }
+        if let Some(index) = active {
+            let target = event.event.clone();
+            let subscription = self.proxies[index].event(event_id, event.eventgroup, event.is_field)
+                .subscribe(move |data| target.fire(data));
+            event.forwarder = Some((index, subscription));
+        }
+    }
+
+    fn event(&self, event_id: MethodID, eventgroup: EventGroupID, is_field: bool) -> Arc<Event<Bytes>> {
+        let mut events = self.events.lock().unwrap();
+        if let Some(event) = events.get(&event_id) {
+            return event.event.clone();
+        }
+        let mut event = FailoverEvent { eventgroup, is_field, event: Arc::new(Event::default()), forwarder: None };
+        self.redirect(event_id, &mut event, *self.active.lock().unwrap());
+        let result = event.event.clone();
+        events.insert(event_id, event);
+        result
+    }
+}
+
+/// Client side view of an attribute (CommonAPI `Attribute`/`ObservableAttribute`).
+pub struct Attribute<'a> {
+    proxy: &'a Proxy,
+    deployment: AttributeDeployment,
+}
+
+impl Attribute<'_> {
+    /// Reads the attribute value via its getter method.
+    pub fn get_value_async(&self, callback: impl FnOnce(CallStatus, Bytes) + Send + 'static) {
+        self.proxy.call_method_async(self.deployment.getter, &Bytes::new(), Reliability::Unreliable, callback)
+    }
+
+    /// Writes the attribute value via its setter method. The callback receives the value the
+    /// provider actually applied.
+    pub fn set_value_async(&self, value: &Bytes, callback: impl FnOnce(CallStatus, Bytes) + Send + 'static) {
+        match self.deployment.setter {
+            Some(setter) => self.proxy.call_method_async(setter, value, Reliability::Unreliable, callback),
+            None => callback(CallStatus::InvalidValue, Bytes::new()),
+        }
+    }
+
+    /// Event fired with each notification of the attribute; `None` for attributes without notifier.
+    pub fn get_changed_event(&self) -> Option<Arc<Event<Bytes>>> {
+        self.deployment.notifier
+            .map(|(event_id, eventgroup)| self.proxy.event(event_id, eventgroup, true))
+    }
+}
+
+/// Client side view of a broadcast (CommonAPI `Event`).
+pub struct Broadcast<'a> {
+    proxy: &'a Proxy,
+    deployment: BroadcastDeployment,
+}
+
+impl Broadcast<'_> {
+    /// Event fired with the payload of each broadcast.
+    pub fn get_event(&self) -> Arc<Event<Bytes>> {
+        self.proxy.event(self.deployment.event_id, self.deployment.eventgroup, false)
+    }
+}
+
+/// Service implementation behind a [StubAdapter] (CommonAPI `Stub`).
+pub trait Stub: Send + Sync {
+    /// Handles a method call that is not an attribute getter or setter.
+    /// Returns the response payload or the return code for an error message.
+    fn on_method_call(&self, client: ClientID, method_id: MethodID, payload: &Bytes) -> Result<Bytes, ReturnCode>;
+
+    /// Called when a client wants to set an attribute (CommonAPI `onRemoteSet...`).
+    /// Returns the value to be applied or `None` to reject the change.
+    fn on_remote_set_attribute(&self, _client: ClientID, _getter: MethodID, value: &Bytes) -> Option<Bytes> {
+        Some(value.clone())
+    }
+}
+
+struct StubAttribute {
+    deployment: AttributeDeployment,
+    value: Bytes,
+}
+
+/// Provider side adapter connecting a [Stub] to SOME/IP (CommonAPI `StubAdapter`).
+pub struct StubAdapter {
+    app: Arc<VSomeipApplication>,
+    address: Address,
+    stub: Arc<dyn Stub>,
+    attributes: Mutex<Vec<StubAttribute>>,
+    broadcasts: Mutex<Vec<BroadcastDeployment>>,
+}
+
+impl StubAdapter {
+    pub fn new(app: Arc<VSomeipApplication>, address: Address, stub: Arc<dyn Stub>) -> Self {
+        StubAdapter { app, address, stub, attributes: Mutex::new(Vec::new()), broadcasts: Mutex::new(Vec::new()) }
+    }
+
+    /// Registers an attribute with its initial value and offers its notifier event.
+    pub fn register_attribute(&self, deployment: AttributeDeployment, initial: Bytes) -> Result<(), SendError> {
+        if let Some((event_id, eventgroup)) = deployment.notifier {
+            self.app.offer_event_seg(self.address.service_id, self.address.instance_id, event_id, eventgroup,
+                                     EventKind::Field, None, false, true, Reliability::Unknown)?;
+            self.notify(event_id, &initial, false);
+        }
+        self.attributes.lock().unwrap().push(StubAttribute { deployment, value: initial });
+        Ok(())
+    }
+
+    /// Registers a broadcast and offers its event.
+    pub fn register_broadcast(&self, deployment: BroadcastDeployment) -> Result<(), SendError> {
+        self.app.offer_event_seg(self.address.service_id, self.address.instance_id, deployment.event_id,
+                                 deployment.eventgroup, EventKind::Event, None, false, true, Reliability::Unknown)?;
+        self.broadcasts.lock().unwrap().push(deployment);
+        Ok(())
+    }
+
+    /// Offers the service instance.
+    pub fn offer(&self) -> Result<(), SendError> {
+        self.app.offer_service(self.address)
+    }
+
+    /// Stops offering the service instance and its events.
+    pub fn stop_offer(&self) {
+        let address = self.address;
+        for attribute in self.attributes.lock().unwrap().iter() {
+            if let Some((event_id, _)) = attribute.deployment.notifier {
+                self.app.stop_offer_event(address.service_id, address.instance_id, event_id);
+            }
+        }
+        for broadcast in self.broadcasts.lock().unwrap().iter() {
+            self.app.stop_offer_event(address.service_id, address.instance_id, broadcast.event_id);
+        }
+        self.app.stop_offer_service(address);
+    }
+
+    /// Changes the value of an attribute (identified by its getter) and notifies subscribers
+    /// (CommonAPI `fire...AttributeChanged`).
+    pub fn set_attribute(&self, getter: MethodID, value: Bytes) {
+        let mut attributes = self.attributes.lock().unwrap();
+        if let Some(attribute) = attributes.iter_mut().find(|a| a.deployment.getter == getter) {
+            self.notify_attribute(attribute, value);
+        }
+    }
+
+    /// Returns the current value of an attribute.
+    pub fn get_attribute(&self, getter: MethodID) -> Option<Bytes> {
+        self.attributes.lock().unwrap().iter()
+            .find(|a| a.deployment.getter == getter)
+            .map(|a| a.value.clone())
+    }
+
+    /// Sends a broadcast (CommonAPI `fire...Event`).
+    pub fn fire_broadcast(&self, event_id: MethodID, payload: &Bytes) -> Result<(), SendError> {
+        self.app.notify(self.address.service_id, self.address.instance_id, event_id, payload, true)
+    }
+
+    /// Processes a message received by the application; requests for the service instance are
+    /// answered. Returns whether the message belonged to this adapter.
+    pub fn handle(&self, msg: &VSomeipMessage) -> bool {
+        let (header, data, needs_response) = match msg {
+            VSomeipMessage::Message(MessageType::Request { header, data }) => (header, data, true),
+            VSomeipMessage::Message(MessageType::RequestNoReturn { header, data }) => (header, data, false),
+            _ => return false,
+        };
+        if header.service_id != self.address.service_id || header.instance_id != self.address.instance_id {
+            return false;
+        }
+        let payload = data.as_bytes_ref();
+        let result = self.handle_attribute(header, payload)
+            .unwrap_or_else(|| self.stub.on_method_call(header.client_id, header.method_id, payload));
+        if needs_response {
+            match result {
+                Ok(response) => {
+                    if let Err(e) = self.app.send_response(header, ReturnCode::Ok, &response) {
+                        log::warn!("Cannot send response: {}", e);
+                        self.send_error(header, ReturnCode::NotOk);
+                    }
+                }
+                Err(rc) => self.send_error(header, rc),
+            }
+        }
+        true
+    }
+
+    fn handle_attribute(&self, header: &MessageHeader, payload: &Bytes) -> Option<Result<Bytes, ReturnCode>> {
+        let mut attributes = self.attributes.lock().unwrap();
+        if let Some(attribute) = attributes.iter().find(|a| a.deployment.getter == header.method_id) {
+            return Some(Ok(attribute.value.clone()));
+        }
+        let attribute = attributes.iter_mut().find(|a| a.deployment.setter == Some(header.method_id))?;
+        match self.stub.on_remote_set_attribute(header.client_id, attribute.deployment.getter, payload) {
+            Some(value) => {
+                self.notify_attribute(attribute, value.clone());
+                Some(Ok(value))
+            }
+            None => Some(Ok(attribute.value.clone())),
+        }
+    }
+
+    fn notify_attribute(&self, attribute: &mut StubAttribute, value: Bytes) {
+        if attribute.value == value {
+            return;
+        }
+        attribute.value = value;
+        if let Some((event_id, _)) = attribute.deployment.notifier {
+            self.notify(event_id, &attribute.value, false);
+        }
+    }
+
+    fn send_error(&self, header: &MessageHeader, return_code: ReturnCode) {
+        if let Err(e) = self.app.send_error(header, return_code) {
+            log::warn!("Cannot send error response: {}", e);
+        }
+    }
+
+    /// Notifies an attribute change, a failure is only logged as there is no caller to report it to.
+    fn notify(&self, event_id: MethodID, payload: &Bytes, force: bool) {
+        if let Err(e) = self.app.notify(self.address.service_id, self.address.instance_id, event_id, payload, force) {
+            log::warn!("Cannot notify attribute: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn call_status_test() {
+        assert_eq!(CallStatus::from(&ReturnCode::Ok), CallStatus::Success);
+        assert_eq!(CallStatus::from(&ReturnCode::NotReachable), CallStatus::NotAvailable);
+        assert_eq!(CallStatus::from(&ReturnCode::NotOk), CallStatus::RemoteError);
+    }
+
+    #[test]
+    fn call_error_test() {
+        #[derive(Debug)]
+        struct Exception(String);
+        impl fmt::Display for Exception {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { write!(f, "{}", self.0) }
+        }
+        impl Error for Exception {}
+
+        let error = CallError { status: CallStatus::RemoteError,
+            return_code: Some(ReturnCode::ApplicationSpecific(0x20)), payload: Bytes::from_static(b"boom"),
+            error: Some(Box::new(Exception("boom".to_string()))) };
+        assert_eq!(error.downcast_ref::<Exception>().unwrap().0, "boom");
+        assert_eq!(error.to_string(), "call failed: RemoteError (APPLICATION(0x20)): boom");
+        assert!(CallError::from(CallStatus::NotAvailable).downcast_ref::<Exception>().is_none());
+    }
+
+    #[test]
+    fn event_subscribe_test() {
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let event = Event::<u32>::default();
+        let r = received.clone();
+        let s1 = event.subscribe(move |v| r.lock().unwrap().push(*v));
+        let r = received.clone();
+        event.subscribe(move |v| r.lock().unwrap().push(*v * 10));
+        event.fire(&1);
+        event.unsubscribe(s1);
+        event.fire(&2);
+        assert_eq!(*received.lock().unwrap(), vec![1, 10, 20]);
+    }
+
+    #[test]
+    fn event_reentrant_test() {
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let event = Arc::new(Event::<u32>::default());
+        let own = Arc::new(std::sync::OnceLock::new());
+        let (e, r, o) = (event.clone(), received.clone(), own.clone());
+        let subscription = event.subscribe(move |v| {
+            r.lock().unwrap().push(*v);
+            // replaces itself by another listener and fires again
+            e.unsubscribe(*o.get().unwrap());
+            let r = r.clone();
+            e.subscribe(move |v| r.lock().unwrap().push(*v * 10));
+            e.fire(&(*v + 1));
+        });
+        own.set(subscription).unwrap();
+        event.fire(&1);
+        assert_eq!(*received.lock().unwrap(), vec![1, 20]);
+        event.fire(&3);
+        assert_eq!(*received.lock().unwrap(), vec![1, 20, 30]);
+    }
+
+    /// Call answered by a task after the delay, `None` for an error.
+    fn answer(callback: CallCallback, after: Duration, response: Option<&'static [u8]>) {
+        tokio::spawn(async move {
+            tokio::time::sleep(after).await;
+            callback(response.map(Bytes::from_static).ok_or_else(|| CallError::from(CallStatus::RemoteError)));
+        });
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn hedged_primary_succeeds_test() {
+        let delay = Duration::from_millis(100);
+        let result = hedged(delay, |callback| answer(callback, Duration::from_millis(10), Some(b"primary")),
+                            |_| panic!("hedged although the primary answered")).await;
+        assert_eq!(result.unwrap(), Bytes::from_static(b"primary"));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn hedged_primary_fails_test() {
+        let delay = Duration::from_millis(100);
+        // the primary's error is returned if there is no other instance
+        let result = hedged(delay, |callback| answer(callback, Duration::from_millis(10), None), |_| false).await;
+        assert_eq!(result.unwrap_err().status, CallStatus::RemoteError);
+        let result = hedged(delay, |callback| answer(callback, Duration::from_millis(10), None), |callback| {
+            answer(callback, Duration::from_millis(10), Some(b"hedge"));
+            true
+        }).await;
+        assert_eq!(result.unwrap(), Bytes::from_static(b"hedge"));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn hedged_all_fail_test() {
+        let delay = Duration::from_millis(100);
+        /// Call failing with the status after the delay.
+        fn fail(callback: CallCallback, after: Duration, status: CallStatus) {
+            tokio::spawn(async move {
+                tokio::time::sleep(after).await;
+                callback(Err(CallError::from(status)));
+            });
+        }
+        // the primary failed before the delay, its error is kept when the hedge fails too
+        let result = hedged(delay, |callback| fail(callback, Duration::from_millis(10), CallStatus::RemoteError),
+                            |callback| {
+            fail(callback, Duration::from_millis(10), CallStatus::ConnectionFailed);
+            true
+        }).await;
+        assert_eq!(result.unwrap_err().status, CallStatus::RemoteError);
+        // the slow primary fails after the hedge, the hedge's error came first
+        let result = hedged(delay, |callback| fail(callback, Duration::from_secs(1), CallStatus::RemoteError),
+                            |callback| {
+            fail(callback, Duration::from_millis(10), CallStatus::ConnectionFailed);
+            true
+        }).await;
+        assert_eq!(result.unwrap_err().status, CallStatus::ConnectionFailed);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn hedged_primary_slow_test() {
+        let delay = Duration::from_millis(100);
+        let start = tokio::time::Instant::now();
+        let result = hedged(delay, |callback| answer(callback, Duration::from_secs(1), Some(b"primary")), |callback| {
+            answer(callback, Duration::from_millis(10), Some(b"hedge"));
+            true
+        }).await;
+        assert_eq!(result.unwrap(), Bytes::from_static(b"hedge"));
+        assert_eq!(start.elapsed(), Duration::from_millis(110));
+        // the slow primary's response is taken if the hedge fails
+        let result = hedged(delay, |callback| answer(callback, Duration::from_secs(1), Some(b"primary")), |callback| {
+            answer(callback, Duration::from_millis(10), None);
+            true
+        }).await;
+        assert_eq!(result.unwrap(), Bytes::from_static(b"primary"));
+    }
+}
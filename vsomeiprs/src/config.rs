@@ -0,0 +1,327 @@
+// SPDX-License-Identifier: MPL-2.0
+//
+// Copyright (C) 2024 Alexander Seifarth
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::fmt::Write;
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use super::{InstanceID, MethodID, ServiceID};
+
+/// Name of the environment variable vsomeip reads its configuration file (or directory) from.
+pub const VSOMEIP_CONFIGURATION_ENV: &str = "VSOMEIP_CONFIGURATION";
+
+/// Default maximum segment length used by vsomeip for SOME/IP-TP.
+pub const DEFAULT_TP_MAX_SEGMENT_LENGTH: u16 = 1392;
+
+/// Builder for a vsomeip JSON configuration.
+///
+/// vsomeip reads its configuration when an application is created, so the configuration must be
+/// written and applied (see [Configuration::apply]) before [crate::VSomeipApplication::create()]
+/// is called, or passed to [crate::ApplicationBuilder::configuration()].
+/// ```rust
+/// use std::time::Duration;
+/// use vsomeiprs::{Configuration, InstanceID, MethodID, ServiceConfig, ServiceID, TpConfig};
+///
+/// let config = Configuration::new()
+///     .logging("fatal", false)
+///     .service(ServiceConfig::new(ServiceID(0x1234), InstanceID(1))
+///         .unreliable(30509)
+///         .tp_service_to_client(TpConfig::new(MethodID(0x8001))
+///             .max_segment_length(1024)
+///             .separation_time(Duration::from_micros(500))));
+/// assert!(config.to_json().contains("someip-tp"));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Configuration {
+    unicast: Option<IpAddr>,
+    logging: Option<(String, bool)>,
+    max_payload_size_local: Option<u32>,
+    max_payload_size_reliable: Option<u32>,
+    max_payload_size_unreliable: Option<u32>,
+    services: Vec<ServiceConfig>,
+    applications: Vec<(String, u8)>,
+}
+
+impl Configuration {
+    /// Creates an empty configuration - vsomeip defaults apply to everything not set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the unicast address of the host.
+    pub fn unicast(mut self, address: IpAddr) -> Self {
+        self.unicast = Some(address);
+        self
+    }
+
+    /// Sets the vsomeip log level ("fatal", "error", "warning", "info", "debug", "verbose") and
+    /// whether vsomeip logs to the console.
+    pub fn logging(mut self, level: &str, console: bool) -> Self {
+        self.logging = Some((level.to_string(), console));
+        self
+    }
+
+    /// Maximum payload size for messages between applications on the same host.
+    pub fn max_payload_size_local(mut self, size: u32) -> Self {
+        self.max_payload_size_local = Some(size);
+        self
+    }
+
+    /// Maximum payload size for messages sent over TCP.
+    pub fn max_payload_size_reliable(mut self, size: u32) -> Self {
+        self.max_payload_size_reliable = Some(size);
+        self
+    }
+
+    /// Maximum payload size for messages sent over UDP. Payloads beyond the UDP MTU require
+    /// SOME/IP-TP to be configured for the method or event (see [ServiceConfig]).
+    pub fn max_payload_size_unreliable(mut self, size: u32) -> Self {
+        self.max_payload_size_unreliable = Some(size);
+        self
+    }
+
+    /// Adds the configuration of a service instance.
+    pub fn service(mut self, service: ServiceConfig) -> Self {
+        self.services.push(service);
+        self
+    }
+
+    /// Sets the number of i/o threads of the application `name`.
+    pub fn application_threads(mut self, name: &str, threads: u8) -> Self {
+        self.applications.retain(|(n, _)| n != name);
+        self.applications.push((name.to_string(), threads));
+        self
+    }
+
+    /// Renders the configuration in the JSON format read by vsomeip.
+    pub fn to_json(&self) -> String {
+        let mut members = Vec::new();
+        if let Some(address) = &self.unicast {
+            members.push(format!("\"unicast\": {}", json_string(&address.to_string())));
+        }
+        if let Some((level, console)) = &self.logging {
+            members.push(format!("\"logging\": {{ \"level\": {}, \"console\": \"{}\", \
+                \"file\": {{ \"enable\": \"false\" }}, \"dlt\": \"false\" }}", json_string(level), console));
+        }
+        if let Some(size) = self.max_payload_size_local {
+            members.push(format!("\"max-payload-size-local\": \"{}\"", size));
+        }
+        if let Some(size) = self.max_payload_size_reliable {
+            members.push(format!("\"max-payload-size-reliable\": \"{}\"", size));
+        }
+        if let Some(size) = self.max_payload_size_unreliable {
+            members.push(format!("\"max-payload-size-unreliable\": \"{}\"", size));
+        }
+        if !self.services.is_empty() {
+            let services: Vec<String> = self.services.iter().map(|s| s.to_json()).collect();
+            members.push(format!("\"services\": [ {} ]", services.join(", ")));
+        }
+        if !self.applications.is_empty() {
+            let applications: Vec<String> = self.applications.iter()
+                .map(|(name, threads)| format!("{{ \"name\": {}, \"threads\": \"{}\" }}", json_string(name), threads))
+                .collect();
+            members.push(format!("\"applications\": [ {} ]", applications.join(", ")));
+        }
+        format!("{{ {} }}", members.join(", "))
+    }
+
+    /// Writes the JSON configuration to the file `path`.
+    pub fn write_to(&self, path: &Path) -> std::io::Result<()> {
+        std::fs::write(path, self.to_json())
+    }
+
+    /// Writes the configuration to `path` and returns the environment variable pointing vsomeip
+    /// to it, as name and value.
+    /// With `application` set only the vsomeip application of that name will use the
+    /// configuration (`VSOMEIP_CONFIGURATION_<application>`), otherwise all applications
+    /// created afterwards will use it.
+    ///
+    /// The environment is not modified, the caller sets the variable for the process that creates
+    /// the applications, e.g. with [std::process::Command::env()]. Setting it in the current
+    /// process with [std::env::set_var()] is only sound before other threads are started.
+    pub fn apply(&self, path: &Path, application: Option<&str>) -> std::io::Result<(String, PathBuf)> {
+        self.write_to(path)?;
+        let name = match application {
+            Some(name) => format!("{}_{}", VSOMEIP_CONFIGURATION_ENV, name),
+            None => VSOMEIP_CONFIGURATION_ENV.to_string(),
+        };
+        Ok((name, path.to_path_buf()))
+    }
+}
+
+/// Configuration of a single service instance.
+#[derive(Debug, Clone)]
+pub struct ServiceConfig {
+    service_id: ServiceID,
+    instance_id: InstanceID,
+    reliable: Option<u16>,
+    unreliable: Option<u16>,
+    tp_client_to_service: Vec<TpConfig>,
+    tp_service_to_client: Vec<TpConfig>,
+}
+
+impl ServiceConfig {
+    pub fn new(service_id: ServiceID, instance_id: InstanceID) -> Self {
+        ServiceConfig { service_id, instance_id, reliable: None, unreliable: None,
+            tp_client_to_service: Vec::new(), tp_service_to_client: Vec::new() }
+    }
+
+    /// Sets the TCP port the service instance is offered on.
+    pub fn reliable(mut self, port: u16) -> Self {
+        self.reliable = Some(port);
+        self
+    }
+
+    /// Sets the UDP port the service instance is offered on.
+    pub fn unreliable(mut self, port: u16) -> Self {
+        self.unreliable = Some(port);
+        self
+    }
+
+    /// Enables SOME/IP-TP for a method whose requests (client -> service) may exceed the UDP MTU.
+    pub fn tp_client_to_service(mut self, tp: TpConfig) -> Self {
+        self.tp_client_to_service.push(tp);
+        self
+    }
+
+    /// Enables SOME/IP-TP for a method or event whose responses/notifications (service -> client)
+    /// may exceed the UDP MTU.
+    pub fn tp_service_to_client(mut self, tp: TpConfig) -> Self {
+        self.tp_service_to_client.push(tp);
+        self
+    }
+
+    fn to_json(&self) -> String {
+        let mut members = vec![
+            format!("\"service\": \"0x{}\"", self.service_id),
+            format!("\"instance\": \"0x{}\"", self.instance_id),
+        ];
+        if let Some(port) = self.reliable {
+            members.push(format!("\"reliable\": {{ \"port\": \"{}\", \"enable-magic-cookies\": \"false\" }}", port));
+        }
+        if let Some(port) = self.unreliable {
+            members.push(format!("\"unreliable\": \"{}\"", port));
+        }
+        if !self.tp_client_to_service.is_empty() || !self.tp_service_to_client.is_empty() {
+            let c2s: Vec<String> = self.tp_client_to_service.iter().map(|t| t.to_json()).collect();
+            let s2c: Vec<String> = self.tp_service_to_client.iter().map(|t| t.to_json()).collect();
+            members.push(format!("\"someip-tp\": {{ \"client-to-service\": [ {} ], \"service-to-client\": [ {} ] }}",
+                c2s.join(", "), s2c.join(", ")));
+        }
+        format!("{{ {} }}", members.join(", "))
+    }
+}
+
+/// SOME/IP-TP settings for a single method or event.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct TpConfig {
+    method_id: MethodID,
+    max_segment_length: Option<u16>,
+    separation_time: Option<Duration>,
+}
+
+impl TpConfig {
+    /// SOME/IP-TP with vsomeip's default segment length and separation time.
+    pub fn new(method_id: MethodID) -> Self {
+        TpConfig { method_id, max_segment_length: None, separation_time: None }
+    }
+
+    /// Maximum length of a single segment's payload.
+    /// SOME/IP-TP requires segment lengths (except for the last segment) to be a multiple of 16,
+    /// so the value is rounded down accordingly.
+    pub fn max_segment_length(mut self, length: u16) -> Self {
+        self.max_segment_length = Some(length & !0xf);
+        self
+    }
+
+    /// Minimum time between sending two consecutive segments of the same message.
+    /// vsomeip uses microsecond resolution.
+    pub fn separation_time(mut self, time: Duration) -> Self {
+        self.separation_time = Some(time);
+        self
+    }
+
+    fn to_json(self) -> String {
+        let mut members = vec![format!("\"method\": \"0x{}\"", self.method_id)];
+        if let Some(length) = self.max_segment_length {
+            members.push(format!("\"max-segment-length\": \"{}\"", length));
+        }
+        if let Some(time) = self.separation_time {
+            members.push(format!("\"separation-time\": \"{}\"", time.as_micros()));
+        }
+        format!("{{ {} }}", members.join(", "))
+    }
+}
+
+fn json_string(value: &str) -> String {
+    let mut out = String::with_capacity(value.len() + 2);
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            c if (c as u32) < 0x20 => { let _ = write!(out, "\\u{:04x}", c as u32); }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn tp_config_json_test() {
+        let tp = TpConfig::new(MethodID(0x8001))
+            .max_segment_length(1000)
+            .separation_time(Duration::from_micros(250));
+        assert_eq!(tp.to_json(),
+                   "{ \"method\": \"0x8001\", \"max-segment-length\": \"992\", \"separation-time\": \"250\" }");
+    }
+
+    #[test]
+    fn configuration_json_test() {
+        let config = Configuration::new()
+            .logging("fatal", false)
+            .max_payload_size_local(65536)
+            .service(ServiceConfig::new(ServiceID(0x1234), InstanceID(0x0001))
+                .unreliable(30509)
+                .tp_client_to_service(TpConfig::new(MethodID(0x0002))));
+        assert_eq!(config.to_json(),
+                   "{ \"logging\": { \"level\": \"fatal\", \"console\": \"false\", \"file\": { \"enable\": \"false\" }, \
+                   \"dlt\": \"false\" }, \"max-payload-size-local\": \"65536\", \"services\": [ { \"service\": \"0x1234\", \
+                   \"instance\": \"0x0001\", \"unreliable\": \"30509\", \"someip-tp\": { \"client-to-service\": \
+                   [ { \"method\": \"0x0002\" } ], \"service-to-client\": [  ] } } ] }");
+    }
+
+    #[test]
+    fn application_threads_json_test() {
+        let config = Configuration::new()
+            .application_threads("client", 2)
+            .application_threads("client", 4);
+        assert_eq!(config.to_json(), "{ \"applications\": [ { \"name\": \"client\", \"threads\": \"4\" } ] }");
+    }
+
+    #[test]
+    fn apply_test() {
+        let path = std::env::temp_dir().join("vsomeiprs-apply-test.json");
+        let config = Configuration::new().logging("fatal", false);
+        assert_eq!(config.apply(&path, Some("client")).unwrap(),
+                   ("VSOMEIP_CONFIGURATION_client".to_string(), path.clone()));
+        assert_eq!(config.apply(&path, None).unwrap(), (VSOMEIP_CONFIGURATION_ENV.to_string(), path.clone()));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), config.to_json());
+        assert!(std::env::var_os("VSOMEIP_CONFIGURATION_client").is_none());
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn json_string_escape_test() {
+        assert_eq!(json_string("a\"b\\c\n"), "\"a\\\"b\\\\c\\u000a\"");
+    }
+}
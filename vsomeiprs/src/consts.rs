@@ -0,0 +1,79 @@
+// SPDX-License-Identifier: MPL-2.0
+//
+// Copyright (C) 2024 Alexander Seifarth
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Reserved and well-known values of the SOME/IP specification.
+//! The wildcards used by vsomeip (`ANY_SERVICE`, `ANY_METHOD`, ...) are defined with their types.
+
+use super::{ClientID, EventGroupID, InstanceID, MethodID, ServiceID};
+
+/// Reserved service ID.
+pub const RESERVED_SERVICE_ID: ServiceID = ServiceID(0x0000);
+/// Service ID used by SOME/IP-SD messages (equal to `ANY_SERVICE` in vsomeip).
+pub const SD_SERVICE_ID: ServiceID = ServiceID(0xffff);
+/// Service ID reserved for non SOME/IP services announced by SOME/IP-SD.
+pub const NON_SOMEIP_SERVICE_ID: ServiceID = ServiceID(0xfffe);
+
+/// Method ID used by SOME/IP-SD messages.
+pub const SD_METHOD_ID: MethodID = MethodID(0x8100);
+/// Bit 15 of a method ID is set for events/notifiers and cleared for methods.
+pub const EVENT_ID_FLAG: u16 = 0x8000;
+/// Reserved method ID.
+pub const RESERVED_METHOD_ID: MethodID = MethodID(0x0000);
+/// Reserved method ID at the end of the method range.
+pub const RESERVED_LAST_METHOD_ID: MethodID = MethodID(0x7fff);
+/// Reserved event ID at the start of the event range.
+pub const RESERVED_EVENT_ID: MethodID = MethodID(0x8000);
+
+/// Reserved instance ID.
+pub const RESERVED_INSTANCE_ID: InstanceID = InstanceID(0x0000);
+
+/// Reserved event group ID.
+pub const RESERVED_EVENTGROUP_ID: EventGroupID = EventGroupID(0x0000);
+
+/// Client ID of the vsomeip routing manager host, also used as "unknown" in outgoing messages.
+pub const ROUTING_CLIENT: ClientID = ClientID(0x0000);
+/// Client ID vsomeip uses for illegal/unassigned clients.
+pub const ILLEGAL_CLIENT: ClientID = ClientID(0xffff);
+
+impl MethodID {
+    /// Returns whether the ID is an event (notifier) ID, i.e. bit 15 is set.
+    pub const fn is_event_id(&self) -> bool {
+        self.0 & EVENT_ID_FLAG != 0
+    }
+
+    /// Returns whether the ID is a method ID, i.e. bit 15 is cleared.
+    pub const fn is_method_id(&self) -> bool {
+        !self.is_event_id()
+    }
+
+    /// Returns whether the ID is reserved by the SOME/IP specification.
+    pub const fn is_reserved(&self) -> bool {
+        self.0 == RESERVED_METHOD_ID.0 || self.0 == RESERVED_LAST_METHOD_ID.0 || self.0 == RESERVED_EVENT_ID.0
+    }
+}
+
+impl ServiceID {
+    /// Returns whether the ID is reserved by the SOME/IP specification (incl. the SD service).
+    pub const fn is_reserved(&self) -> bool {
+        self.0 == RESERVED_SERVICE_ID.0 || self.0 == NON_SOMEIP_SERVICE_ID.0 || self.0 == SD_SERVICE_ID.0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn method_id_kind_test() {
+        assert!(MethodID(0x8001).is_event_id());
+        assert!(MethodID(0x0001).is_method_id());
+        assert!(SD_METHOD_ID.is_event_id());
+        assert!(RESERVED_LAST_METHOD_ID.is_reserved());
+        assert!(!MethodID(0x7ffe).is_reserved());
+        assert!(SD_SERVICE_ID.is_reserved());
+    }
+}
@@ -0,0 +1,213 @@
+// SPDX-License-Identifier: MPL-2.0
+//
+// Copyright (C) 2024 Alexander Seifarth
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+use tokio::time::{interval_at, Instant, MissedTickBehavior};
+use super::{InstanceID, MethodID, Payload, SendError, ServiceID, VSomeipApplication};
+
+/// Periodic notifications of an event, the value of each notification is taken from a source
+/// closure. The notifications end when the source returns `None`.
+///
+/// The notifications follow a fixed schedule of period ticks, so the delay of a single
+/// notification does not shift the following ones. The first notification is sent after the
+/// offset, which spreads the notifications of several events of a provider over the period. A
+/// tick missed e.g. due to a busy runtime is skipped by default, see
+/// [CyclicNotification::with_missed_tick_behavior()]. Notification errors are logged. A zero
+/// period is rejected when the notifications are started.
+/// ```rust,no_run
+/// use std::sync::Arc;
+/// use std::time::Duration;
+/// use vsomeiprs::{CyclicNotification, InstanceID, MethodID, Payload, ServiceID, VSomeipApplication};
+///
+/// async fn provide(app: Arc<VSomeipApplication>) {
+///     let mut speed = 0u16;
+///     let notifier = CyclicNotification::new(ServiceID(0x1234), InstanceID(1), MethodID(0x8001),
+///                                            Duration::from_millis(100))
+///         .with_offset(Duration::from_millis(20))
+///         .start(app, move || {
+///             speed += 1;
+///             Some(Payload::from(speed.to_be_bytes().to_vec()))
+///         })
+///         .expect("period is not zero");
+///     tokio::time::sleep(Duration::from_secs(10)).await;
+///     notifier.stop().await;
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct CyclicNotification {
+    service_id: ServiceID,
+    instance_id: InstanceID,
+    notifier_id: MethodID,
+    period: Duration,
+    offset: Duration,
+    missed_tick_behavior: MissedTickBehavior,
+    force: bool,
+}
+
+impl CyclicNotification {
+    /// Notifications every `period` starting right away, forced, i.e. also if the value did not
+    /// change.
+    pub fn new(service_id: ServiceID, instance_id: InstanceID, notifier_id: MethodID, period: Duration) -> Self {
+        CyclicNotification { service_id, instance_id, notifier_id, period, offset: Duration::ZERO,
+            missed_tick_behavior: MissedTickBehavior::Skip, force: true }
+    }
+
+    /// Delays the first notification.
+    pub fn with_offset(mut self, offset: Duration) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// Selects how missed ticks are made up for: [MissedTickBehavior::Skip] (default) keeps the
+    /// schedule, [MissedTickBehavior::Delay] keeps the period between notifications and shifts
+    /// the following ones, [MissedTickBehavior::Burst] sends the missed notifications at once.
+    pub fn with_missed_tick_behavior(mut self, missed_tick_behavior: MissedTickBehavior) -> Self {
+        self.missed_tick_behavior = missed_tick_behavior;
+        self
+    }
+
+    /// Sets whether a notification is sent also if the value did not change (default), see
+    /// [VSomeipApplication::notify()].
+    pub fn with_force(mut self, force: bool) -> Self {
+        self.force = force;
+        self
+    }
+
+    /// Sends the notifications until the source returns `None`. Dropping the future stops them.
+    /// Fails right away if the period is zero.
+    pub async fn run<F>(self, app: &VSomeipApplication, source: F) -> Result<(), ZeroPeriod>
+        where F: FnMut() -> Option<Payload>
+    {
+        self.check()?;
+        let (service_id, instance_id, notifier_id, force) = (self.service_id, self.instance_id, self.notifier_id,
+                                                             self.force);
+        self.notify_with(source, |payload| app.notify(service_id, instance_id, notifier_id, payload, force)).await;
+        Ok(())
+    }
+
+    /// Sends the notifications from a task until the source returns `None` or the returned
+    /// [CyclicNotifier] is stopped or dropped. Must be called from within a tokio runtime.
+    /// Fails if the period is zero.
+    pub fn start<F>(self, app: Arc<VSomeipApplication>, source: F) -> Result<CyclicNotifier, ZeroPeriod>
+        where F: FnMut() -> Option<Payload> + Send + 'static
+    {
+        self.check()?;
+        let (stop, stopped) = oneshot::channel();
+        let task = tokio::spawn(async move {
+            tokio::select! {
+                _ = self.run(&app, source) => {}
+                // also when the notifier is dropped
+                _ = stopped => {}
+            }
+        });
+        Ok(CyclicNotifier { stop, task })
+    }
+
+    fn check(&self) -> Result<(), ZeroPeriod> {
+        if self.period.is_zero() { Err(ZeroPeriod) } else { Ok(()) }
+    }
+
+    /// Passes the values of the source to `notify` on the schedule until the source ends.
+    async fn notify_with<F, N>(self, mut source: F, mut notify: N)
+        where F: FnMut() -> Option<Payload>,
+              N: FnMut(Payload) -> Result<(), SendError>
+    {
+        let mut interval = interval_at(Instant::now() + self.offset, self.period);
+        interval.set_missed_tick_behavior(self.missed_tick_behavior);
+        loop {
+            interval.tick().await;
+            let Some(payload) = source() else { return };
+            if let Err(e) = notify(payload) {
+                log::warn!("Cannot notify {}.{}.{}: {}", self.service_id, self.instance_id, self.notifier_id, e);
+            }
+        }
+    }
+}
+
+/// Error of starting a [CyclicNotification] with a zero period.
+#[derive(Eq, PartialEq, Debug, Clone, Copy)]
+pub struct ZeroPeriod;
+
+impl fmt::Display for ZeroPeriod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "the period of the cyclic notifications is zero")
+    }
+}
+
+impl std::error::Error for ZeroPeriod {}
+
+/// Task sending the notifications of a [CyclicNotification]. Dropping it stops the notifications
+/// without waiting for the task.
+pub struct CyclicNotifier {
+    stop: oneshot::Sender<()>,
+    task: JoinHandle<()>,
+}
+
+impl CyclicNotifier {
+    /// Returns whether the notifications ended.
+    pub fn is_finished(&self) -> bool {
+        self.task.is_finished()
+    }
+
+    /// Waits until the source ended the notifications.
+    pub async fn finished(self) {
+        let CyclicNotifier { stop: _stop, task } = self;
+        join(task).await;
+    }
+
+    /// Stops the notifications and waits until the task ended, no notification is sent after.
+    pub async fn stop(self) {
+        let CyclicNotifier { stop, task } = self;
+        drop(stop);
+        join(task).await;
+    }
+}
+
+async fn join(task: JoinHandle<()>) {
+    if let Err(e) = task.await {
+        log::error!("Cyclic notification task failed: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn cyclic_notification_test() {
+        let cyclic = CyclicNotification::new(ServiceID(1), InstanceID(1), MethodID(0x8001), Duration::from_millis(100));
+        assert_eq!(cyclic.offset, Duration::ZERO);
+        assert_eq!(cyclic.missed_tick_behavior, MissedTickBehavior::Skip);
+        assert!(cyclic.force);
+        let cyclic = cyclic.with_offset(Duration::from_millis(10))
+            .with_missed_tick_behavior(MissedTickBehavior::Delay)
+            .with_force(false);
+        assert_eq!(cyclic.offset, Duration::from_millis(10));
+        assert_eq!(cyclic.missed_tick_behavior, MissedTickBehavior::Delay);
+        assert!(!cyclic.force);
+        assert_eq!(cyclic.check(), Ok(()));
+        assert_eq!(CyclicNotification::new(ServiceID(1), InstanceID(1), MethodID(0x8001), Duration::ZERO).check(),
+                   Err(ZeroPeriod));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn period_test() {
+        let start = Instant::now();
+        let mut sent = Vec::new();
+        let mut count = 0;
+        CyclicNotification::new(ServiceID(1), InstanceID(1), MethodID(0x8001), Duration::from_millis(100))
+            .with_offset(Duration::from_millis(20))
+            .notify_with(|| { count += 1; (count <= 3).then(Payload::default) },
+                         |_| { sent.push(Instant::now() - start); Ok(()) })
+            .await;
+        assert_eq!(sent, [Duration::from_millis(20), Duration::from_millis(120), Duration::from_millis(220)]);
+    }
+}
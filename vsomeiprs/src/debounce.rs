@@ -0,0 +1,142 @@
+// SPDX-License-Identifier: MPL-2.0
+//
+// Copyright (C) 2024 Alexander Seifarth
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+use super::{InstanceID, ServiceID, VSomeipMessage};
+
+/// Debounces the availability messages of an application's receiver.
+///
+/// A [VSomeipMessage::ServiceAvailability] is forwarded only after the availability of the
+/// service instance has been stable for the configured time. Transitions reverted within that
+/// time are not reported at all, so flapping providers don't cause subscribe/unsubscribe storms
+/// in the application or the [crate::commonapi::Proxy] resubscription. All other messages are
+/// forwarded immediately, so they may overtake a delayed availability message.
+///
+/// The debouncer only affects the messages of the receiver, the replay of
+/// [crate::VSomeipApplication::set_resubscribe_on_availability()] is not debounced.
+///
+/// The debouncing task runs until the [AvailabilityDebouncer] is dropped. It must be started from
+/// within a tokio runtime.
+/// ```rust,no_run
+/// use std::time::Duration;
+/// use vsomeiprs::{AvailabilityDebouncer, VSomeipApplication};
+///
+/// async fn run() {
+///     let (app, recv) = VSomeipApplication::create("client").unwrap();
+///     let (_debouncer, mut recv) = AvailabilityDebouncer::start(recv, Duration::from_millis(500));
+///     while let Some(msg) = recv.recv().await {
+///         println!("{:?}", msg);
+///     }
+/// }
+/// ```
+pub struct AvailabilityDebouncer {
+    task: JoinHandle<()>,
+}
+
+impl Drop for AvailabilityDebouncer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+impl AvailabilityDebouncer {
+    /// Starts debouncing the messages of `recv`, availability changes must be stable for `stable`.
+    pub fn start(recv: UnboundedReceiver<VSomeipMessage>, stable: Duration)
+        -> (Self, UnboundedReceiver<VSomeipMessage>)
+    {
+        let (sender, debounced) = tokio::sync::mpsc::unbounded_channel();
+        let task = tokio::spawn(debounce(recv, sender, stable));
+        (AvailabilityDebouncer { task }, debounced)
+    }
+}
+
+/// Debouncing state of a service instance.
+#[derive(Default)]
+struct InstanceState {
+    reported: Option<bool>,
+    pending: Option<(Instant, VSomeipMessage)>,
+}
+
+async fn debounce(mut recv: UnboundedReceiver<VSomeipMessage>, sender: UnboundedSender<VSomeipMessage>,
+                  stable: Duration) {
+    let mut instances: HashMap<(ServiceID, InstanceID), InstanceState> = HashMap::new();
+    loop {
+        let next = instances.iter()
+            .filter_map(|(key, state)| state.pending.as_ref().map(|(deadline, _)| (*deadline, *key)))
+            .min();
+        let msg = match next {
+            Some((deadline, key)) => {
+                tokio::select! {
+                    msg = recv.recv() => msg,
+                    _ = tokio::time::sleep_until(deadline) => {
+                        let state = instances.get_mut(&key).unwrap();
+                        if let Some((_, msg @ VSomeipMessage::ServiceAvailability { avail, .. })) = state.pending.take() {
+                            state.reported = Some(avail);
+                            if sender.send(msg).is_err() {
+                                return;
+                            }
+                        }
+                        continue;
+                    }
+                }
+            }
+            None => recv.recv().await,
+        };
+        let Some(msg) = msg else { return };
+        if let VSomeipMessage::ServiceAvailability { service_id, instance_id, avail, .. } = msg {
+            let state = instances.entry((service_id, instance_id)).or_default();
+            if state.reported == Some(avail) {
+                // reverted before it became stable
+                state.pending = None;
+            } else {
+                state.pending = Some((Instant::now() + stable, msg));
+            }
+        } else if sender.send(msg).is_err() {
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::InterfaceVersion;
+
+    fn avail(avail: bool) -> VSomeipMessage {
+        VSomeipMessage::ServiceAvailability { service_id: ServiceID(1), instance_id: InstanceID(1), avail,
+            version: InterfaceVersion::make_any() }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn debounce_test() {
+        let (sender, recv) = tokio::sync::mpsc::unbounded_channel();
+        let (_debouncer, mut debounced) = AvailabilityDebouncer::start(recv, Duration::from_millis(100));
+
+        sender.send(avail(true)).unwrap();
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        assert!(matches!(debounced.try_recv(), Ok(VSomeipMessage::ServiceAvailability { avail: true, .. })));
+
+        // flapping is suppressed
+        sender.send(avail(false)).unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        sender.send(avail(true)).unwrap();
+        sender.send(VSomeipMessage::RegistrationState(true)).unwrap();
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        assert!(matches!(debounced.try_recv(), Ok(VSomeipMessage::RegistrationState(true))));
+        assert!(debounced.try_recv().is_err());
+
+        sender.send(avail(false)).unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(debounced.try_recv().is_err());
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(matches!(debounced.try_recv(), Ok(VSomeipMessage::ServiceAvailability { avail: false, .. })));
+    }
+}
@@ -0,0 +1,107 @@
+// SPDX-License-Identifier: MPL-2.0
+//
+// Copyright (C) 2024 Alexander Seifarth
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::time::Duration;
+use super::{MissingServices, ServiceHandle, ServiceRegistry, VSomeipApplication};
+
+/// Service instances an application requires before it starts, e.g. its providers.
+///
+/// [Dependencies::ready()] requests all of them and resolves once they are available at the same
+/// time, or fails with the missing ones after the timeout (10 seconds by default).
+/// ```rust,no_run
+/// use std::time::Duration;
+/// use vsomeiprs::{Dependencies, InstanceID, InterfaceVersion, ServiceID, VSomeipApplication};
+///
+/// async fn start(app: &VSomeipApplication) {
+///     let version = InterfaceVersion::make_major(1);
+///     let services = Dependencies::new()
+///         .require((ServiceID(0x1234), InstanceID(1), version))
+///         .require((ServiceID(0x1235), InstanceID(1), version))
+///         .with_timeout(Duration::from_secs(30))
+///         .ready(app).await
+///         .expect("required services");
+///     // the services stay requested while `services` exists
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Dependencies {
+    required: Vec<ServiceHandle>,
+    timeout: Duration,
+}
+
+impl Default for Dependencies {
+    fn default() -> Self {
+        Dependencies { required: Vec::new(), timeout: Duration::from_secs(10) }
+    }
+}
+
+impl Dependencies {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a required service instance, a service required twice is requested once.
+    pub fn require(mut self, service: impl Into<ServiceHandle>) -> Self {
+        let service = service.into();
+        if !self.required.contains(&service) {
+            self.required.push(service);
+        }
+        self
+    }
+
+    /// Sets the time to wait for the services in [Dependencies::ready()].
+    pub fn with_timeout(self, timeout: Duration) -> Self {
+        Dependencies { timeout, ..self }
+    }
+
+    pub fn required(&self) -> &[ServiceHandle] {
+        &self.required
+    }
+
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    /// Requests the required services and waits until all of them are available at the same
+    /// time. Returns the registry keeping them requested, or the missing services after the
+    /// timeout, the services are released then.
+    pub async fn ready<'a>(&self, app: &'a VSomeipApplication) -> Result<ServiceRegistry<'a>, MissingServices> {
+        let (registry, result) = self.wait(app).await;
+        result.map(|_| registry)
+    }
+
+    /// Requests the required services and waits for them, returns the registry requesting them
+    /// whether they are available or not.
+    pub(crate) async fn wait<'a>(&self, app: &'a VSomeipApplication)
+        -> (ServiceRegistry<'a>, Result<(), MissingServices>)
+    {
+        let registry = self.required.iter().fold(ServiceRegistry::new(app), |registry, service| {
+            registry.with_service(*service)
+        });
+        let result = registry.wait_all(self.timeout).await;
+        (registry, result)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{InstanceID, InterfaceVersion, ServiceID};
+
+    #[test]
+    fn require_test() {
+        let version = InterfaceVersion::make_major(1);
+        let dependencies = Dependencies::new()
+            .require((ServiceID(1), InstanceID(1), version))
+            .require((ServiceID(2), InstanceID(1), version))
+            .require((ServiceID(1), InstanceID(1), version))
+            .with_timeout(Duration::from_secs(1));
+        assert_eq!(dependencies.required(), [ServiceHandle::new(ServiceID(1), InstanceID(1), version),
+                                             ServiceHandle::new(ServiceID(2), InstanceID(1), version)]);
+        assert_eq!(dependencies.timeout(), Duration::from_secs(1));
+    }
+}
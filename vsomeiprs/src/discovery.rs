@@ -0,0 +1,217 @@
+// SPDX-License-Identifier: MPL-2.0
+//
+// Copyright (C) 2024 Alexander Seifarth
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+use super::{InstanceID, MajorVersion, MinorVersion, SdEntryType, SdEvent, SdObserver, SdObserverConfig, ServiceID,
+            ANY_INSTANCE, ANY_MINOR_VERSION, ANY_SERVICE};
+
+/// Service instance found or lost by a [ServiceBrowser].
+#[derive(Eq, PartialEq, Debug, Clone, Copy)]
+pub enum DiscoveryEvent {
+    /// The service instance is offered by `source` (first offer or changed version).
+    ServiceOffered{ service_id: ServiceID, instance_id: InstanceID, major: MajorVersion, minor: MinorVersion,
+        source: SocketAddr },
+    /// `source` stopped offering the service instance or its offer expired, i.e. it was not
+    /// renewed within its TTL.
+    ServiceStopped{ service_id: ServiceID, instance_id: InstanceID, source: SocketAddr },
+}
+
+/// Enumerates the services offered on the network via SOME/IP-SD, e.g. for monitoring tools.
+///
+/// The browser observes the SD traffic with an [SdObserver] and reports the offers matching the
+/// requested service and instance, which may be [ANY_SERVICE] and [ANY_INSTANCE]. Cyclic offers
+/// are reported only once per sender, a service instance offered again after it was stopped or
+/// its offer expired is reported again.
+///
+/// Like the [SdObserver] it only sees offers sent to the SD multicast group. Services offered by
+/// applications on the same host, which vsomeip announces via its routing manager, and unicast
+/// offers answering another node's find are not reported. A vsomeip application learns about those
+/// by requesting the services, see [crate::VSomeipApplication::watch_availability()].
+///
+/// The browser task runs until the [ServiceBrowser] is dropped. It must be started from within a
+/// tokio runtime.
+/// ```rust,no_run
+/// use vsomeiprs::{DiscoveryEvent, SdObserverConfig, ServiceBrowser, ANY_INSTANCE, ANY_SERVICE};
+///
+/// async fn list_services() {
+///     let (_browser, mut events) = ServiceBrowser::start(SdObserverConfig::default(), ANY_SERVICE, ANY_INSTANCE)
+///         .expect("SD socket");
+///     while let Some(event) = events.recv().await {
+///         if let DiscoveryEvent::ServiceOffered { service_id, instance_id, major, minor, source } = event {
+///             println!("{}.{} v{}.{} at {}", service_id, instance_id, major.id(), minor.id(), source);
+///         }
+///     }
+/// }
+/// ```
+pub struct ServiceBrowser {
+    task: JoinHandle<()>,
+}
+
+impl Drop for ServiceBrowser {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+impl ServiceBrowser {
+    /// Starts browsing for offers of the service instance.
+    pub fn start(config: SdObserverConfig, service_id: ServiceID, instance_id: InstanceID)
+        -> std::io::Result<(Self, UnboundedReceiver<DiscoveryEvent>)>
+    {
+        let (observer, events) = SdObserver::start(config)?;
+        let (sender, recv) = tokio::sync::mpsc::unbounded_channel();
+        let task = tokio::spawn(browse(observer, events, sender, Offers::new(service_id, instance_id)));
+        Ok((ServiceBrowser { task }, recv))
+    }
+}
+
+async fn browse(_observer: SdObserver, mut events: UnboundedReceiver<SdEvent>, sender: UnboundedSender<DiscoveryEvent>,
+                mut offers: Offers) {
+    loop {
+        let expiry = offers.next_expiry();
+        let discovered = tokio::select! {
+            event = events.recv() => match event {
+                Some(event) => offers.process(&event, Instant::now()).into_iter().collect(),
+                None => return,
+            },
+            _ = tokio::time::sleep_until(expiry.unwrap_or_else(Instant::now)), if expiry.is_some() =>
+                offers.expire(Instant::now()),
+        };
+        for event in discovered {
+            if sender.send(event).is_err() {
+                return;
+            }
+        }
+    }
+}
+
+/// TTL of offers that do not expire.
+const TTL_INFINITE: u32 = 0xFF_FFFF;
+
+/// Known offers of the browsed service instances.
+struct Offers {
+    service_id: ServiceID,
+    instance_id: InstanceID,
+    known: HashMap<(ServiceID, InstanceID, SocketAddr), Offer>,
+}
+
+struct Offer {
+    version: (MajorVersion, MinorVersion),
+    /// `None` if the offer does not expire.
+    expires: Option<Instant>,
+}
+
+impl Offers {
+    fn new(service_id: ServiceID, instance_id: InstanceID) -> Self {
+        Offers { service_id, instance_id, known: HashMap::new() }
+    }
+
+    /// Returns the discovery event of the SD entry received at `now`, if it is one.
+    fn process(&mut self, event: &SdEvent, now: Instant) -> Option<DiscoveryEvent> {
+        let entry = &event.entry;
+        if (self.service_id != ANY_SERVICE && entry.service_id != self.service_id)
+            || (self.instance_id != ANY_INSTANCE && entry.instance_id != self.instance_id) {
+            return None;
+        }
+        let key = (entry.service_id, entry.instance_id, event.source);
+        match entry.entry_type {
+            SdEntryType::OfferService => {
+                let version = (entry.major_version, entry.minor_version.unwrap_or(ANY_MINOR_VERSION));
+                let expires = (entry.ttl != TTL_INFINITE).then(|| now + Duration::from_secs(entry.ttl.into()));
+                let previous = self.known.insert(key, Offer { version, expires });
+                if previous.is_some_and(|offer| offer.version == version) {
+                    return None;
+                }
+                Some(DiscoveryEvent::ServiceOffered { service_id: entry.service_id, instance_id: entry.instance_id,
+                    major: version.0, minor: version.1, source: event.source })
+            }
+            SdEntryType::StopOfferService => {
+                self.known.remove(&key)?;
+                Some(DiscoveryEvent::ServiceStopped { service_id: entry.service_id, instance_id: entry.instance_id,
+                    source: event.source })
+            }
+            _ => None,
+        }
+    }
+
+    /// Removes the offers whose TTL passed at `now`, returns their stop events.
+    fn expire(&mut self, now: Instant) -> Vec<DiscoveryEvent> {
+        let mut stopped = Vec::new();
+        self.known.retain(|(service_id, instance_id, source), offer| {
+            let expired = offer.expires.is_some_and(|expires| expires <= now);
+            if expired {
+                stopped.push(DiscoveryEvent::ServiceStopped { service_id: *service_id, instance_id: *instance_id,
+                    source: *source });
+            }
+            !expired
+        });
+        stopped
+    }
+
+    /// Returns when the next offer expires, `None` if none does.
+    fn next_expiry(&self) -> Option<Instant> {
+        self.known.values().filter_map(|offer| offer.expires).min()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{SdEntry, SessionID};
+
+    fn event(entry_type: SdEntryType, service: u16, minor: u32) -> SdEvent {
+        SdEvent { source: "192.168.1.10:30490".parse().unwrap(), session_id: SessionID(1), reboot: false,
+            entry: SdEntry { entry_type, service_id: ServiceID(service), instance_id: InstanceID(1),
+                major_version: MajorVersion(1), ttl: 3, minor_version: Some(MinorVersion(minor)),
+                eventgroup_id: None, counter: 0, options: Vec::new() } }
+    }
+
+    #[test]
+    fn offers_test() {
+        let now = Instant::now();
+        let mut offers = Offers::new(ANY_SERVICE, InstanceID(1));
+        assert!(matches!(offers.process(&event(SdEntryType::OfferService, 0x10, 0), now),
+            Some(DiscoveryEvent::ServiceOffered { service_id: ServiceID(0x10), .. })));
+        // cyclic offer
+        assert_eq!(offers.process(&event(SdEntryType::OfferService, 0x10, 0), now), None);
+        assert!(matches!(offers.process(&event(SdEntryType::OfferService, 0x10, 1), now),
+            Some(DiscoveryEvent::ServiceOffered { minor: MinorVersion(1), .. })));
+        assert!(offers.process(&event(SdEntryType::FindService, 0x20, 0), now).is_none());
+        assert!(matches!(offers.process(&event(SdEntryType::StopOfferService, 0x10, 1), now),
+            Some(DiscoveryEvent::ServiceStopped { service_id: ServiceID(0x10), .. })));
+        assert_eq!(offers.process(&event(SdEntryType::StopOfferService, 0x10, 1), now), None);
+
+        let mut offers = Offers::new(ServiceID(0x20), ANY_INSTANCE);
+        assert_eq!(offers.process(&event(SdEntryType::OfferService, 0x10, 0), now), None);
+    }
+
+    #[test]
+    fn expire_test() {
+        let now = Instant::now();
+        let mut offers = Offers::new(ANY_SERVICE, ANY_INSTANCE);
+        let mut infinite = event(SdEntryType::OfferService, 0x20, 0);
+        infinite.entry.ttl = TTL_INFINITE;
+        offers.process(&event(SdEntryType::OfferService, 0x10, 0), now);
+        offers.process(&infinite, now);
+        assert_eq!(offers.next_expiry(), Some(now + Duration::from_secs(3)));
+        assert!(offers.expire(now + Duration::from_secs(2)).is_empty());
+
+        // the cyclic offer renews the TTL
+        offers.process(&event(SdEntryType::OfferService, 0x10, 0), now + Duration::from_secs(2));
+        assert!(offers.expire(now + Duration::from_secs(3)).is_empty());
+        assert!(matches!(offers.expire(now + Duration::from_secs(5)).as_slice(),
+            [DiscoveryEvent::ServiceStopped { service_id: ServiceID(0x10), .. }]));
+        assert_eq!(offers.next_expiry(), None);
+        assert!(matches!(offers.process(&event(SdEntryType::OfferService, 0x10, 0), now + Duration::from_secs(5)),
+            Some(DiscoveryEvent::ServiceOffered { service_id: ServiceID(0x10), .. })));
+    }
+}
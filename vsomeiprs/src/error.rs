@@ -0,0 +1,171 @@
+// SPDX-License-Identifier: MPL-2.0
+//
+// Copyright (C) 2024 Alexander Seifarth
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::fmt;
+use super::{InstanceID, PayloadTooLarge, ServiceID};
+
+/// Errors of the [crate::VSomeipApplication] API.
+/// APIs failing for a single reason return that reason's error type, which converts into
+/// [VSomeipError].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VSomeipError {
+    /// The vsomeip runtime could not be obtained.
+    NoRuntime,
+    /// The application name contains a NUL character.
+    InvalidName,
+    /// vsomeip could not create the application object, e.g. the name is already in use.
+    CreateFailed,
+    /// The application could not be initialized, e.g. because of an invalid configuration.
+    InitFailed,
+    /// The application's dispatch thread could not be started.
+    StartFailed,
+    /// The application did not register at the routing manager in time.
+    RegistrationTimeout,
+    /// The configuration could not be applied.
+    Configuration(String),
+    /// A message sink is attached to the application already.
+    SinkAlreadyAttached,
+    /// The all-wildcard message handler (any service, instance and method) is reserved for the
+    /// application's main channel.
+    ReservedHandler,
+    PayloadTooLarge(PayloadTooLarge),
+    MissingServices(MissingServices),
+    Send(SendError),
+}
+
+impl fmt::Display for VSomeipError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VSomeipError::NoRuntime => write!(f, "vsomeip runtime not available"),
+            VSomeipError::InvalidName => write!(f, "invalid application name"),
+            VSomeipError::CreateFailed => write!(f, "failed to create the vsomeip application"),
+            VSomeipError::InitFailed => write!(f, "failed to initialize the vsomeip application"),
+            VSomeipError::StartFailed => write!(f, "failed to start the vsomeip application"),
+            VSomeipError::RegistrationTimeout => write!(f, "timeout waiting for the application's registration"),
+            VSomeipError::Configuration(reason) => write!(f, "invalid configuration: {}", reason),
+            VSomeipError::SinkAlreadyAttached => write!(f, "a message sink is already attached to the application"),
+            VSomeipError::ReservedHandler => write!(f, "the all-wildcard message handler is reserved"),
+            VSomeipError::PayloadTooLarge(e) => write!(f, "{}", e),
+            VSomeipError::MissingServices(e) => write!(f, "{}", e),
+            VSomeipError::Send(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for VSomeipError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            VSomeipError::PayloadTooLarge(e) => Some(e),
+            VSomeipError::MissingServices(e) => Some(e),
+            VSomeipError::Send(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// Error of the send, offer and subscribe calls of [crate::VSomeipApplication].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SendError {
+    /// The payload exceeds the maximum payload size.
+    PayloadTooLarge(PayloadTooLarge),
+    /// The message type of the header is not one of the [crate::MessageKind]s.
+    InvalidMessageType(u8),
+    /// The value of the return code is out of the range of its variant, see
+    /// [crate::ReturnCode::is_valid()].
+    InvalidReturnCode(crate::ReturnCode),
+    /// The application is not registered at the routing manager, vsomeip would drop the message.
+    NotRegistered,
+    /// The service instance of the request is not available, vsomeip would drop the request.
+    NotAvailable,
+    /// The event is not offered by the application, vsomeip would drop the notification.
+    NotOffered,
+    /// vsomeip threw an exception processing the call, the reason is logged by the vsomeip
+    /// wrapper. Other failures, e.g. of offers and subscriptions, are only logged by vsomeip and
+    /// not reported.
+    Failed,
+}
+
+impl fmt::Display for SendError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SendError::PayloadTooLarge(e) => write!(f, "{}", e),
+            SendError::InvalidMessageType(value) => write!(f, "invalid message type {:#04x}", value),
+            SendError::InvalidReturnCode(rc) => write!(f, "invalid return code {}", rc),
+            SendError::NotRegistered => write!(f, "application not registered at the routing manager"),
+            SendError::NotAvailable => write!(f, "service instance not available"),
+            SendError::NotOffered => write!(f, "event not offered"),
+            SendError::Failed => write!(f, "vsomeip failed to process the call"),
+        }
+    }
+}
+
+impl std::error::Error for SendError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SendError::PayloadTooLarge(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<PayloadTooLarge> for SendError {
+    fn from(e: PayloadTooLarge) -> Self {
+        SendError::PayloadTooLarge(e)
+    }
+}
+
+/// Error of [crate::VSomeipApplication::wait_for_service()].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WaitError {
+    /// The service did not become available in time.
+    Timeout,
+}
+
+impl fmt::Display for WaitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WaitError::Timeout => write!(f, "timeout waiting for the service"),
+        }
+    }
+}
+
+impl std::error::Error for WaitError {}
+
+/// Error returned by [crate::VSomeipApplication::await_services()] with the service instances
+/// that did not become available in time.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MissingServices(pub Vec<(ServiceID, InstanceID)>);
+
+impl fmt::Display for MissingServices {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "services not available:")?;
+        for (service_id, instance_id) in &self.0 {
+            write!(f, " {}.{}", service_id, instance_id)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for MissingServices {}
+
+impl From<PayloadTooLarge> for VSomeipError {
+    fn from(e: PayloadTooLarge) -> Self {
+        VSomeipError::PayloadTooLarge(e)
+    }
+}
+
+impl From<MissingServices> for VSomeipError {
+    fn from(e: MissingServices) -> Self {
+        VSomeipError::MissingServices(e)
+    }
+}
+
+impl From<SendError> for VSomeipError {
+    fn from(e: SendError) -> Self {
+        VSomeipError::Send(e)
+    }
+}
@@ -0,0 +1,135 @@
+// SPDX-License-Identifier: MPL-2.0
+//
+// Copyright (C) 2024 Alexander Seifarth
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::sync::{Arc, RwLock};
+use bytes::Bytes;
+use super::{MessageHeader, MessageKind, MessageType};
+
+/// An interceptor is invoked for every message an application sends or receives.
+///
+/// Interceptors are the common extension point for cross-cutting functionality like E2E
+/// protection, encryption, metrics or logging. Both methods have pass-through default
+/// implementations, so an interceptor only needs to implement the direction it cares about.
+///
+/// Interceptors are called from the vsomeip dispatcher threads (receive path) and from the
+/// threads calling the send methods, so they must not block.
+pub trait Interceptor: Send + Sync {
+    /// Called before a message is handed to vsomeip.
+    /// Returns the (possibly transformed) payload or `None` to drop the message.
+    /// For requests and notifications the `client_id` and `session_id` of the header are not yet
+    /// known and set to `UNKNOWN_CLIENT` and `NO_SESSION`.
+    fn on_send(&self, _kind: MessageKind, _header: &MessageHeader, payload: Bytes) -> Option<Bytes> {
+        Some(payload)
+    }
+
+    /// Called for every received message before it is delivered to the application.
+    /// Returns the (possibly transformed) message or `None` to drop it.
+    fn on_receive(&self, msg: MessageType) -> Option<MessageType> {
+        Some(msg)
+    }
+}
+
+/// Ordered chain of interceptors.
+///
+/// On the send path interceptors are applied in the order they were added, on the receive path
+/// in reverse order. So the interceptor added last is the one closest to the wire, e.g. adding an
+/// E2E interceptor before an encryption interceptor protects before encrypting when sending and
+/// decrypts before checking when receiving.
+#[derive(Default)]
+pub(crate) struct InterceptorChain {
+    interceptors: RwLock<Vec<Arc<dyn Interceptor>>>,
+}
+
+impl InterceptorChain {
+    pub(crate) fn add(&self, interceptor: Arc<dyn Interceptor>) {
+        self.interceptors.write().unwrap().push(interceptor);
+    }
+
+    pub(crate) fn clear(&self) {
+        self.interceptors.write().unwrap().clear();
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.interceptors.read().unwrap().is_empty()
+    }
+
+    pub(crate) fn on_send(&self, kind: MessageKind, header: &MessageHeader, payload: Bytes) -> Option<Bytes> {
+        self.interceptors.read().unwrap().iter()
+            .try_fold(payload, |payload, i| i.on_send(kind, header, payload))
+    }
+
+    pub(crate) fn on_receive(&self, msg: MessageType) -> Option<MessageType> {
+        self.interceptors.read().unwrap().iter().rev()
+            .try_fold(msg, |msg, i| i.on_receive(msg))
+    }
+}
+
+/// Interceptor that logs all sent and received messages with `log::debug!`.
+#[derive(Default)]
+pub struct LogInterceptor;
+
+impl Interceptor for LogInterceptor {
+    fn on_send(&self, kind: MessageKind, header: &MessageHeader, payload: Bytes) -> Option<Bytes> {
+        log::debug!("send {} {}: [{:?}]", kind, header, payload);
+        Some(payload)
+    }
+
+    fn on_receive(&self, msg: MessageType) -> Option<MessageType> {
+        log::debug!("recv {}", msg);
+        Some(msg)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{InstanceID, InterfaceVersion, MethodID, ServiceID, NO_SESSION, SOMEIP_PROTOCOL_VERSION, UNKNOWN_CLIENT};
+
+    struct Append(u8);
+
+    impl Interceptor for Append {
+        fn on_send(&self, _kind: MessageKind, _header: &MessageHeader, payload: Bytes) -> Option<Bytes> {
+            let mut v = payload.to_vec();
+            v.push(self.0);
+            Some(Bytes::from(v))
+        }
+    }
+
+    struct DropAll;
+
+    impl Interceptor for DropAll {
+        fn on_send(&self, _kind: MessageKind, _header: &MessageHeader, _payload: Bytes) -> Option<Bytes> {
+            None
+        }
+    }
+
+    fn header() -> MessageHeader {
+        MessageHeader { service_id: ServiceID(1), instance_id: InstanceID(1), method_id: MethodID(1),
+            client_id: UNKNOWN_CLIENT, session_id: NO_SESSION, interface_version: InterfaceVersion::make_any(),
+            protocol_version: SOMEIP_PROTOCOL_VERSION, raw_message_type: 0, raw_return_code: 0, length: 8,
+            reliable: false, origin: Default::default() }
+    }
+
+    #[test]
+    fn chain_order_test() {
+        let chain = InterceptorChain::default();
+        chain.add(Arc::new(Append(1)));
+        chain.add(Arc::new(Append(2)));
+        let pl = chain.on_send(MessageKind::Request, &header(), Bytes::from_static(&[0]));
+        assert_eq!(pl, Some(Bytes::from_static(&[0, 1, 2])));
+    }
+
+    #[test]
+    fn chain_drop_test() {
+        let chain = InterceptorChain::default();
+        chain.add(Arc::new(DropAll));
+        chain.add(Arc::new(Append(1)));
+        assert_eq!(chain.on_send(MessageKind::Notification, &header(), Bytes::new()), None);
+        chain.clear();
+        assert_eq!(chain.on_send(MessageKind::Notification, &header(), Bytes::new()), Some(Bytes::new()));
+    }
+}
@@ -8,11 +8,106 @@
 mod types;
 pub use types::*;
 
+mod config;
+pub use config::*;
+
+mod error;
+pub use error::*;
+
+mod builder;
+pub use builder::*;
+
+pub mod consts;
+
+mod sd;
+pub use sd::*;
+
+mod discovery;
+pub use discovery::*;
+
+mod interceptor;
+pub use interceptor::*;
+
+mod debounce;
+pub use debounce::*;
+
+mod outgoing;
+pub use outgoing::*;
+
+mod request;
+pub use request::{RequestError, RequestHandle, RequestOptions, Response};
+
+mod tracker;
+pub use tracker::{PendingResponse, RequestKey, RequestTracker};
+
+mod stream;
+pub use stream::*;
+
+mod offer;
+pub use offer::*;
+
+mod subscription;
+pub use subscription::*;
+
+mod requested;
+pub use requested::*;
+
+mod registry;
+pub use registry::*;
+
+mod dependencies;
+pub use dependencies::*;
+
+mod cyclic;
+pub use cyclic::*;
+
+mod typed;
+pub use typed::{Availability, TypedReceivers};
+use typed::TypedSenders;
+
+mod availability;
+use availability::AvailabilityWatches;
+
+mod limits;
+pub use limits::PayloadTooLarge;
+use limits::PayloadLimits;
+
+mod change;
+use change::{ChangeFilters, LockedFilters};
+
+mod reoffer;
+use reoffer::{OfferReplay, Offers};
+
+mod resubscribe;
+use resubscribe::{RequestedEvent, SubscriptionReplay, Subscriptions};
+
+mod subscriber;
+pub use subscriber::{InitialValue, SubscriptionHandler};
+use subscriber::SubscriptionHandlers;
+
+pub mod codec;
+
+pub mod schema;
+
+pub mod commonapi;
+
+pub mod standby;
+
+pub mod service;
+
 use std::ffi::{c_char, CString};
+use std::future::Future;
+use std::collections::{HashMap, HashSet};
 use std::fmt::{Debug, Formatter};
+use std::net::{Ipv4Addr, SocketAddr};
+use std::path::Path;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::Duration;
 use bytes::Bytes;
 use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
+use tokio::sync::mpsc::error::TrySendError;
 use tokio::time::timeout;
 
 mod ffi {
@@ -26,8 +121,25 @@ mod ffi {
 #[derive(Debug)]
 pub enum VSomeipMessage {
     RegistrationState(bool),
-    ServiceAvailability{ service_id: u16, instance_id: u16, avail: bool },
-    Message(MessageType)
+    /// The routing state changed, see [VSomeipApplication::set_routing_state()]
+    RoutingState(RoutingState),
+    /// Availability of a requested service.
+    /// `version` is the offered version that became available or unavailable, e.g. the concrete
+    /// major and minor version of a service requested with [ANY_MAJOR_VERSION]. It is the version
+    /// the service was requested with if vsomeip does not know the offered one.
+    ServiceAvailability{ service_id: ServiceID, instance_id: InstanceID, avail: bool, version: InterfaceVersion },
+    Message(MessageType),
+    /// A vsomeip callback panicked, the event it was processing has been dropped.
+    /// `callback` names the callback, `message` is the panic message.
+    /// See [VSomeipApplication::set_abort_on_panic()].
+    CallbackPanic{ callback: &'static str, message: String },
+    /// Result of a subscription, see [VSomeipApplication::register_subscription_status_handler()].
+    /// `accepted` is `false` if the provider refused the subscription (SubscribeEventgroupNack).
+    SubscriptionStatus{ service_id: ServiceID, instance_id: InstanceID, event_group_id: EventGroupID,
+        notifier_id: MethodID, accepted: bool },
+    /// vsomeip delivered a message of an unknown message type, the message has been dropped.
+    /// This usually indicates an unsupported vsomeip version. `message_type` is the raw value.
+    ProtocolAnomaly{ header: MessageHeader, message_type: u8 },
 }
 
 /// Waits until a `RegistrationState(true)` message is received or a timeout occurs.
@@ -77,20 +189,333 @@ pub async fn wait_registered_for(timeout_time: Duration, recv: &mut UnboundedRec
 /// The [drop()] method of [VSomeipApplication] will revert all of these, i.e. remove all handlers,
 /// stop the start-thread and wait for it to complete and then remove the vsomeip application
 /// object.
+///
+/// # Thread safety
+/// [VSomeipApplication] is `Send` and `Sync`, all methods may be called concurrently (see
+/// [ApplicationHandle] for the reasoning). The vsomeip callbacks run on vsomeip's dispatcher
+/// threads and only access the `Sync` [CallbackContext]s.
 pub struct VSomeipApplication {
-    app: ffi::application_t,
-    sender2: Box<UnboundedSender<VSomeipMessage>>,
+    app: ApplicationHandle,
+    context: Box<CallbackContext>,
+    callbacks_registered: AtomicBool,
+    message_handlers: Mutex<MessageHandlers>,
+    resources: Mutex<Resources>,
 }
 
-impl Drop for VSomeipApplication {
-    fn drop(&mut self) {
-        unsafe { ffi::application_delete(self.app) }
+/// Handle of the shim's application object.
+///
+/// The handle is only dereferenced inside the shim. All shim functions taking it may be called
+/// concurrently from any thread:
+/// - they forward to `vsomeip::application`, whose public API is internally synchronized,
+/// - the shim's own mutable state (dedicated message handlers, routing state) is guarded by a
+///   mutex or atomic.
+///
+/// The handle is created in [VSomeipApplication::create()] and deleted in `drop()`, which both
+/// have exclusive access to the application.
+struct ApplicationHandle(ffi::application_t);
+
+// SAFETY: see above - the shim object may be used from and moved to any thread.
+unsafe impl Send for ApplicationHandle {}
+
+// SAFETY: see above - all functions taking the handle are internally synchronized.
+unsafe impl Sync for ApplicationHandle {}
+
+/// State shared between the application object and the vsomeip callbacks.
+/// The callbacks receive a pointer to it as their `target` argument and may run concurrently on
+/// several vsomeip dispatcher threads, so it must be `Sync`. It is owned by the application and
+/// outlives the vsomeip application object, which is deleted first in `drop()`.
+struct CallbackContext {
+    sink: Mutex<Sink>,
+    interceptors: Arc<InterceptorChain>,
+    abort_on_panic: Arc<AtomicBool>,
+    limits: Arc<PayloadLimits>,
+    change_filters: Arc<ChangeFilters>,
+    pending: Arc<RequestTracker>,
+    availability: Arc<AvailabilityWatches>,
+    /// Last registration state, replayed when attaching the sink.
+    registration: Arc<tokio::sync::watch::Sender<bool>>,
+    offers: Arc<OfferReplay>,
+    subscriptions: Arc<SubscriptionReplay>,
+    subscription_handlers: Arc<SubscriptionHandlers>,
+    /// The application the offers and subscriptions are replayed on, set once it is created.
+    app: OnceLock<ApplicationHandle>,
+    /// Whether this is the context of the application's main channel, whose callbacks are
+    /// unregistered once its receiver was closed.
+    main: bool,
+    /// Set while the callbacks of the main channel are unregistered, see [CallbackContext::quiesce()].
+    quiescent: AtomicBool,
+}
+
+/// Channel the callbacks deliver their messages into.
+#[derive(Default)]
+struct Sink {
+    /// `None` before a sink is attached and after the receiver has been closed.
+    sender: Option<SinkSender>,
+}
+
+/// Sender of a sink, either the application's single channel or its typed channels.
+enum SinkSender {
+    Channel(UnboundedSender<VSomeipMessage>),
+    /// Channel of limited capacity, messages are dropped while it is full.
+    Bounded(tokio::sync::mpsc::Sender<VSomeipMessage>),
+    Typed(TypedSenders),
+}
+
+impl CallbackContext {
+    fn new(sender: Option<UnboundedSender<VSomeipMessage>>) -> Box<Self> {
+        Box::new(CallbackContext { sink: Mutex::new(Sink { sender: sender.map(SinkSender::Channel) }),
+            interceptors: Arc::default(), abort_on_panic: Arc::default(), limits: Arc::default(),
+            change_filters: Arc::default(),
+            pending: Arc::default(), availability: Arc::default(),
+            registration: Arc::new(tokio::sync::watch::Sender::new(false)), offers: Arc::default(),
+            subscriptions: Arc::default(), subscription_handlers: Arc::default(), app: OnceLock::new(), main: true,
+            quiescent: AtomicBool::new(false) })
+    }
+
+    /// Creates a context for another channel of the same application sharing its settings.
+    fn share(&self, sender: UnboundedSender<VSomeipMessage>) -> Box<Self> {
+        Box::new(CallbackContext { sink: Mutex::new(Sink { sender: Some(SinkSender::Channel(sender)) }),
+            interceptors: self.interceptors.clone(), abort_on_panic: self.abort_on_panic.clone(),
+            limits: self.limits.clone(), change_filters: self.change_filters.clone(), pending: self.pending.clone(),
+            availability: self.availability.clone(),
+            registration: self.registration.clone(), offers: self.offers.clone(),
+            subscriptions: self.subscriptions.clone(), subscription_handlers: self.subscription_handlers.clone(),
+            app: self.app.get().map(|app| OnceLock::from(ApplicationHandle(app.0))).unwrap_or_default(),
+            main: false, quiescent: AtomicBool::new(false) })
+    }
+
+    /// Offers the events and services again after the application lost its registration.
+    fn reoffer(&self, offers: Offers) {
+        let Some(app) = self.app.get() else { return };
+        for ((service_id, instance_id, notifier_id), event) in &offers.events {
+            if let Err(e) = offer_event_ffi(app, *service_id, *instance_id, event) {
+                log::warn!("Cannot re-offer event {}.{}.{}: {}", service_id, instance_id, notifier_id, e);
+            }
+        }
+        for service in offers.services {
+            if let Err(e) = offer_service_ffi(app, service) {
+                log::warn!("Cannot re-offer service {}: {}", service, e);
+            }
+        }
+    }
+
+    /// Asks the subscription handler of the event group whether the client's subscription is
+    /// accepted and stores the initial values before accepting it, vsomeip sends them to the new
+    /// subscriber once the subscription is established. Subscriptions to event groups without
+    /// handler are accepted.
+    fn on_subscription(&self, service_id: ServiceID, instance_id: InstanceID, event_group_id: EventGroupID,
+                       client_id: ClientID, subscribed: bool) -> bool
+    {
+        let Some(handler) = self.subscription_handlers.get(service_id, instance_id, event_group_id) else {
+            return true;
+        };
+        if !subscribed {
+            handler.on_unsubscribe(client_id);
+            return true;
+        }
+        if !handler.on_subscribe(client_id) {
+            return false;
+        }
+        let Some(app) = self.app.get() else { return true };
+        for (notifier_id, payload) in handler.initial_values(client_id) {
+            // not forced, the subscribers already there are notified only if the value changed
+            if let Err(e) = self.notify(app, service_id, instance_id, notifier_id, payload.into_bytes(), false) {
+                log::warn!("Cannot store the initial value of {}.{}.{} for client {}: {}", service_id, instance_id,
+                           notifier_id, client_id, e);
+            }
+        }
+        true
+    }
+
+    /// Fails unless the application is registered, vsomeip drops messages sent before.
+    fn check_registered(&self) -> Result<(), SendError> {
+        match *self.registration.borrow() {
+            true => Ok(()),
+            false => Err(SendError::NotRegistered),
+        }
+    }
+
+    /// Fails unless the application offers the event, vsomeip drops notifications of other
+    /// events.
+    fn check_offered(&self, service_id: ServiceID, instance_id: InstanceID, notifier_id: MethodID)
+        -> Result<(), SendError>
+    {
+        match self.offers.is_event_offered(service_id, instance_id, notifier_id) {
+            true => Ok(()),
+            false => Err(SendError::NotOffered),
+        }
+    }
+
+    fn notify(&self, app: &ApplicationHandle, service_id: ServiceID, instance_id: InstanceID, notifier_id: MethodID,
+              value: Bytes, force_notification: bool) -> Result<(), SendError>
+    {
+        self.check_offered(service_id, instance_id, notifier_id)?;
+        self.change_filters.locked(service_id, instance_id, &[notifier_id], |filters| {
+            let Some(payload) = self.notification(filters, service_id, instance_id, notifier_id, &value,
+                                                  force_notification)? else { return Ok(()) };
+            map_send_result(unsafe {
+                ffi::application_notify(app.0, service_id.id(), instance_id.id(), notifier_id.id(),
+                    force_notification, payload.as_ptr(), payload.len() as u32)
+            })?;
+            filters.notified(notifier_id, value);
+            Ok(())
+        })
+    }
+
+    /// Returns the payload of a notification to send, `None` if the value has no significant
+    /// change or an interceptor dropped it.
+    fn notification(&self, filters: &LockedFilters<'_>, service_id: ServiceID, instance_id: InstanceID,
+                    notifier_id: MethodID, value: &Bytes, force_notification: bool) -> Result<Option<Bytes>, SendError>
+    {
+        if !filters.is_changed(notifier_id, value, force_notification) {
+            return Ok(None);
+        }
+        let header = outgoing_header(service_id, instance_id, notifier_id, InterfaceVersion::make_any(), false);
+        let Some(payload) = self.interceptors.on_send(MessageKind::Notification, &header, value.clone())
+            else { return Ok(None) };
+        self.limits.check(service_id, payload.len())?;
+        Ok(Some(payload))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn notify_one(&self, app: &ApplicationHandle, service_id: ServiceID, instance_id: InstanceID,
+                  notifier_id: MethodID, client_id: ClientID, payload: Payload, force_notification: bool)
+        -> Result<(), SendError>
+    {
+        self.check_offered(service_id, instance_id, notifier_id)?;
+        let header = MessageHeader { client_id,
+            ..outgoing_header(service_id, instance_id, notifier_id, InterfaceVersion::make_any(), false) };
+        let Some(payload) = self.interceptors.on_send(MessageKind::Notification, &header, payload.into_bytes())
+            else { return Ok(()) };
+        self.limits.check(service_id, payload.len())?;
+        map_send_result(unsafe {
+            ffi::application_notify_one(app.0, service_id.id(), instance_id.id(), notifier_id.id(),
+                client_id.id(), force_notification, payload.as_ptr(), payload.len() as u32)
+        })
+    }
+
+    /// Requests the events and subscribes the event groups again after their service became
+    /// available again.
+    fn resubscribe(&self, subscriptions: Subscriptions) {
+        let Some(app) = self.app.get() else { return };
+        for ((service_id, instance_id, notifier_id), event) in &subscriptions.events {
+            request_event_ffi(app, *service_id, *instance_id, *notifier_id, &event.event_groups, event.kind,
+                              event.reliability);
+        }
+        for ((service_id, instance_id, event_group_id, notifier_id), major) in subscriptions.event_groups {
+            if let Err(e) = subscribe_ffi(app, service_id, instance_id, event_group_id, notifier_id, major) {
+                log::warn!("Cannot re-subscribe event group {}.{}.{}: {}", service_id, instance_id, event_group_id,
+                           e);
+            }
+        }
+    }
+
+    /// Delivers a message into the sink. The message is dropped if no sink is attached or its
+    /// receiver has been closed. A closed sink is detached, so a new one can be attached, and the
+    /// main channel's callbacks are unregistered.
+    fn send(&self, msg: VSomeipMessage) {
+        let mut sink = self.sink.lock().unwrap_or_else(|e| e.into_inner());
+        if let VSomeipMessage::RegistrationState(registered) = msg {
+            // updated with the sink locked, so attach() replays the state delivered last
+            self.registration.send_replace(registered);
+        }
+        match &sink.sender {
+            Some(SinkSender::Channel(sender)) => {
+                if let Err(e) = sender.send(msg) {
+                    log::warn!("Receiver of the application's messages closed, dropping {:?} and further messages",
+                               e.0);
+                    sink.sender = None;
+                    self.quiesce();
+                }
+            }
+            Some(SinkSender::Bounded(sender)) => match sender.try_send(msg) {
+                Ok(()) => {}
+                Err(TrySendError::Full(msg)) =>
+                    log::warn!("Channel of the application's messages is full, dropping {:?}", msg),
+                Err(TrySendError::Closed(msg)) => {
+                    log::warn!("Receiver of the application's messages closed, dropping {:?} and further messages",
+                               msg);
+                    sink.sender = None;
+                    self.quiesce();
+                }
+            },
+            Some(SinkSender::Typed(senders)) => senders.send(msg),
+            None => log::debug!("No sink attached, dropping {:?}", msg),
+        }
+    }
+
+    /// Unregisters the state, routing state and main message handlers once the receiver of the
+    /// main channel was closed, so vsomeip does not call into the context for messages nobody
+    /// receives. Pending requests fail as their responses cannot arrive anymore. The callbacks are
+    /// registered again when the next sink is attached.
+    fn quiesce(&self) {
+        if !self.main || self.quiescent.swap(true, Ordering::AcqRel) {
+            return;
+        }
+        if let Some(app) = self.app.get() {
+            unsafe { ffi::application_unregister_main_handlers(app.0) }
+        }
+        self.pending.clear();
+    }
+
+    /// Whether the callbacks of the main channel are unregistered, invocations that were already
+    /// running meanwhile are dropped.
+    fn is_quiescent(&self) -> bool {
+        self.quiescent.load(Ordering::Acquire)
+    }
+
+    /// Attaches the sink, the current registration state is sent into it if registered already.
+    /// The callbacks of a quiescent main channel are registered again.
+    fn attach(&self, sender: SinkSender) -> Result<(), VSomeipError> {
+        let mut sink = self.sink.lock().unwrap_or_else(|e| e.into_inner());
+        if sink.sender.is_some() {
+            return Err(VSomeipError::SinkAlreadyAttached);
+        }
+        if self.quiescent.swap(false, Ordering::AcqRel) {
+            if let Some(app) = self.app.get() {
+                register_main_handlers(app, self);
+            }
+        }
+        if *self.registration.borrow() {
+            match &sender {
+                SinkSender::Channel(sender) => { let _ = sender.send(VSomeipMessage::RegistrationState(true)); }
+                SinkSender::Bounded(sender) => { let _ = sender.try_send(VSomeipMessage::RegistrationState(true)); }
+                SinkSender::Typed(senders) => senders.send(VSomeipMessage::RegistrationState(true)),
+            }
+        }
+        sink.sender = Some(sender);
+        Ok(())
     }
 }
 
-unsafe impl Send for VSomeipApplication {}
+/// Contexts of the dedicated message handlers, boxed as vsomeip holds raw pointers to them.
+/// A context is dropped once its handler has been replaced, unregistered or cleared at the shim,
+/// which does not call the handler anymore afterwards.
+type MessageHandlers = HashMap<(ServiceID, InstanceID, MethodID), Box<CallbackContext>>;
+
+/// Offered services and subscriptions of the application, released by
+/// [VSomeipApplication::shutdown()].
+#[derive(Default)]
+struct Resources {
+    offered: HashSet<ServiceHandle>,
+    subscribed: HashSet<(ServiceID, InstanceID, EventGroupID)>,
+}
+
+const _: () = {
+    const fn assert_sync<T: Sync>() {}
+    const fn assert_send_sync<T: Send + Sync>() {}
+    assert_sync::<CallbackContext>();
+    assert_send_sync::<VSomeipApplication>();
+    assert_send_sync::<VSomeipPayload>();
+};
 
-unsafe impl Sync for VSomeipApplication {}
+impl Drop for VSomeipApplication {
+    fn drop(&mut self) {
+        unsafe { ffi::application_delete(self.app.0) }
+        // fails the futures of pending requests
+        self.context.pending.clear();
+    }
+}
 
 impl VSomeipApplication {
     /// Creates a new vsomeip application object.
@@ -102,54 +527,492 @@ impl VSomeipApplication {
     /// - `name` - The name of the application object. Note that vsomeip might modify it if not unique.
     ///
     /// # Returns
-    /// The application object and the channel receiver are returned in case of success (OK).
-    pub fn create(name: &str) -> Result<(Self, UnboundedReceiver<VSomeipMessage>), ()> {
-        let name_cstr = CString::new(name).unwrap();
+    /// The application object and the channel receiver are returned in case of success (OK),
+    /// otherwise the [VSomeipError] telling which step failed.
+    pub fn create(name: &str) -> Result<(Self, UnboundedReceiver<VSomeipMessage>), VSomeipError> {
+        let application = Self::create_detached(name)?;
+        let (sender, recv) = tokio::sync::mpsc::unbounded_channel();
+        application.attach_sink(sender).expect("new application has no sink");
+        Ok( (application, recv) )
+    }
+
+    /// Creates a new vsomeip application object delivering its messages into separate channels
+    /// for requests, responses, notifications, availability, registration state and all other
+    /// messages instead of a single channel, see [TypedReceivers].
+    pub fn create_typed(name: &str) -> Result<(Self, TypedReceivers), VSomeipError> {
+        let application = Self::create_detached(name)?;
+        let (senders, receivers) = TypedSenders::channels();
+        application.context.attach(SinkSender::Typed(senders)).expect("new application has no sink");
+        Ok( (application, receivers) )
+    }
+
+    /// Returns a builder creating the application with further options, e.g. its configuration.
+    pub fn builder(name: &str) -> ApplicationBuilder {
+        ApplicationBuilder::new(name)
+    }
+
+    /// Creates a new vsomeip application object without a channel.
+    /// The sink for the application's messages is attached later with
+    /// [VSomeipApplication::attach_sink()], messages arriving before are dropped.
+    pub fn create_detached(name: &str) -> Result<Self, VSomeipError> {
+        Self::create_configured(name, None)
+    }
+
+    /// Creates the application like [VSomeipApplication::create_detached()], reading the
+    /// configuration file (or directory) `configuration` instead of vsomeip's default lookup.
+    fn create_configured(name: &str, configuration: Option<&Path>) -> Result<Self, VSomeipError> {
+        let name_cstr = CString::new(name).map_err(|_| VSomeipError::InvalidName)?;
         let name_c: *const c_char = name_cstr.as_ptr() as *const c_char;
-        let app = unsafe { ffi::create_application(name_c) };
-        if app.is_null() {
-            return Err(());
+        let mut app: ffi::application_t = std::ptr::null_mut();
+        match configuration {
+            Some(path) => {
+                let path = path.to_str().and_then(|path| CString::new(path).ok())
+                    .ok_or_else(|| VSomeipError::Configuration(format!("invalid path {}", path.display())))?;
+                map_create_result(unsafe {
+                    ffi::create_application_with_configuration(name_c, path.as_ptr(), &mut app)
+                })?;
+            }
+            None => map_create_result(unsafe { ffi::create_application(name_c, &mut app) })?,
         }
-        let (sender, recv) = tokio::sync::mpsc::unbounded_channel();
-        let mut application = VSomeipApplication {app, sender2: Box::new(sender)};
+        let context = CallbackContext::new(None);
+        let _ = context.app.set(ApplicationHandle(app));
+        let application = VSomeipApplication {app: ApplicationHandle(app), context,
+            callbacks_registered: AtomicBool::new(false), message_handlers: Mutex::default(),
+            resources: Mutex::default()};
         application.setup_channel_callbacks();
-        Ok( (application, recv) )
+        Ok(application)
+    }
+
+    /// Shuts the application down without blocking the async runtime.
+    /// Stops offering the offered services, unsubscribes the subscribed event groups and
+    /// unregisters all handlers, then stops the application and its i/o threads on a blocking
+    /// task. Resolves when the teardown is complete; the application's channels are closed then.
+    /// Dropping the application instead does the same teardown, but blocks the calling thread.
+    pub async fn shutdown(self) {
+        let resources = std::mem::take(&mut *self.resources.lock().unwrap());
+        for (service_id, instance_id, event_group_id) in resources.subscribed {
+            self.unsubscribe(service_id, instance_id, event_group_id);
+        }
+        for service in resources.offered {
+            self.stop_offer_service(service);
+        }
+        {
+            let mut handlers = self.message_handlers.lock().unwrap();
+            unsafe {
+                ffi::application_clear_all_handlers(self.app.0);
+            }
+            handlers.clear();
+        }
+        if let Err(e) = tokio::task::spawn_blocking(move || drop(self)).await {
+            log::error!("Stopping the application failed: {}", e);
+        }
+    }
+
+    /// Attaches the sender the application's messages are delivered into.
+    /// Only one sink can be attached at a time, another one can be attached once the receiver of
+    /// the current sink has been dropped and a message for it was discarded. If the application has
+    /// registered already, `RegistrationState(true)` is sent into the sink right away.
+    ///
+    /// When a message for a dropped receiver is discarded, the state, routing state and message
+    /// handlers of the channel are unregistered at vsomeip and pending requests fail: the
+    /// application is quiescent until the next sink is attached, which registers them again.
+    /// Messages of dedicated handlers and availability changes are not affected.
+    pub fn attach_sink(&self, sender: UnboundedSender<VSomeipMessage>) -> Result<(), VSomeipError> {
+        self.context.attach(SinkSender::Channel(sender))
+    }
+
+    /// Registers the vsomeip callbacks (state, routing state, message).
+    /// Each callback invocation is transformed into a `VSomeipMessage` and sent into the sink.
+    /// Does nothing if the callbacks are registered already, after all handlers have been
+    /// cleared they are registered again.
+    fn setup_channel_callbacks(&self) {
+        if self.callbacks_registered.swap(true, Ordering::AcqRel) {
+            return;
+        }
+        register_main_handlers(&self.app, &self.context);
+    }
+
+    /// Sets whether the process is aborted when a vsomeip callback panics.
+    /// Panics must not unwind into vsomeip's threads, so they are caught at the callback
+    /// boundary, logged and reported as [VSomeipMessage::CallbackPanic] (default). With `abort`
+    /// set the process is aborted instead, as a last resort for applications that cannot
+    /// continue with a lost event.
+    pub fn set_abort_on_panic(&self, abort: bool) {
+        self.context.abort_on_panic.store(abort, Ordering::Relaxed);
+    }
+
+    /// Sets whether the offered services and events are offered again when the application
+    /// registers at the routing manager after it lost the registration, e.g. when the routing
+    /// manager restarted. Disabled by default, then a provider has to offer them again itself on
+    /// [VSomeipMessage::RegistrationState]. The offers are replayed before the
+    /// `RegistrationState(true)` message is delivered, services stopped in the meantime are not.
+    pub fn set_reoffer_on_registration(&self, enabled: bool) {
+        self.context.offers.set_enabled(enabled);
+    }
+
+    /// Sets whether the requested events and subscribed event groups of a service instance are
+    /// requested and subscribed again when the instance becomes available after it was
+    /// unavailable, e.g. when its provider restarted. Disabled by default. The subscriptions are
+    /// replayed before the [VSomeipMessage::ServiceAvailability] message is delivered, released
+    /// events and unsubscribed event groups are not.
+    ///
+    /// The replay is driven by vsomeip's availability callback and deliberately not debounced by
+    /// an [AvailabilityDebouncer]: vsomeip drops the subscriptions of an unavailable service, so
+    /// a delayed replay would lose the initial and further notifications of an instance that comes
+    /// back. A flapping service is subscribed again on each of its returns. Applications that want
+    /// to debounce leave this disabled and subscribe again on the debounced availability messages.
+    pub fn set_resubscribe_on_availability(&self, enabled: bool) {
+        self.context.subscriptions.set_enabled(enabled);
+    }
+
+    /// Sets the maximum payload size of the application's messages, `None` removes the limit.
+    /// Sending a larger payload fails with [PayloadTooLarge] before it reaches vsomeip, larger
+    /// received messages are dropped and counted (see
+    /// [VSomeipApplication::dropped_oversized_messages()]).
+    /// The limit is independent of vsomeip's configuration, which may reject messages anyway.
+    pub fn set_max_payload_size(&self, limit: Option<usize>) {
+        self.context.limits.set_application_limit(limit);
+    }
+
+    /// Sets the maximum payload size for the messages of a service, overriding the limit set with
+    /// [VSomeipApplication::set_max_payload_size()]. `None` removes the service's limit.
+    pub fn set_service_max_payload_size(&self, service_id: ServiceID, limit: Option<usize>) {
+        self.context.limits.set_service_limit(service_id, limit);
+    }
+
+    /// Returns the number of received messages dropped because of their payload size.
+    pub fn dropped_oversized_messages(&self) -> u64 {
+        self.context.limits.dropped()
+    }
+
+    /// Sets the predicate deciding whether a value of the event changed significantly compared to
+    /// the last notified one, like vsomeip's epsilon change function. [VSomeipApplication::notify()]
+    /// and [VSomeipApplication::fast_notify()] drop values without significant change, unless the
+    /// notification is forced. The predicate is applied to the payload before the interceptors,
+    /// the first value after offering the event is always notified. Replaces the predicate set
+    /// before for the event.
+    /// ```rust,no_run
+    /// use vsomeiprs::{InstanceID, MethodID, ServiceID, VSomeipApplication};
+    ///
+    /// fn provide(app: &VSomeipApplication) {
+    ///     // notify the speed only if it changed by more than 2 km/h
+    ///     app.set_change_filter(ServiceID(0x1234), InstanceID(1), MethodID(0x8001), |old, new| {
+    ///         let speed = |value: &[u8]| u16::from_be_bytes([value[0], value[1]]);
+    ///         speed(old).abs_diff(speed(new)) > 2
+    ///     });
+    /// }
+    /// ```
+    pub fn set_change_filter(&self, service_id: ServiceID, instance_id: InstanceID, notifier_id: MethodID,
+                             is_changed: impl Fn(&Bytes, &Bytes) -> bool + Send + Sync + 'static)
+    {
+        self.context.change_filters.insert(service_id, instance_id, notifier_id, Box::new(is_changed));
+    }
+
+    /// Removes the predicate set with [VSomeipApplication::set_change_filter()], all values of the
+    /// event are notified again.
+    pub fn remove_change_filter(&self, service_id: ServiceID, instance_id: InstanceID, notifier_id: MethodID) {
+        self.context.change_filters.remove(service_id, instance_id, notifier_id);
+    }
+
+    /// Returns the pointer handed to the vsomeip callbacks as `target`.
+    fn context_ptr(&self) -> *const std::os::raw::c_void {
+        &(*self.context) as *const CallbackContext as *const std::os::raw::c_void
+    }
+
+    /// Registers a dedicated handler for messages of a service instance's method.
+    /// Matching messages are delivered into the returned receiver only, not into the receiver
+    /// returned by [VSomeipApplication::create()]. The filtering happens inside the vsomeip
+    /// handler dispatch, so unrelated services in one application don't share a queue.
+    ///
+    /// `instance_id` and `method_id` may be [ANY_INSTANCE] and [ANY_METHOD] to cover all
+    /// instances/methods of a service. Registering the same combination again replaces the
+    /// previous handler. Fails with [VSomeipError::ReservedHandler] if all of them are wildcards.
+    pub fn register_message_handler(&self, service_id: ServiceID, instance_id: InstanceID, method_id: MethodID)
+        -> Result<UnboundedReceiver<VSomeipMessage>, VSomeipError>
+    {
+        if service_id == ANY_SERVICE && instance_id == ANY_INSTANCE && method_id == ANY_METHOD {
+            return Err(VSomeipError::ReservedHandler);
+        }
+        let (sender, recv) = tokio::sync::mpsc::unbounded_channel();
+        let context = self.context.share(sender);
+        let target = &(*context) as *const CallbackContext as *const std::os::raw::c_void;
+        let mut handlers = self.message_handlers.lock().unwrap();
+        unsafe {
+            ffi::application_register_message_handler(self.app.0, service_id.id(), instance_id.id(),
+                                                      method_id.id(), Some(message_handler2), target);
+        }
+        // the shim does not call a replaced handler anymore, so its context is dropped here
+        handlers.insert((service_id, instance_id, method_id), context);
+        Ok(recv)
     }
 
-    /// Registers the vsomeip callbacks (state, availability, message).
-    /// Each callback invocation is transformed into a `VSomeipMessage` and sent in the unbounded
-    /// channel.
-    /// This method must be invoked only once!
-    fn setup_channel_callbacks(&mut self) {
-        // TODO panic when this method is called more than once.
+    /// Registers a dedicated message handler like [VSomeipApplication::register_message_handler()]
+    /// and invokes `handler` for every message received by it, one after the other.
+    /// The handling task is spawned on the current tokio runtime and ends when the handler is
+    /// unregistered, replaced or cleared. Fails like [VSomeipApplication::register_message_handler()].
+    /// ```rust,no_run
+    /// use vsomeiprs::{InstanceID, MessageType, MethodID, ReturnCode, ServiceID, VSomeipApplication};
+    /// use std::sync::Arc;
+    ///
+    /// fn serve(app: Arc<VSomeipApplication>) {
+    ///     let responder = app.clone();
+    ///     app.register_method_handler(ServiceID(0x1234), InstanceID(1), MethodID(1), move |msg| {
+    ///         let app = responder.clone();
+    ///         async move {
+    ///             if let MessageType::Request { header, data } = msg {
+    ///                 let _ = app.send_response(&header, ReturnCode::Ok, data);
+    ///             }
+    ///         }
+    ///     }).expect("registered");
+    /// }
+    /// ```
+    pub fn register_method_handler<F, Fut>(&self, service_id: ServiceID, instance_id: InstanceID,
+                                           method_id: MethodID, mut handler: F)
+        -> Result<tokio::task::JoinHandle<()>, VSomeipError>
+        where F: FnMut(MessageType) -> Fut + Send + 'static,
+              Fut: Future<Output = ()> + Send + 'static
+    {
+        let mut recv = self.register_message_handler(service_id, instance_id, method_id)?;
+        Ok(tokio::spawn(async move {
+            while let Some(msg) = recv.recv().await {
+                if let VSomeipMessage::Message(msg) = msg {
+                    handler(msg).await;
+                }
+            }
+        }))
+    }
+
+    /// Unregisters a message handler registered with [VSomeipApplication::register_message_handler()].
+    /// Matching messages are delivered into the application's main channel again.
+    /// Returns `false` if no such handler was registered.
+    pub fn unregister_message_handler(&self, service_id: ServiceID, instance_id: InstanceID, method_id: MethodID)
+        -> bool
+    {
+        let mut handlers = self.message_handlers.lock().unwrap();
+        let Some(context) = handlers.remove(&(service_id, instance_id, method_id)) else {
+            return false;
+        };
         unsafe {
-            let sender_ptr = &(*self.sender2) as *const UnboundedSender<VSomeipMessage>;
-            ffi::application_register_handlers(
-                self.app,
-                Some(state_handler),
-                Some(message_handler2),
-                sender_ptr as *const std::os::raw::c_void);
+            ffi::application_unregister_message_handler(self.app.0, service_id.id(), instance_id.id(),
+                                                        method_id.id());
         }
+        // not called by the shim anymore
+        drop(context);
+        true
+    }
+
+    /// Clears all handlers registered at vsomeip, e.g. before reconfiguring the application.
+    /// This removes the dedicated message handlers and the availability handlers of requested
+    /// services. The handlers of the application's main channel are set up again, so the
+    /// application stays usable; services must be requested again to receive their availability.
+    pub fn clear_all_handlers(&self) {
+        let mut handlers = self.message_handlers.lock().unwrap();
+        unsafe {
+            ffi::application_clear_all_handlers(self.app.0);
+        }
+        self.callbacks_registered.store(false, Ordering::Release);
+        self.setup_channel_callbacks();
+        handlers.clear();
+    }
+
+    /// Returns whether the application is registered at the routing manager.
+    pub fn is_registered(&self) -> bool {
+        *self.context.registration.borrow()
+    }
+
+    /// Returns a watch of the registration state, so any task can check or await the registration
+    /// without consuming messages from the application's channel. The
+    /// [VSomeipMessage::RegistrationState] messages are still sent into the channel.
+    /// ```rust,no_run
+    /// use vsomeiprs::VSomeipApplication;
+    ///
+    /// async fn run(app: &VSomeipApplication) {
+    ///     let mut registered = app.watch_registration();
+    ///     registered.wait_for(|registered| *registered).await.expect("application alive");
+    /// }
+    /// ```
+    pub fn watch_registration(&self) -> tokio::sync::watch::Receiver<bool> {
+        self.context.registration.subscribe()
+    }
+
+    /// Returns whether this application hosts the routing manager.
+    /// The routing manager host must be running before the other applications of a device can
+    /// register.
+    pub fn is_routing_host(&self) -> bool {
+        unsafe { ffi::application_is_routing(self.app.0) }
+    }
+
+    /// Alias of [VSomeipApplication::is_routing_host()].
+    pub fn is_routing(&self) -> bool {
+        self.is_routing_host()
+    }
+
+    /// Returns the SOME/IP client id assigned to the application, [UNKNOWN_CLIENT] before the
+    /// application has registered at the routing manager. Requests sent by the application carry
+    /// it, so responses can be validated against it.
+    pub fn client_id(&self) -> ClientID {
+        ClientID::from(unsafe { ffi::application_get_client(self.app.0) })
+    }
+
+    /// Returns the name of the application. vsomeip may have changed the name given on creation.
+    pub fn name(&self) -> String {
+        // the string is owned by the vsomeip application object
+        unsafe { std::ffi::CStr::from_ptr(ffi::application_get_name(self.app.0)) }.to_string_lossy().into_owned()
+    }
+
+    /// Sets the routing state, e.g. to suspend service discovery during partial networking.
+    /// Only effective for the application hosting the routing manager. Changes reported by
+    /// vsomeip are delivered as [VSomeipMessage::RoutingState].
+    pub fn set_routing_state(&self, state: RoutingState) {
+        unsafe { ffi::application_set_routing_state(self.app.0, routing_state_to_ffi(state)); }
+    }
+
+    /// Returns the routing state last set or reported by vsomeip.
+    /// [RoutingState::Unknown] until the state is set or reported for the first time.
+    pub fn get_routing_state(&self) -> RoutingState {
+        map_routing_state(unsafe { ffi::application_get_routing_state(self.app.0) })
+    }
+
+    /// Returns whether vsomeip reported the routing to be ready, i.e. the routing manager is able
+    /// to send and receive SOME/IP messages. Only reported to the application hosting the routing
+    /// manager, e.g. to start offering once the network is up.
+    pub fn is_routing_ready(&self) -> bool {
+        unsafe { ffi::application_is_routing_ready(self.app.0) }
+    }
+
+    /// Appends an interceptor to the application's interceptor chain.
+    /// All messages sent or received afterwards pass the interceptor (see [Interceptor] for the
+    /// order in which the chain is applied).
+    pub fn add_interceptor(&self, interceptor: Arc<dyn Interceptor>) {
+        self.context.interceptors.add(interceptor);
+    }
+
+    /// Removes all interceptors from the application.
+    pub fn clear_interceptors(&self) {
+        self.context.interceptors.clear();
     }
 
     /// Requests a SOME/IP service.
     /// A consumer must request a desired service before it can use it. Once it is requested the
     /// service's availability notifications will be sent to the application.
-    pub fn request_service(&self, service_id: ServiceID, instance_id: InstanceID, version: InterfaceVersion)
+    pub fn request_service(&self, service: impl Into<ServiceHandle>)
     {
+        let ServiceHandle { service_id, instance_id, version } = service.into();
         unsafe {
-            let sender_ptr = &(*self.sender2) as *const UnboundedSender<VSomeipMessage>;
-            ffi::application_request_service(self.app, service_id.id(), instance_id.id(),
+            ffi::application_request_service(self.app.0, service_id.id(), instance_id.id(),
                                              version.major.id(), version.minor.id(),
                                              Some(avail_handler),
-                                             sender_ptr as *const std::os::raw::c_void);
+                                             self.context_ptr());
         }
     }
 
+    /// Requests a SOME/IP service like [VSomeipApplication::request_service()] and returns a watch
+    /// of its availability, so consumers can await availability changes without demultiplexing
+    /// the application's channel. The availability messages are still sent into the channel.
+    ///
+    /// The watch starts with the availability last reported by vsomeip, `false` if there is none.
+    /// With [ANY_INSTANCE] the watch is `true` while any instance of the service is available.
+    pub fn watch_availability(&self, service: impl Into<ServiceHandle>) -> tokio::sync::watch::Receiver<bool> {
+        let service = service.into();
+        let watch = self.context.availability.watch(service.service_id, service.instance_id);
+        self.request_service(service);
+        watch
+    }
+
+    /// Returns a receiver of the availability changes of all requested services, so several tasks
+    /// can observe availability independently, e.g. a proxy per service. The availability messages
+    /// are still sent into the application's channel.
+    ///
+    /// Only changes reported after the call are received, the current state is available from
+    /// [VSomeipApplication::is_available()]. A receiver lagging behind by more than 64 changes
+    /// loses the oldest ones, see [tokio::sync::broadcast].
+    /// ```rust,no_run
+    /// use vsomeiprs::{ServiceID, VSomeipApplication};
+    ///
+    /// async fn observe(app: &VSomeipApplication, service_id: ServiceID) {
+    ///     let mut availability = app.subscribe_availability();
+    ///     while let Ok(change) = availability.recv().await {
+    ///         if change.service_id == service_id {
+    ///             println!("{}.{} available: {}", change.service_id, change.instance_id, change.avail);
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub fn subscribe_availability(&self) -> tokio::sync::broadcast::Receiver<Availability> {
+        self.context.availability.subscribe()
+    }
+
+    /// Returns whether the service instance is available, [ANY_INSTANCE] and the any versions
+    /// are accepted as wildcards. Unlike [VSomeipMessage::ServiceAvailability] this does not
+    /// require the service to be requested.
+    pub fn is_available(&self, service: impl Into<ServiceHandle>) -> bool {
+        let ServiceHandle { service_id, instance_id, version } = service.into();
+        unsafe {
+            ffi::application_is_available(self.app.0, service_id.id(), instance_id.id(), version.major.id(),
+                                          version.minor.id())
+        }
+    }
+
+    /// Returns the service instances the application offered and did not stop offering yet,
+    /// sorted by id. This is bookkeeping of the calls to [VSomeipApplication::offer_service()],
+    /// vsomeip is not queried, e.g. an offer vsomeip rejected is listed nonetheless.
+    pub fn offered_services(&self) -> Vec<ServiceHandle> {
+        let mut services: Vec<ServiceHandle> = self.resources.lock().unwrap().offered.iter().copied().collect();
+        services.sort();
+        services
+    }
+
+    /// Requests the service instance and waits until it is available, e.g. before sending the
+    /// first request to it. Resolves immediately if it is available already.
+    pub async fn wait_for_service(&self, service: impl Into<ServiceHandle>, timeout_time: Duration)
+        -> Result<(), WaitError>
+    {
+        let mut available = self.watch_availability(service);
+        let result = timeout(timeout_time, available.wait_for(|avail| *avail)).await;
+        match result {
+            Ok(Ok(_)) => Ok(()),
+            // the watch is closed only when the application is dropped
+            Ok(Err(_)) | Err(_) => Err(WaitError::Timeout),
+        }
+    }
+
+    /// Requests all listed services and waits until every one of them is available at the same
+    /// time like [Dependencies::ready()], but the services stay requested in either case.
+    /// [ANY_INSTANCE] waits for any instance of the service.
+    /// Fails with the missing service instances if they are not available within `timeout_time`.
+    pub async fn await_services(&self, services: &[ServiceHandle], timeout_time: Duration)
+        -> Result<(), MissingServices>
+    {
+        let dependencies = services.iter()
+            .fold(Dependencies::new(), |dependencies, service| dependencies.require(*service))
+            .with_timeout(timeout_time);
+        let (registry, result) = dependencies.wait(self).await;
+        registry.release();
+        result
+    }
+
+    /// Requests the service instance like [VSomeipApplication::request_service()] until the
+    /// returned guard is dropped.
+    /// ```rust,no_run
+    /// use vsomeiprs::{InstanceID, InterfaceVersion, ServiceID, VSomeipApplication};
+    ///
+    /// async fn consume(app: &VSomeipApplication) {
+    ///     let _service = app.request_service_scoped((ServiceID(0x1234), InstanceID(1),
+    ///                                                InterfaceVersion::make_version(1, 0)));
+    ///     // call methods, the service is released on return or panic
+    /// }
+    /// ```
+    pub fn request_service_scoped(&self, service: impl Into<ServiceHandle>) -> RequestedServiceGuard<'_> {
+        let service = service.into();
+        self.request_service(service);
+        RequestedServiceGuard::new(self, service)
+    }
+
     /// Releases a requested SOME/IP service.
-    pub fn release_service(&self, service_id: ServiceID, instance_id: InstanceID, version: InterfaceVersion) {
+    pub fn release_service(&self, service: impl Into<ServiceHandle>) {
+        let ServiceHandle { service_id, instance_id, version } = service.into();
         unsafe {
-            ffi::application_release_service(self.app, service_id.id(), instance_id.id(), version.major.id());
+            ffi::application_release_service(self.app.0, service_id.id(), instance_id.id(), version.major.id());
         }
     }
 
@@ -159,56 +1022,120 @@ impl VSomeipApplication {
     ///      VSOMEIP will then consider the second and later providers as hot-standby for the 
     ///      currently active provider. Therefore, there will be error message or any other 
     ///      indication that a provider is not the active one.
-    pub fn offer_service(&self, service_id: ServiceID, instance_id: InstanceID, version: InterfaceVersion) {
-        unsafe {
-            ffi::application_offer_service(self.app, service_id.id(), instance_id.id(), 
-                                           version.major.id(), version.minor.id())
-        }
+    pub fn offer_service(&self, service: impl Into<ServiceHandle>) -> Result<(), SendError> {
+        let service = service.into();
+        offer_service_ffi(&self.app, service)?;
+        self.context.offers.offer_service(service);
+        self.resources.lock().unwrap().offered.insert(service);
+        Ok(())
     }
     
+    /// Offers the service instance like [VSomeipApplication::offer_service()] until the returned
+    /// guard is dropped.
+    /// ```rust,no_run
+    /// use vsomeiprs::{InstanceID, InterfaceVersion, ServiceID, VSomeipApplication};
+    ///
+    /// async fn provide(app: &VSomeipApplication) {
+    ///     let _offer = app.offer_service_scoped((ServiceID(0x1234), InstanceID(1),
+    ///                                            InterfaceVersion::make_version(1, 0))).expect("offered");
+    ///     // answer requests, the offer is stopped on return or panic
+    /// }
+    /// ```
+    pub fn offer_service_scoped(&self, service: impl Into<ServiceHandle>) -> Result<OfferGuard<'_>, SendError> {
+        let service = service.into();
+        self.offer_service(service)?;
+        Ok(OfferGuard::new(self, service))
+    }
+
     /// A provider indicates that it is no longer offering the service instance.
-    pub fn stop_offer_service(&self, service_id: ServiceID, instance_id: InstanceID, version: InterfaceVersion) {
+    pub fn stop_offer_service(&self, service: impl Into<ServiceHandle>) {
+        let service = service.into();
+        self.resources.lock().unwrap().offered.remove(&service);
+        self.context.offers.stop_offer_service(service);
+        let ServiceHandle { service_id, instance_id, version } = service;
         unsafe {
-            ffi::application_stop_offer_service(self.app, service_id.id(), instance_id.id(),
+            ffi::application_stop_offer_service(self.app.0, service_id.id(), instance_id.id(),
                                                 version.major.id(), version.minor.id())
         }
     }
 
     /// Offers an event.
+    /// The `reliability` selects the transport of the notifications, [Reliability::Unknown]
+    /// leaves it to the vsomeip configuration of the service.
+    /// Notifications of a [EventKind::SelectiveEvent] are sent to single subscribers with
+    /// [VSomeipApplication::notify_one()], consumers request it with
+    /// [VSomeipApplication::request_selective_event()].
     pub fn offer_event(&self,  service_id: ServiceID, instance_id: InstanceID, notifier_id: MethodID,
                         event_groups: Vec<EventGroupID>,
-                        is_field: bool,
+                        kind: EventKind,
                         cycle: Option<Duration>,
                         change_resets_cycle: bool,
-                        update_on_change: bool)
+                        update_on_change: bool,
+                        reliability: Reliability) -> Result<(), SendError>
     {
-        unsafe {
-            ffi::application_offer_event(self.app, service_id.id(), instance_id.id(), notifier_id.id(),
-                                         event_groups.as_ptr() as *const ffi::eventgroup_id,
-                                         event_groups.len() as u32,
-                                         is_field,
-                                         cycle.map(|x| x.as_millis() as u32).unwrap_or(0),
-                                         change_resets_cycle, update_on_change)
+        let event = EventDescriptor { notifier_id, event_groups, kind, cycle, change_resets_cycle, update_on_change,
+            reliability };
+        offer_event_ffi(&self.app, service_id, instance_id, &event)?;
+        self.context.offers.offer_event(service_id, instance_id, event);
+        Ok(())
+    }
+
+    /// Offers a service instance together with its events.
+    /// All events and the service are offered with a single call into the vsomeip wrapper, which
+    /// reduces the startup time of large interfaces compared to [VSomeipApplication::offer_event()]
+    /// and [VSomeipApplication::offer_service()] calls per event. The events are offered before
+    /// the service.
+    pub fn offer_service_with_events(&self, descriptor: &ServiceDescriptor) -> Result<(), SendError> {
+        let event_groups: Vec<Vec<ffi::eventgroup_id>> = descriptor.events.iter()
+            .map(|e| e.event_groups.iter().map(|eg| eg.id()).collect())
+            .collect();
+        let events: Vec<ffi::event_descriptor> = descriptor.events.iter().zip(event_groups.iter())
+            .map(|(e, groups)| ffi::event_descriptor {
+                notifier: e.notifier_id.id(),
+                event_groups: groups.as_ptr(),
+                event_groups_size: groups.len() as u32,
+                event_type: event_kind_to_ffi(e.kind),
+                cycle: e.cycle.map(|x| x.as_millis() as u32).unwrap_or(0),
+                change_resets_cycle: e.change_resets_cycle,
+                update_on_change: e.update_on_change,
+                reliability: reliability_to_ffi(e.reliability),
+            })
+            .collect();
+        map_send_result(unsafe {
+            ffi::application_offer_service_with_events(self.app.0, descriptor.service_id.id(),
+                                                       descriptor.instance_id.id(),
+                                                       descriptor.version.major.id(),
+                                                       descriptor.version.minor.id(),
+                                                       events.as_ptr(), events.len() as u32)
+        })?;
+        for event in &descriptor.events {
+            self.context.offers.offer_event(descriptor.service_id, descriptor.instance_id, event.clone());
         }
+        self.context.offers.offer_service(descriptor.into());
+        self.resources.lock().unwrap().offered.insert(descriptor.into());
+        Ok(())
     }
 
     /// Offers an event with a single event group.
     pub fn offer_event_seg(&self,  service_id: ServiceID, instance_id: InstanceID, notifier_id: MethodID,
                        event_group: EventGroupID,
-                       is_field: bool,
+                       kind: EventKind,
                        cycle: Option<Duration>,
                        change_resets_cycle: bool,
-                       update_on_change: bool)
+                       update_on_change: bool,
+                       reliability: Reliability) -> Result<(), SendError>
     {
-        self.offer_event(service_id, instance_id, notifier_id, vec![event_group], is_field,
-                        cycle, change_resets_cycle, update_on_change)
+        self.offer_event(service_id, instance_id, notifier_id, vec![event_group], kind,
+                        cycle, change_resets_cycle, update_on_change, reliability)
     }
 
     /// Stops offering of an event.
     pub fn stop_offer_event(&self, service_id: ServiceID, instance_id: InstanceID, notifier_id: MethodID)
     {
+        self.context.offers.stop_offer_event(service_id, instance_id, notifier_id);
+        self.context.change_filters.reset(service_id, instance_id, notifier_id);
         unsafe {
-            ffi::application_stop_offer_event(self.app, service_id.id(), instance_id.id(), notifier_id.id())
+            ffi::application_stop_offer_event(self.app.0, service_id.id(), instance_id.id(), notifier_id.id())
         }
     }
 
@@ -218,28 +1145,46 @@ impl VSomeipApplication {
     /// is not interested in them. Otherwise, vsomeip will discard initial event notifications
     /// arriving after the first subscription for the event group. This may result in lost
     /// notifications for other consumer subscribing later.
+    /// The `reliability` is the transport expected for the notifications, [Reliability::Unknown]
+    /// accepts the one offered by the provider.
     pub fn request_event(&self,  service_id: ServiceID, instance_id: InstanceID, notifier_id: MethodID,
                        event_groups: Vec<EventGroupID>,
-                       is_field: bool)
+                       is_field: bool,
+                       reliability: Reliability)
     {
-        unsafe {
-            ffi::application_request_event(self.app, service_id.id(), instance_id.id(), notifier_id.id(),
-                   event_groups.as_ptr() as *const ffi::eventgroup_id, event_groups.len() as u32, is_field)
-        }
+        self.request_event_typed(service_id, instance_id, notifier_id, &event_groups,
+                                 EventKind::from_is_field(is_field), reliability)
+    }
+
+    /// Requests a selective event, see [VSomeipApplication::offer_event()].
+    pub fn request_selective_event(&self, service_id: ServiceID, instance_id: InstanceID, notifier_id: MethodID,
+                                   event_groups: Vec<EventGroupID>, reliability: Reliability)
+    {
+        self.request_event_typed(service_id, instance_id, notifier_id, &event_groups, EventKind::SelectiveEvent,
+                                 reliability)
+    }
+
+    fn request_event_typed(&self, service_id: ServiceID, instance_id: InstanceID, notifier_id: MethodID,
+                           event_groups: &[EventGroupID], kind: EventKind, reliability: Reliability)
+    {
+        request_event_ffi(&self.app, service_id, instance_id, notifier_id, event_groups, kind, reliability);
+        self.context.subscriptions.request_event(service_id, instance_id, notifier_id,
+            RequestedEvent { event_groups: event_groups.to_vec(), kind, reliability });
     }
 
     /// Same as `request_event` but for a signle event group
     pub fn request_event_seg(&self, service_id: ServiceID, instance_id: InstanceID, notifier_id: MethodID,
-                             event_group: EventGroupID, is_field: bool)
+                             event_group: EventGroupID, is_field: bool, reliability: Reliability)
     {
-        self.request_event(service_id, instance_id, notifier_id, vec![event_group], is_field)
+        self.request_event(service_id, instance_id, notifier_id, vec![event_group], is_field, reliability)
     }
 
     /// Release a previously requested event.
     pub fn release_event(&self, service_id: ServiceID, instance_id: InstanceID, notifier_id: MethodID)
     {
+        self.context.subscriptions.release_event(service_id, instance_id, notifier_id);
         unsafe {
-            ffi::application_release_event(self.app, service_id.id(), instance_id.id(), notifier_id.id())
+            ffi::application_release_event(self.app.0, service_id.id(), instance_id.id(), notifier_id.id())
         }
     }
 
@@ -249,55 +1194,390 @@ impl VSomeipApplication {
     ///         indeed subscribe to the event group `event_group_id`. The local vsomeip uses the
     ///         `notifier_id` only to filter which event notifications from the event group will
     ///         be forwarded to the application.
-    pub fn subscribe(&self, service_id: ServiceID, instance_id: InstanceID, event_group_id: EventGroupID,
-                        notifier_id: MethodID, major_version: MajorVersion)
+    /// The subscription is for the major version of the service handle.
+    pub fn subscribe(&self, service: impl Into<ServiceHandle>, event_group_id: EventGroupID, notifier_id: MethodID)
+        -> Result<(), SendError>
     {
-        unsafe {
-            ffi::application_subscribe_event(self.app, service_id.id(), instance_id.id(),
-                                             event_group_id.id(), notifier_id.id(), major_version.id())
-        }
+        let ServiceHandle { service_id, instance_id, version } = service.into();
+        subscribe_ffi(&self.app, service_id, instance_id, event_group_id, notifier_id, version.major)?;
+        self.context.subscriptions.subscribe(service_id, instance_id, event_group_id, notifier_id, version.major);
+        self.resources.lock().unwrap().subscribed.insert((service_id, instance_id, event_group_id));
+        Ok(())
     }
 
     /// Unsubscribe a consumer from a previously subscribed event group.
     pub fn unsubscribe(&self, service_id: ServiceID, instance_id: InstanceID, event_group_id: EventGroupID)
     {
+        self.resources.lock().unwrap().subscribed.remove(&(service_id, instance_id, event_group_id));
+        self.context.subscriptions.unsubscribe(service_id, instance_id, event_group_id);
         unsafe {
-            ffi::application_unsubscribe_event(self.app, service_id.id(), instance_id.id(),
+            ffi::application_unsubscribe_event(self.app.0, service_id.id(), instance_id.id(),
                                                event_group_id.id())
         }
     }
 
+    /// Unsubscribes the event from the event group, the subscriptions of the other events of the
+    /// group are kept. The event group is unsubscribed with its last event.
+    pub fn unsubscribe_event(&self, service_id: ServiceID, instance_id: InstanceID, event_group_id: EventGroupID,
+                             notifier_id: MethodID)
+    {
+        if !self.context.subscriptions.unsubscribe_event(service_id, instance_id, event_group_id, notifier_id) {
+            // unsubscribed with its last event, shutdown() must not unsubscribe it again
+            self.resources.lock().unwrap().subscribed.remove(&(service_id, instance_id, event_group_id));
+        }
+        unsafe {
+            ffi::application_unsubscribe_single_event(self.app.0, service_id.id(), instance_id.id(),
+                                                      event_group_id.id(), notifier_id.id())
+        }
+    }
+
+    /// Requests the event and subscribes its event group until the returned guard is dropped.
+    /// The notifications are delivered into the application's main channel as after
+    /// [VSomeipApplication::subscribe()]. Fails if the subscription fails, the event is released
+    /// then.
+    /// ```rust,no_run
+    /// use vsomeiprs::{EventGroupID, InstanceID, InterfaceVersion, MethodID, Reliability, ServiceID, TypedReceivers,
+    ///                 VSomeipApplication};
+    ///
+    /// async fn run(app: &VSomeipApplication, mut recv: TypedReceivers) {
+    ///     let service = (ServiceID(0x1234), InstanceID(1), InterfaceVersion::make_major(1));
+    ///     let _subscription = app.subscribe_scoped(service, EventGroupID(1), MethodID(0x8001), false,
+    ///                                              Reliability::Unknown).expect("subscribed");
+    ///     while let Some(notification) = recv.notifications.recv().await {
+    ///         println!("{:?}", notification);
+    ///     }
+    /// }
+    /// ```
+    pub fn subscribe_scoped(&self, service: impl Into<ServiceHandle>, event_group_id: EventGroupID,
+                            notifier_id: MethodID, is_field: bool, reliability: Reliability)
+        -> Result<SubscriptionGuard<'_>, SendError>
+    {
+        let service = service.into();
+        let ServiceHandle { service_id, instance_id, .. } = service;
+        self.request_event_seg(service_id, instance_id, notifier_id, event_group_id, is_field, reliability);
+        // the guard cleans up on drop, also if the subscription failed
+        let guard = SubscriptionGuard::new(self, service_id, instance_id, event_group_id, notifier_id);
+        self.subscribe(service, event_group_id, notifier_id)?;
+        Ok(guard)
+    }
+
+    /// Requests the event, subscribes its event group and returns a stream of its notifications.
+    /// Only notifications of the given service instance and notifier are yielded, they are not
+    /// delivered into the application's main channel while the stream exists (see
+    /// [VSomeipApplication::register_message_handler()]). Dropping the stream unsubscribes the
+    /// event group and releases the event. Fails if the handler cannot be registered or the
+    /// subscription fails, the event is released then.
+    /// ```rust,no_run
+    /// use futures_core::Stream;
+    /// use std::pin::pin;
+    /// use vsomeiprs::{EventGroupID, InstanceID, InterfaceVersion, MethodID, Reliability, ServiceID,
+    ///                 VSomeipApplication};
+    ///
+    /// async fn run(app: &VSomeipApplication) {
+    ///     let service = (ServiceID(0x1234), InstanceID(1), InterfaceVersion::make_major(1));
+    ///     let mut events = pin!(app.subscribe_stream(service, EventGroupID(1), MethodID(0x8001), false,
+    ///                                                Reliability::Unknown).expect("subscribed"));
+    ///     while let Some(notification) = std::future::poll_fn(|cx| events.as_mut().poll_next(cx)).await {
+    ///         println!("{:?}", notification.data);
+    ///     }
+    /// }
+    /// ```
+    pub fn subscribe_stream(&self, service: impl Into<ServiceHandle>, event_group_id: EventGroupID,
+                            notifier_id: MethodID, is_field: bool, reliability: Reliability)
+        -> Result<NotificationStream<'_>, VSomeipError>
+    {
+        let service = service.into();
+        let ServiceHandle { service_id, instance_id, .. } = service;
+        let recv = self.register_message_handler(service_id, instance_id, notifier_id)?;
+        self.request_event_seg(service_id, instance_id, notifier_id, event_group_id, is_field, reliability);
+        // the stream cleans up on drop, also if the subscription failed
+        let stream = NotificationStream::new(self, service_id, instance_id, event_group_id, notifier_id, recv);
+        self.subscribe(service, event_group_id, notifier_id)?;
+        Ok(stream)
+    }
+
+    /// Registers for the status of subscriptions to the event group, delivered as
+    /// [VSomeipMessage::SubscriptionStatus] into the application's main channel. Without it a
+    /// refused subscription goes unnoticed, the consumer just never receives notifications.
+    /// `notifier_id` may be [ANY_METHOD] to get the status for all events of the event group.
+    pub fn register_subscription_status_handler(&self, service_id: ServiceID, instance_id: InstanceID,
+                                                event_group_id: EventGroupID, notifier_id: MethodID)
+    {
+        unsafe {
+            ffi::application_register_subscription_status_handler(self.app.0, service_id.id(), instance_id.id(),
+                event_group_id.id(), notifier_id.id(), Some(subscription_status_handler), self.context_ptr())
+        }
+    }
+
+    /// Unregisters a handler registered with
+    /// [VSomeipApplication::register_subscription_status_handler()].
+    pub fn unregister_subscription_status_handler(&self, service_id: ServiceID, instance_id: InstanceID,
+                                                  event_group_id: EventGroupID, notifier_id: MethodID)
+    {
+        unsafe {
+            ffi::application_unregister_subscription_status_handler(self.app.0, service_id.id(), instance_id.id(),
+                event_group_id.id(), notifier_id.id())
+        }
+    }
+
+    /// Registers the handler deciding about the subscriptions of clients to the event group of an
+    /// offered service instance and providing the initial values vsomeip sends to each new
+    /// subscriber once its subscription is established, see [SubscriptionHandler]. Replaces the
+    /// handler registered before for the event group.
+    pub fn register_subscription_handler(&self, service_id: ServiceID, instance_id: InstanceID,
+                                         event_group_id: EventGroupID, handler: Arc<dyn SubscriptionHandler>)
+    {
+        self.context.subscription_handlers.insert(service_id, instance_id, event_group_id, handler);
+        unsafe {
+            ffi::application_register_subscription_handler(self.app.0, service_id.id(), instance_id.id(),
+                event_group_id.id(), Some(subscription_handler), self.context_ptr())
+        }
+    }
+
+    /// Unregisters a handler registered with [VSomeipApplication::register_subscription_handler()],
+    /// subscriptions to the event group are accepted again.
+    pub fn unregister_subscription_handler(&self, service_id: ServiceID, instance_id: InstanceID,
+                                           event_group_id: EventGroupID)
+    {
+        unsafe {
+            ffi::application_unregister_subscription_handler(self.app.0, service_id.id(), instance_id.id(),
+                event_group_id.id())
+        }
+        self.context.subscription_handlers.remove(service_id, instance_id, event_group_id);
+    }
+
     /// Updates the data for an event or field and sends a notification if changed or forced.
+    /// Values without significant change are dropped, see
+    /// [VSomeipApplication::set_change_filter()].
+    /// Fails if the event is not offered or the payload exceeds the maximum payload size.
     pub fn notify(&self, service_id: ServiceID, instance_id: InstanceID, notifier_id: MethodID,
-                  payload: &Bytes, force_notification: bool)
+                  payload: impl Into<Payload>, force_notification: bool) -> Result<(), SendError>
     {
-        unsafe {
-            ffi::application_notify(self.app, service_id.id(), instance_id.id(), notifier_id.id(),
-                force_notification, payload.as_ptr(), payload.len() as u32)
+        self.context.notify(&self.app, service_id, instance_id, notifier_id, payload.into().into_bytes(),
+                            force_notification)
+    }
+
+    /// Updates several events of a service instance at once, e.g. the correlated fields a provider
+    /// updates per cycle, like [VSomeipApplication::notify()] for each of them.
+    /// All payloads pass the change filters, interceptors and the maximum payload size check
+    /// before the first notification is sent, so nothing is sent if one exceeds it. A notifier
+    /// listed more than once is notified with its last payload only.
+    /// The notifications are passed to vsomeip with a single call, but not atomically: if vsomeip
+    /// fails on one of them, the ones before it have been sent and their change filters updated.
+    pub fn notify_batch(&self, service_id: ServiceID, instance_id: InstanceID, notifications: &[(MethodID, Payload)],
+                        force_notification: bool) -> Result<(), SendError>
+    {
+        let notifier_ids: Vec<MethodID> = notifications.iter().map(|(notifier_id, _)| *notifier_id).collect();
+        for notifier_id in &notifier_ids {
+            self.context.check_offered(service_id, instance_id, *notifier_id)?;
+        }
+        self.context.change_filters.locked(service_id, instance_id, &notifier_ids, |filters| {
+            let mut batch = Vec::with_capacity(notifications.len());
+            for (index, (notifier_id, payload)) in notifications.iter().enumerate() {
+                if notifications[index + 1..].iter().any(|(later, _)| later == notifier_id) {
+                    continue;
+                }
+                let value = payload.clone().into_bytes();
+                if let Some(payload) = self.context.notification(filters, service_id, instance_id, *notifier_id,
+                                                                 &value, force_notification)? {
+                    batch.push((*notifier_id, payload, value));
+                }
+            }
+            if batch.is_empty() {
+                return Ok(());
+            }
+            let ffi_batch: Vec<ffi::notification> = batch.iter()
+                .map(|(notifier_id, payload, _)| ffi::notification {
+                    notifier: notifier_id.id(),
+                    data: payload.as_ptr(),
+                    data_len: payload.len() as u32,
+                })
+                .collect();
+            let mut sent = 0;
+            let result = map_send_result(unsafe {
+                ffi::application_notify_batch(self.app.0, service_id.id(), instance_id.id(), force_notification,
+                                              ffi_batch.as_ptr(), ffi_batch.len() as u32, &mut sent)
+            });
+            for (notifier_id, _, value) in batch.into_iter().take(sent as usize) {
+                filters.notified(notifier_id, value);
+            }
+            result
+        })
+    }
+
+    /// Updates the data for an event and sends a notification to the subscriber `client_id` only,
+    /// e.g. for selective events (see [VSomeipApplication::offer_event()]).
+    /// Fails if the event is not offered or the payload exceeds the maximum payload size.
+    pub fn notify_one(&self, service_id: ServiceID, instance_id: InstanceID, notifier_id: MethodID,
+                      client_id: ClientID, payload: impl Into<Payload>, force_notification: bool)
+        -> Result<(), SendError>
+    {
+        self.context.notify_one(&self.app, service_id, instance_id, notifier_id, client_id, payload.into(),
+                                force_notification)
+    }
+
+    /// Like [VSomeipApplication::notify()], but reuses a vsomeip payload object per notifier
+    /// instead of creating one per notification, for providers notifying at high rates.
+    /// Notifications of the same notifier are serialized. The data is not copied on the Rust side
+    /// unless interceptors are installed or a change filter is set for the event, which are still
+    /// applied.
+    pub fn fast_notify(&self, service_id: ServiceID, instance_id: InstanceID, notifier_id: MethodID,
+                       data: &[u8], force_notification: bool) -> Result<(), SendError>
+    {
+        if !self.context.interceptors.is_empty()
+            || self.context.change_filters.contains(service_id, instance_id, notifier_id) {
+            return self.notify(service_id, instance_id, notifier_id, data, force_notification);
         }
+        self.context.check_offered(service_id, instance_id, notifier_id)?;
+        self.context.limits.check(service_id, data.len())?;
+        map_send_result(unsafe {
+            ffi::application_fast_notify(self.app.0, service_id.id(), instance_id.id(), notifier_id.id(),
+                force_notification, data.as_ptr(), data.len() as u32)
+        })
     }
 
     /// Sends a request message.
+    /// The request is sent via TCP for [Reliability::Reliable] and via UDP otherwise, a request
+    /// cannot be sent on both transports.
     /// # Return
     /// Returns the assigned session id. The response (or error) from the provider will carry the
-    /// same session id which allows to link them to the request.
-    pub fn send_request(&self, service_id: ServiceID, instance_id: InstanceID, method_id: MethodID,
-        major: MajorVersion, payload: &Bytes, reliable: bool) -> SessionID
-    { 
-        SessionID::from(
-        unsafe {
-                ffi::application_send_request(self.app, service_id.id(), instance_id.id(), method_id.id(),
-                    major.id(), reliable, payload.as_ptr(), payload.len() as u32)
+    /// same session id which allows to link them to the request. [NO_SESSION] is returned if an
+    /// interceptor dropped the request. Fails if the application is not registered, the service
+    /// instance is not available or the payload exceeds the maximum payload size.
+    /// The request carries the major version of the service handle.
+    pub fn send_request(&self, service: impl Into<ServiceHandle>, method_id: MethodID, payload: impl Into<Payload>,
+                        reliability: Reliability) -> Result<SessionID, SendError>
+    {
+        let ServiceHandle { service_id, instance_id, version } = service.into();
+        let major = version.major;
+        self.context.check_registered()?;
+        if !self.is_available((service_id, instance_id, major)) {
+            return Err(SendError::NotAvailable);
+        }
+        let reliable = reliability == Reliability::Reliable;
+        let header = outgoing_header(service_id, instance_id, method_id, InterfaceVersion::make_major(major.id()),
+                                     reliable);
+        let Some(payload) = self.context.interceptors.on_send(MessageKind::Request, &header, payload.into().into_bytes())
+            else { return Ok(NO_SESSION) };
+        self.context.limits.check(service_id, payload.len())?;
+        let mut session: ffi::session_id = 0;
+        map_send_result(unsafe {
+            ffi::application_send_request(self.app.0, service_id.id(), instance_id.id(), method_id.id(),
+                major.id(), reliable, payload.as_ptr(), payload.len() as u32, &mut session)
+        })?;
+        Ok(SessionID::from(session))
+    }
+
+    /// Sends a request and returns a future resolving with its response.
+    ///
+    /// The request is sent immediately, not when the future is first polled. The response (or
+    /// error) is delivered to the future only, it is not sent into the application's channel, but
+    /// the channel must still be received from for the response to be dispatched.
+    ///
+    /// The future has no timeout of its own: it waits until the response arrives, until the
+    /// request is older than the maximum age of the [VSomeipApplication::request_tracker()]
+    /// ([RequestError::Timeout], none by default) or until the application is dropped
+    /// ([RequestError::Closed]). Dropping the future, e.g. when an outer timeout elapsed, removes
+    /// the request from the tracker but does not cancel it at vsomeip: a late response is then
+    /// delivered into the application's channel like an unrequested one, unless the handle was
+    /// cancelled with [RequestHandle::cancel()] suppressing it. See
+    /// [VSomeipApplication::send_request_with()] for requests with timeout and retransmission.
+    /// ```rust,no_run
+    /// use std::time::Duration;
+    /// use vsomeiprs::{InstanceID, InterfaceVersion, MethodID, Reliability, ServiceID, VSomeipApplication};
+    ///
+    /// async fn call(app: &VSomeipApplication) {
+    ///     let service = (ServiceID(0x1234), InstanceID(1), InterfaceVersion::make_major(1));
+    ///     let response = app.send_request_await(service, MethodID(1), vec![1, 2, 3], Reliability::Unreliable);
+    ///     match tokio::time::timeout(Duration::from_secs(1), response).await {
+    ///         Ok(Ok(response)) => println!("{:?}", response.data),
+    ///         Ok(Err(e)) => println!("request failed: {}", e),
+    ///         Err(_) => println!("timeout"),
+    ///     }
+    /// }
+    /// ```
+    pub fn send_request_await(&self, service: impl Into<ServiceHandle>, method_id: MethodID,
+                              payload: impl Into<Payload>, reliability: Reliability) -> RequestHandle
+    {
+        let service = service.into();
+        let result = self.context.pending.track(|| {
+            match self.send_request(service, method_id, payload, reliability)? {
+                NO_SESSION => Err(RequestError::Dropped),
+                session_id => Ok(RequestKey { service_id: service.service_id, method_id, session_id }),
+            }
+        });
+        RequestHandle::new(result)
+    }
+
+    /// Sends a request and returns a future resolving with its response, failing with
+    /// [RequestError::Timeout] if no response arrives within the options' timeout. A request that
+    /// timed out is retransmitted with a new session up to `options.retries` times, a late
+    /// response to an earlier transmission is discarded then.
+    ///
+    /// Unlike [VSomeipApplication::send_request_await()] the request is sent when the future is
+    /// first polled.
+    /// ```rust,no_run
+    /// use std::time::Duration;
+    /// use vsomeiprs::{InstanceID, InterfaceVersion, MethodID, RequestOptions, ServiceID, VSomeipApplication};
+    ///
+    /// async fn call(app: &VSomeipApplication) {
+    ///     let service = (ServiceID(0x1234), InstanceID(1), InterfaceVersion::make_major(1));
+    ///     let options = RequestOptions { timeout: Duration::from_millis(500), retries: 2, ..Default::default() };
+    ///     match app.send_request_with(service, MethodID(1), vec![1, 2, 3], options).await {
+    ///         Ok(response) => println!("{:?}", response.data),
+    ///         Err(e) => println!("request failed: {}", e),
+    ///     }
+    /// }
+    /// ```
+    pub fn send_request_with(&self, service: impl Into<ServiceHandle>, method_id: MethodID,
+                             payload: impl Into<Payload>, options: RequestOptions)
+        -> impl Future<Output = Result<Response, RequestError>> + Send + '_
+    {
+        let service = service.into();
+        let payload = payload.into();
+        let reliability = if options.reliable { Reliability::Reliable } else { Reliability::Unreliable };
+        async move {
+            let mut backoff = options.backoff;
+            for attempt in 0..=options.retries {
+                if attempt > 0 {
+                    tokio::time::sleep(backoff).await;
+                    backoff = backoff.saturating_mul(2);
+                }
+                let mut response = self.send_request_await(service, method_id, payload.clone(), reliability);
+                if let Ok(result) = timeout(options.timeout, &mut response).await {
+                    return result;
+                }
+                response.cancel(true);
+                log::debug!("Request {}.{}.{} timed out (attempt {})", service.service_id, service.instance_id,
+                            method_id, attempt + 1);
             }
-        )
+            Err(RequestError::Timeout)
+        }
+    }
+
+    /// Returns the tracker of the requests sent with [VSomeipApplication::send_request_await()],
+    /// e.g. to set the maximum age of unanswered requests.
+    pub fn request_tracker(&self) -> &Arc<RequestTracker> {
+        &self.context.pending
     }
 
     /// Sends a response message.
     /// # Argument
     /// - source_request        The message header of the linked request.
-    pub fn send_response(&self, source_request: &MessageHeader, return_code: ReturnCode, payload: &Bytes) {
-        unsafe {
-            ffi::application_send_response(self.app,
+    ///
+    /// Fails if the application is not registered, the payload exceeds the maximum payload size or
+    /// the return code is not valid.
+    pub fn send_response(&self, source_request: &MessageHeader, return_code: ReturnCode, payload: impl Into<Payload>)
+        -> Result<(), SendError>
+    {
+        let return_code = return_code_to_ffi(return_code)?;
+        self.context.check_registered()?;
+        let Some(payload) = self.context.interceptors.on_send(MessageKind::Response, source_request,
+                                                              payload.into().into_bytes())
+            else { return Ok(()) };
+        self.context.limits.check(source_request.service_id, payload.len())?;
+        map_send_result(unsafe {
+            ffi::application_send_response(self.app.0,
                                            source_request.service_id.id(),
                                            source_request.instance_id.id(),
                                            source_request.method_id.id(),
@@ -305,18 +1585,46 @@ impl VSomeipApplication {
                                            source_request.session_id.id(),
                                            source_request.interface_version.major.id(),
                                            source_request.reliable,
-                                           return_code_to_ffi(return_code),
+                                           return_code,
                                            payload.as_ptr(),
-                                           payload.len() as u32);
-        }
+                                           payload.len() as u32)
+        })
     }
 
-    /// Sends an error message.
+    /// Sends a message with the given header, e.g. for gateways forwarding received messages.
+    /// Message type and return code are taken from the header's `raw_message_type` and
+    /// `raw_return_code`, see [MessageHeader::builder()]. vsomeip inserts the protocol version and
+    /// the length, and the client and session id of requests.
+    /// Fails if the message type is not a [MessageKind], the application is not registered or the
+    /// payload exceeds the maximum payload size.
+    pub fn send_message(&self, header: &MessageHeader, payload: impl Into<Payload>) -> Result<(), SendError> {
+        let kind = MessageKind::from_wire(header.raw_message_type)
+            .ok_or(SendError::InvalidMessageType(header.raw_message_type))?;
+        self.context.check_registered()?;
+        let Some(payload) = self.context.interceptors.on_send(kind, header, payload.into().into_bytes())
+            else { return Ok(()) };
+        self.context.limits.check(header.service_id, payload.len())?;
+        let ffi_header = header_to_ffi(header);
+        map_send_result(unsafe {
+            ffi::application_send_message(self.app.0, &ffi_header, payload.as_ptr(), payload.len() as u32)
+        })
+    }
+
+    /// Sends an error message (message type ERROR) with the return code.
+    /// Error messages carry no payload, interceptors can only observe or drop them.
     /// # Argument
     /// - source_request        The message header of the linked request.
-    pub fn send_error(&self, source_request: &MessageHeader, return_code: ReturnCode) {
-        unsafe {
-            ffi::application_send_error(self.app,
+    ///
+    /// Fails if the return code is not valid, see [ReturnCode::is_valid()], or the application is
+    /// not registered.
+    pub fn send_error(&self, source_request: &MessageHeader, return_code: ReturnCode) -> Result<(), SendError> {
+        let return_code = return_code_to_ffi(return_code)?;
+        self.context.check_registered()?;
+        if self.context.interceptors.on_send(MessageKind::Error, source_request, Bytes::new()).is_none() {
+            return Ok(());
+        }
+        map_send_result(unsafe {
+            ffi::application_send_error(self.app.0,
                                         source_request.service_id.id(),
                                         source_request.instance_id.id(),
                                         source_request.method_id.id(),
@@ -324,40 +1632,169 @@ impl VSomeipApplication {
                                         source_request.session_id.id(),
                                         source_request.interface_version.major.id(),
                                         source_request.reliable,
-                                        return_code_to_ffi(return_code));
-        }
+                                        return_code)
+        })
     }
 }
 
-macro_rules! to_sender {
+/// Header describing an outgoing message for the interceptors.
+fn outgoing_header(service_id: ServiceID, instance_id: InstanceID, method_id: MethodID,
+                   interface_version: InterfaceVersion, reliable: bool) -> MessageHeader {
+    MessageHeader { interface_version, reliable, ..MessageHeader::new(service_id, instance_id, method_id) }
+}
+
+macro_rules! to_context {
     ($target:ident) => {
-        ($target as *mut UnboundedSender<VSomeipMessage>).as_ref().unwrap()
+        ($target as *const CallbackContext).as_ref().unwrap()
     };
 }
 
-extern "C"
-fn state_handler(state: ffi::state_type_ce, target: *const std::os::raw::c_void) {
+/// Runs the body of a vsomeip callback, unwinding into vsomeip's C++ threads is undefined
+/// behaviour. A panic is logged and reported in the callback's channel, or aborts the process if
+/// requested by [VSomeipApplication::set_abort_on_panic()].
+fn guard_callback(name: &'static str, target: *const std::os::raw::c_void, body: impl FnOnce()) {
+    let Err(panic) = catch_unwind(AssertUnwindSafe(body)) else { return };
+    let message = match panic.downcast_ref::<&str>() {
+        Some(s) => s.to_string(),
+        None => panic.downcast_ref::<String>().cloned().unwrap_or_else(|| "unknown panic".to_string()),
+    };
+    log::error!("vsomeip callback {} panicked: {}", name, message);
+    let context = unsafe { to_context!(target) };
+    if context.abort_on_panic.load(Ordering::Relaxed) {
+        std::process::abort();
+    }
+    // the channel may be closed, which might have been the cause of the panic
+    context.send(VSomeipMessage::CallbackPanic { callback: name, message });
+}
+
+fn offer_service_ffi(app: &ApplicationHandle, service: ServiceHandle) -> Result<(), SendError> {
+    let ServiceHandle { service_id, instance_id, version } = service;
+    map_send_result(unsafe {
+        ffi::application_offer_service(app.0, service_id.id(), instance_id.id(), version.major.id(), version.minor.id())
+    })
+}
+
+fn offer_event_ffi(app: &ApplicationHandle, service_id: ServiceID, instance_id: InstanceID, event: &EventDescriptor)
+    -> Result<(), SendError>
+{
+    map_send_result(unsafe {
+        ffi::application_offer_event(app.0, service_id.id(), instance_id.id(), event.notifier_id.id(),
+                                     event.event_groups.as_ptr() as *const ffi::eventgroup_id,
+                                     event.event_groups.len() as u32,
+                                     event_kind_to_ffi(event.kind),
+                                     event.cycle.map(|x| x.as_millis() as u32).unwrap_or(0),
+                                     event.change_resets_cycle, event.update_on_change,
+                                     reliability_to_ffi(event.reliability))
+    })
+}
+
+fn request_event_ffi(app: &ApplicationHandle, service_id: ServiceID, instance_id: InstanceID, notifier_id: MethodID,
+                     event_groups: &[EventGroupID], kind: EventKind, reliability: Reliability)
+{
+    unsafe {
+        ffi::application_request_event(app.0, service_id.id(), instance_id.id(), notifier_id.id(),
+               event_groups.as_ptr() as *const ffi::eventgroup_id, event_groups.len() as u32,
+               event_kind_to_ffi(kind), reliability_to_ffi(reliability))
+    }
+}
+
+fn subscribe_ffi(app: &ApplicationHandle, service_id: ServiceID, instance_id: InstanceID,
+                 event_group_id: EventGroupID, notifier_id: MethodID, major: MajorVersion) -> Result<(), SendError>
+{
+    map_send_result(unsafe {
+        ffi::application_subscribe_event(app.0, service_id.id(), instance_id.id(), event_group_id.id(),
+                                         notifier_id.id(), major.id())
+    })
+}
+
+/// Registers the callbacks of the application's main channel with `context` as their target.
+fn register_main_handlers(app: &ApplicationHandle, context: &CallbackContext) {
+    let target = context as *const CallbackContext as *const std::os::raw::c_void;
     unsafe {
-        // TODO how to react on failed transmission?
-        // -> unwrap() ==> panic
-        to_sender!(target).send(
-            VSomeipMessage::RegistrationState( state == ffi::state_type_ce_REGISTERED)).unwrap();
+        ffi::application_register_handlers(app.0, Some(state_handler), Some(message_handler2), target);
+        ffi::application_register_routing_state_handler(app.0, Some(routing_state_handler), target);
     }
 }
 
+extern "C"
+fn state_handler(state: ffi::state_type_ce, target: *const std::os::raw::c_void) {
+    guard_callback("state_handler", target, || unsafe {
+        let context = to_context!(target);
+        if context.is_quiescent() {
+            return;
+        }
+        let registered = state == ffi::state_type_ce_REGISTERED;
+        if let Some(offers) = context.offers.on_registration(registered) {
+            context.reoffer(offers);
+        }
+        context.send(VSomeipMessage::RegistrationState(registered));
+    })
+}
+
+extern "C"
+fn routing_state_handler(state: ffi::routing_state_ce, target: *const std::os::raw::c_void) {
+    guard_callback("routing_state_handler", target, || unsafe {
+        let context = to_context!(target);
+        if !context.is_quiescent() {
+            context.send(VSomeipMessage::RoutingState(map_routing_state(state)));
+        }
+    })
+}
+
 extern "C"
 fn avail_handler(svc_id: u16,
                  inst_id: u16,
                  avail: ffi::availability_state_e,
+                 major: u8,
+                 minor: u32,
                  target: *const std::os::raw::c_void)
 {
-    unsafe {
-        // TODO how to react on failed transmission?
-        // -> unwrap() ==> panic
-        to_sender!(target).send(
-    VSomeipMessage::ServiceAvailability { service_id: svc_id, instance_id: inst_id,
-                avail : avail == ffi::availability_state_e_AS_AVAILABLE }).unwrap()
-    }
+    guard_callback("avail_handler", target, || unsafe {
+        let context = to_context!(target);
+        let (service_id, instance_id) = (ServiceID::from(svc_id), InstanceID::from(inst_id));
+        let avail = avail == ffi::availability_state_e_AS_AVAILABLE;
+        let version = InterfaceVersion::make_version(major, minor);
+        let availability = Availability { service_id, instance_id, avail, version };
+        // not debounced, see VSomeipApplication::set_resubscribe_on_availability()
+        if let Some(subscriptions) = context.subscriptions.on_availability(&availability) {
+            context.resubscribe(subscriptions);
+        }
+        context.availability.update(availability);
+        context.send(VSomeipMessage::ServiceAvailability { service_id, instance_id, avail, version })
+    })
+}
+
+extern "C"
+fn subscription_status_handler(svc_id: u16,
+                               inst_id: u16,
+                               eg_id: u16,
+                               event_id: u16,
+                               error: u16,
+                               target: *const std::os::raw::c_void)
+{
+    guard_callback("subscription_status_handler", target, || unsafe {
+        to_context!(target).send(
+            VSomeipMessage::SubscriptionStatus { service_id: ServiceID::from(svc_id),
+                instance_id: InstanceID::from(inst_id), event_group_id: EventGroupID::from(eg_id),
+                notifier_id: MethodID::from(event_id), accepted: error == 0 })
+    })
+}
+
+extern "C"
+fn subscription_handler(svc_id: u16,
+                        inst_id: u16,
+                        eg_id: u16,
+                        client: u16,
+                        subscribed: bool,
+                        target: *const std::os::raw::c_void) -> bool
+{
+    // a subscription is refused if the handler panicked
+    let mut accepted = false;
+    guard_callback("subscription_handler", target, || unsafe {
+        accepted = to_context!(target).on_subscription(ServiceID::from(svc_id), InstanceID::from(inst_id),
+            EventGroupID::from(eg_id), ClientID::from(client), subscribed);
+    });
+    accepted
 }
 
 fn make_header(hdr: &ffi::message_header) -> MessageHeader {
@@ -368,7 +1805,44 @@ fn make_header(hdr: &ffi::message_header) -> MessageHeader {
         client_id: ClientID::from(hdr.client),
         session_id: SessionID::from(hdr.session),
         interface_version: InterfaceVersion::make_major(hdr.if_version),
+        protocol_version: ProtocolVersion::from(hdr.proto_version),
+        raw_message_type: hdr.message_type as u8,
+        raw_return_code: hdr.return_code as u8,
+        length: hdr.length,
         reliable: hdr.is_reliable,
+        origin: make_origin(hdr),
+    }
+}
+
+fn header_to_ffi(header: &MessageHeader) -> ffi::message_header {
+    ffi::message_header {
+        service: header.service_id.id(),
+        instance: header.instance_id.id(),
+        method: header.method_id.id(),
+        client: header.client_id.id(),
+        session: header.session_id.id(),
+        proto_version: header.protocol_version.id(),
+        if_version: header.interface_version.major.id(),
+        message_type: header.raw_message_type as ffi::message_type,
+        return_code: header.raw_return_code as ffi::return_code,
+        length: 0,
+        is_initial: false,
+        is_reliable: header.reliable,
+        remote_address: 0,
+        remote_port: 0,
+    }
+}
+
+fn make_origin(hdr: &ffi::message_header) -> Origin {
+    // local applications communicating via TCP have a port, but a loopback address. vsomeip leaves
+    // the address unset if it has no IPv4 sender address, e.g. for IPv6 peers.
+    let address = Ipv4Addr::from(hdr.remote_address);
+    if address.is_unspecified() {
+        Origin::Unknown
+    } else if address.is_loopback() {
+        Origin::Local
+    } else {
+        Origin::Remote(SocketAddr::from((address, hdr.remote_port)))
     }
 }
 
@@ -386,12 +1860,15 @@ fn map_return_code(rt: ffi::return_code) -> ReturnCode {
         ffi::return_code_E_MALFORMED_MESSAGE => ReturnCode::MalformedMessage,
         ffi::return_code_E_WRONG_MESSAGE_TYPE => ReturnCode::WrongMessageType,
         ffi::return_code_E_UNKNOWN => ReturnCode::Unknown,
-        val => { panic!("Unknown return code {}", val); }
+        val => ReturnCode::from_wire(val as u8),
     }
 }
 
-fn return_code_to_ffi(rc: ReturnCode) -> ffi::return_code {
-    match rc {
+fn return_code_to_ffi(rc: ReturnCode) -> Result<ffi::return_code, SendError> {
+    if !rc.is_valid() {
+        return Err(SendError::InvalidReturnCode(rc));
+    }
+    Ok(match rc {
         ReturnCode::Ok => ffi::return_code_E_OK,
         ReturnCode::NotOk => ffi::return_code_E_NOT_OK,
         ReturnCode::UnknownService => ffi::return_code_E_UNKNOWN_SERVICE,
@@ -403,7 +1880,66 @@ fn return_code_to_ffi(rc: ReturnCode) -> ffi::return_code {
         ReturnCode::WrongInterfaceVersion => ffi::return_code_E_WRONG_INTERFACE_VERSION,
         ReturnCode::MalformedMessage => ffi::return_code_E_MALFORMED_MESSAGE,
         ReturnCode::WrongMessageType => ffi::return_code_E_WRONG_MESSAGE_TYPE,
+        ReturnCode::ApplicationSpecific(code) | ReturnCode::Reserved(code) => code as ffi::return_code,
         ReturnCode::Unknown => ffi::return_code_E_UNKNOWN,
+    })
+}
+
+fn reliability_to_ffi(reliability: Reliability) -> ffi::reliability_type_ce {
+    match reliability {
+        Reliability::Reliable => ffi::reliability_type_ce_RT_RELIABLE,
+        Reliability::Unreliable => ffi::reliability_type_ce_RT_UNRELIABLE,
+        Reliability::Both => ffi::reliability_type_ce_RT_BOTH,
+        Reliability::Unknown => ffi::reliability_type_ce_RT_UNKNOWN,
+    }
+}
+
+fn event_kind_to_ffi(kind: EventKind) -> ffi::event_type_ce {
+    match kind {
+        EventKind::Event => ffi::event_type_ce_ET_EVENT,
+        EventKind::SelectiveEvent => ffi::event_type_ce_ET_SELECTIVE_EVENT,
+        EventKind::Field => ffi::event_type_ce_ET_FIELD,
+    }
+}
+
+fn map_create_result(result: ffi::create_result_ce) -> Result<(), VSomeipError> {
+    match result {
+        ffi::create_result_ce_CR_OK => Ok(()),
+        ffi::create_result_ce_CR_NO_RUNTIME => Err(VSomeipError::NoRuntime),
+        ffi::create_result_ce_CR_INIT_FAILED => Err(VSomeipError::InitFailed),
+        ffi::create_result_ce_CR_START_FAILED => Err(VSomeipError::StartFailed),
+        _ => Err(VSomeipError::CreateFailed),
+    }
+}
+
+fn map_send_result(result: ffi::send_result_ce) -> Result<(), SendError> {
+    match result {
+        ffi::send_result_ce_SR_OK => Ok(()),
+        _ => Err(SendError::Failed),
+    }
+}
+
+fn map_routing_state(state: ffi::routing_state_ce) -> RoutingState {
+    match state {
+        ffi::routing_state_ce_RS_RUNNING => RoutingState::Running,
+        ffi::routing_state_ce_RS_SUSPENDED => RoutingState::Suspended,
+        ffi::routing_state_ce_RS_RESUMED => RoutingState::Resumed,
+        ffi::routing_state_ce_RS_SHUTDOWN => RoutingState::Shutdown,
+        ffi::routing_state_ce_RS_DIAGNOSIS => RoutingState::Diagnosis,
+        ffi::routing_state_ce_RS_DELAYED_RESUME => RoutingState::DelayedResume,
+        _ => RoutingState::Unknown,
+    }
+}
+
+fn routing_state_to_ffi(state: RoutingState) -> ffi::routing_state_ce {
+    match state {
+        RoutingState::Running => ffi::routing_state_ce_RS_RUNNING,
+        RoutingState::Suspended => ffi::routing_state_ce_RS_SUSPENDED,
+        RoutingState::Resumed => ffi::routing_state_ce_RS_RESUMED,
+        RoutingState::Shutdown => ffi::routing_state_ce_RS_SHUTDOWN,
+        RoutingState::Diagnosis => ffi::routing_state_ce_RS_DIAGNOSIS,
+        RoutingState::DelayedResume => ffi::routing_state_ce_RS_DELAYED_RESUME,
+        RoutingState::Unknown => ffi::routing_state_ce_RS_UNKNOWN,
     }
 }
 
@@ -413,54 +1949,78 @@ fn message_handler2(
     payload: ffi::payload_t,
     target: *const std::os::raw::c_void)
 {
-    let data = VSomeipPayload::from(payload);
-    let header = make_header(&msg_header);
-
-    let msg = match msg_header.message_type {
-        ffi::message_type_MT_REQUEST => MessageType::Request {header, data},
-        ffi::message_type_MT_REQUEST_NO_RETURN => MessageType::RequestNoReturn {header, data},
-        ffi::message_type_MT_NOTIFICATION => MessageType::Notification {header, data,
-            is_initial: msg_header.is_initial},
-        ffi::message_type_MT_RESPONSE => MessageType::Response {header, data},
-        ffi::message_type_MT_ERROR => MessageType::Error {header, data,
-            return_code: map_return_code(msg_header.return_code)},
-
-        // the following vsomeip message types shouldn't be sent upstream from libvsomeip
-        // so we ignore them
-        ffi::message_type_MT_REQUEST_ACK => { return /* ignored */ },
-        ffi::message_type_MT_REQUEST_NO_RETURN_ACK => { return /* ignored */ },
-        ffi::message_type_MT_NOTIFICATION_ACK => { return /* ignored */ },
-        ffi::message_type_MT_RESPONSE_ACK => { return /* ignored */ },
-        ffi::message_type_MT_ERROR_ACK => { return /* ignored */ },
-        ffi::message_type_MT_UNKNOWN => { return /* ignored */ },
-
-        // an unknown vsomeip message type usually indicates that vsomeip is in an undefined
-        // state, or we have linked to an unsupported vsomeip version.
-        val => { panic!("Unknown message type from vsomeip {}", val)}
-    };
+    guard_callback("message_handler2", target, || {
+        let data = VSomeipPayload::from(payload);
+        // the payload is released with `data`
+        if unsafe { to_context!(target) }.is_quiescent() {
+            return;
+        }
+        let header = make_header(&msg_header);
 
-    unsafe {
-        // TODO how to react on failed transmission?
-        // -> unwrap() ==> panic
-        to_sender!(target).send(VSomeipMessage::Message(msg)).unwrap()
-    }
+        let msg = match msg_header.message_type {
+            ffi::message_type_MT_REQUEST => MessageType::Request {header, data},
+            ffi::message_type_MT_REQUEST_NO_RETURN => MessageType::RequestNoReturn {header, data},
+            ffi::message_type_MT_NOTIFICATION => MessageType::Notification {header, data,
+                is_initial: msg_header.is_initial},
+            // a response with another return code than E_OK reports an error as well
+            ffi::message_type_MT_RESPONSE if msg_header.return_code != ffi::return_code_E_OK =>
+                MessageType::Error {header, data, return_code: map_return_code(msg_header.return_code)},
+            ffi::message_type_MT_RESPONSE => MessageType::Response {header, data},
+            ffi::message_type_MT_ERROR => MessageType::Error {header, data,
+                return_code: map_return_code(msg_header.return_code)},
+
+            // the following vsomeip message types shouldn't be sent upstream from libvsomeip
+            // so we ignore them
+            ffi::message_type_MT_REQUEST_ACK => { return /* ignored */ },
+            ffi::message_type_MT_REQUEST_NO_RETURN_ACK => { return /* ignored */ },
+            ffi::message_type_MT_NOTIFICATION_ACK => { return /* ignored */ },
+            ffi::message_type_MT_RESPONSE_ACK => { return /* ignored */ },
+            ffi::message_type_MT_ERROR_ACK => { return /* ignored */ },
+            ffi::message_type_MT_UNKNOWN => { return /* ignored */ },
+
+            // an unknown vsomeip message type usually indicates that vsomeip is in an undefined
+            // state, or we have linked to an unsupported vsomeip version.
+            val => {
+                log::warn!("Unknown message type {:#04x} from vsomeip, message dropped", val);
+                unsafe {
+                    to_context!(target).send(VSomeipMessage::ProtocolAnomaly { header, message_type: val as u8 });
+                }
+                return;
+            }
+        };
+
+        unsafe {
+            let context = to_context!(target);
+            if !context.limits.accept_received(msg.header().service_id, msg.payload().as_bytes_ref().len()) {
+                return;
+            }
+            let Some(msg) = context.interceptors.on_receive(msg) else { return };
+            let Some(msg) = context.pending.complete(msg) else { return };
+            context.send(VSomeipMessage::Message(msg))
+        }
+    })
 }
 
 /// Encapsulation of a vsomeip::payload object.
+///
+/// The data is not copied out of a received payload object: the `Bytes` returned by
+/// [VSomeipPayload::as_bytes_ref()] and [VSomeipPayload::into_bytes()] own a reference to it, so
+/// they and their clones stay valid after the payload, its message and the application are gone.
 pub struct VSomeipPayload {
-    payload: ffi::payload_t,
     bytes: Bytes
 }
 
-impl Drop for VSomeipPayload {
-    fn drop(&mut self) {
-        unsafe { ffi::payload_destroy(self.payload) }
+impl From<ffi::payload_t> for VSomeipPayload {
+    fn from(value: ffi::payload_t) -> Self {
+        Self{ bytes: payload_to_bytes(value) }
     }
 }
 
-impl From<ffi::payload_t> for VSomeipPayload {
-    fn from(value: ffi::payload_t) -> Self {
-        Self{ payload: value, bytes: payload_to_bytes(value) }
+/// Creates a payload not backed by a vsomeip payload object, e.g. for interceptors replacing the
+/// data of a received message.
+impl From<Bytes> for VSomeipPayload {
+    fn from(value: Bytes) -> Self {
+        Self{ bytes: value }
     }
 }
 
@@ -470,32 +2030,195 @@ impl Debug for VSomeipPayload {
     }
 }
 
-unsafe impl Send for VSomeipPayload {}
-
-unsafe impl Sync for VSomeipPayload {}
-
 impl VSomeipPayload {
 
     /// Returns the data within the payload as `Bytes` reference.
-    /// NOTE: This involves no copying, but the reference's lifetime is bound to the
-    /// VSomeipPayload object.
+    /// NOTE: This involves no copying, the data stays owned by the vsomeip payload object which
+    /// is kept alive by all clones of the `Bytes`.
     pub fn as_bytes_ref(&self) -> &Bytes  {
         &self.bytes
     }
+
+    /// Returns the data within the payload as `Bytes` without copying.
+    pub fn into_bytes(self) -> Bytes {
+        self.bytes
+    }
+}
+
+/// Received vsomeip payload object owning the data of the `Bytes` created from it.
+struct PayloadHandle {
+    payload: ffi::payload_t,
+    data: *const u8,
+    len: usize,
+}
+
+// SAFETY: the handle is a `std::shared_ptr<vsomeip::payload>` owned exclusively by this object.
+// The payload of a received message is not modified anymore and the shared pointer's reference
+// count is atomic, so the data may be read and the handle destroyed on any thread.
+unsafe impl Send for PayloadHandle {}
+
+impl AsRef<[u8]> for PayloadHandle {
+    fn as_ref(&self) -> &[u8] {
+        if self.data.is_null() || self.len == 0 {
+            &[]
+        } else {
+            unsafe { std::slice::from_raw_parts(self.data, self.len) }
+        }
+    }
+}
+
+impl Drop for PayloadHandle {
+    fn drop(&mut self) {
+        unsafe { ffi::payload_destroy(self.payload) }
+    }
 }
 
 fn payload_to_bytes(payload: ffi::payload_t) -> Bytes {
     if payload.is_null() {
         Bytes::new()
     } else {
-        unsafe {
-            let pli = ffi::payload_get_info(payload);
-            if pli.data.is_null() || pli.len == 0 {
-                Bytes::new()
-            } else {
-                Bytes::from_static(std::slice::from_raw_parts(pli.data, pli.len as usize))
+        let pli = unsafe { ffi::payload_get_info(payload) };
+        Bytes::from_owner(PayloadHandle { payload, data: pli.data, len: pli.len as usize })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::service::CallError;
+
+    #[test]
+    fn callback_panic_is_reported_test() {
+        let (sender, mut recv) = tokio::sync::mpsc::unbounded_channel();
+        let context = CallbackContext::new(Some(sender));
+        let target = &(*context) as *const CallbackContext as *const std::os::raw::c_void;
+        guard_callback("test_handler", target, || panic!("boom"));
+        match recv.try_recv() {
+            Ok(VSomeipMessage::CallbackPanic { callback, message }) => {
+                assert_eq!(callback, "test_handler");
+                assert_eq!(message, "boom");
             }
+            other => panic!("unexpected {:?}", other),
         }
     }
-}
 
+    #[test]
+    fn attach_sink_replays_registration_test() {
+        let context = CallbackContext::new(None);
+        context.send(VSomeipMessage::RegistrationState(true));
+        let (sender, mut recv) = tokio::sync::mpsc::unbounded_channel();
+        assert_eq!(context.attach(SinkSender::Channel(sender.clone())), Ok(()));
+        assert_eq!(context.attach(SinkSender::Channel(sender)), Err(VSomeipError::SinkAlreadyAttached));
+        assert!(matches!(recv.try_recv(), Ok(VSomeipMessage::RegistrationState(true))));
+        assert!(*context.registration.subscribe().borrow());
+        context.sink.lock().unwrap().sender = None;
+        assert!(recv.try_recv().is_err());
+        assert!(recv.is_closed());
+    }
+
+    #[test]
+    fn closed_receiver_detaches_sink_test() {
+        let (sender, recv) = tokio::sync::mpsc::unbounded_channel();
+        let context = CallbackContext::new(Some(sender));
+        context.send(VSomeipMessage::RegistrationState(true));
+        drop(recv);
+        context.send(VSomeipMessage::RoutingState(RoutingState::Running));
+        context.send(VSomeipMessage::RoutingState(RoutingState::Suspended));
+        let (sender, mut recv) = tokio::sync::mpsc::unbounded_channel();
+        assert_eq!(context.attach(SinkSender::Channel(sender)), Ok(()));
+        assert!(matches!(recv.try_recv(), Ok(VSomeipMessage::RegistrationState(true))));
+    }
+
+    #[tokio::test]
+    async fn error_response_round_trip_test() {
+        let context = CallbackContext::new(None);
+        let target = &(*context) as *const CallbackContext as *const std::os::raw::c_void;
+        let call = |session, message_type, return_code| {
+            let pending = context.pending.track(|| Ok(request_key(session))).unwrap();
+            message_handler2(response_header(session, message_type, return_code), std::ptr::null_mut(), target);
+            pending
+        };
+        let ok = call(1, ffi::message_type_MT_RESPONSE, ffi::return_code_E_OK);
+        assert!(ok.await.is_ok());
+        let error = call(2, ffi::message_type_MT_ERROR, ffi::return_code_E_NOT_READY);
+        assert!(matches!(error.await.map_err(CallError::from), Err(CallError::NotReady)));
+        // a response with an error return code is an error as well
+        let error = call(3, ffi::message_type_MT_RESPONSE, ffi::return_code_E_UNKNOWN_METHOD);
+        assert!(matches!(error.await.map_err(CallError::from), Err(CallError::UnknownMethod)));
+        let error = call(4, ffi::message_type_MT_ERROR, 0x42);
+        match error.await.map_err(CallError::from) {
+            Err(CallError::Application { code, data }) => assert_eq!((code, data.len()), (0x42, 0)),
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn closed_receiver_quiesces_test() {
+        let (sender, recv) = tokio::sync::mpsc::unbounded_channel();
+        let context = CallbackContext::new(Some(sender));
+        let target = &(*context) as *const CallbackContext as *const std::os::raw::c_void;
+        let pending = context.pending.track(|| Ok(request_key(1))).unwrap();
+        drop(recv);
+        routing_state_handler(ffi::routing_state_ce_RS_RUNNING, target);
+        assert!(context.is_quiescent());
+        assert!(matches!(pending.await, Err(RequestError::Closed)));
+
+        // no callback reaches the quiescent context
+        let _pending = context.pending.track(|| Ok(request_key(2))).unwrap();
+        message_handler2(response_header(2, ffi::message_type_MT_RESPONSE, ffi::return_code_E_OK),
+                         std::ptr::null_mut(), target);
+        assert_eq!(context.pending.len(), 1);
+        state_handler(ffi::state_type_ce_REGISTERED, target);
+        assert!(!*context.registration.borrow());
+
+        let (sender, mut recv) = tokio::sync::mpsc::unbounded_channel();
+        assert_eq!(context.attach(SinkSender::Channel(sender)), Ok(()));
+        assert!(!context.is_quiescent());
+        routing_state_handler(ffi::routing_state_ce_RS_SUSPENDED, target);
+        assert!(matches!(recv.try_recv(), Ok(VSomeipMessage::RoutingState(RoutingState::Suspended))));
+    }
+
+    #[test]
+    fn request_handle_drop_test() {
+        let (sender, mut recv) = tokio::sync::mpsc::unbounded_channel();
+        let context = CallbackContext::new(Some(sender));
+        let target = &(*context) as *const CallbackContext as *const std::os::raw::c_void;
+        let response = |session| response_header(session, ffi::message_type_MT_RESPONSE, ffi::return_code_E_OK);
+
+        // a dropped handle leaves no entry behind, its late response is unrequested
+        drop(RequestHandle::new(context.pending.track(|| Ok(request_key(1)))));
+        assert!(context.pending.is_empty());
+        message_handler2(response(1), std::ptr::null_mut(), target);
+        assert!(matches!(recv.try_recv(), Ok(VSomeipMessage::Message(MessageType::Response { .. }))));
+
+        // the late response of a handle cancelled with suppression is discarded
+        RequestHandle::new(context.pending.track(|| Ok(request_key(2)))).cancel(true);
+        assert!(context.pending.is_empty());
+        message_handler2(response(2), std::ptr::null_mut(), target);
+        assert!(recv.try_recv().is_err());
+    }
+
+    #[test]
+    fn make_origin_test() {
+        let header = response_header(1, ffi::message_type_MT_RESPONSE, ffi::return_code_E_OK);
+        let origin = |remote_address: Ipv4Addr, remote_port| make_origin(&ffi::message_header {
+            remote_address: remote_address.into(), remote_port, ..header });
+        assert_eq!(origin(Ipv4Addr::UNSPECIFIED, 0), Origin::Unknown);
+        assert_eq!(origin(Ipv4Addr::UNSPECIFIED, 30509), Origin::Unknown);
+        assert_eq!(origin(Ipv4Addr::LOCALHOST, 31000), Origin::Local);
+        assert_eq!(origin(Ipv4Addr::new(192, 168, 1, 2), 30509),
+                   Origin::Remote(SocketAddr::from((Ipv4Addr::new(192, 168, 1, 2), 30509))));
+    }
+
+    fn request_key(session: u16) -> RequestKey {
+        RequestKey { service_id: ServiceID(0x1234), method_id: MethodID(1), session_id: SessionID(session) }
+    }
+
+    fn response_header(session: u16, message_type: ffi::message_type, return_code: ffi::return_code)
+        -> ffi::message_header
+    {
+        ffi::message_header { service: 0x1234, instance: 1, method: 1, client: 0x10, session, proto_version: 1,
+            if_version: 1, message_type, return_code, length: 8, is_initial: false, is_reliable: false,
+            remote_address: 0, remote_port: 0 }
+    }
+}
@@ -0,0 +1,118 @@
+// SPDX-License-Identifier: MPL-2.0
+//
+// Copyright (C) 2024 Alexander Seifarth
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::RwLock;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use super::ServiceID;
+
+/// Error returned when sending a payload exceeding the configured maximum payload size, see
+/// [crate::VSomeipApplication::set_max_payload_size()].
+#[derive(Eq, PartialEq, Debug, Clone, Copy)]
+pub struct PayloadTooLarge {
+    pub service_id: ServiceID,
+    pub size: usize,
+    pub limit: usize,
+}
+
+impl fmt::Display for PayloadTooLarge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "payload of {} bytes for service {} exceeds the maximum of {} bytes",
+               self.size, self.service_id, self.limit)
+    }
+}
+
+impl std::error::Error for PayloadTooLarge {}
+
+/// Maximum payload sizes of an application, shared with its callbacks.
+/// A service specific limit takes precedence over the application's limit.
+pub(crate) struct PayloadLimits {
+    /// `usize::MAX` if unlimited
+    application: AtomicUsize,
+    services: RwLock<HashMap<ServiceID, usize>>,
+    dropped: AtomicU64,
+}
+
+impl Default for PayloadLimits {
+    fn default() -> Self {
+        PayloadLimits { application: AtomicUsize::new(usize::MAX), services: RwLock::default(),
+            dropped: AtomicU64::new(0) }
+    }
+}
+
+impl PayloadLimits {
+    pub(crate) fn set_application_limit(&self, limit: Option<usize>) {
+        self.application.store(limit.unwrap_or(usize::MAX), Ordering::Relaxed);
+    }
+
+    pub(crate) fn set_service_limit(&self, service_id: ServiceID, limit: Option<usize>) {
+        let mut services = self.services.write().unwrap();
+        match limit {
+            Some(limit) => { services.insert(service_id, limit); }
+            None => { services.remove(&service_id); }
+        }
+    }
+
+    /// Returns the limit applying to the service.
+    pub(crate) fn limit(&self, service_id: ServiceID) -> usize {
+        let services = self.services.read().unwrap();
+        match services.get(&service_id) {
+            Some(limit) => *limit,
+            None => self.application.load(Ordering::Relaxed),
+        }
+    }
+
+    pub(crate) fn check(&self, service_id: ServiceID, size: usize) -> Result<(), PayloadTooLarge> {
+        let limit = self.limit(service_id);
+        if size > limit {
+            Err(PayloadTooLarge { service_id, size, limit })
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Checks a received payload, oversized ones are counted.
+    pub(crate) fn accept_received(&self, service_id: ServiceID, size: usize) -> bool {
+        match self.check(service_id, size) {
+            Ok(()) => true,
+            Err(e) => {
+                let dropped = self.dropped.fetch_add(1, Ordering::Relaxed) + 1;
+                log::warn!("Dropping received message: {} ({} dropped so far)", e, dropped);
+                false
+            }
+        }
+    }
+
+    pub(crate) fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn limits_test() {
+        let limits = PayloadLimits::default();
+        assert_eq!(limits.check(ServiceID(1), 1 << 20), Ok(()));
+
+        limits.set_application_limit(Some(100));
+        limits.set_service_limit(ServiceID(2), Some(1000));
+        assert_eq!(limits.check(ServiceID(1), 101),
+                   Err(PayloadTooLarge { service_id: ServiceID(1), size: 101, limit: 100 }));
+        assert_eq!(limits.check(ServiceID(2), 1000), Ok(()));
+
+        assert!(limits.accept_received(ServiceID(1), 100));
+        assert!(!limits.accept_received(ServiceID(1), 101));
+        assert_eq!(limits.dropped(), 1);
+
+        limits.set_service_limit(ServiceID(2), None);
+        assert!(limits.check(ServiceID(2), 101).is_err());
+    }
+}
@@ -0,0 +1,40 @@
+// SPDX-License-Identifier: MPL-2.0
+//
+// Copyright (C) 2024 Alexander Seifarth
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use super::{ServiceHandle, VSomeipApplication};
+
+/// Offer of a service instance, returned by [VSomeipApplication::offer_service_scoped()].
+///
+/// Dropping the guard stops offering the service instance, also when the provider's task ends
+/// early or panics. [OfferGuard::release()] keeps the service offered.
+#[must_use = "dropping the guard stops offering the service instance"]
+pub struct OfferGuard<'a> {
+    app: &'a VSomeipApplication,
+    service: ServiceHandle,
+}
+
+impl<'a> OfferGuard<'a> {
+    pub(crate) fn new(app: &'a VSomeipApplication, service: ServiceHandle) -> Self {
+        OfferGuard { app, service }
+    }
+
+    pub fn service(&self) -> ServiceHandle {
+        self.service
+    }
+
+    /// Drops the guard without stopping the offer, it is stopped with
+    /// [VSomeipApplication::stop_offer_service()] or when the application is dropped.
+    pub fn release(self) {
+        std::mem::forget(self);
+    }
+}
+
+impl Drop for OfferGuard<'_> {
+    fn drop(&mut self) {
+        self.app.stop_offer_service(self.service);
+    }
+}
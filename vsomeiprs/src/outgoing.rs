@@ -0,0 +1,171 @@
+// SPDX-License-Identifier: MPL-2.0
+//
+// Copyright (C) 2024 Alexander Seifarth
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use tokio::sync::Notify;
+use tokio::task::JoinHandle;
+use super::{InstanceID, MessageHeader, MethodID, Payload, Reliability, ReturnCode, ServiceID, VSomeipApplication};
+
+/// Priority class of an outgoing message, higher priorities are sent first.
+#[derive(Eq, PartialEq, Ord, PartialOrd, Debug, Clone, Copy)]
+pub enum Priority {
+    /// Bulk data, e.g. telemetry notifications.
+    Bulk = 0,
+    /// Event and field notifications.
+    Notification = 1,
+    /// Responses and errors of method calls.
+    Response = 2,
+}
+
+const PRIORITIES: usize = 3;
+
+enum Outgoing {
+    Response { header: MessageHeader, return_code: ReturnCode, payload: Payload },
+    Error { header: MessageHeader, return_code: ReturnCode },
+    Notification { service_id: ServiceID, instance_id: InstanceID, notifier_id: MethodID, payload: Payload,
+        force: bool },
+}
+
+/// Outgoing messages of one transport, one queue per priority.
+#[derive(Default)]
+struct Queue {
+    messages: Mutex<[VecDeque<Outgoing>; PRIORITIES]>,
+    ready: Notify,
+}
+
+impl Queue {
+    fn push(&self, priority: Priority, msg: Outgoing) {
+        self.messages.lock().unwrap()[priority as usize].push_back(msg);
+        self.ready.notify_one();
+    }
+
+    fn pop(&self) -> Option<Outgoing> {
+        self.messages.lock().unwrap().iter_mut().rev().find_map(|queue| queue.pop_front())
+    }
+
+    fn len(&self) -> usize {
+        self.messages.lock().unwrap().iter().map(|queue| queue.len()).sum()
+    }
+}
+
+/// Prioritized sending of responses and notifications.
+///
+/// Messages are queued per transport (reliable/unreliable) and priority [Priority] and sent by a
+/// task per transport, always taking the highest priority first. So a burst of low priority
+/// notifications cannot delay time-critical responses, and a congested transport does not delay
+/// the other one. Requests are not queued as their session id is needed right away, they are
+/// sent directly with [VSomeipApplication::send_request()].
+///
+/// Send errors of queued messages, e.g. [crate::PayloadTooLarge], are logged.
+/// The tasks run until the [OutgoingQueues] are dropped, messages still queued are discarded.
+/// They must be started from within a tokio runtime.
+pub struct OutgoingQueues {
+    reliable: Arc<Queue>,
+    unreliable: Arc<Queue>,
+    tasks: [JoinHandle<()>; 2],
+}
+
+impl Drop for OutgoingQueues {
+    fn drop(&mut self) {
+        for task in &self.tasks {
+            task.abort();
+        }
+    }
+}
+
+impl OutgoingQueues {
+    pub fn start(app: Arc<VSomeipApplication>) -> Self {
+        let reliable = Arc::new(Queue::default());
+        let unreliable = Arc::new(Queue::default());
+        let tasks = [tokio::spawn(send_queued(app.clone(), reliable.clone())),
+                     tokio::spawn(send_queued(app, unreliable.clone()))];
+        OutgoingQueues { reliable, unreliable, tasks }
+    }
+
+    /// Queues a response with [Priority::Response] on the transport of the request.
+    pub fn send_response(&self, source_request: &MessageHeader, return_code: ReturnCode, payload: impl Into<Payload>) {
+        self.queue(source_request.reliable).push(Priority::Response,
+            Outgoing::Response { header: source_request.clone(), return_code, payload: payload.into() });
+    }
+
+    /// Queues an error message with [Priority::Response] on the transport of the request.
+    pub fn send_error(&self, source_request: &MessageHeader, return_code: ReturnCode) {
+        self.queue(source_request.reliable).push(Priority::Response,
+            Outgoing::Error { header: source_request.clone(), return_code });
+    }
+
+    /// Queues a notification with the given priority, [Priority::Notification] for regular
+    /// events and fields. `reliability` is the transport the event was offered with.
+    #[allow(clippy::too_many_arguments)]
+    pub fn notify(&self, service_id: ServiceID, instance_id: InstanceID, notifier_id: MethodID,
+                  payload: impl Into<Payload>, force_notification: bool, priority: Priority,
+                  reliability: Reliability) {
+        self.queue(reliability == Reliability::Reliable).push(priority,
+            Outgoing::Notification { service_id, instance_id, notifier_id, payload: payload.into(),
+                force: force_notification });
+    }
+
+    /// Returns the number of queued messages.
+    pub fn len(&self) -> usize {
+        self.reliable.len() + self.unreliable.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn queue(&self, reliable: bool) -> &Queue {
+        if reliable { &self.reliable } else { &self.unreliable }
+    }
+}
+
+async fn send_queued(app: Arc<VSomeipApplication>, queue: Arc<Queue>) {
+    loop {
+        let Some(msg) = queue.pop() else {
+            queue.ready.notified().await;
+            continue;
+        };
+        let result = match msg {
+            Outgoing::Response { header, return_code, payload } =>
+                app.send_response(&header, return_code, payload),
+            Outgoing::Error { header, return_code } => app.send_error(&header, return_code),
+            Outgoing::Notification { service_id, instance_id, notifier_id, payload, force } =>
+                app.notify(service_id, instance_id, notifier_id, payload, force),
+        };
+        if let Err(e) = result {
+            log::warn!("Cannot send queued message: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn notification(notifier: u16) -> Outgoing {
+        Outgoing::Notification { service_id: ServiceID(1), instance_id: InstanceID(1), notifier_id: MethodID(notifier),
+            payload: Payload::new(), force: false }
+    }
+
+    #[test]
+    fn priority_test() {
+        let queue = Queue::default();
+        queue.push(Priority::Bulk, notification(1));
+        queue.push(Priority::Notification, notification(2));
+        queue.push(Priority::Bulk, notification(3));
+        queue.push(Priority::Response, Outgoing::Error {
+            header: MessageHeader::new(ServiceID(1), InstanceID(1), MethodID(4)), return_code: ReturnCode::NotOk });
+        assert_eq!(queue.len(), 4);
+
+        let order: Vec<u16> = std::iter::from_fn(|| queue.pop()).map(|msg| match msg {
+            Outgoing::Notification { notifier_id, .. } => notifier_id.id(),
+            Outgoing::Error { header, .. } | Outgoing::Response { header, .. } => header.method_id.id(),
+        }).collect();
+        assert_eq!(order, vec![4, 2, 1, 3]);
+    }
+}
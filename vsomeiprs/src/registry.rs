@@ -0,0 +1,223 @@
+// SPDX-License-Identifier: MPL-2.0
+//
+// Copyright (C) 2024 Alexander Seifarth
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::{broadcast, watch};
+use tokio::time::timeout;
+use super::{Availability, MissingServices, RequestedServiceGuard, ServiceHandle, VSomeipApplication, WaitError,
+            ANY_INSTANCE};
+
+/// Requests a set of service instances and tracks their availability, for applications depending
+/// on many services.
+///
+/// The registry answers whether a service is up, waits for one or all of them and reports the
+/// availability changes of its services, without consuming the application's channel. The
+/// services are released when they are removed or the registry is dropped.
+/// ```rust,no_run
+/// use std::time::Duration;
+/// use vsomeiprs::{InstanceID, InterfaceVersion, ServiceID, ServiceRegistry, VSomeipApplication};
+///
+/// async fn run(app: &VSomeipApplication) {
+///     let version = InterfaceVersion::make_major(1);
+///     let mut registry = ServiceRegistry::new(app)
+///         .with_service((ServiceID(0x1234), InstanceID(1), version))
+///         .with_service((ServiceID(0x1235), InstanceID(1), version));
+///     if let Err(missing) = registry.wait_all(Duration::from_secs(5)).await {
+///         println!("{}", missing);
+///     }
+///     while let Some(change) = registry.changed().await {
+///         println!("{}.{} available: {}", change.service_id, change.instance_id, change.avail);
+///     }
+/// }
+/// ```
+pub struct ServiceRegistry<'a> {
+    app: &'a VSomeipApplication,
+    services: BTreeMap<ServiceHandle, Tracked<'a>>,
+    changes: broadcast::Receiver<Availability>,
+}
+
+struct Tracked<'a> {
+    request: RequestedServiceGuard<'a>,
+    available: watch::Receiver<bool>,
+}
+
+impl<'a> ServiceRegistry<'a> {
+    pub fn new(app: &'a VSomeipApplication) -> Self {
+        ServiceRegistry { app, services: BTreeMap::new(), changes: app.subscribe_availability() }
+    }
+
+    /// Adds the service instance, see [ServiceRegistry::add()].
+    pub fn with_service(mut self, service: impl Into<ServiceHandle>) -> Self {
+        self.add(service);
+        self
+    }
+
+    /// Requests the service instance and tracks its availability. Does nothing if the service
+    /// is tracked already.
+    pub fn add(&mut self, service: impl Into<ServiceHandle>) {
+        let service = service.into();
+        if !self.services.contains_key(&service) {
+            let available = self.app.context.availability.watch(service.service_id, service.instance_id);
+            let request = self.app.request_service_scoped(service);
+            self.services.insert(service, Tracked { request, available });
+        }
+    }
+
+    /// Releases the service instance, returns `false` if it is not tracked.
+    pub fn remove(&mut self, service: impl Into<ServiceHandle>) -> bool {
+        self.services.remove(&service.into()).is_some()
+    }
+
+    pub fn contains(&self, service: impl Into<ServiceHandle>) -> bool {
+        self.services.contains_key(&service.into())
+    }
+
+    /// Returns the tracked service instances, sorted by id.
+    pub fn services(&self) -> impl Iterator<Item = ServiceHandle> + '_ {
+        self.services.keys().copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.services.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.services.is_empty()
+    }
+
+    /// Returns whether the service instance is available, `false` if it is not tracked.
+    pub fn is_up(&self, service: impl Into<ServiceHandle>) -> bool {
+        self.services.get(&service.into()).is_some_and(|tracked| *tracked.available.borrow())
+    }
+
+    /// Returns whether all tracked service instances are available.
+    pub fn all_up(&self) -> bool {
+        self.services.values().all(|tracked| *tracked.available.borrow())
+    }
+
+    /// Returns the tracked service instances that are not available, sorted by id.
+    pub fn missing(&self) -> Vec<ServiceHandle> {
+        self.services.iter()
+            .filter(|(_, tracked)| !*tracked.available.borrow())
+            .map(|(service, _)| *service)
+            .collect()
+    }
+
+    /// Returns a watch of the availability of the service instance, `None` if it is not tracked.
+    pub fn watch(&self, service: impl Into<ServiceHandle>) -> Option<watch::Receiver<bool>> {
+        self.services.get(&service.into()).map(|tracked| tracked.available.clone())
+    }
+
+    /// Waits until the service instance is available, it is tracked if it is not yet. Resolves
+    /// immediately if it is available already.
+    pub async fn wait_up(&mut self, service: impl Into<ServiceHandle>, timeout_time: Duration)
+        -> Result<(), WaitError>
+    {
+        let service = service.into();
+        self.add(service);
+        let mut available = self.services[&service].available.clone();
+        let result = timeout(timeout_time, async { available.wait_for(|avail| *avail).await.is_ok() }).await;
+        match result {
+            Ok(true) => Ok(()),
+            // the watch is closed only when the application is dropped
+            Ok(false) | Err(_) => Err(WaitError::Timeout),
+        }
+    }
+
+    /// Waits until all tracked service instances are available at the same time. Fails with the
+    /// missing ones if they are not available within `timeout_time`.
+    pub async fn wait_all(&self, timeout_time: Duration) -> Result<(), MissingServices> {
+        let mut watches: Vec<_> = self.services.values().map(|tracked| tracked.available.clone()).collect();
+        let _ = timeout(timeout_time, all_up(&mut watches)).await;
+        match self.missing() {
+            missing if missing.is_empty() => Ok(()),
+            missing => Err(MissingServices(missing.iter().map(|s| (s.service_id, s.instance_id)).collect())),
+        }
+    }
+
+    /// Drops the registry without releasing its services, they stay requested until
+    /// [VSomeipApplication::release_service()].
+    pub fn release(self) {
+        self.services.into_values().for_each(|tracked| tracked.request.release());
+    }
+
+    /// Returns the next availability change of a tracked service instance, `None` when the
+    /// application is shut down. Only changes reported after the registry was created are
+    /// returned.
+    pub async fn changed(&mut self) -> Option<Availability> {
+        loop {
+            match self.changes.recv().await {
+                Ok(availability) if tracks(self.services.keys(), &availability) => return Some(availability),
+                Ok(_) => {}
+                Err(RecvError::Lagged(lost)) =>
+                    log::warn!("Cannot report {} availability changes, the registry lagged behind", lost),
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    }
+}
+
+/// Waits until all watches are `true` at the same time, returns early if one of them is closed.
+async fn all_up(watches: &mut [watch::Receiver<bool>]) {
+    // a service may become unavailable again while waiting for the others
+    while !watches.iter().all(|available| *available.borrow()) {
+        for available in watches.iter_mut() {
+            if available.wait_for(|avail| *avail).await.is_err() {
+                return;
+            }
+        }
+    }
+}
+
+/// Returns whether the availability is of one of the services, [ANY_INSTANCE] matching all
+/// instances of the service.
+fn tracks<'s>(mut services: impl Iterator<Item = &'s ServiceHandle>, availability: &Availability) -> bool {
+    services.any(|service| service.service_id == availability.service_id
+        && (service.instance_id == availability.instance_id || service.instance_id == ANY_INSTANCE))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::availability::AvailabilityWatches;
+    use crate::{InstanceID, InterfaceVersion, ServiceID};
+
+    #[test]
+    fn tracks_test() {
+        let version = InterfaceVersion::make_major(1);
+        let services = [ServiceHandle::new(ServiceID(1), InstanceID(1), version),
+                        ServiceHandle::new(ServiceID(2), ANY_INSTANCE, version)];
+        let availability = |service, instance| Availability { service_id: ServiceID(service),
+            instance_id: InstanceID(instance), avail: true, version: InterfaceVersion::make_any() };
+        assert!(tracks(services.iter(), &availability(1, 1)));
+        assert!(!tracks(services.iter(), &availability(1, 2)));
+        assert!(tracks(services.iter(), &availability(2, 7)));
+        assert!(!tracks(services.iter(), &availability(3, 1)));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn all_up_test() {
+        let availability = |service, avail| Availability { service_id: ServiceID(service),
+            instance_id: InstanceID(1), avail, version: InterfaceVersion::make_any() };
+        let watches = AvailabilityWatches::default();
+        let mut up = vec![watches.watch(ServiceID(1), InstanceID(1)), watches.watch(ServiceID(2), ANY_INSTANCE)];
+        watches.update(availability(1, true));
+        assert!(timeout(Duration::from_millis(10), all_up(&mut up)).await.is_err());
+
+        // the first service is lost again while waiting for the second
+        let waiting = tokio::spawn(async move { all_up(&mut up).await });
+        tokio::task::yield_now().await;
+        watches.update(availability(1, false));
+        watches.update(availability(2, true));
+        tokio::task::yield_now().await;
+        assert!(!waiting.is_finished());
+        watches.update(availability(1, true));
+        assert!(timeout(Duration::from_millis(10), waiting).await.is_ok());
+    }
+}
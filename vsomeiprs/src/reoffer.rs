@@ -0,0 +1,108 @@
+// SPDX-License-Identifier: MPL-2.0
+//
+// Copyright (C) 2024 Alexander Seifarth
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use super::{EventDescriptor, InstanceID, MethodID, ServiceHandle, ServiceID};
+
+/// Services and events offered by an application, offered again when the application registers
+/// at the routing manager after it lost the registration, see
+/// [crate::VSomeipApplication::set_reoffer_on_registration()].
+#[derive(Default)]
+pub(crate) struct OfferReplay {
+    enabled: AtomicBool,
+    lost: AtomicBool,
+    offers: Mutex<Offers>,
+}
+
+/// Offers to replay, events before services.
+#[derive(Default, Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Offers {
+    pub(crate) events: BTreeMap<(ServiceID, InstanceID, MethodID), EventDescriptor>,
+    pub(crate) services: BTreeSet<ServiceHandle>,
+}
+
+impl OfferReplay {
+    pub(crate) fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+    }
+
+    pub(crate) fn offer_service(&self, service: ServiceHandle) {
+        self.offers().services.insert(service);
+    }
+
+    pub(crate) fn stop_offer_service(&self, service: ServiceHandle) {
+        self.offers().services.remove(&service);
+    }
+
+    pub(crate) fn offer_event(&self, service_id: ServiceID, instance_id: InstanceID, event: EventDescriptor) {
+        self.offers().events.insert((service_id, instance_id, event.notifier_id), event);
+    }
+
+    pub(crate) fn stop_offer_event(&self, service_id: ServiceID, instance_id: InstanceID, notifier_id: MethodID) {
+        self.offers().events.remove(&(service_id, instance_id, notifier_id));
+    }
+
+    pub(crate) fn is_event_offered(&self, service_id: ServiceID, instance_id: InstanceID, notifier_id: MethodID)
+        -> bool
+    {
+        self.offers().events.contains_key(&(service_id, instance_id, notifier_id))
+    }
+
+    /// Tracks the registration state, returns the offers to replay if the application registered
+    /// again after it lost the registration and replaying is enabled.
+    pub(crate) fn on_registration(&self, registered: bool) -> Option<Offers> {
+        if !registered {
+            self.lost.store(true, Ordering::Relaxed);
+            return None;
+        }
+        match self.lost.swap(false, Ordering::Relaxed) && self.enabled.load(Ordering::Relaxed) {
+            true => Some(self.offers().clone()),
+            false => None,
+        }
+    }
+
+    fn offers(&self) -> std::sync::MutexGuard<'_, Offers> {
+        self.offers.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{EventGroupID, InterfaceVersion};
+
+    #[test]
+    fn replay_test() {
+        let replay = OfferReplay::default();
+        let service = ServiceHandle::new(ServiceID(1), InstanceID(1), InterfaceVersion::make_version(1, 0));
+        let event = EventDescriptor::new(MethodID(0x8001), vec![EventGroupID(1)]);
+        replay.offer_event(ServiceID(1), InstanceID(1), event.clone());
+        replay.offer_event(ServiceID(1), InstanceID(1), EventDescriptor::new(MethodID(0x8002), vec![]));
+        replay.offer_service(service);
+        replay.stop_offer_event(ServiceID(1), InstanceID(1), MethodID(0x8002));
+        assert!(replay.is_event_offered(ServiceID(1), InstanceID(1), MethodID(0x8001)));
+        assert!(!replay.is_event_offered(ServiceID(1), InstanceID(1), MethodID(0x8002)));
+
+        // the first registration and re-registrations while disabled are not replayed
+        assert_eq!(replay.on_registration(true), None);
+        assert_eq!(replay.on_registration(false), None);
+        assert_eq!(replay.on_registration(true), None);
+
+        replay.set_enabled(true);
+        assert_eq!(replay.on_registration(true), None);
+        assert_eq!(replay.on_registration(false), None);
+        let offers = replay.on_registration(true).unwrap();
+        assert_eq!(offers.events.into_values().collect::<Vec<_>>(), [event]);
+        assert_eq!(offers.services.into_iter().collect::<Vec<_>>(), [service]);
+
+        replay.stop_offer_service(service);
+        replay.on_registration(false);
+        assert!(replay.on_registration(true).unwrap().services.is_empty());
+    }
+}
@@ -0,0 +1,136 @@
+// SPDX-License-Identifier: MPL-2.0
+//
+// Copyright (C) 2024 Alexander Seifarth
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use super::{MessageHeader, PendingResponse, RequestKey, ReturnCode, SendError, VSomeipPayload};
+
+/// Response to a request sent with [crate::VSomeipApplication::send_request_await()].
+#[derive(Debug)]
+pub struct Response {
+    pub header: MessageHeader,
+    pub data: VSomeipPayload,
+}
+
+/// Error of a request sent with [crate::VSomeipApplication::send_request_await()].
+#[derive(Debug)]
+pub enum RequestError {
+    /// The request could not be sent.
+    Send(SendError),
+    /// The request was dropped by an interceptor.
+    Dropped,
+    /// The provider answered with an error message.
+    Error { header: MessageHeader, return_code: ReturnCode, data: VSomeipPayload },
+    /// The response did not arrive in time.
+    Timeout,
+    /// The application was dropped before the response arrived.
+    Closed,
+}
+
+impl fmt::Display for RequestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RequestError::Send(e) => write!(f, "{}", e),
+            RequestError::Dropped => write!(f, "request dropped by an interceptor"),
+            RequestError::Error { return_code, .. } => write!(f, "error response: {:?}", return_code),
+            RequestError::Timeout => write!(f, "timeout waiting for the response"),
+            RequestError::Closed => write!(f, "application closed before the response arrived"),
+        }
+    }
+}
+
+impl std::error::Error for RequestError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            RequestError::Send(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<SendError> for RequestError {
+    fn from(e: SendError) -> Self {
+        RequestError::Send(e)
+    }
+}
+
+/// Timeout and retransmission of a request sent with
+/// [crate::VSomeipApplication::send_request_with()].
+#[derive(Eq, PartialEq, Debug, Clone, Copy)]
+pub struct RequestOptions {
+    /// Time to wait for the response to each transmission.
+    pub timeout: Duration,
+    /// Number of retransmissions after a timeout before failing with [RequestError::Timeout].
+    pub retries: u32,
+    /// Delay before the first retransmission, doubled for each further one.
+    pub backoff: Duration,
+    /// Sends the request over the reliable (TCP) endpoint.
+    pub reliable: bool,
+}
+
+impl Default for RequestOptions {
+    /// Returns options with a timeout of 5 seconds, no retransmission and unreliable transport.
+    fn default() -> Self {
+        RequestOptions { timeout: Duration::from_secs(5), retries: 0, backoff: Duration::from_millis(100),
+            reliable: false }
+    }
+}
+
+/// Future of the response to a request sent with [crate::VSomeipApplication::send_request_await()].
+///
+/// Dropping the handle stops waiting like [RequestHandle::cancel()] without suppression.
+#[must_use = "the response is discarded if the handle is dropped"]
+pub struct RequestHandle {
+    state: HandleState,
+}
+
+enum HandleState {
+    Pending(PendingResponse),
+    /// The request was not sent, the error is taken when polled.
+    Failed(Option<RequestError>),
+}
+
+impl RequestHandle {
+    pub(crate) fn new(result: Result<PendingResponse, RequestError>) -> Self {
+        let state = match result {
+            Ok(pending) => HandleState::Pending(pending),
+            Err(e) => HandleState::Failed(Some(e)),
+        };
+        RequestHandle { state }
+    }
+
+    /// Returns the key of the request, `None` if it was not sent.
+    pub fn key(&self) -> Option<RequestKey> {
+        match &self.state {
+            HandleState::Pending(pending) => Some(pending.key()),
+            HandleState::Failed(_) => None,
+        }
+    }
+
+    /// Stops waiting for the response, e.g. when the caller's own deadline expired. With
+    /// `suppress_late` a response arriving later is discarded, otherwise it is delivered into the
+    /// application's channel like an unrequested one.
+    pub fn cancel(self, suppress_late: bool) {
+        if let HandleState::Pending(pending) = self.state {
+            pending.cancel(suppress_late);
+        }
+    }
+}
+
+impl Future for RequestHandle {
+    type Output = Result<Response, RequestError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match &mut self.state {
+            HandleState::Pending(pending) => Pin::new(pending).poll(cx),
+            HandleState::Failed(e) => Poll::Ready(Err(e.take().unwrap_or(RequestError::Closed))),
+        }
+    }
+}
@@ -0,0 +1,40 @@
+// SPDX-License-Identifier: MPL-2.0
+//
+// Copyright (C) 2024 Alexander Seifarth
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use super::{ServiceHandle, VSomeipApplication};
+
+/// Request of a service instance, returned by [VSomeipApplication::request_service_scoped()].
+///
+/// Dropping the guard releases the service instance, so short-lived consumer tasks do not leave
+/// stale requests behind. [RequestedServiceGuard::release()] keeps the service requested.
+#[must_use = "dropping the guard releases the service instance"]
+pub struct RequestedServiceGuard<'a> {
+    app: &'a VSomeipApplication,
+    service: ServiceHandle,
+}
+
+impl<'a> RequestedServiceGuard<'a> {
+    pub(crate) fn new(app: &'a VSomeipApplication, service: ServiceHandle) -> Self {
+        RequestedServiceGuard { app, service }
+    }
+
+    pub fn service(&self) -> ServiceHandle {
+        self.service
+    }
+
+    /// Drops the guard without releasing the service, it stays requested until
+    /// [VSomeipApplication::release_service()].
+    pub fn release(self) {
+        std::mem::forget(self);
+    }
+}
+
+impl Drop for RequestedServiceGuard<'_> {
+    fn drop(&mut self) {
+        self.app.release_service(self.service);
+    }
+}
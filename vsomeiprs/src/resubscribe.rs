@@ -0,0 +1,155 @@
+// SPDX-License-Identifier: MPL-2.0
+//
+// Copyright (C) 2024 Alexander Seifarth
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::collections::{BTreeMap, HashSet};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use super::{Availability, EventGroupID, EventKind, InstanceID, MajorVersion, MethodID, Reliability, ServiceID,
+            ANY_INSTANCE};
+
+/// Events requested and event groups subscribed by an application, requested and subscribed again
+/// when their service instance becomes available after it was unavailable, see
+/// [crate::VSomeipApplication::set_resubscribe_on_availability()].
+#[derive(Default)]
+pub(crate) struct SubscriptionReplay {
+    enabled: AtomicBool,
+    state: Mutex<State>,
+}
+
+#[derive(Default)]
+struct State {
+    /// Service instances reported unavailable since they were last available.
+    lost: HashSet<(ServiceID, InstanceID)>,
+    subscriptions: Subscriptions,
+}
+
+/// Requests and subscriptions to replay, events before event groups.
+#[derive(Default, Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Subscriptions {
+    pub(crate) events: BTreeMap<(ServiceID, InstanceID, MethodID), RequestedEvent>,
+    pub(crate) event_groups: BTreeMap<(ServiceID, InstanceID, EventGroupID, MethodID), MajorVersion>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct RequestedEvent {
+    pub(crate) event_groups: Vec<EventGroupID>,
+    pub(crate) kind: EventKind,
+    pub(crate) reliability: Reliability,
+}
+
+impl SubscriptionReplay {
+    pub(crate) fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+    }
+
+    pub(crate) fn request_event(&self, service_id: ServiceID, instance_id: InstanceID, notifier_id: MethodID,
+                                event: RequestedEvent)
+    {
+        self.state().subscriptions.events.insert((service_id, instance_id, notifier_id), event);
+    }
+
+    pub(crate) fn release_event(&self, service_id: ServiceID, instance_id: InstanceID, notifier_id: MethodID) {
+        self.state().subscriptions.events.remove(&(service_id, instance_id, notifier_id));
+    }
+
+    pub(crate) fn subscribe(&self, service_id: ServiceID, instance_id: InstanceID, event_group_id: EventGroupID,
+                            notifier_id: MethodID, major: MajorVersion)
+    {
+        self.state().subscriptions.event_groups.insert((service_id, instance_id, event_group_id, notifier_id), major);
+    }
+
+    /// Removes the subscription of the event to the event group, returns whether other events of
+    /// the event group are still subscribed.
+    pub(crate) fn unsubscribe_event(&self, service_id: ServiceID, instance_id: InstanceID,
+                                    event_group_id: EventGroupID, notifier_id: MethodID) -> bool
+    {
+        let event_groups = &mut self.state().subscriptions.event_groups;
+        event_groups.remove(&(service_id, instance_id, event_group_id, notifier_id));
+        event_groups.keys().any(|(s, i, eg, _)| (*s, *i, *eg) == (service_id, instance_id, event_group_id))
+    }
+
+    /// Removes the subscriptions of all events of the event group.
+    pub(crate) fn unsubscribe(&self, service_id: ServiceID, instance_id: InstanceID, event_group_id: EventGroupID) {
+        self.state().subscriptions.event_groups
+            .retain(|(s, i, eg, _), _| (*s, *i, *eg) != (service_id, instance_id, event_group_id));
+    }
+
+    /// Tracks the availability of the service instances, returns the requests and subscriptions
+    /// of the instance to replay if it became available after it was unavailable and replaying
+    /// is enabled. Those for [ANY_INSTANCE] are replayed for each instance.
+    pub(crate) fn on_availability(&self, availability: &Availability) -> Option<Subscriptions> {
+        let mut state = self.state();
+        let instance = (availability.service_id, availability.instance_id);
+        if !availability.avail {
+            state.lost.insert(instance);
+            return None;
+        }
+        if !state.lost.remove(&instance) || !self.enabled.load(Ordering::Relaxed) {
+            return None;
+        }
+        let matches = |service_id: ServiceID, instance_id: InstanceID| service_id == availability.service_id
+            && (instance_id == availability.instance_id || instance_id == ANY_INSTANCE);
+        let subscriptions = &state.subscriptions;
+        Some(Subscriptions {
+            events: subscriptions.events.iter()
+                .filter(|((s, i, _), _)| matches(*s, *i))
+                .map(|(key, event)| (*key, event.clone()))
+                .collect(),
+            event_groups: subscriptions.event_groups.iter()
+                .filter(|((s, i, _, _), _)| matches(*s, *i))
+                .map(|(key, major)| (*key, *major))
+                .collect(),
+        })
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::InterfaceVersion;
+
+    #[test]
+    fn replay_test() {
+        let replay = SubscriptionReplay::default();
+        let event = RequestedEvent { event_groups: vec![EventGroupID(1)], kind: EventKind::Field,
+            reliability: Reliability::Unknown };
+        replay.request_event(ServiceID(1), InstanceID(1), MethodID(0x8001), event.clone());
+        replay.request_event(ServiceID(2), InstanceID(1), MethodID(0x8001), event.clone());
+        replay.subscribe(ServiceID(1), InstanceID(1), EventGroupID(1), MethodID(0x8001), MajorVersion(1));
+        replay.subscribe(ServiceID(1), InstanceID(1), EventGroupID(2), MethodID(0x8002), MajorVersion(1));
+        replay.unsubscribe(ServiceID(1), InstanceID(1), EventGroupID(2));
+        // the other events of the event group stay subscribed
+        replay.subscribe(ServiceID(1), InstanceID(1), EventGroupID(1), MethodID(0x8003), MajorVersion(1));
+        assert!(replay.unsubscribe_event(ServiceID(1), InstanceID(1), EventGroupID(1), MethodID(0x8003)));
+        replay.subscribe(ServiceID(1), InstanceID(1), EventGroupID(3), MethodID(0x8004), MajorVersion(1));
+        assert!(!replay.unsubscribe_event(ServiceID(1), InstanceID(1), EventGroupID(3), MethodID(0x8004)));
+        let availability = |service, avail| Availability { service_id: ServiceID(service),
+            instance_id: InstanceID(1), avail, version: InterfaceVersion::make_version(1, 0) };
+
+        // the first availability and cycles while disabled are not replayed
+        assert_eq!(replay.on_availability(&availability(1, true)), None);
+        assert_eq!(replay.on_availability(&availability(1, false)), None);
+        assert_eq!(replay.on_availability(&availability(1, true)), None);
+
+        replay.set_enabled(true);
+        assert_eq!(replay.on_availability(&availability(1, false)), None);
+        let subscriptions = replay.on_availability(&availability(1, true)).unwrap();
+        assert_eq!(subscriptions.events.into_iter().collect::<Vec<_>>(),
+                   [((ServiceID(1), InstanceID(1), MethodID(0x8001)), event)]);
+        assert_eq!(subscriptions.event_groups.into_iter().collect::<Vec<_>>(),
+                   [((ServiceID(1), InstanceID(1), EventGroupID(1), MethodID(0x8001)), MajorVersion(1))]);
+        assert_eq!(replay.on_availability(&availability(1, true)), None);
+
+        replay.release_event(ServiceID(1), InstanceID(1), MethodID(0x8001));
+        replay.on_availability(&availability(1, false));
+        assert!(replay.on_availability(&availability(1, true)).unwrap().events.is_empty());
+    }
+}
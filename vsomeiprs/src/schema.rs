@@ -0,0 +1,376 @@
+// SPDX-License-Identifier: MPL-2.0
+//
+// Copyright (C) 2024 Alexander Seifarth
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Decoding of payloads by schemas given at runtime, e.g. for monitoring tools printing the
+//! traffic of service interfaces they were not compiled against.
+//!
+//! A [Schema] describes the wire format of a payload like the types implementing
+//! [SomeipDeserialize](crate::codec::SomeipDeserialize) and decodes it into a [Value], which
+//! prints as `Trip { name: "home", distance_km: 12 }` or indented by `{:#}`. The
+//! [SchemaRegistry] maps the methods and events of service interfaces to the schemas of their
+//! payloads and decodes received messages:
+//! ```rust
+//! use vsomeiprs::schema::{PayloadSchema, Schema, SchemaRegistry};
+//! use vsomeiprs::{MethodID, ServiceID};
+//!
+//! let mut registry = SchemaRegistry::new();
+//! registry.insert(ServiceID(0x1234), MethodID(0x8001), PayloadSchema::event("Speedometer.SpeedChanged", Schema::U16));
+//! let schema = registry.get(ServiceID(0x1234), MethodID(0x8001)).unwrap();
+//! assert_eq!(schema.request.decode(&[0, 42], registry.profile()).unwrap().to_string(), "42");
+//! ```
+//!
+//! With the `serde` feature the registry deserializes from the JSON or YAML descriptions of
+//! `vsomeiprs-codegen`, so the schemas of a tool are loaded from the descriptions the services
+//! were generated from:
+//! ```rust,ignore
+//! let registry: SchemaRegistry = serde_json::from_str(&std::fs::read_to_string("vehicle.json")?)?;
+//! ```
+//! The payload of a method is the sequence of its arguments, a struct with the argument names
+//! as members, or the argument's value if it has one. The getter of a field has an empty request
+//! and the field value as response, the setter and notifier the field value.
+
+use std::collections::HashMap;
+use std::fmt;
+use crate::codec::{CodecError, Decoder, DeploymentProfile, SomeipDeserialize};
+use crate::{MessageType, MethodID, ServiceID};
+
+#[cfg(feature = "serde")]
+mod description;
+
+/// Wire format of a payload or a part of it.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Schema {
+    /// Empty payload, e.g. of a method without arguments.
+    Unit,
+    Bool,
+    U8,
+    U16,
+    U32,
+    U64,
+    I8,
+    I16,
+    I32,
+    I64,
+    F32,
+    F64,
+    /// String with the string deployment and length field of the profile.
+    String,
+    /// Dynamic array with the length field of the profile.
+    Vector(Box<Schema>),
+    /// Fixed size array without length field.
+    Array(Box<Schema>, usize),
+    /// Struct with the struct length field and member alignment of the profile.
+    Struct { name: String, members: Vec<(String, Schema)> },
+    /// Arguments of a method, written like a struct without length field.
+    Arguments(Vec<(String, Schema)>),
+    /// Enumeration written as its integer base type.
+    Enum { name: String, base: Box<Schema>, enumerators: Vec<(String, u64)> },
+}
+
+impl Schema {
+    /// Decodes a payload with the deployment profile. Data following the value is ignored, e.g.
+    /// members appended by a newer interface version.
+    pub fn decode(&self, data: &[u8], profile: &DeploymentProfile) -> Result<Value, CodecError> {
+        self.decode_from(&mut Decoder::with_profile(data, *profile))
+    }
+
+    /// Decodes the value at the position of the decoder.
+    pub fn decode_from(&self, decoder: &mut Decoder<'_>) -> Result<Value, CodecError> {
+        Ok(match self {
+            Schema::Unit => Value::Unit,
+            Schema::Bool => Value::Bool(bool::deserialize(decoder)?),
+            Schema::U8 => Value::UInt(u8::deserialize(decoder)?.into()),
+            Schema::U16 => Value::UInt(u16::deserialize(decoder)?.into()),
+            Schema::U32 => Value::UInt(u32::deserialize(decoder)?.into()),
+            Schema::U64 => Value::UInt(u64::deserialize(decoder)?),
+            Schema::I8 => Value::Int(i8::deserialize(decoder)?.into()),
+            Schema::I16 => Value::Int(i16::deserialize(decoder)?.into()),
+            Schema::I32 => Value::Int(i32::deserialize(decoder)?.into()),
+            Schema::I64 => Value::Int(i64::deserialize(decoder)?),
+            Schema::F32 => Value::Float(f32::deserialize(decoder)?.into()),
+            Schema::F64 => Value::Float(f64::deserialize(decoder)?),
+            Schema::String => Value::String(String::deserialize(decoder)?),
+            Schema::Vector(item) => decoder.with_length(decoder.profile().array_length, |decoder| {
+                let mut items = Vec::new();
+                while !decoder.is_empty() {
+                    let remaining = decoder.remaining();
+                    items.push(item.decode_from(decoder)?);
+                    // e.g. an element schema of an empty struct without length field
+                    if decoder.remaining() == remaining {
+                        return Err(CodecError::Unsupported("array of zero-sized elements"));
+                    }
+                }
+                Ok(Value::List(items))
+            })?,
+            Schema::Array(item, len) => Value::List((0..*len).map(|_| item.decode_from(decoder))
+                .collect::<Result<_, _>>()?),
+            Schema::Struct { name, members } => {
+                let members = decoder.with_length(decoder.profile().struct_length, |decoder| {
+                    Self::decode_members(members, decoder)
+                })?;
+                Value::Struct { name: Some(name.clone()), members }
+            }
+            Schema::Arguments(members) =>
+                Value::Struct { name: None, members: Self::decode_members(members, decoder)? },
+            Schema::Enum { base, enumerators, .. } => {
+                let value = match base.decode_from(decoder)? {
+                    Value::UInt(value) => value,
+                    Value::Int(value) => value as u64,
+                    _ => return Err(CodecError::Unsupported("enumeration with non-integer base type")),
+                };
+                let name = enumerators.iter().find(|(_, enumerator)| *enumerator == value)
+                    .map(|(name, _)| name.clone());
+                Value::Enumerator { name, value }
+            }
+        })
+    }
+
+    fn decode_members(members: &[(String, Schema)], decoder: &mut Decoder<'_>)
+        -> Result<Vec<(String, Value)>, CodecError>
+    {
+        members.iter()
+            .map(|(name, schema)| {
+                decoder.align(decoder.profile().alignment)?;
+                Ok((name.clone(), schema.decode_from(decoder)?))
+            })
+            .collect()
+    }
+}
+
+/// Value of a payload decoded by a [Schema].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Unit,
+    Bool(bool),
+    UInt(u64),
+    Int(i64),
+    Float(f64),
+    String(String),
+    List(Vec<Value>),
+    /// Struct or, without name, method arguments.
+    Struct { name: Option<String>, members: Vec<(String, Value)> },
+    /// Value of an enumeration, without name if it is no enumerator of the schema.
+    Enumerator { name: Option<String>, value: u64 },
+}
+
+impl Value {
+    fn write(&self, f: &mut fmt::Formatter<'_>, indent: usize) -> fmt::Result {
+        // with `{:#}` each item and member on a line of its own
+        let (separator, inner, outer) = match f.alternate() {
+            true => (",\n".to_string(), "    ".repeat(indent + 1), format!("\n{}", "    ".repeat(indent))),
+            false => (", ".to_string(), String::new(), String::new()),
+        };
+        match self {
+            Value::Unit => write!(f, "()"),
+            Value::Bool(value) => write!(f, "{}", value),
+            Value::UInt(value) => write!(f, "{}", value),
+            Value::Int(value) => write!(f, "{}", value),
+            Value::Float(value) => write!(f, "{}", value),
+            Value::String(value) => write!(f, "{:?}", value),
+            Value::List(items) if items.is_empty() => write!(f, "[]"),
+            Value::List(items) => {
+                write!(f, "[{}", if f.alternate() { "\n" } else { "" })?;
+                for (index, item) in items.iter().enumerate() {
+                    write!(f, "{}{}", if index > 0 { separator.as_str() } else { "" }, inner)?;
+                    item.write(f, indent + 1)?;
+                }
+                write!(f, "{}]", outer)
+            }
+            Value::Struct { name, members } => {
+                if let Some(name) = name {
+                    write!(f, "{} ", name)?;
+                }
+                if members.is_empty() {
+                    return write!(f, "{{}}");
+                }
+                write!(f, "{{{}", if f.alternate() { "\n" } else { " " })?;
+                for (index, (member, value)) in members.iter().enumerate() {
+                    write!(f, "{}{}{}: ", if index > 0 { separator.as_str() } else { "" }, inner, member)?;
+                    value.write(f, indent + 1)?;
+                }
+                write!(f, "{}}}", if f.alternate() { outer.as_str() } else { " " })
+            }
+            Value::Enumerator { name: Some(name), .. } => write!(f, "{}", name),
+            Value::Enumerator { name: None, value } => write!(f, "{}", value),
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write(f, 0)
+    }
+}
+
+/// Schemas of the payloads of a method or event.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PayloadSchema {
+    /// Name to print, e.g. `Speedometer.StartTrip`.
+    pub name: String,
+    /// Payload of requests and notifications.
+    pub request: Schema,
+    /// Payload of responses, `None` for events and fire-and-forget methods.
+    pub response: Option<Schema>,
+}
+
+impl PayloadSchema {
+    pub fn method(name: impl Into<String>, request: Schema, response: Schema) -> Self {
+        PayloadSchema { name: name.into(), request, response: Some(response) }
+    }
+
+    /// Schema of an event or a fire-and-forget method.
+    pub fn event(name: impl Into<String>, schema: Schema) -> Self {
+        PayloadSchema { name: name.into(), request: schema, response: None }
+    }
+}
+
+/// Schemas of the payloads of the methods and events of service interfaces, see the module
+/// documentation.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize), serde(try_from = "description::Description"))]
+pub struct SchemaRegistry {
+    payloads: HashMap<(ServiceID, MethodID), PayloadSchema>,
+    profile: DeploymentProfile,
+}
+
+impl SchemaRegistry {
+    /// Returns an empty registry decoding with the default [DeploymentProfile].
+    pub fn new() -> Self {
+        SchemaRegistry::default()
+    }
+
+    pub fn with_profile(self, profile: DeploymentProfile) -> Self {
+        SchemaRegistry { profile, ..self }
+    }
+
+    pub fn profile(&self) -> &DeploymentProfile {
+        &self.profile
+    }
+
+    /// Sets the schema of a method or event, returns the one replaced.
+    pub fn insert(&mut self, service_id: ServiceID, method_id: MethodID, schema: PayloadSchema)
+        -> Option<PayloadSchema>
+    {
+        self.payloads.insert((service_id, method_id), schema)
+    }
+
+    /// Adds the schemas of another registry, e.g. loaded from a further description.
+    pub fn extend(&mut self, other: SchemaRegistry) {
+        self.payloads.extend(other.payloads);
+    }
+
+    pub fn get(&self, service_id: ServiceID, method_id: MethodID) -> Option<&PayloadSchema> {
+        self.payloads.get(&(service_id, method_id))
+    }
+
+    pub fn len(&self) -> usize {
+        self.payloads.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.payloads.is_empty()
+    }
+
+    /// Returns the name of the method or event of the message and its decoded payload. Returns
+    /// `None` for an unknown method or event, an error message and a response without schema.
+    pub fn decode(&self, msg: &MessageType) -> Option<(&str, Result<Value, CodecError>)> {
+        let header = msg.header();
+        let payload = self.get(header.service_id, header.method_id)?;
+        let schema = match msg {
+            MessageType::Request { .. } | MessageType::RequestNoReturn { .. } | MessageType::Notification { .. } =>
+                &payload.request,
+            MessageType::Response { .. } => payload.response.as_ref()?,
+            MessageType::Error { .. } => return None,
+        };
+        Some((&payload.name, schema.decode(msg.payload().as_bytes_ref(), &self.profile)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use bytes::Bytes;
+    use crate::{InstanceID, MessageHeader, VSomeipPayload};
+    use crate::codec::LengthWidth;
+
+    fn trip() -> Schema {
+        let mode = Schema::Enum { name: "DriveMode".to_string(), base: Box::new(Schema::U8),
+                                  enumerators: vec![("ECO".to_string(), 0), ("SPORT".to_string(), 1)] };
+        Schema::Struct { name: "Trip".to_string(), members: vec![
+            ("name".to_string(), Schema::String),
+            ("speeds".to_string(), Schema::Vector(Box::new(Schema::U16))),
+            ("modes".to_string(), Schema::Array(Box::new(mode), 2)),
+        ] }
+    }
+
+    #[test]
+    fn decode_test() {
+        let data = [0, 0, 0, 2, b'a', 0, 0, 0, 0, 4, 0, 1, 0, 2, 1, 7, 0xff];
+        let profile = DeploymentProfile { string: crate::codec::StringDeployment { bom: false, ..Default::default() },
+                                          ..DeploymentProfile::default() };
+        let value = trip().decode(&data, &profile).unwrap();
+        assert_eq!(value.to_string(), "Trip { name: \"a\", speeds: [1, 2], modes: [SPORT, 7] }");
+        assert_eq!(format!("{:#}", value), "Trip {\n    name: \"a\",\n    speeds: [\n        1,\n        2\n    \
+                                            ],\n    modes: [\n        SPORT,\n        7\n    ]\n}");
+        assert!(matches!(trip().decode(&data[..8], &profile), Err(CodecError::Truncated { .. })));
+
+        let profile = DeploymentProfile { struct_length: LengthWidth::U8, alignment: 2,
+                                          ..DeploymentProfile::default() };
+        let arguments = Schema::Arguments(vec![("flag".to_string(), Schema::Bool),
+                                               ("limit".to_string(), Schema::I16),
+                                               ("point".to_string(), Schema::Struct { name: "Point".to_string(),
+                                                                                      members: vec![] })]);
+        assert_eq!(arguments.decode(&[1, 0, 0xff, 0xfe, 0], &profile).unwrap().to_string(),
+                   "{ flag: true, limit: -2, point: Point {} }");
+        assert_eq!(Schema::Unit.decode(&[], &profile), Ok(Value::Unit));
+        let units = Schema::Vector(Box::new(Schema::Arguments(vec![])));
+        assert_eq!(units.decode(&[0, 0, 0, 1, 0], &DeploymentProfile::default()),
+                   Err(CodecError::Unsupported("array of zero-sized elements")));
+    }
+
+    #[test]
+    fn registry_test() {
+        let mut registry = SchemaRegistry::new();
+        registry.insert(ServiceID(0x1234), MethodID(1), PayloadSchema::method("Speedometer.SetLimit", Schema::U16,
+                                                                              Schema::Bool));
+        registry.insert(ServiceID(0x1234), MethodID(0x8001), PayloadSchema::event("Speedometer.Speed", Schema::U8));
+        assert_eq!(registry.len(), 2);
+        let message = |method_id: u16, kind: fn(MessageHeader, VSomeipPayload) -> MessageType| {
+            let header = MessageHeader::builder().service(ServiceID(0x1234)).instance(InstanceID(1))
+                .method(MethodID(method_id)).build().unwrap();
+            kind(header, VSomeipPayload::from(Bytes::from_static(&[1, 2])))
+        };
+        let request = message(1, |header, data| MessageType::Request { header, data });
+        assert_eq!(registry.decode(&request), Some(("Speedometer.SetLimit", Ok(Value::UInt(0x102)))));
+        let response = message(1, |header, data| MessageType::Response { header, data });
+        assert_eq!(registry.decode(&response), Some(("Speedometer.SetLimit", Ok(Value::Bool(true)))));
+        let notification = message(0x8001,
+                                   |header, data| MessageType::Notification { header, is_initial: false, data });
+        assert_eq!(registry.decode(&notification), Some(("Speedometer.Speed", Ok(Value::UInt(1)))));
+        assert_eq!(registry.decode(&message(2, |header, data| MessageType::Request { header, data })), None);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn description_test() {
+        let registry: SchemaRegistry =
+            serde_json::from_str(include_str!("../../vsomeiprs-codegen/tests/vehicle.json")).unwrap();
+        // 3 methods, getter and setter, event and notifier
+        assert_eq!(registry.len(), 7);
+        let start_trip = registry.get(ServiceID(0x1234), MethodID(2)).unwrap();
+        assert_eq!(start_trip.name, "Speedometer.StartTrip");
+        assert!(matches!(&start_trip.request, Schema::Arguments(args) if args.len() == 2));
+        assert!(matches!(&start_trip.response, Some(Schema::Struct { name, .. }) if name == "Trip"));
+        let getter = registry.get(ServiceID(0x1234), MethodID(0x10)).unwrap();
+        assert_eq!(getter.request, Schema::Unit);
+        assert!(matches!(&getter.response, Some(Schema::Enum { name, .. }) if name == "DriveMode"));
+        assert_eq!(registry.get(ServiceID(0x1234), MethodID(3)).unwrap().response, None);
+        let error = serde_json::from_str::<SchemaRegistry>("{ \"types\": [ { \"name\": \"A\", \"type\": \"B\" } ] }")
+            .unwrap_err();
+        assert!(error.to_string().contains("unresolved type B"));
+    }
+}
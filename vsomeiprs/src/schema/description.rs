@@ -0,0 +1,224 @@
+// SPDX-License-Identifier: MPL-2.0
+//
+// Copyright (C) 2024 Alexander Seifarth
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use serde::Deserialize;
+use super::{PayloadSchema, Schema, SchemaRegistry};
+use crate::{MethodID, ServiceID};
+
+/// JSON or YAML description of `vsomeiprs-codegen`, elements not needed for decoding like the
+/// version and event groups are ignored.
+#[derive(Deserialize)]
+pub(super) struct Description {
+    #[serde(default)]
+    types: Vec<TypeDescription>,
+    #[serde(default)]
+    interfaces: Vec<InterfaceDescription>,
+}
+
+#[derive(Deserialize)]
+struct TypeDescription {
+    name: String,
+    #[serde(rename = "type")]
+    alias: Option<String>,
+    #[serde(rename = "struct")]
+    members: Option<Vec<Typed>>,
+    #[serde(rename = "enum")]
+    base: Option<String>,
+    #[serde(default)]
+    enumerators: Vec<EnumeratorDescription>,
+}
+
+#[derive(Deserialize)]
+struct Typed {
+    name: String,
+    #[serde(rename = "type")]
+    type_name: String,
+}
+
+#[derive(Deserialize)]
+struct EnumeratorDescription {
+    name: String,
+    value: Number,
+}
+
+#[derive(Deserialize)]
+struct InterfaceDescription {
+    name: String,
+    service_id: Number,
+    #[serde(default)]
+    methods: Vec<MethodDescription>,
+    #[serde(default)]
+    events: Vec<EventDescription>,
+    #[serde(default)]
+    fields: Vec<FieldDescription>,
+}
+
+#[derive(Deserialize)]
+struct MethodDescription {
+    name: String,
+    id: Number,
+    #[serde(default, rename = "in")]
+    in_args: Vec<Typed>,
+    #[serde(default, rename = "out")]
+    out_args: Vec<Typed>,
+    #[serde(default)]
+    fire_and_forget: bool,
+}
+
+#[derive(Deserialize)]
+struct EventDescription {
+    name: String,
+    id: Number,
+    #[serde(rename = "type")]
+    type_name: String,
+}
+
+#[derive(Deserialize)]
+struct FieldDescription {
+    name: String,
+    #[serde(rename = "type")]
+    type_name: String,
+    getter: Option<Number>,
+    setter: Option<Number>,
+    notifier: Option<Number>,
+}
+
+/// ID or value, a number or a string with a decimal or `0x` prefixed hexadecimal number.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Number {
+    Integer(u64),
+    Text(String),
+}
+
+impl Number {
+    fn value<T: TryFrom<u64>>(&self) -> Result<T, String> {
+        let value = match self {
+            Number::Integer(value) => Some(*value),
+            Number::Text(text) => match text.trim().strip_prefix("0x") {
+                Some(hex) => u64::from_str_radix(hex, 16).ok(),
+                None => text.trim().parse().ok(),
+            },
+        };
+        value.and_then(|value| T::try_from(value).ok()).ok_or_else(|| match self {
+            Number::Integer(value) => format!("invalid number {}", value),
+            Number::Text(text) => format!("invalid number {}", text),
+        })
+    }
+}
+
+impl TryFrom<Description> for SchemaRegistry {
+    type Error = String;
+
+    fn try_from(description: Description) -> Result<Self, Self::Error> {
+        let schema = |name: &str| description.schema(name, 0);
+        let arguments = |args: &[Typed]| match args {
+            [] => Ok(Schema::Unit),
+            [arg] => schema(&arg.type_name),
+            _ => Ok(Schema::Arguments(args.iter()
+                .map(|arg| Ok((arg.name.clone(), schema(&arg.type_name)?)))
+                .collect::<Result<_, String>>()?)),
+        };
+        for data_type in &description.types {
+            schema(&data_type.name)?;
+        }
+        let mut registry = SchemaRegistry::new();
+        for interface in &description.interfaces {
+            let service_id = ServiceID(interface.service_id.value()?);
+            let mut insert = |id: &Number, payload| {
+                registry.insert(service_id, MethodID(id.value()?), payload);
+                Ok::<_, String>(())
+            };
+            let name = |member: &str| format!("{}.{}", interface.name, member);
+            for method in &interface.methods {
+                let request = arguments(&method.in_args)?;
+                insert(&method.id, match method.fire_and_forget {
+                    true => PayloadSchema::event(name(&method.name), request),
+                    false => PayloadSchema::method(name(&method.name), request, arguments(&method.out_args)?),
+                })?;
+            }
+            for event in &interface.events {
+                insert(&event.id, PayloadSchema::event(name(&event.name), schema(&event.type_name)?))?;
+            }
+            for field in &interface.fields {
+                let value = schema(&field.type_name)?;
+                if let Some(getter) = &field.getter {
+                    insert(getter, PayloadSchema::method(name(&format!("Get{}", field.name)), Schema::Unit,
+                                                         value.clone()))?;
+                }
+                if let Some(setter) = &field.setter {
+                    insert(setter, PayloadSchema::method(name(&format!("Set{}", field.name)), value.clone(),
+                                                         value.clone()))?;
+                }
+                if let Some(notifier) = &field.notifier {
+                    insert(notifier, PayloadSchema::event(name(&field.name), value))?;
+                }
+            }
+        }
+        Ok(registry)
+    }
+}
+
+impl Description {
+    /// Returns the schema of a type name in Rust syntax, e.g. `u16`, `Vec<Speed>` or `[u8; 4]`,
+    /// or an AUTOSAR primitive like `uint16`, resolving the types of the description.
+    fn schema(&self, name: &str, depth: usize) -> Result<Schema, String> {
+        let name = name.trim();
+        if let Some(item) = name.strip_prefix("Vec<").and_then(|rest| rest.strip_suffix('>')) {
+            return Ok(Schema::Vector(Box::new(self.schema(item, depth)?)));
+        }
+        if let Some((item, len)) = name.strip_prefix('[').and_then(|rest| rest.strip_suffix(']'))
+            .and_then(|rest| rest.rsplit_once(';'))
+        {
+            let len = len.trim().parse().map_err(|_| format!("invalid array length {}", len.trim()))?;
+            return Ok(Schema::Array(Box::new(self.schema(item, depth)?), len));
+        }
+        if let Some(primitive) = primitive(name) {
+            return Ok(primitive);
+        }
+        let data_type = self.types.iter().find(|data_type| data_type.name == name)
+            .ok_or_else(|| format!("unresolved type {}", name))?;
+        if depth > self.types.len() {
+            return Err(format!("recursive type {}", name));
+        }
+        match (&data_type.alias, &data_type.members, &data_type.base) {
+            (Some(alias), None, None) => self.schema(alias, depth + 1),
+            (None, Some(members), None) => Ok(Schema::Struct {
+                name: name.to_string(),
+                members: members.iter()
+                    .map(|member| Ok((member.name.clone(), self.schema(&member.type_name, depth + 1)?)))
+                    .collect::<Result<_, String>>()?,
+            }),
+            (None, None, Some(base)) => Ok(Schema::Enum {
+                name: name.to_string(),
+                base: Box::new(primitive(base).ok_or_else(|| format!("invalid enum base type {}", base))?),
+                enumerators: data_type.enumerators.iter()
+                    .map(|enumerator| Ok((enumerator.name.clone(), enumerator.value.value()?)))
+                    .collect::<Result<_, String>>()?,
+            }),
+            _ => Err(format!("type {} needs exactly one of type, struct and enum", name)),
+        }
+    }
+}
+
+fn primitive(name: &str) -> Option<Schema> {
+    Some(match name.to_ascii_lowercase().as_str() {
+        "bool" | "boolean" => Schema::Bool,
+        "u8" | "uint8" | "uint8_t" => Schema::U8,
+        "u16" | "uint16" | "uint16_t" => Schema::U16,
+        "u32" | "uint32" | "uint32_t" => Schema::U32,
+        "u64" | "uint64" | "uint64_t" => Schema::U64,
+        "i8" | "sint8" | "int8" | "int8_t" => Schema::I8,
+        "i16" | "sint16" | "int16" | "int16_t" => Schema::I16,
+        "i32" | "sint32" | "int32" | "int32_t" => Schema::I32,
+        "i64" | "sint64" | "int64" | "int64_t" => Schema::I64,
+        "f32" | "float" | "float32" | "float32_t" => Schema::F32,
+        "f64" | "double" | "float64" | "float64_t" => Schema::F64,
+        "string" => Schema::String,
+        _ => return None,
+    })
+}
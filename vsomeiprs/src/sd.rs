@@ -0,0 +1,451 @@
+// SPDX-License-Identifier: MPL-2.0
+//
+// Copyright (C) 2024 Alexander Seifarth
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::fmt;
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6};
+use bytes::{Buf, Bytes};
+use socket2::{Domain, Protocol, Socket, Type};
+use tokio::net::UdpSocket;
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
+use tokio::task::JoinHandle;
+use super::{EventGroupID, InstanceID, MajorVersion, MinorVersion, Reliability, ServiceID, SessionID};
+
+pub use super::consts::{SD_METHOD_ID, SD_SERVICE_ID};
+/// Default SOME/IP-SD port.
+pub const SD_DEFAULT_PORT: u16 = 30490;
+/// Default SOME/IP-SD multicast group used by vsomeip.
+pub const SD_DEFAULT_MULTICAST: Ipv4Addr = Ipv4Addr::new(224, 224, 224, 245);
+
+const SOMEIP_HEADER_SIZE: usize = 16;
+/// Flags and reserved bytes and the lengths of the entries and options arrays.
+const SD_HEADER_SIZE: usize = 12;
+const SD_ENTRY_SIZE: usize = 16;
+
+/// Type of SOME/IP-SD entry.
+/// Entries with a TTL of 0 are reported with their "stop" (or NACK) meaning.
+#[derive(Eq, PartialEq, Debug, Clone, Copy)]
+pub enum SdEntryType {
+    FindService,
+    OfferService,
+    StopOfferService,
+    SubscribeEventgroup,
+    StopSubscribeEventgroup,
+    SubscribeEventgroupAck,
+    SubscribeEventgroupNack,
+    Unknown(u8),
+}
+
+impl fmt::Display for SdEntryType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SdEntryType::FindService => write!(f, "FIND"),
+            SdEntryType::OfferService => write!(f, "OFFER"),
+            SdEntryType::StopOfferService => write!(f, "STOP_OFFER"),
+            SdEntryType::SubscribeEventgroup => write!(f, "SUBSCRIBE"),
+            SdEntryType::StopSubscribeEventgroup => write!(f, "STOP_SUBSCRIBE"),
+            SdEntryType::SubscribeEventgroupAck => write!(f, "SUBSCRIBE_ACK"),
+            SdEntryType::SubscribeEventgroupNack => write!(f, "SUBSCRIBE_NACK"),
+            SdEntryType::Unknown(t) => write!(f, "UNKNOWN({:02x})", t),
+        }
+    }
+}
+
+/// Kind of endpoint described by an endpoint option.
+#[derive(Eq, PartialEq, Debug, Clone, Copy)]
+pub enum SdEndpointKind {
+    /// Unicast endpoint of a service or client.
+    Unicast,
+    /// Multicast endpoint for event groups.
+    Multicast,
+    /// Endpoint of the SD instance itself.
+    ServiceDiscovery,
+}
+
+/// SOME/IP-SD option referenced by an entry.
+#[derive(Eq, PartialEq, Debug, Clone)]
+pub enum SdOption {
+    /// Configuration option: list of `key=value` strings.
+    Configuration(Vec<String>),
+    LoadBalancing{ priority: u16, weight: u16 },
+    /// IPv4/IPv6 endpoint; the transport is `Reliable` for TCP and `Unreliable` for UDP.
+    Endpoint{ kind: SdEndpointKind, address: SocketAddr, transport: Reliability },
+    /// Option of unknown type, kept as raw data.
+    Unknown{ option_type: u8, data: Bytes },
+}
+
+/// A single SOME/IP-SD entry together with the options it references.
+#[derive(Eq, PartialEq, Debug, Clone)]
+pub struct SdEntry {
+    pub entry_type: SdEntryType,
+    pub service_id: ServiceID,
+    pub instance_id: InstanceID,
+    pub major_version: MajorVersion,
+    /// Time-to-live in seconds (0xffffff means "until next reboot").
+    pub ttl: u32,
+    /// Minor version - only present in service entries.
+    pub minor_version: Option<MinorVersion>,
+    /// Eventgroup - only present in eventgroup entries.
+    pub eventgroup_id: Option<EventGroupID>,
+    /// Counter to distinguish parallel subscriptions (eventgroup entries only).
+    pub counter: u8,
+    pub options: Vec<SdOption>,
+}
+
+impl fmt::Display for SdEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}.{} v{}", self.entry_type, self.service_id, self.instance_id, self.major_version.id())?;
+        if let Some(eventgroup) = self.eventgroup_id {
+            write!(f, " eg {}", eventgroup)?;
+        }
+        write!(f, " ttl {}", self.ttl)
+    }
+}
+
+/// Decoded SOME/IP-SD message.
+#[derive(Eq, PartialEq, Debug, Clone)]
+pub struct SdMessage {
+    pub session_id: SessionID,
+    /// Reboot flag of the sender.
+    pub reboot: bool,
+    /// Unicast flag (sender supports receiving unicast SD messages).
+    pub unicast: bool,
+    pub entries: Vec<SdEntry>,
+}
+
+/// Reasons why a datagram is not a valid SOME/IP-SD message.
+#[derive(Eq, PartialEq, Debug, Clone, Copy)]
+pub enum SdParseError {
+    /// The datagram is shorter than the lengths it announces.
+    Truncated,
+    /// The datagram is a SOME/IP message, but not a SD message.
+    NotServiceDiscovery,
+    /// An entry references options that do not exist.
+    InvalidOptionIndex,
+}
+
+impl fmt::Display for SdParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SdParseError::Truncated => write!(f, "truncated SD message"),
+            SdParseError::NotServiceDiscovery => write!(f, "not a SD message"),
+            SdParseError::InvalidOptionIndex => write!(f, "invalid option index"),
+        }
+    }
+}
+
+impl std::error::Error for SdParseError {}
+
+impl SdMessage {
+    /// Parses a complete SOME/IP-SD message including the SOME/IP header.
+    pub fn parse(data: &[u8]) -> Result<SdMessage, SdParseError> {
+        if data.len() < SOMEIP_HEADER_SIZE + SD_HEADER_SIZE {
+            return Err(SdParseError::Truncated);
+        }
+        let mut hdr = &data[..SOMEIP_HEADER_SIZE];
+        let service = hdr.get_u16();
+        let method = hdr.get_u16();
+        let length = hdr.get_u32() as usize;
+        let _client = hdr.get_u16();
+        let session = hdr.get_u16();
+        if service != SD_SERVICE_ID.id() || method != SD_METHOD_ID.id() {
+            return Err(SdParseError::NotServiceDiscovery);
+        }
+        // the length field counts the bytes following it, 8 of them belong to the SOME/IP header
+        if length < 8 + SD_HEADER_SIZE || data.len() < length + 8 {
+            return Err(SdParseError::Truncated);
+        }
+        let mut body = &data[SOMEIP_HEADER_SIZE..length + 8];
+        let flags = body.get_u8();
+        body.advance(3);
+        let entries_len = body.get_u32() as usize;
+        if body.remaining() < entries_len + 4 {
+            return Err(SdParseError::Truncated);
+        }
+        let mut entries_data = &body[..entries_len];
+        body.advance(entries_len);
+        let options_len = body.get_u32() as usize;
+        if body.remaining() < options_len {
+            return Err(SdParseError::Truncated);
+        }
+        let options = parse_options(&body[..options_len])?;
+
+        let mut entries = Vec::with_capacity(entries_len / SD_ENTRY_SIZE);
+        while entries_data.remaining() >= SD_ENTRY_SIZE {
+            entries.push(parse_entry(&mut entries_data, &options)?);
+        }
+        Ok(SdMessage {
+            session_id: SessionID(session),
+            reboot: flags & 0x80 != 0,
+            unicast: flags & 0x40 != 0,
+            entries,
+        })
+    }
+}
+
+fn parse_entry(data: &mut &[u8], options: &[SdOption]) -> Result<SdEntry, SdParseError> {
+    let raw_type = data.get_u8();
+    let index1 = data.get_u8() as usize;
+    let index2 = data.get_u8() as usize;
+    let counts = data.get_u8();
+    let service_id = ServiceID(data.get_u16());
+    let instance_id = InstanceID(data.get_u16());
+    let major_version = MajorVersion(data.get_u8());
+    let ttl = (data.get_u16() as u32) << 8 | data.get_u8() as u32;
+    let (minor_version, eventgroup_id, counter) = match raw_type {
+        0x00 | 0x01 => (Some(MinorVersion(data.get_u32())), None, 0),
+        _ => {
+            let _reserved = data.get_u8();
+            let counter = data.get_u8() & 0x0f;
+            (None, Some(EventGroupID(data.get_u16())), counter)
+        }
+    };
+    let entry_type = match (raw_type, ttl) {
+        (0x00, _) => SdEntryType::FindService,
+        (0x01, 0) => SdEntryType::StopOfferService,
+        (0x01, _) => SdEntryType::OfferService,
+        (0x06, 0) => SdEntryType::StopSubscribeEventgroup,
+        (0x06, _) => SdEntryType::SubscribeEventgroup,
+        (0x07, 0) => SdEntryType::SubscribeEventgroupNack,
+        (0x07, _) => SdEntryType::SubscribeEventgroupAck,
+        (t, _) => SdEntryType::Unknown(t),
+    };
+    let mut entry_options = Vec::new();
+    for (index, count) in [(index1, (counts >> 4) as usize), (index2, (counts & 0x0f) as usize)] {
+        if count > 0 {
+            let run = options.get(index..index + count).ok_or(SdParseError::InvalidOptionIndex)?;
+            entry_options.extend_from_slice(run);
+        }
+    }
+    Ok(SdEntry { entry_type, service_id, instance_id, major_version, ttl, minor_version, eventgroup_id,
+        counter, options: entry_options })
+}
+
+fn parse_options(mut data: &[u8]) -> Result<Vec<SdOption>, SdParseError> {
+    let mut options = Vec::new();
+    while data.remaining() >= 3 {
+        // the length field counts the bytes following the type field
+        let length = data.get_u16() as usize;
+        let option_type = data.get_u8();
+        if length == 0 || data.remaining() < length {
+            return Err(SdParseError::Truncated);
+        }
+        let mut value = &data[1..length];
+        data.advance(length);
+        let option = match (option_type, value.len()) {
+            (0x01, _) => SdOption::Configuration(parse_configuration(value)),
+            (0x02, 4) => SdOption::LoadBalancing{ priority: value.get_u16(), weight: value.get_u16() },
+            (0x04 | 0x14 | 0x24, 8) => {
+                let address = Ipv4Addr::from(value.get_u32());
+                let _reserved = value.get_u8();
+                let transport = transport_from(value.get_u8());
+                let port = value.get_u16();
+                SdOption::Endpoint{ kind: endpoint_kind(option_type), transport,
+                    address: SocketAddr::V4(SocketAddrV4::new(address, port)) }
+            }
+            (0x06 | 0x16 | 0x26, 20) => {
+                let address = Ipv6Addr::from(value.get_u128());
+                let _reserved = value.get_u8();
+                let transport = transport_from(value.get_u8());
+                let port = value.get_u16();
+                SdOption::Endpoint{ kind: endpoint_kind(option_type), transport,
+                    address: SocketAddr::V6(SocketAddrV6::new(address, port, 0, 0)) }
+            }
+            _ => SdOption::Unknown{ option_type, data: Bytes::copy_from_slice(value) },
+        };
+        options.push(option);
+    }
+    Ok(options)
+}
+
+fn parse_configuration(mut data: &[u8]) -> Vec<String> {
+    let mut items = Vec::new();
+    while let Some((&len, rest)) = data.split_first() {
+        let len = len as usize;
+        if len == 0 || rest.len() < len {
+            break;
+        }
+        items.push(String::from_utf8_lossy(&rest[..len]).into_owned());
+        data = &rest[len..];
+    }
+    items
+}
+
+fn endpoint_kind(option_type: u8) -> SdEndpointKind {
+    match option_type & 0xf0 {
+        0x10 => SdEndpointKind::Multicast,
+        0x20 => SdEndpointKind::ServiceDiscovery,
+        _ => SdEndpointKind::Unicast,
+    }
+}
+
+fn transport_from(protocol: u8) -> Reliability {
+    match protocol {
+        0x06 => Reliability::Reliable,
+        0x11 => Reliability::Unreliable,
+        _ => Reliability::Unknown,
+    }
+}
+
+/// A SD entry observed on the network.
+#[derive(Debug, Clone)]
+pub struct SdEvent {
+    /// Sender of the SD message.
+    pub source: SocketAddr,
+    pub session_id: SessionID,
+    pub reboot: bool,
+    pub entry: SdEntry,
+}
+
+/// Settings for [SdObserver].
+#[derive(Debug, Clone, Copy)]
+pub struct SdObserverConfig {
+    /// SD multicast group to join.
+    pub multicast: Ipv4Addr,
+    /// SD port.
+    pub port: u16,
+    /// Local interface address used to join the multicast group.
+    pub interface: Ipv4Addr,
+}
+
+impl Default for SdObserverConfig {
+    fn default() -> Self {
+        SdObserverConfig { multicast: SD_DEFAULT_MULTICAST, port: SD_DEFAULT_PORT, interface: Ipv4Addr::UNSPECIFIED }
+    }
+}
+
+/// Opt-in observer of the raw SOME/IP-SD traffic on the network.
+///
+/// The observer joins the SD multicast group next to vsomeip (the SD port is shared) and delivers
+/// every entry of every received SD message - find, offer and subscribe entries with their
+/// endpoints and TTLs - as [SdEvent] into the returned receiver. SD messages sent as unicast to
+/// the local vsomeip instance are not visible to the observer.
+///
+/// The observer task runs until the [SdObserver] is dropped. It must be started from within a
+/// tokio runtime.
+pub struct SdObserver {
+    task: JoinHandle<()>,
+}
+
+impl Drop for SdObserver {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+impl SdObserver {
+    /// Starts observing SD traffic as configured.
+    pub fn start(config: SdObserverConfig) -> std::io::Result<(Self, UnboundedReceiver<SdEvent>)> {
+        let socket = Socket::new(Domain::IPV4, Type::DGRAM, Some(Protocol::UDP))?;
+        socket.set_reuse_address(true)?;
+        socket.set_reuse_port(true)?;
+        socket.set_nonblocking(true)?;
+        socket.bind(&SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, config.port)).into())?;
+        socket.join_multicast_v4(&config.multicast, &config.interface)?;
+        let socket = UdpSocket::from_std(socket.into())?;
+
+        let (sender, recv) = tokio::sync::mpsc::unbounded_channel();
+        let task = tokio::spawn(observe(socket, sender));
+        Ok((SdObserver { task }, recv))
+    }
+}
+
+async fn observe(socket: UdpSocket, sender: UnboundedSender<SdEvent>) {
+    let mut buffer = vec![0u8; 65536];
+    loop {
+        let (len, source) = match socket.recv_from(&mut buffer).await {
+            Ok(r) => r,
+            Err(e) => {
+                log::error!("SD observer stopped: {}", e);
+                return;
+            }
+        };
+        match SdMessage::parse(&buffer[..len]) {
+            Ok(msg) => {
+                for entry in msg.entries {
+                    let event = SdEvent { source, session_id: msg.session_id, reboot: msg.reboot, entry };
+                    if sender.send(event).is_err() {
+                        return;
+                    }
+                }
+            }
+            Err(e) => log::debug!("Ignoring datagram from {}: {}", source, e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn sd_message(entries: &[u8], options: &[u8]) -> Vec<u8> {
+        let mut msg = vec![0xff, 0xff, 0x81, 0x00];
+        msg.extend_from_slice(&((8 + 12 + entries.len() + options.len()) as u32).to_be_bytes());
+        msg.extend_from_slice(&[0x00, 0x00, 0x00, 0x05, 0x01, 0x01, 0x02, 0x00]);
+        msg.extend_from_slice(&[0xc0, 0x00, 0x00, 0x00]);
+        msg.extend_from_slice(&(entries.len() as u32).to_be_bytes());
+        msg.extend_from_slice(entries);
+        msg.extend_from_slice(&(options.len() as u32).to_be_bytes());
+        msg.extend_from_slice(options);
+        msg
+    }
+
+    #[test]
+    fn parse_offer_test() {
+        let entry = [0x01, 0x00, 0x00, 0x10, 0x12, 0x34, 0x00, 0x01, 0x02, 0x00, 0x00, 0x03,
+                     0x00, 0x00, 0x00, 0x05];
+        let option = [0x00, 0x09, 0x04, 0x00, 192, 168, 1, 10, 0x00, 0x11, 0x77, 0x2d];
+        let msg = SdMessage::parse(&sd_message(&entry, &option)).unwrap();
+        assert!(msg.reboot);
+        assert!(msg.unicast);
+        assert_eq!(msg.session_id, SessionID(5));
+        assert_eq!(msg.entries.len(), 1);
+        let e = &msg.entries[0];
+        assert_eq!(e.entry_type, SdEntryType::OfferService);
+        assert_eq!(e.service_id, ServiceID(0x1234));
+        assert_eq!(e.instance_id, InstanceID(1));
+        assert_eq!(e.major_version, MajorVersion(2));
+        assert_eq!(e.minor_version, Some(MinorVersion(5)));
+        assert_eq!(e.ttl, 3);
+        assert_eq!(e.options, vec![SdOption::Endpoint{ kind: SdEndpointKind::Unicast,
+            address: "192.168.1.10:30509".parse().unwrap(), transport: Reliability::Unreliable }]);
+    }
+
+    #[test]
+    fn parse_stop_subscribe_test() {
+        let entry = [0x06, 0x00, 0x00, 0x00, 0x12, 0x34, 0x00, 0x01, 0x02, 0x00, 0x00, 0x00,
+                     0x00, 0x03, 0x00, 0x08];
+        let msg = SdMessage::parse(&sd_message(&entry, &[])).unwrap();
+        let e = &msg.entries[0];
+        assert_eq!(e.entry_type, SdEntryType::StopSubscribeEventgroup);
+        assert_eq!(e.eventgroup_id, Some(EventGroupID(8)));
+        assert_eq!(e.counter, 3);
+        assert!(e.options.is_empty());
+    }
+
+    #[test]
+    fn parse_invalid_test() {
+        let entry = [0x01, 0x02, 0x00, 0x10, 0x12, 0x34, 0x00, 0x01, 0x02, 0x00, 0x00, 0x03,
+                     0x00, 0x00, 0x00, 0x05];
+        assert_eq!(SdMessage::parse(&sd_message(&entry, &[])), Err(SdParseError::InvalidOptionIndex));
+        let mut msg = sd_message(&entry, &[]);
+        msg.truncate(30);
+        assert_eq!(SdMessage::parse(&msg), Err(SdParseError::Truncated));
+        msg[1] = 0x00;
+        assert_eq!(SdMessage::parse(&msg), Err(SdParseError::NotServiceDiscovery));
+    }
+
+    #[test]
+    fn parse_truncated_body_test() {
+        // the length announces a body shorter than the SD header, the datagram is long enough
+        for length in 8..20u32 {
+            let mut msg = sd_message(&[], &[]);
+            msg[4..8].copy_from_slice(&length.to_be_bytes());
+            assert_eq!(SdMessage::parse(&msg), Err(SdParseError::Truncated));
+        }
+        assert!(SdMessage::parse(&sd_message(&[], &[])).unwrap().entries.is_empty());
+    }
+}
@@ -0,0 +1,998 @@
+// SPDX-License-Identifier: MPL-2.0
+//
+// Copyright (C) 2024 Alexander Seifarth
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Typed access to service instances, the runtime of the proxies and skeletons generated by
+//! `vsomeiprs-codegen`.
+//!
+//! A [ServiceProxy] calls methods of a service instance and decodes its notifications, a
+//! [ServiceSkeleton] offers a service instance, answers its requests and sends its notifications.
+//! Payloads are serialized by the [crate::codec] with the [DeploymentProfile] of the proxy or
+//! skeleton. A [Field] reads, writes and subscribes to a field of a service instance, a
+//! [FieldCache] keeps its last value for synchronous reads, a [FieldProvider] stores the value of
+//! a field, answers its getter and setter and notifies its changes. A [Dispatcher] routes the
+//! requests to a skeleton to async handlers of their methods.
+//! [Layer]s wrap the method calls of a proxy and the requests handled by a dispatcher, e.g. the
+//! [AccessControl] of the clients of a service instance or the [RateLimit] of their requests.
+//! [service_interface!] generates typed proxies and skeletons of a declared interface on top of
+//! them, [include_generated!] includes the ones generated from ARXML by a build script.
+//! ```rust,no_run
+//! use std::sync::Arc;
+//! use vsomeiprs::commonapi::Address;
+//! use vsomeiprs::service::ServiceProxy;
+//! use vsomeiprs::{InstanceID, InterfaceVersion, MethodID, Reliability, ServiceID, VSomeipApplication};
+//!
+//! async fn run() {
+//!     let (app, _recv) = VSomeipApplication::create("client").unwrap();
+//!     let address = Address::new(ServiceID(0x1234), InstanceID(1), InterfaceVersion::make_version(1, 0));
+//!     let proxy = ServiceProxy::new(Arc::new(app), address);
+//!     proxy.request_service();
+//!     let speed: u16 = proxy.call(MethodID(1), &(), Reliability::Unreliable).await.unwrap();
+//! }
+//! ```
+
+use std::fmt;
+use std::future::Future;
+use std::sync::Arc;
+use bytes::Bytes;
+use tokio::sync::mpsc::UnboundedReceiver;
+use super::codec::{self, CodecError, DeploymentProfile, SomeipDeserialize, SomeipSerialize};
+use super::commonapi::Address;
+use super::{EventDescriptor, EventGroupID, MessageHeader, MessageKind, MessageType, MethodID, OfferGuard, Reliability,
+            RequestError, RequestOptions, RequestedServiceGuard, ReturnCode, SendError, ServiceDescriptor,
+            VSomeipApplication};
+
+mod dispatcher;
+pub use dispatcher::*;
+
+mod field;
+pub use field::*;
+
+mod layer;
+pub use layer::*;
+
+mod access;
+pub use access::*;
+
+mod rate;
+pub use rate::*;
+
+/// Channel of the requests received by an application, [crate::TypedReceivers::requests].
+pub type RequestReceiver = UnboundedReceiver<MessageType>;
+
+/// Error of a typed method call, the SOME/IP return code of an error response is mapped to the
+/// variant of the same meaning.
+#[derive(Debug)]
+pub enum CallError {
+    /// The request could not be sent.
+    Send(SendError),
+    /// The request was dropped by an interceptor.
+    Dropped,
+    /// No response arrived in time, including the retransmissions of the proxy's
+    /// [RequestOptions], or the provider answered with [ReturnCode::Timeout].
+    Timeout,
+    /// The application was dropped before the response arrived.
+    Closed,
+    /// The request could not be serialized or the response could not be deserialized.
+    Codec(CodecError),
+    /// The provider does not know the service instance.
+    UnknownService,
+    /// The provider does not know the method.
+    UnknownMethod,
+    /// The provider is running but not ready to answer the request.
+    NotReady,
+    /// The provider is not reachable.
+    NotReachable,
+    /// The provider implements another protocol or interface version, the return code tells which.
+    WrongVersion(ReturnCode),
+    /// The provider could not deserialize the request.
+    MalformedMessage,
+    /// The method failed with a service specific error code (0x20 - 0x5E) and the payload of the
+    /// error response.
+    Application { code: u8, data: Bytes },
+    /// The provider answered with [ReturnCode::NotOk] or a return code without meaning to a
+    /// request.
+    Failed(ReturnCode),
+}
+
+impl CallError {
+    /// Maps the return code and payload of an error response.
+    pub fn from_return_code(return_code: ReturnCode, data: Bytes) -> Self {
+        match return_code {
+            ReturnCode::Timeout => CallError::Timeout,
+            ReturnCode::UnknownService => CallError::UnknownService,
+            ReturnCode::UnknownMethod => CallError::UnknownMethod,
+            ReturnCode::NotReady => CallError::NotReady,
+            ReturnCode::NotReachable => CallError::NotReachable,
+            ReturnCode::WrongProtocolVersion | ReturnCode::WrongInterfaceVersion =>
+                CallError::WrongVersion(return_code),
+            ReturnCode::MalformedMessage => CallError::MalformedMessage,
+            ReturnCode::ApplicationSpecific(code) => CallError::Application { code, data },
+            _ => CallError::Failed(return_code),
+        }
+    }
+
+    /// Returns the return code of the error response, `None` for errors of the client side.
+    pub fn return_code(&self) -> Option<ReturnCode> {
+        match self {
+            CallError::UnknownService => Some(ReturnCode::UnknownService),
+            CallError::UnknownMethod => Some(ReturnCode::UnknownMethod),
+            CallError::NotReady => Some(ReturnCode::NotReady),
+            CallError::NotReachable => Some(ReturnCode::NotReachable),
+            CallError::WrongVersion(return_code) | CallError::Failed(return_code) => Some(*return_code),
+            CallError::MalformedMessage => Some(ReturnCode::MalformedMessage),
+            CallError::Application { code, .. } => Some(ReturnCode::ApplicationSpecific(*code)),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for CallError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CallError::Send(e) => write!(f, "{}", e),
+            CallError::Dropped => write!(f, "request dropped by an interceptor"),
+            CallError::Timeout => write!(f, "timeout waiting for the response"),
+            CallError::Closed => write!(f, "application closed before the response arrived"),
+            CallError::Codec(e) => write!(f, "{}", e),
+            CallError::Application { code, .. } => write!(f, "application error {:#04x}", code),
+            CallError::UnknownService | CallError::UnknownMethod | CallError::NotReady | CallError::NotReachable |
+            CallError::WrongVersion(_) | CallError::MalformedMessage | CallError::Failed(_) =>
+                write!(f, "error response: {:?}", self.return_code().unwrap_or(ReturnCode::NotOk)),
+        }
+    }
+}
+
+impl std::error::Error for CallError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CallError::Send(e) => Some(e),
+            CallError::Codec(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<RequestError> for CallError {
+    fn from(e: RequestError) -> Self {
+        match e {
+            RequestError::Send(e) => CallError::Send(e),
+            RequestError::Dropped => CallError::Dropped,
+            RequestError::Error { return_code, data, .. } =>
+                CallError::from_return_code(return_code, data.into_bytes()),
+            RequestError::Timeout => CallError::Timeout,
+            RequestError::Closed => CallError::Closed,
+        }
+    }
+}
+
+impl From<SendError> for CallError {
+    fn from(e: SendError) -> Self {
+        CallError::Send(e)
+    }
+}
+
+impl From<CodecError> for CallError {
+    fn from(e: CodecError) -> Self {
+        CallError::Codec(e)
+    }
+}
+
+/// Error of a typed response or notification.
+#[derive(Debug)]
+pub enum ServiceError {
+    Send(SendError),
+    /// A payload could not be serialized.
+    Codec(CodecError),
+}
+
+impl fmt::Display for ServiceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ServiceError::Send(e) => write!(f, "{}", e),
+            ServiceError::Codec(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for ServiceError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ServiceError::Send(e) => Some(e),
+            ServiceError::Codec(e) => Some(e),
+        }
+    }
+}
+
+impl From<SendError> for ServiceError {
+    fn from(e: SendError) -> Self {
+        ServiceError::Send(e)
+    }
+}
+
+impl From<CodecError> for ServiceError {
+    fn from(e: CodecError) -> Self {
+        ServiceError::Codec(e)
+    }
+}
+
+/// Client side of a service instance with typed method calls.
+#[derive(Clone)]
+pub struct ServiceProxy {
+    app: Arc<VSomeipApplication>,
+    address: Address,
+    profile: DeploymentProfile,
+    options: RequestOptions,
+    layers: Vec<Arc<dyn Layer>>,
+}
+
+impl ServiceProxy {
+    /// Returns a proxy serializing with the default [DeploymentProfile] and calling with the
+    /// default [RequestOptions].
+    pub fn new(app: Arc<VSomeipApplication>, address: Address) -> Self {
+        ServiceProxy {
+            app, address, profile: DeploymentProfile::default(), options: RequestOptions::default(), layers: Vec::new(),
+        }
+    }
+
+    pub fn with_profile(self, profile: DeploymentProfile) -> Self {
+        ServiceProxy { profile, ..self }
+    }
+
+    /// Sets the timeout and retransmissions of method calls, the transport is given by the method.
+    pub fn with_options(self, options: RequestOptions) -> Self {
+        ServiceProxy { options, ..self }
+    }
+
+    /// Appends a layer wrapping the method calls, see [Layer] for the order.
+    pub fn with_layer(mut self, layer: Arc<dyn Layer>) -> Self {
+        self.layers.push(layer);
+        self
+    }
+
+    pub fn app(&self) -> &Arc<VSomeipApplication> {
+        &self.app
+    }
+
+    pub fn address(&self) -> &Address {
+        &self.address
+    }
+
+    pub fn profile(&self) -> &DeploymentProfile {
+        &self.profile
+    }
+
+    pub fn options(&self) -> &RequestOptions {
+        &self.options
+    }
+
+    /// Requests the service instance, see [VSomeipApplication::request_service()].
+    pub fn request_service(&self) {
+        self.app.request_service(self.address);
+    }
+
+    /// Requests the service instance until the returned guard is dropped.
+    pub fn request_service_scoped(&self) -> RequestedServiceGuard<'_> {
+        self.app.request_service_scoped(self.address)
+    }
+
+    pub fn is_available(&self) -> bool {
+        self.app.is_available(self.address)
+    }
+
+    /// Calls the method with the serialized input and deserializes the output from the response.
+    ///
+    /// The request is sent with [VSomeipApplication::send_request_with()] and the proxy's
+    /// [RequestOptions], each transmission with a new session whose response is awaited. An error
+    /// response is mapped by [CallError::from_return_code()]. The serialized request passes the
+    /// proxy's [Layer]s.
+    pub async fn call<I, O>(&self, method_id: MethodID, input: &I, reliability: Reliability) -> Result<O, CallError>
+        where I: SomeipSerialize + ?Sized, O: SomeipDeserialize
+    {
+        let header = MessageHeader {
+            interface_version: self.address.version,
+            reliable: reliability == Reliability::Reliable,
+            ..MessageHeader::new(self.address.service_id, self.address.instance_id, method_id)
+        };
+        let request = LayerRequest { header, payload: codec::encode_with(input, &self.profile)? };
+        let endpoint = |request: LayerRequest| -> BoxFuture<'_, Result<Bytes, CallError>> {
+            let LayerRequest { header, payload } = request;
+            let options = RequestOptions { reliable: header.reliable, ..self.options };
+            Box::pin(async move {
+                let service = (header.service_id, header.instance_id, header.interface_version);
+                let response = self.app.send_request_with(service, header.method_id, payload, options).await?;
+                Ok(response.data.into_bytes())
+            })
+        };
+        let payload = Next::new(&self.layers, |layer, request, next| layer.call(request, next), &endpoint)
+            .run(request).await?;
+        Ok(codec::decode_with(&payload, &self.profile)?)
+    }
+
+    /// Calls a fire-and-forget method with the serialized input.
+    pub fn call_no_return<I>(&self, method_id: MethodID, input: &I, reliability: Reliability)
+        -> Result<(), CallError>
+        where I: SomeipSerialize + ?Sized
+    {
+        let payload = codec::encode_with(input, &self.profile)?;
+        let header = MessageHeader {
+            interface_version: self.address.version,
+            raw_message_type: MessageKind::RequestNoReturn.into(),
+            reliable: reliability == Reliability::Reliable,
+            ..MessageHeader::new(self.address.service_id, self.address.instance_id, method_id)
+        };
+        Ok(self.app.send_message(&header, payload)?)
+    }
+
+    /// Requests the events (`true` for fields) of the event group and subscribes to it.
+    pub fn subscribe(&self, event_group_id: EventGroupID, events: &[(MethodID, bool)], reliability: Reliability)
+        -> Result<(), SendError>
+    {
+        let Address { service_id, instance_id, .. } = self.address;
+        for (notifier_id, is_field) in events {
+            self.app.request_event(service_id, instance_id, *notifier_id, vec![event_group_id], *is_field,
+                                   reliability);
+        }
+        for (notifier_id, _) in events {
+            self.app.subscribe(self.address, event_group_id, *notifier_id)?;
+        }
+        Ok(())
+    }
+
+    /// Returns the event ID and the payload if the message is a notification of the instance.
+    pub fn notification<'m>(&self, msg: &'m MessageType) -> Option<(MethodID, &'m Bytes)> {
+        match msg {
+            MessageType::Notification { header, data, .. } if self.matches(header) =>
+                Some((header.method_id, data.as_bytes_ref())),
+            _ => None,
+        }
+    }
+
+    /// Deserializes the payload of a notification with the proxy's profile.
+    pub fn decode<T: SomeipDeserialize>(&self, data: &[u8]) -> Result<T, CodecError> {
+        codec::decode_with(data, &self.profile)
+    }
+
+    fn matches(&self, header: &MessageHeader) -> bool {
+        header.service_id == self.address.service_id && header.instance_id == self.address.instance_id
+    }
+}
+
+/// Provider side of a service instance with typed responses and notifications.
+#[derive(Clone)]
+pub struct ServiceSkeleton {
+    app: Arc<VSomeipApplication>,
+    address: Address,
+    profile: DeploymentProfile,
+}
+
+impl ServiceSkeleton {
+    /// Returns a skeleton serializing with the default [DeploymentProfile].
+    pub fn new(app: Arc<VSomeipApplication>, address: Address) -> Self {
+        ServiceSkeleton { app, address, profile: DeploymentProfile::default() }
+    }
+
+    pub fn with_profile(self, profile: DeploymentProfile) -> Self {
+        ServiceSkeleton { profile, ..self }
+    }
+
+    pub fn app(&self) -> &Arc<VSomeipApplication> {
+        &self.app
+    }
+
+    pub fn address(&self) -> &Address {
+        &self.address
+    }
+
+    pub fn profile(&self) -> &DeploymentProfile {
+        &self.profile
+    }
+
+    /// Offers the service instance with the events, see
+    /// [VSomeipApplication::offer_service_with_events()].
+    pub fn offer(&self, events: Vec<EventDescriptor>) -> Result<(), SendError> {
+        let Address { service_id, instance_id, version } = self.address;
+        self.app.offer_service_with_events(&ServiceDescriptor { service_id, instance_id, version, events })
+    }
+
+    /// Offers the service instance with the events until the returned guard is dropped.
+    pub fn offer_scoped(&self, events: Vec<EventDescriptor>) -> Result<OfferGuard<'_>, SendError> {
+        self.offer(events)?;
+        Ok(OfferGuard::new(&self.app, self.address.into()))
+    }
+
+    pub fn stop_offer(&self) {
+        self.app.stop_offer_service(self.address);
+    }
+
+    /// Returns the header and the payload if the message is a request (with or without return)
+    /// to the instance.
+    pub fn request<'m>(&self, msg: &'m MessageType) -> Option<(&'m MessageHeader, &'m Bytes)> {
+        match msg {
+            MessageType::Request { header, data } | MessageType::RequestNoReturn { header, data }
+                if header.service_id == self.address.service_id && header.instance_id == self.address.instance_id =>
+                Some((header, data.as_bytes_ref())),
+            _ => None,
+        }
+    }
+
+    /// Deserializes the payload of a request with the skeleton's profile.
+    pub fn decode<T: SomeipDeserialize>(&self, data: &[u8]) -> Result<T, CodecError> {
+        codec::decode_with(data, &self.profile)
+    }
+
+    /// Answers the request with the serialized output or with an error message of the return
+    /// code. An output that cannot be serialized is answered with [ReturnCode::NotOk].
+    pub fn respond<O>(&self, request: &MessageHeader, result: Result<O, ReturnCode>) -> Result<(), ServiceError>
+        where O: SomeipSerialize
+    {
+        let output = match result {
+            Ok(output) => output,
+            Err(return_code) => return Ok(self.app.send_error(request, return_code)?),
+        };
+        match codec::encode_with(&output, &self.profile) {
+            Ok(payload) => Ok(self.app.send_response(request, ReturnCode::Ok, payload)?),
+            Err(e) => {
+                self.app.send_error(request, ReturnCode::NotOk)?;
+                Err(e.into())
+            }
+        }
+    }
+
+    /// Sends a notification of the event with the serialized value.
+    pub fn notify<T>(&self, event_id: MethodID, value: &T) -> Result<(), ServiceError>
+        where T: SomeipSerialize + ?Sized
+    {
+        let payload = codec::encode_with(value, &self.profile)?;
+        Ok(self.app.notify(self.address.service_id, self.address.instance_id, event_id, payload, false)?)
+    }
+
+    /// Passes the requests received from the channel to `dispatch` one after the other until the
+    /// channel is closed. A response that could not be sent is only logged as there is no caller
+    /// to report it to.
+    pub async fn run<F, R>(&self, requests: &mut RequestReceiver, mut dispatch: F)
+        where F: FnMut(MessageType) -> R, R: Future<Output = Result<bool, ServiceError>>
+    {
+        while let Some(msg) = requests.recv().await {
+            if let Err(e) = dispatch(msg).await {
+                log::warn!("Cannot answer request to {}.{}: {}", self.address.service_id, self.address.instance_id, e);
+            }
+        }
+    }
+}
+
+/// Declares a service interface with the IDs and payload types of its methods, events and fields
+/// and generates a typed proxy, a stub trait for its implementation and a skeleton dispatching
+/// requests to the stub, the counterpart of the code generated by `vsomeiprs-codegen` for
+/// interfaces without an ARXML description.
+///
+/// The interface declares:
+/// - `methods` with their arguments, optional output and method ID, optionally `reliable` for
+///   TCP, each an async proxy function returning the output or a [CallError] and a stub function
+///   returning the output or an error [ReturnCode],
+/// - `fire_and_forget` methods without output and response,
+/// - `events` as variants of the event enum with their payload type, event ID and event groups,
+/// - `fields` as variants of the event enum with the ID of their notifier and optionally the
+///   names and IDs of their getter and setter, which are handled like methods.
+///
+/// Events and fields are requested with the transport of the configuration.
+///
+/// Arguments are serialized as a tuple, i.e. without length field. The interface name holds the
+/// constants `SERVICE_ID` and `VERSION` and the [EventDescriptor]s of the events and fields.
+/// ```rust,no_run
+/// use std::sync::Arc;
+/// use vsomeiprs::{InstanceID, ReturnCode, VSomeipApplication};
+///
+/// vsomeiprs::service_interface! {
+///     /// Speedometer of the vehicle.
+///     pub interface Speedometer {
+///         service_id: 0x1234,
+///         version: (1, 0),
+///         proxy: SpeedometerProxy,
+///         stub: SpeedometerStub,
+///         skeleton: SpeedometerSkeleton,
+///         event: SpeedometerEvent,
+///         methods {
+///             fn set_speed_limit(limit: u16) = 0x0001;
+///             fn start_trip(name: String, mode: u8) -> u32 = 0x0002, reliable;
+///         }
+///         fire_and_forget {
+///             fn reset() = 0x0003;
+///         }
+///         events {
+///             SpeedChanged(u16) = 0x8001 in [0x0001];
+///         }
+///         fields {
+///             DriveMode(u8) = 0x8002 in [0x0001] { get get_drive_mode = 0x0010; set set_drive_mode = 0x0011; }
+///         }
+///     }
+/// }
+///
+/// struct Cluster;
+///
+/// impl SpeedometerStub for Cluster {
+///     async fn set_speed_limit(&self, _limit: u16) -> Result<(), ReturnCode> { Ok(()) }
+///     async fn start_trip(&self, _name: String, _mode: u8) -> Result<u32, ReturnCode> { Ok(1) }
+///     async fn reset(&self) {}
+///     async fn get_drive_mode(&self) -> Result<u8, ReturnCode> { Ok(0) }
+///     async fn set_drive_mode(&self, value: u8) -> Result<u8, ReturnCode> { Ok(value) }
+/// }
+///
+/// async fn run() {
+///     let (app, mut recv) = VSomeipApplication::create_typed("cluster").unwrap();
+///     let skeleton = SpeedometerSkeleton::new(Arc::new(app), InstanceID(1));
+///     skeleton.offer().unwrap();
+///     skeleton.notify(&SpeedometerEvent::SpeedChanged(50)).unwrap();
+///     skeleton.run(&Cluster, &mut recv.requests).await;
+/// }
+/// ```
+#[macro_export]
+macro_rules! service_interface {
+    (
+        $(#[$meta:meta])*
+        $vis:vis interface $name:ident {
+            service_id: $service_id:literal,
+            version: ($major:literal, $minor:literal),
+            proxy: $proxy:ident,
+            stub: $stub:ident,
+            skeleton: $skeleton:ident,
+            event: $event:ident,
+            $(methods {
+                $(
+                    $(#[$method_meta:meta])*
+                    fn $method:ident($($arg:ident: $arg_ty:ty),* $(,)?) $(-> $out:ty)? = $method_id:literal
+                        $(, $reliable:ident)?;
+                )*
+            })?
+            $(fire_and_forget {
+                $(
+                    $(#[$ff_meta:meta])*
+                    fn $ff:ident($($ff_arg:ident: $ff_arg_ty:ty),* $(,)?) = $ff_id:literal $(, $ff_reliable:ident)?;
+                )*
+            })?
+            $(events {
+                $(
+                    $(#[$ev_meta:meta])*
+                    $ev:ident($ev_ty:ty) = $ev_id:literal in [$($ev_group:literal),* $(,)?];
+                )*
+            })?
+            $(fields {
+                $(
+                    $(#[$field_meta:meta])*
+                    $field:ident($field_ty:ty) = $notifier_id:literal in [$($field_group:literal),* $(,)?] {
+                        $(get $getter:ident = $getter_id:literal $(, $getter_reliable:ident)?;)?
+                        $(set $setter:ident = $setter_id:literal $(, $setter_reliable:ident)?;)?
+                    }
+                )*
+            })?
+        }
+    ) => {
+        $(#[$meta])*
+        $vis struct $name;
+
+        impl $name {
+            pub const SERVICE_ID: $crate::ServiceID = $crate::ServiceID::new($service_id);
+            pub const VERSION: $crate::InterfaceVersion = $crate::InterfaceVersion::make_version($major, $minor);
+
+            /// Returns the descriptors of the events and fields.
+            pub fn event_descriptors() -> Vec<$crate::EventDescriptor> {
+                Vec::from([
+                    $($($crate::EventDescriptor::new($crate::MethodID::new($ev_id),
+                                                     Vec::from([$($crate::EventGroupID::new($ev_group)),*])),)*)?
+                    $($($crate::EventDescriptor::field($crate::MethodID::new($notifier_id),
+                                                       Vec::from([$($crate::EventGroupID::new($field_group)),*])),)*)?
+                ])
+            }
+        }
+
+        /// Notification of an event or field.
+        #[derive(Debug, Clone, PartialEq)]
+        $vis enum $event {
+            $($($(#[$ev_meta])* $ev($ev_ty),)*)?
+            $($($(#[$field_meta])* $field($field_ty),)*)?
+        }
+
+        impl $event {
+            pub fn event_id(&self) -> $crate::MethodID {
+                match *self {
+                    $($($event::$ev(_) => $crate::MethodID::new($ev_id),)*)?
+                    $($($event::$field(_) => $crate::MethodID::new($notifier_id),)*)?
+                }
+            }
+        }
+
+        $vis struct $proxy {
+            proxy: $crate::service::ServiceProxy,
+        }
+
+        impl $proxy {
+            pub fn new(app: ::std::sync::Arc<$crate::VSomeipApplication>, instance_id: $crate::InstanceID) -> Self {
+                let address = $crate::commonapi::Address::new($name::SERVICE_ID, instance_id, $name::VERSION);
+                $proxy { proxy: $crate::service::ServiceProxy::new(app, address) }
+            }
+
+            pub fn with_profile(self, profile: $crate::codec::DeploymentProfile) -> Self {
+                $proxy { proxy: self.proxy.with_profile(profile) }
+            }
+
+            pub fn with_options(self, options: $crate::RequestOptions) -> Self {
+                $proxy { proxy: self.proxy.with_options(options) }
+            }
+
+            pub fn with_layer(self, layer: ::std::sync::Arc<dyn $crate::service::Layer>) -> Self {
+                $proxy { proxy: self.proxy.with_layer(layer) }
+            }
+
+            pub fn proxy(&self) -> &$crate::service::ServiceProxy {
+                &self.proxy
+            }
+
+            $($(
+                $(#[$method_meta])*
+                pub async fn $method(&self, $($arg: $arg_ty),*)
+                    -> Result<$crate::__service_output!($($out)?), $crate::service::CallError>
+                {
+                    self.proxy.call($crate::MethodID::new($method_id), &($($arg,)*),
+                                    $crate::__service_reliability!($($reliable)?)).await
+                }
+            )*)?
+
+            $($(
+                $(#[$ff_meta])*
+                pub fn $ff(&self, $($ff_arg: $ff_arg_ty),*) -> Result<(), $crate::service::CallError> {
+                    self.proxy.call_no_return($crate::MethodID::new($ff_id), &($($ff_arg,)*),
+                                              $crate::__service_reliability!($($ff_reliable)?))
+                }
+            )*)?
+
+            $($(
+                $(
+                    pub async fn $getter(&self) -> Result<$field_ty, $crate::service::CallError> {
+                        self.proxy.call($crate::MethodID::new($getter_id), &(),
+                                        $crate::__service_reliability!($($getter_reliable)?)).await
+                    }
+                )?
+                $(
+                    pub async fn $setter(&self, value: $field_ty) -> Result<$field_ty, $crate::service::CallError> {
+                        self.proxy.call($crate::MethodID::new($setter_id), &(value,),
+                                        $crate::__service_reliability!($($setter_reliable)?)).await
+                    }
+                )?
+            )*)?
+
+            /// Requests the events and fields of the event group and subscribes to it.
+            pub fn subscribe(&self, event_group_id: $crate::EventGroupID) -> Result<(), $crate::SendError> {
+                let events = $name::event_descriptors().into_iter()
+                    .filter(|descriptor| descriptor.event_groups.contains(&event_group_id))
+                    .map(|descriptor| (descriptor.notifier_id, descriptor.kind == $crate::EventKind::Field))
+                    .collect::<Vec<_>>();
+                self.proxy.subscribe(event_group_id, &events, $crate::Reliability::Unknown)
+            }
+
+            /// Returns the event if the message is a notification of the service instance.
+            pub fn decode_event(&self, msg: &$crate::MessageType)
+                -> Option<Result<$event, $crate::codec::CodecError>>
+            {
+                match self.proxy.notification(msg)? {
+                    $($((event_id, data) if event_id.id() == $ev_id =>
+                        Some(self.proxy.decode(data).map($event::$ev)),)*)?
+                    $($((event_id, data) if event_id.id() == $notifier_id =>
+                        Some(self.proxy.decode(data).map($event::$field)),)*)?
+                    _ => None,
+                }
+            }
+        }
+
+        $(#[$meta])*
+        $vis trait $stub {
+            $($(
+                $(#[$method_meta])*
+                fn $method(&self, $($arg: $arg_ty),*) -> impl ::std::future::Future<
+                    Output = Result<$crate::__service_output!($($out)?), $crate::ReturnCode>> + Send;
+            )*)?
+
+            $($(
+                $(#[$ff_meta])*
+                fn $ff(&self, $($ff_arg: $ff_arg_ty),*) -> impl ::std::future::Future<Output = ()> + Send;
+            )*)?
+
+            $($(
+                $(
+                    fn $getter(&self)
+                        -> impl ::std::future::Future<Output = Result<$field_ty, $crate::ReturnCode>> + Send;
+                )?
+                $(
+                    fn $setter(&self, value: $field_ty)
+                        -> impl ::std::future::Future<Output = Result<$field_ty, $crate::ReturnCode>> + Send;
+                )?
+            )*)?
+        }
+
+        $vis struct $skeleton {
+            skeleton: $crate::service::ServiceSkeleton,
+        }
+
+        impl $skeleton {
+            pub fn new(app: ::std::sync::Arc<$crate::VSomeipApplication>, instance_id: $crate::InstanceID) -> Self {
+                let address = $crate::commonapi::Address::new($name::SERVICE_ID, instance_id, $name::VERSION);
+                $skeleton { skeleton: $crate::service::ServiceSkeleton::new(app, address) }
+            }
+
+            pub fn with_profile(self, profile: $crate::codec::DeploymentProfile) -> Self {
+                $skeleton { skeleton: self.skeleton.with_profile(profile) }
+            }
+
+            pub fn skeleton(&self) -> &$crate::service::ServiceSkeleton {
+                &self.skeleton
+            }
+
+            /// Offers the service instance with its events and fields.
+            pub fn offer(&self) -> Result<(), $crate::SendError> {
+                self.skeleton.offer($name::event_descriptors())
+            }
+
+            pub fn stop_offer(&self) {
+                self.skeleton.stop_offer()
+            }
+
+            /// Sends a notification of the event or field.
+            pub fn notify(&self, event: &$event) -> Result<(), $crate::service::ServiceError> {
+                match *event {
+                    $($($event::$ev(ref value) => self.skeleton.notify(event.event_id(), value),)*)?
+                    $($($event::$field(ref value) => self.skeleton.notify(event.event_id(), value),)*)?
+                }
+            }
+
+            /// Answers the requests to the service instance received from the channel by the stub
+            /// until the channel is closed, see `vsomeiprs::service::ServiceSkeleton::run()`.
+            pub async fn run<S: $stub>(&self, stub: &S, requests: &mut $crate::service::RequestReceiver) {
+                self.skeleton.run(requests, |msg| async move { self.dispatch(stub, &msg).await }).await
+            }
+
+            /// Calls the stub for a request to the service instance and sends its response,
+            /// `E_UNKNOWN_METHOD` for an unknown method. Requests without return are not answered.
+            /// Returns `false` if the message is no request to the instance.
+            pub async fn dispatch<S: $stub>(&self, stub: &S, msg: &$crate::MessageType)
+                -> Result<bool, $crate::service::ServiceError>
+            {
+                let Some((header, data)) = self.skeleton.request(msg) else {
+                    return Ok(false);
+                };
+                let expects_response = matches!(msg, $crate::MessageType::Request { .. });
+                match (header.method_id.id(), data) {
+                    $($(($method_id, data) => {
+                        let result = match self.skeleton.decode::<($($arg_ty,)*)>(data) {
+                            Ok(($($arg,)*)) => stub.$method($($arg),*).await,
+                            Err(_) => Err($crate::ReturnCode::MalformedMessage),
+                        };
+                        if expects_response {
+                            self.skeleton.respond(header, result)?;
+                        }
+                    })*)?
+                    $($(($ff_id, data) => {
+                        if let Ok(($($ff_arg,)*)) = self.skeleton.decode::<($($ff_arg_ty,)*)>(data) {
+                            stub.$ff($($ff_arg),*).await;
+                        }
+                    })*)?
+                    $($(
+                        $(($getter_id, _) => {
+                            let result = stub.$getter().await;
+                            if expects_response {
+                                self.skeleton.respond(header, result)?;
+                            }
+                        })?
+                        $(($setter_id, data) => {
+                            let result = match self.skeleton.decode::<$field_ty>(data) {
+                                Ok(value) => stub.$setter(value).await,
+                                Err(_) => Err($crate::ReturnCode::MalformedMessage),
+                            };
+                            if expects_response {
+                                self.skeleton.respond(header, result)?;
+                            }
+                        })?
+                    )*)?
+                    _ if expects_response => {
+                        self.skeleton.respond::<()>(header, Err($crate::ReturnCode::UnknownMethod))?;
+                    }
+                    _ => {}
+                }
+                Ok(true)
+            }
+        }
+    };
+}
+
+/// Includes the code generated by `vsomeiprs_codegen::generate()` in the build script for the
+/// ARXML file of the name, e.g. `include_generated!("vehicle")` for `vehicle.arxml`. The code
+/// requires the `derive` feature.
+/// ```rust,ignore
+/// pub mod vehicle {
+///     vsomeiprs::include_generated!("vehicle");
+/// }
+/// use vehicle::speedometer::SpeedometerProxy;
+/// ```
+#[macro_export]
+macro_rules! include_generated {
+    ($name:literal) => {
+        include!(concat!(env!("OUT_DIR"), "/", $name, ".rs"));
+    };
+}
+
+/// Output type of a method declared by [service_interface!], `()` if omitted.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __service_output {
+    () => { () };
+    ($out:ty) => { $out };
+}
+
+/// Transport of a method or field declared by [service_interface!], `reliable` for TCP.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __service_reliability {
+    () => { $crate::Reliability::Unreliable };
+    (reliable) => { $crate::Reliability::Reliable };
+}
+
+#[cfg(test)]
+mod test {
+    use std::future::Future;
+    use std::time::Duration;
+    use crate::{EventKind, InstanceID, InterfaceVersion, MessageType, ReturnCode, ServiceID};
+    use super::*;
+
+    // constructors and notifications need a vsomeip application
+    #[allow(dead_code)]
+    mod speedometer {
+        service_interface! {
+            pub interface Speedometer {
+                service_id: 0x1234,
+                version: (1, 2),
+                proxy: SpeedometerProxy,
+                stub: SpeedometerStub,
+                skeleton: SpeedometerSkeleton,
+                event: SpeedometerEvent,
+                methods {
+                    fn set_speed_limit(limit: u16) = 0x0001;
+                    fn start_trip(name: String, mode: u8) -> u32 = 0x0002, reliable;
+                }
+                fire_and_forget {
+                    fn reset() = 0x0003;
+                }
+                events {
+                    SpeedChanged(u16) = 0x8001 in [1];
+                }
+                fields {
+                    DriveMode(u8) = 0x8002 in [1, 2] { get get_drive_mode = 0x0010, reliable; }
+                }
+            }
+        }
+    }
+
+    use speedometer::*;
+
+    struct Cluster;
+
+    impl SpeedometerStub for Cluster {
+        async fn set_speed_limit(&self, _limit: u16) -> Result<(), ReturnCode> {
+            Ok(())
+        }
+
+        async fn start_trip(&self, name: String, mode: u8) -> Result<u32, ReturnCode> {
+            Ok(name.len() as u32 + mode as u32)
+        }
+
+        async fn reset(&self) {}
+
+        async fn get_drive_mode(&self) -> Result<u8, ReturnCode> {
+            Err(ReturnCode::NotReady)
+        }
+    }
+
+    fn assert_send<F: Future + Send>(_future: F) {}
+
+    #[test]
+    fn field_deployment_test() {
+        let mut deployment = FieldDeployment { getter: Some(MethodID(0x10)), setter: None,
+                                               notifier: Some((MethodID(0x8002), vec![EventGroupID(1)])),
+                                               reliable: false, cycle: None };
+        assert_eq!(deployment.event_descriptor(),
+                   Some(EventDescriptor::field(MethodID(0x8002), vec![EventGroupID(1)])));
+        deployment.cycle = Some(Duration::from_millis(100));
+        assert_eq!(deployment.event_descriptor().unwrap().cycle, Some(Duration::from_millis(100)));
+        deployment.notifier = None;
+        assert_eq!(deployment.event_descriptor(), None);
+    }
+
+    #[test]
+    fn call_error_test() {
+        assert!(matches!(CallError::from_return_code(ReturnCode::UnknownMethod, Bytes::new()),
+                         CallError::UnknownMethod));
+        assert!(matches!(CallError::from_return_code(ReturnCode::Timeout, Bytes::new()), CallError::Timeout));
+        assert!(matches!(CallError::from_return_code(ReturnCode::WrongInterfaceVersion, Bytes::new()),
+                         CallError::WrongVersion(ReturnCode::WrongInterfaceVersion)));
+        let e = CallError::from_return_code(ReturnCode::ApplicationSpecific(0x21), Bytes::from_static(&[1]));
+        assert!(matches!(&e, CallError::Application { code: 0x21, data } if data[..] == [1]));
+        assert_eq!(e.return_code(), Some(ReturnCode::ApplicationSpecific(0x21)));
+        let e = CallError::from_return_code(ReturnCode::Reserved(0x0b), Bytes::new());
+        assert_eq!(e.return_code(), Some(ReturnCode::Reserved(0x0b)));
+        assert_eq!(CallError::from(RequestError::Timeout).return_code(), None);
+        assert!(matches!(CallError::from(RequestError::Dropped), CallError::Dropped));
+    }
+
+    #[test]
+    fn service_interface_test() {
+        assert_eq!(Speedometer::SERVICE_ID, ServiceID(0x1234));
+        assert_eq!(Speedometer::VERSION, InterfaceVersion::make_version(1, 2));
+        let descriptors = Speedometer::event_descriptors();
+        assert_eq!(descriptors, [EventDescriptor::new(MethodID(0x8001), vec![EventGroupID(1)]),
+                                 EventDescriptor::field(MethodID(0x8002), vec![EventGroupID(1), EventGroupID(2)])]);
+        assert_eq!(descriptors[1].kind, EventKind::Field);
+        assert_eq!(SpeedometerEvent::SpeedChanged(1).event_id(), MethodID(0x8001));
+        assert_eq!(SpeedometerEvent::DriveMode(1).event_id(), MethodID(0x8002));
+        // the dispatcher of a stub implemented with async functions can be spawned
+        let _ = |skeleton: &SpeedometerSkeleton, msg: &MessageType| assert_send(skeleton.dispatch(&Cluster, msg));
+        let _ = |skeleton: &SpeedometerSkeleton, requests: &mut RequestReceiver|
+            assert_send(skeleton.run(&Cluster, requests));
+        let _ = |proxy: &SpeedometerProxy| assert_send(proxy.start_trip("trip".to_string(), 1));
+    }
+
+    #[test]
+    fn dispatcher_test() {
+        let dispatcher = |skeleton: ServiceSkeleton| Dispatcher::new(skeleton)
+            .on_request(MethodID(1), |_header, payload| async move { Ok(payload.len() as u16) })
+            .on_request(MethodID(2), |_header, _payload| async { Err::<(), _>(ReturnCode::NotReady) });
+        let _ = |skeleton: ServiceSkeleton, requests: RequestReceiver| {
+            let dispatcher = dispatcher(skeleton);
+            assert!(dispatcher.handles(MethodID(1)) && !dispatcher.handles(MethodID(3)));
+            // the dispatcher can be spawned
+            assert_send(dispatcher.run(requests))
+        };
+    }
+
+    /// Appends its tag to the request and the response, answers requests to method 9 itself.
+    struct TagLayer(u8);
+
+    impl Layer for TagLayer {
+        fn handle<'a>(&'a self, mut request: LayerRequest, next: Next<'a, ReturnCode>)
+            -> BoxFuture<'a, Result<Bytes, ReturnCode>>
+        {
+            if request.header.method_id == MethodID(9) {
+                return Box::pin(async { Err(ReturnCode::NotReady) });
+            }
+            request.payload = [&request.payload[..], &[self.0]].concat().into();
+            Box::pin(async move {
+                let response = next.run(request).await?;
+                Ok([&response[..], &[self.0]].concat().into())
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn layer_test() {
+        let layers: Vec<Arc<dyn Layer>> = vec![Arc::new(TagLayer(1)), Arc::new(LogLayer), Arc::new(TagLayer(2))];
+        let endpoint = |request: LayerRequest| -> BoxFuture<'_, Result<Bytes, ReturnCode>> {
+            Box::pin(async move { Ok([&request.payload[..], &[0]].concat().into()) })
+        };
+        let run = |method_id, payload: &'static [u8]| {
+            let header = MessageHeader::new(ServiceID(0x1234), InstanceID(1), method_id);
+            Next::new(&layers, |layer, request, next| layer.handle(request, next), &endpoint)
+                .run(LayerRequest { header, payload: Bytes::from_static(payload) })
+        };
+        // the layer added first sees the request first and the response last
+        assert_eq!(run(MethodID(1), &[7]).await.unwrap(), [7, 1, 2, 0, 2, 1][..]);
+        assert_eq!(run(MethodID(9), &[7]).await.unwrap_err(), ReturnCode::NotReady);
+        // calls pass the layers unchanged by default
+        let layers: Vec<Arc<dyn Layer>> = vec![Arc::new(TagLayer(1))];
+        let endpoint = |request: LayerRequest| -> BoxFuture<'_, Result<Bytes, CallError>> {
+            Box::pin(async move { Ok(request.payload) })
+        };
+        let header = MessageHeader::new(ServiceID(0x1234), InstanceID(1), MethodID(1));
+        let response = Next::new(&layers, |layer, request, next| layer.call(request, next), &endpoint)
+            .run(LayerRequest { header, payload: Bytes::from_static(&[7]) }).await;
+        assert_eq!(response.unwrap(), [7][..]);
+    }
+}
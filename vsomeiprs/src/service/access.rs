@@ -0,0 +1,243 @@
+// SPDX-License-Identifier: MPL-2.0
+//
+// Copyright (C) 2024 Alexander Seifarth
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::collections::{BTreeSet, HashMap};
+use std::ops::RangeInclusive;
+use std::sync::Arc;
+use bytes::Bytes;
+use super::{BoxFuture, Layer, LayerRequest, Next};
+use crate::commonapi::Event;
+use crate::{ClientID, EventGroupID, InstanceID, MethodID, ReturnCode, ServiceID, SubscriptionHandler,
+            VSomeipApplication};
+
+/// Clients an [AccessControl] rule grants access to.
+#[derive(Eq, PartialEq, Debug, Clone)]
+pub enum ClientClass {
+    Any,
+    Ids(Vec<ClientID>),
+    /// Client IDs in the range, e.g. those of the applications of an ECU sharing a client ID
+    /// prefix.
+    Range(RangeInclusive<ClientID>),
+}
+
+impl ClientClass {
+    pub fn contains(&self, client_id: ClientID) -> bool {
+        match self {
+            ClientClass::Any => true,
+            ClientClass::Ids(ids) => ids.contains(&client_id),
+            ClientClass::Range(range) => range.contains(&client_id),
+        }
+    }
+}
+
+impl From<ClientID> for ClientClass {
+    fn from(client_id: ClientID) -> Self {
+        ClientClass::Ids(vec![client_id])
+    }
+}
+
+/// Method call or subscription refused by an [AccessControl].
+#[derive(Eq, PartialEq, Debug, Clone, Copy)]
+pub enum Access {
+    Method(MethodID),
+    EventGroup(EventGroupID),
+}
+
+/// Audit record of a refused access, see [AccessControl::get_violation_event()].
+#[derive(Eq, PartialEq, Debug, Clone, Copy)]
+pub struct AccessViolation {
+    pub service_id: ServiceID,
+    pub instance_id: InstanceID,
+    pub client_id: ClientID,
+    pub access: Access,
+}
+
+/// Provider side access control: declares which clients may call the methods and subscribe to the
+/// event groups of a service instance.
+///
+/// A method or event group with rules is accessible to the clients of its [ClientClass]es only,
+/// one without rules to all clients unless [AccessControl::deny_unlisted()] is set. As [Layer]
+/// of a [super::Dispatcher] it answers refused requests with [ReturnCode::NotOk] and drops refused
+/// fire-and-forget requests. Refused subscriptions are answered with a SubscribeEventgroupNack,
+/// see [AccessControl::register_subscription_handlers()]. Each refused access is reported by the
+/// violation event.
+/// ```rust,no_run
+/// use std::sync::Arc;
+/// use vsomeiprs::service::{AccessControl, ClientClass, Dispatcher, ServiceSkeleton};
+/// use vsomeiprs::{ClientID, EventGroupID, MethodID};
+///
+/// fn protect(skeleton: ServiceSkeleton) -> Dispatcher {
+///     let acl = Arc::new(AccessControl::new()
+///         .allow_method(MethodID(0x10), ClientClass::Range(ClientID(0x1000)..=ClientID(0x10ff)))
+///         .allow_method(MethodID(0x10), ClientID(0x2001).into())
+///         .allow_event_group(EventGroupID(1), ClientClass::Any)
+///         .deny_unlisted());
+///     acl.get_violation_event().subscribe(|violation| println!("access refused: {:?}", violation));
+///     let address = *skeleton.address();
+///     acl.register_subscription_handlers(skeleton.app(), address.service_id, address.instance_id,
+///                                        &[EventGroupID(1), EventGroupID(2)]);
+///     Dispatcher::new(skeleton).with_layer(acl)
+/// }
+/// ```
+#[derive(Default)]
+pub struct AccessControl {
+    methods: HashMap<MethodID, Vec<ClientClass>>,
+    event_groups: HashMap<EventGroupID, Vec<ClientClass>>,
+    deny_unlisted: bool,
+    violation_event: Event<AccessViolation>,
+}
+
+impl AccessControl {
+    /// Access control granting all clients access.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Grants the clients access to the method, in addition to the ones granted before.
+    pub fn allow_method(mut self, method_id: MethodID, clients: ClientClass) -> Self {
+        self.methods.entry(method_id).or_default().push(clients);
+        self
+    }
+
+    /// Grants the clients access to the event group, in addition to the ones granted before.
+    pub fn allow_event_group(mut self, event_group_id: EventGroupID, clients: ClientClass) -> Self {
+        self.event_groups.entry(event_group_id).or_default().push(clients);
+        self
+    }
+
+    /// Refuses all clients access to the methods and event groups without rules.
+    pub fn deny_unlisted(mut self) -> Self {
+        self.deny_unlisted = true;
+        self
+    }
+
+    pub fn may_call(&self, client_id: ClientID, method_id: MethodID) -> bool {
+        self.is_allowed(self.methods.get(&method_id), client_id)
+    }
+
+    pub fn may_subscribe(&self, client_id: ClientID, event_group_id: EventGroupID) -> bool {
+        self.is_allowed(self.event_groups.get(&event_group_id), client_id)
+    }
+
+    /// Returns the event reporting the refused accesses.
+    pub fn get_violation_event(&self) -> &Event<AccessViolation> {
+        &self.violation_event
+    }
+
+    /// Registers the subscription handlers checking the subscriptions to the offered event groups
+    /// and the ones with rules of the service instance. Replaces other subscription handlers of
+    /// these event groups. Subscriptions to event groups neither offered nor with rules are not
+    /// checked, so with [AccessControl::deny_unlisted()] all offered event groups must be passed.
+    pub fn register_subscription_handlers(self: &Arc<Self>, app: &VSomeipApplication, service_id: ServiceID,
+                                          instance_id: InstanceID, offered: &[EventGroupID])
+    {
+        for event_group_id in self.checked_event_groups(offered) {
+            let handler = AccessHandler { acl: self.clone(), service_id, instance_id, event_group_id };
+            app.register_subscription_handler(service_id, instance_id, event_group_id, Arc::new(handler));
+        }
+    }
+
+    /// Returns the offered event groups and the ones with rules.
+    fn checked_event_groups(&self, offered: &[EventGroupID]) -> BTreeSet<EventGroupID> {
+        offered.iter().chain(self.event_groups.keys()).copied().collect()
+    }
+
+    fn is_allowed(&self, rules: Option<&Vec<ClientClass>>, client_id: ClientID) -> bool {
+        match rules {
+            Some(rules) => rules.iter().any(|clients| clients.contains(client_id)),
+            None => !self.deny_unlisted,
+        }
+    }
+
+    fn refuse(&self, service_id: ServiceID, instance_id: InstanceID, client_id: ClientID, access: Access) {
+        self.violation_event.fire(&AccessViolation { service_id, instance_id, client_id, access });
+    }
+}
+
+impl Layer for AccessControl {
+    fn handle<'a>(&'a self, request: LayerRequest, next: Next<'a, ReturnCode>)
+        -> BoxFuture<'a, Result<Bytes, ReturnCode>>
+    {
+        let header = &request.header;
+        if self.may_call(header.client_id, header.method_id) {
+            return next.run(request);
+        }
+        self.refuse(header.service_id, header.instance_id, header.client_id, Access::Method(header.method_id));
+        Box::pin(async { Err(ReturnCode::NotOk) })
+    }
+}
+
+/// Subscription handler of an event group checking the subscriptions with the access control.
+struct AccessHandler {
+    acl: Arc<AccessControl>,
+    service_id: ServiceID,
+    instance_id: InstanceID,
+    event_group_id: EventGroupID,
+}
+
+impl SubscriptionHandler for AccessHandler {
+    fn on_subscribe(&self, client_id: ClientID) -> bool {
+        if self.acl.may_subscribe(client_id, self.event_group_id) {
+            return true;
+        }
+        self.acl.refuse(self.service_id, self.instance_id, client_id, Access::EventGroup(self.event_group_id));
+        false
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::sync::Mutex;
+    use crate::MessageHeader;
+
+    #[tokio::test]
+    async fn access_control_test() {
+        let acl = Arc::new(AccessControl::new()
+            .allow_method(MethodID(1), ClientClass::Range(ClientID(0x1000)..=ClientID(0x10ff)))
+            .allow_method(MethodID(1), ClientID(0x2001).into())
+            .allow_event_group(EventGroupID(1), ClientID(0x1001).into()));
+        assert!(acl.may_call(ClientID(0x10ff), MethodID(1)));
+        assert!(acl.may_call(ClientID(0x2001), MethodID(1)));
+        assert!(!acl.may_call(ClientID(0x2002), MethodID(1)));
+        assert!(acl.may_call(ClientID(0x2002), MethodID(2)));
+        assert!(!AccessControl::new().deny_unlisted().may_call(ClientID(0x2002), MethodID(2)));
+
+        let violations = Arc::new(Mutex::new(Vec::new()));
+        let recorded = violations.clone();
+        acl.get_violation_event().subscribe(move |violation| recorded.lock().unwrap().push(*violation));
+        let layers: Vec<Arc<dyn Layer>> = vec![acl.clone()];
+        let endpoint = |request: LayerRequest| -> BoxFuture<'_, Result<Bytes, ReturnCode>> {
+            Box::pin(async move { Ok(request.payload) })
+        };
+        let run = |client_id| {
+            let header = MessageHeader { client_id,
+                                         ..MessageHeader::new(ServiceID(0x1234), InstanceID(1), MethodID(1)) };
+            Next::new(&layers, |layer, request, next| layer.handle(request, next), &endpoint)
+                .run(LayerRequest { header, payload: Bytes::from_static(&[7]) })
+        };
+        assert_eq!(run(ClientID(0x1001)).await.unwrap(), [7][..]);
+        assert_eq!(run(ClientID(0x3001)).await.unwrap_err(), ReturnCode::NotOk);
+
+        let handler = AccessHandler { acl: acl.clone(), service_id: ServiceID(0x1234), instance_id: InstanceID(1),
+                                      event_group_id: EventGroupID(1) };
+        assert!(handler.on_subscribe(ClientID(0x1001)));
+        assert!(!handler.on_subscribe(ClientID(0x1002)));
+        assert_eq!(acl.checked_event_groups(&[EventGroupID(2)]), BTreeSet::from([EventGroupID(1), EventGroupID(2)]));
+        assert_eq!(*violations.lock().unwrap(), [
+            AccessViolation { service_id: ServiceID(0x1234), instance_id: InstanceID(1), client_id: ClientID(0x3001),
+                              access: Access::Method(MethodID(1)) },
+            AccessViolation { service_id: ServiceID(0x1234), instance_id: InstanceID(1), client_id: ClientID(0x1002),
+                              access: Access::EventGroup(EventGroupID(1)) }]);
+
+        // an offered event group without rules is closed as well
+        let closed = Arc::new(AccessControl::new().deny_unlisted());
+        let handler = AccessHandler { acl: closed, service_id: ServiceID(0x1234), instance_id: InstanceID(1),
+                                      event_group_id: EventGroupID(2) };
+        assert!(!handler.on_subscribe(ClientID(0x1001)));
+    }
+}
@@ -0,0 +1,281 @@
+// SPDX-License-Identifier: MPL-2.0
+//
+// Copyright (C) 2024 Alexander Seifarth
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+use bytes::Bytes;
+use tokio::sync::oneshot;
+use tokio::task::{JoinError, JoinSet};
+use super::{BoxFuture, Layer, LayerRequest, Next, RequestReceiver, ServiceError, ServiceSkeleton};
+use crate::codec::{self, SomeipSerialize};
+use crate::{ClientID, MessageHeader, MessageType, MethodID, ReturnCode};
+
+/// Calls the handler with the request's header and payload, returns the serialized output.
+type Handler = Box<dyn Fn(MessageHeader, Bytes) -> BoxFuture<'static, Result<Bytes, ReturnCode>> + Send + Sync>;
+
+/// Routes the requests to a service instance to the async handlers of their methods, for
+/// providers without a declared interface, see [crate::service_interface!].
+///
+/// A handler receives the header and payload of a request and returns the output, which is
+/// serialized with the skeleton's profile and sent as response, or an error [ReturnCode]. The
+/// output of a fire-and-forget request is dropped. Requests to methods without handler are
+/// answered with [ReturnCode::UnknownMethod]. The requests pass the dispatcher's [Layer]s
+/// before reaching their handler, including the ones to methods without handler.
+///
+/// [Dispatcher::run()] handles each request in its own task, so a slow handler does not hold up
+/// the requests to the other methods. Only one request is handled at a time by default, more with
+/// [Dispatcher::with_concurrency()]. The requests of a client may then overtake each other unless
+/// [Dispatcher::with_client_order()] is set. A provider that cannot keep up limits the requests waiting
+/// in the channel with [Dispatcher::with_queue_limit()], so an overload is answered with
+/// [ReturnCode::NotReady] instead of growing the channel indefinitely.
+/// ```rust,no_run
+/// use std::sync::Arc;
+/// use vsomeiprs::codec;
+/// use vsomeiprs::service::{Dispatcher, LogLayer, ServiceSkeleton};
+/// use vsomeiprs::{MethodID, ReturnCode, TypedReceivers};
+///
+/// async fn provide(skeleton: ServiceSkeleton, recv: TypedReceivers) {
+///     skeleton.offer(vec![]).unwrap();
+///     Dispatcher::new(skeleton)
+///         .with_layer(Arc::new(LogLayer))
+///         .with_concurrency(4)
+///         .with_client_order(true)
+///         .with_queue_limit(32)
+///         .on_request(MethodID(1), |_header, payload| async move {
+///             let limit: u16 = codec::decode(&payload).map_err(|_| ReturnCode::MalformedMessage)?;
+///             Ok(limit > 30)
+///         })
+///         .on_request(MethodID(2), |_header, _payload| async { Ok("v1.2".to_string()) })
+///         .run(recv.requests)
+///         .await;
+/// }
+/// ```
+pub struct Dispatcher {
+    skeleton: ServiceSkeleton,
+    handlers: HashMap<MethodID, Handler>,
+    layers: Vec<Arc<dyn Layer>>,
+    concurrency: usize,
+    client_order: bool,
+    queue_limit: Option<usize>,
+}
+
+impl Dispatcher {
+    pub fn new(skeleton: ServiceSkeleton) -> Self {
+        Dispatcher { skeleton, handlers: HashMap::new(), layers: Vec::new(), concurrency: 1, client_order: false,
+                     queue_limit: None }
+    }
+
+    pub fn skeleton(&self) -> &ServiceSkeleton {
+        &self.skeleton
+    }
+
+    /// Sets the handler of the method, replacing the previous one.
+    pub fn on_request<F, R, O>(mut self, method_id: MethodID, handler: F) -> Self
+        where F: Fn(MessageHeader, Bytes) -> R + Send + Sync + 'static,
+              R: Future<Output = Result<O, ReturnCode>> + Send + 'static,
+              O: SomeipSerialize
+    {
+        let profile = *self.skeleton.profile();
+        let handler: Handler = Box::new(move |header, payload| {
+            let output = handler(header.clone(), payload);
+            Box::pin(async move {
+                codec::encode_with(&output.await?, &profile).map_err(|e| {
+                    log::warn!("Cannot serialize response to {}: {}", header, e);
+                    ReturnCode::NotOk
+                })
+            })
+        });
+        self.handlers.insert(method_id, handler);
+        self
+    }
+
+    /// Appends a layer wrapping the handling of all requests, see [Layer] for the order.
+    pub fn with_layer(mut self, layer: Arc<dyn Layer>) -> Self {
+        self.layers.push(layer);
+        self
+    }
+
+    /// Sets the number of request tasks [Dispatcher::run()] runs concurrently, at least one.
+    /// Further requests wait in the channel.
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Sets whether the requests of a client are handled one after the other in the order they were
+    /// received, while the requests of different clients are handled concurrently. A request
+    /// waiting for the previous one of its client counts against the concurrency.
+    pub fn with_client_order(mut self, client_order: bool) -> Self {
+        self.client_order = client_order;
+        self
+    }
+
+    /// Sets the number of requests that may wait in the channel while [Dispatcher::run()] is busy.
+    /// When more are waiting the oldest ones, whose callers are the first to time out, are
+    /// answered with [ReturnCode::NotReady] without passing the layers, fire-and-forget requests
+    /// are dropped. Unlimited by default.
+    pub fn with_queue_limit(mut self, queue_limit: usize) -> Self {
+        self.queue_limit = Some(queue_limit);
+        self
+    }
+
+    /// Returns whether the method has a handler.
+    pub fn handles(&self, method_id: MethodID) -> bool {
+        self.handlers.contains_key(&method_id)
+    }
+
+    /// Answers the requests received from the channel, e.g. `TypedReceivers::requests`, until the
+    /// channel is closed and the last request was answered, with the configured concurrency and
+    /// queue limit. Must be called from within a tokio runtime. A response that could not be sent
+    /// and a panicking handler are only logged as there is no caller to report it to. Dropping the
+    /// future aborts the running request tasks.
+    pub async fn run(self, mut requests: RequestReceiver) {
+        let dispatcher = Arc::new(self);
+        let mut tasks = JoinSet::new();
+        let mut turns = Turns::default();
+        loop {
+            tokio::select! {
+                msg = requests.recv(), if tasks.len() < dispatcher.concurrency => {
+                    let Some(msg) = msg else { break };
+                    let msg = match dispatcher.queue_limit {
+                        Some(limit) => take_within_limit(&mut requests, msg, limit, |msg| dispatcher.reject(&msg)),
+                        None => msg,
+                    };
+                    let turn = dispatcher.client_order.then(|| turns.take(msg.header().client_id));
+                    let dispatcher = dispatcher.clone();
+                    tasks.spawn(async move {
+                        // the turn passes on when the task ends, also by a panic or abort
+                        let (previous, _done) = turn.unzip();
+                        if let Some(Some(previous)) = previous {
+                            let _ = previous.await;
+                        }
+                        if let Err(e) = dispatcher.dispatch(&msg).await {
+                            let address = dispatcher.skeleton.address();
+                            log::warn!("Cannot answer request to {}.{}: {}", address.service_id,
+                                       address.instance_id, e);
+                        }
+                    });
+                }
+                Some(result) = tasks.join_next(), if !tasks.is_empty() => dispatcher.joined(result),
+            }
+        }
+        while let Some(result) = tasks.join_next().await {
+            dispatcher.joined(result);
+        }
+    }
+
+    /// Passes a request to the service instance through the layers to its handler and sends the
+    /// response. Returns `false` if the message is no request to the instance.
+    pub async fn dispatch(&self, msg: &MessageType) -> Result<bool, ServiceError> {
+        let Some((header, payload)) = self.skeleton.request(msg) else {
+            return Ok(false);
+        };
+        let endpoint = |request: LayerRequest| match self.handlers.get(&request.header.method_id) {
+            Some(handler) => handler(request.header, request.payload),
+            None => Box::pin(async { Err(ReturnCode::UnknownMethod) }),
+        };
+        let request = LayerRequest { header: header.clone(), payload: payload.clone() };
+        let result = Next::new(&self.layers, |layer, request, next| layer.handle(request, next), &endpoint)
+            .run(request).await;
+        if matches!(msg, MessageType::Request { .. }) {
+            match result {
+                Ok(payload) => self.skeleton.app().send_response(header, ReturnCode::Ok, payload)?,
+                Err(return_code) => self.skeleton.app().send_error(header, return_code)?,
+            }
+        }
+        Ok(true)
+    }
+
+    fn joined(&self, result: Result<(), JoinError>) {
+        if let Err(e) = result {
+            let address = self.skeleton.address();
+            log::error!("Request task of {}.{} failed: {}", address.service_id, address.instance_id, e);
+        }
+    }
+
+    /// Answers a request exceeding the queue limit with [ReturnCode::NotReady].
+    fn reject(&self, msg: &MessageType) {
+        let Some((header, _)) = self.skeleton.request(msg) else { return };
+        log::debug!("Rejecting {}, too many queued requests", header);
+        if matches!(msg, MessageType::Request { .. }) {
+            if let Err(e) = self.skeleton.app().send_error(header, ReturnCode::NotReady) {
+                log::warn!("Cannot reject request {}: {}", header, e);
+            }
+        }
+    }
+}
+
+/// The requests of each client in the order they were received, see [Dispatcher::with_client_order()].
+#[derive(Default)]
+struct Turns {
+    last: HashMap<ClientID, oneshot::Receiver<()>>,
+}
+
+impl Turns {
+    /// Returns the end of the client's previous request to wait for and the end of the next one,
+    /// signalled by sending or dropping it.
+    fn take(&mut self, client_id: ClientID) -> (Option<oneshot::Receiver<()>>, oneshot::Sender<()>) {
+        let (done, next) = oneshot::channel();
+        (self.last.insert(client_id, next), done)
+    }
+}
+
+/// Returns the oldest request that leaves at most `limit` requests in the channel, the older ones
+/// are passed to `reject`.
+fn take_within_limit(requests: &mut RequestReceiver, mut msg: MessageType, limit: usize,
+                     mut reject: impl FnMut(MessageType)) -> MessageType
+{
+    while requests.len() > limit {
+        match requests.try_recv() {
+            Ok(next) => reject(std::mem::replace(&mut msg, next)),
+            Err(_) => break,
+        }
+    }
+    msg
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{InstanceID, MessageHeader, ServiceID};
+
+    #[test]
+    fn queue_limit_test() {
+        let (sender, mut requests) = tokio::sync::mpsc::unbounded_channel();
+        let request = |method_id| MessageType::Request {
+            header: MessageHeader::new(ServiceID(0x1234), InstanceID(1), MethodID(method_id)),
+            data: Bytes::new().into() };
+        for method_id in 2..=5 {
+            sender.send(request(method_id)).unwrap();
+        }
+        let mut rejected = Vec::new();
+        let method_id = |msg: &MessageType| msg.header().method_id;
+        let msg = take_within_limit(&mut requests, request(1), 1, |msg| rejected.push(method_id(&msg)));
+        assert_eq!(method_id(&msg), MethodID(4));
+        assert_eq!(rejected, [MethodID(1), MethodID(2), MethodID(3)]);
+        assert_eq!(requests.len(), 1);
+        let msg = take_within_limit(&mut requests, msg, 1, |_| panic!("within the limit"));
+        assert_eq!(method_id(&msg), MethodID(4));
+    }
+
+    #[test]
+    fn turns_test() {
+        let mut turns = Turns::default();
+        let (previous, first) = turns.take(ClientID(1));
+        assert!(previous.is_none());
+        let (previous, second) = turns.take(ClientID(1));
+        let mut previous = previous.unwrap();
+        assert!(turns.take(ClientID(2)).0.is_none());
+        assert_eq!(previous.try_recv(), Err(oneshot::error::TryRecvError::Empty));
+        drop(first);
+        assert_eq!(previous.try_recv(), Err(oneshot::error::TryRecvError::Closed));
+        let (previous, _third) = turns.take(ClientID(1));
+        second.send(()).unwrap();
+        assert_eq!(previous.unwrap().try_recv(), Ok(()));
+    }
+}
@@ -0,0 +1,427 @@
+// SPDX-License-Identifier: MPL-2.0
+//
+// Copyright (C) 2024 Alexander Seifarth
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::marker::PhantomData;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::watch;
+use super::{CallError, ServiceError, ServiceProxy, ServiceSkeleton};
+use crate::codec::{CodecError, SomeipDeserialize, SomeipSerialize};
+use crate::commonapi::Address;
+use crate::{Availability, EventDescriptor, EventGroupID, MessageType, MethodID, Reliability, ReturnCode, SendError};
+
+/// SOME/IP deployment of a field: the getter and setter methods and the notifier event with its
+/// event groups, each optional.
+#[derive(Eq, PartialEq, Debug, Clone)]
+pub struct FieldDeployment {
+    pub getter: Option<MethodID>,
+    pub setter: Option<MethodID>,
+    pub notifier: Option<(MethodID, Vec<EventGroupID>)>,
+    /// Calls the getter and setter over TCP.
+    pub reliable: bool,
+    /// Period of the cyclic notifications the notifier sends in addition to the ones on change.
+    pub cycle: Option<Duration>,
+}
+
+impl FieldDeployment {
+    /// Returns the descriptor of the notifier to offer it with the service instance.
+    pub fn event_descriptor(&self) -> Option<EventDescriptor> {
+        let (notifier_id, event_groups) = self.notifier.clone()?;
+        Some(EventDescriptor { cycle: self.cycle, ..EventDescriptor::field(notifier_id, event_groups) })
+    }
+
+    fn reliability(&self) -> Reliability {
+        if self.reliable { Reliability::Reliable } else { Reliability::Unreliable }
+    }
+}
+
+/// Consumer side of a field of type `T`, reading and writing its value by the getter and setter
+/// and receiving its changes by the notifier.
+/// ```rust,no_run
+/// use vsomeiprs::service::{Field, FieldDeployment, ServiceProxy};
+/// use vsomeiprs::{EventGroupID, MethodID, TypedReceivers};
+///
+/// async fn watch(proxy: ServiceProxy, recv: &mut TypedReceivers) {
+///     let deployment = FieldDeployment {
+///         getter: Some(MethodID(0x10)),
+///         setter: Some(MethodID(0x11)),
+///         notifier: Some((MethodID(0x8002), vec![EventGroupID(1)])),
+///         reliable: true,
+///         cycle: None,
+///     };
+///     let drive_mode = Field::<u8>::new(proxy, deployment);
+///     drive_mode.set(&1).await.unwrap();
+///     drive_mode.subscribe().unwrap();
+///     while let Some(msg) = recv.notifications.recv().await {
+///         if let Some(Ok(value)) = drive_mode.notification(&msg) {
+///             println!("drive mode {}", value);
+///         }
+///     }
+/// }
+/// ```
+pub struct Field<T> {
+    proxy: ServiceProxy,
+    deployment: FieldDeployment,
+    value_type: PhantomData<fn() -> T>,
+}
+
+impl<T: SomeipSerialize + SomeipDeserialize> Field<T> {
+    pub fn new(proxy: ServiceProxy, deployment: FieldDeployment) -> Self {
+        Field { proxy, deployment, value_type: PhantomData }
+    }
+
+    pub fn proxy(&self) -> &ServiceProxy {
+        &self.proxy
+    }
+
+    pub fn deployment(&self) -> &FieldDeployment {
+        &self.deployment
+    }
+
+    /// Reads the value by the getter, fails with [CallError::UnknownMethod] without sending a
+    /// request if the field has no getter.
+    pub async fn get(&self) -> Result<T, CallError> {
+        let getter = self.deployment.getter.ok_or(CallError::UnknownMethod)?;
+        self.proxy.call(getter, &(), self.deployment.reliability()).await
+    }
+
+    /// Writes the value by the setter and returns the value the provider accepted, fails with
+    /// [CallError::UnknownMethod] without sending a request if the field has no setter.
+    pub async fn set(&self, value: &T) -> Result<T, CallError> {
+        let setter = self.deployment.setter.ok_or(CallError::UnknownMethod)?;
+        self.proxy.call(setter, value, self.deployment.reliability()).await
+    }
+
+    /// Requests the notifier and subscribes to its event groups, the provider answers with the
+    /// current value as initial notification. Does nothing if the field has no notifier.
+    pub fn subscribe(&self) -> Result<(), SendError> {
+        let Some((notifier_id, event_groups)) = &self.deployment.notifier else {
+            return Ok(());
+        };
+        let Address { service_id, instance_id, .. } = *self.proxy.address();
+        let app = self.proxy.app();
+        app.request_event(service_id, instance_id, *notifier_id, event_groups.clone(), true, Reliability::Unknown);
+        for event_group_id in event_groups {
+            app.subscribe(*self.proxy.address(), *event_group_id, *notifier_id)?;
+        }
+        Ok(())
+    }
+
+    /// Returns the value if the message is a notification of the field.
+    pub fn notification(&self, msg: &MessageType) -> Option<Result<T, CodecError>> {
+        let (notifier_id, _) = self.deployment.notifier.as_ref()?;
+        match self.proxy.notification(msg)? {
+            (event_id, data) if event_id == *notifier_id => Some(self.proxy.decode(data)),
+            _ => None,
+        }
+    }
+}
+
+/// Consumer side last-value cache of a field of type `T`, so application code can read the value
+/// synchronously or watch its changes.
+///
+/// The cache is filled by the notifications passed to [FieldCache::handle()], starting with the
+/// initial notification the provider sends to each new subscription, or by
+/// [FieldCache::refresh()] for a field without notifier. It is empty until then and again after
+/// the service instance became unavailable, see [FieldCache::handle_availability()], until the
+/// initial notification of the renewed subscription arrives.
+/// ```rust,no_run
+/// use vsomeiprs::service::{Field, FieldCache, FieldDeployment, ServiceProxy};
+/// use vsomeiprs::{EventGroupID, MethodID, TypedReceivers};
+///
+/// async fn cache(proxy: ServiceProxy, mut recv: TypedReceivers) {
+///     let deployment = FieldDeployment {
+///         getter: Some(MethodID(0x10)),
+///         setter: None,
+///         notifier: Some((MethodID(0x8002), vec![EventGroupID(1)])),
+///         reliable: true,
+///         cycle: None,
+///     };
+///     let drive_mode = FieldCache::new(Field::<u8>::new(proxy, deployment));
+///     drive_mode.subscribe().unwrap();
+///     let mut changes = drive_mode.watch();
+///     tokio::spawn(async move {
+///         while changes.changed().await.is_ok() {
+///             println!("drive mode {:?}", *changes.borrow_and_update());
+///         }
+///     });
+///     loop {
+///         tokio::select! {
+///             Some(msg) = recv.notifications.recv() => { let _ = drive_mode.handle(&msg); }
+///             Some(availability) = recv.availability.recv() => drive_mode.handle_availability(&availability),
+///             else => break,
+///         }
+///     }
+/// }
+/// ```
+pub struct FieldCache<T> {
+    field: Field<T>,
+    value: watch::Sender<Option<T>>,
+}
+
+impl<T: SomeipSerialize + SomeipDeserialize> FieldCache<T> {
+    pub fn new(field: Field<T>) -> Self {
+        FieldCache { field, value: watch::Sender::new(None) }
+    }
+
+    pub fn field(&self) -> &Field<T> {
+        &self.field
+    }
+
+    /// Subscribes to the field, see [Field::subscribe()].
+    pub fn subscribe(&self) -> Result<(), SendError> {
+        self.field.subscribe()
+    }
+
+    /// Returns the last received value, `None` if there is none.
+    pub fn current(&self) -> Option<T>
+        where T: Clone
+    {
+        self.value.borrow().clone()
+    }
+
+    /// Returns a receiver of the cached value, it is marked changed on each value received and
+    /// when the value is cleared.
+    pub fn watch(&self) -> watch::Receiver<Option<T>> {
+        self.value.subscribe()
+    }
+
+    /// Stores the value if the message is a notification of the field. Returns whether it was, a
+    /// value that cannot be decoded is not stored.
+    pub fn handle(&self, msg: &MessageType) -> Result<bool, CodecError> {
+        store(&self.value, self.field.notification(msg))
+    }
+
+    /// Clears the value when the service instance of the field became unavailable, as it is no
+    /// longer current.
+    pub fn handle_availability(&self, availability: &Availability) {
+        clear_if_lost(&self.value, self.field.proxy().address(), availability);
+    }
+
+    /// Reads the value by the getter and stores it, see [Field::get()].
+    pub async fn refresh(&self) -> Result<T, CallError>
+        where T: Clone
+    {
+        let value = self.field.get().await?;
+        self.value.send_replace(Some(value.clone()));
+        Ok(value)
+    }
+}
+
+/// Stores the decoded value of a notification, returns whether there was one.
+fn store<T>(value: &watch::Sender<Option<T>>, notification: Option<Result<T, CodecError>>)
+    -> Result<bool, CodecError>
+{
+    let Some(notified) = notification else {
+        return Ok(false);
+    };
+    value.send_replace(Some(notified?));
+    Ok(true)
+}
+
+/// Clears the value if the service instance at `address` became unavailable, receivers are
+/// marked changed only if there was a value.
+fn clear_if_lost<T>(value: &watch::Sender<Option<T>>, address: &Address, availability: &Availability) {
+    if !availability.avail && availability.service_id == address.service_id
+        && availability.instance_id == address.instance_id {
+        value.send_if_modified(|value| value.take().is_some());
+    }
+}
+
+/// Provider side of a field of type `T`, storing its value, answering the requests of its getter
+/// and setter and notifying each change of the value.
+///
+/// The notifier must be offered with the service instance, see
+/// [FieldDeployment::event_descriptor()], and [FieldProvider::publish()] the initial value once
+/// offered, so that subscribers receive it as initial notification. With a
+/// [FieldDeployment::cycle] vsomeip notifies the current value cyclically as well. A validator
+/// decides which values written by the setter are accepted, see
+/// [FieldProvider::with_validator()].
+/// ```rust,no_run
+/// use std::time::Duration;
+/// use vsomeiprs::service::{FieldDeployment, FieldProvider, ServiceSkeleton};
+/// use vsomeiprs::{EventGroupID, MethodID, ReturnCode, TypedReceivers};
+///
+/// async fn provide(skeleton: ServiceSkeleton, recv: &mut TypedReceivers) {
+///     let deployment = FieldDeployment {
+///         getter: Some(MethodID(0x10)),
+///         setter: Some(MethodID(0x11)),
+///         notifier: Some((MethodID(0x8002), vec![EventGroupID(1)])),
+///         reliable: true,
+///         cycle: Some(Duration::from_secs(1)),
+///     };
+///     skeleton.offer(deployment.event_descriptor().into_iter().collect()).unwrap();
+///     let drive_mode = FieldProvider::new(skeleton.clone(), deployment, 0u8)
+///         .with_validator(|_current, value| if value <= 3 { Ok(value) } else { Err(ReturnCode::NotOk) });
+///     drive_mode.publish().unwrap();
+///     while let Some(msg) = recv.requests.recv().await {
+///         drive_mode.handle(&msg).unwrap();
+///     }
+/// }
+/// ```
+pub struct FieldProvider<T> {
+    skeleton: ServiceSkeleton,
+    deployment: FieldDeployment,
+    value: Mutex<T>,
+    validator: Option<Box<Validator<T>>>,
+}
+
+/// Gets the current and the written value, returns the value to store or the return code of the
+/// error response.
+type Validator<T> = dyn Fn(&T, T) -> Result<T, ReturnCode> + Send + Sync;
+
+impl<T> FieldProvider<T>
+    where T: SomeipSerialize + SomeipDeserialize + PartialEq + Clone
+{
+    pub fn new(skeleton: ServiceSkeleton, deployment: FieldDeployment, initial: T) -> Self {
+        FieldProvider { skeleton, deployment, value: Mutex::new(initial), validator: None }
+    }
+
+    /// Validates the values written by the setter. The validator gets the current and the written
+    /// value and returns the value to store, e.g. the written value clamped to its range, or the
+    /// return code the setter is answered with, the value is kept then. Without a validator all
+    /// written values are stored. The validator is called with the field locked, so it must not
+    /// access the field itself.
+    pub fn with_validator(mut self, validator: impl Fn(&T, T) -> Result<T, ReturnCode> + Send + Sync + 'static)
+        -> Self
+    {
+        self.validator = Some(Box::new(validator));
+        self
+    }
+
+    pub fn skeleton(&self) -> &ServiceSkeleton {
+        &self.skeleton
+    }
+
+    pub fn deployment(&self) -> &FieldDeployment {
+        &self.deployment
+    }
+
+    pub fn get(&self) -> T {
+        self.value.lock().unwrap().clone()
+    }
+
+    /// Stores the value and notifies it if it differs from the current one. Returns whether the
+    /// value changed.
+    pub fn set(&self, value: T) -> Result<bool, ServiceError> {
+        self.update(&mut self.value.lock().unwrap(), value)
+    }
+
+    /// Notifies the current value, e.g. the initial value after offering the service instance.
+    pub fn publish(&self) -> Result<(), ServiceError> {
+        self.notify(&self.value.lock().unwrap())
+    }
+
+    /// Answers a request to the getter with the current value and a request to the setter with
+    /// the stored value after [FieldProvider::set()] of the validated value. A value that cannot be
+    /// deserialized is answered with [ReturnCode::MalformedMessage], one rejected by the validator
+    /// with its return code. Requests without return are not answered, a valid value is stored
+    /// nonetheless. Returns `false` if the message is no request to the field. If notifying the
+    /// written value fails, the setter is answered before the error is returned.
+    /// The field stays locked from validating the written value until it is stored, so concurrent
+    /// writes cannot interleave.
+    pub fn handle(&self, msg: &MessageType) -> Result<bool, ServiceError> {
+        let Some((header, data)) = self.skeleton.request(msg) else {
+            return Ok(false);
+        };
+        let expects_response = matches!(msg, MessageType::Request { .. });
+        if self.deployment.getter == Some(header.method_id) {
+            if expects_response {
+                self.skeleton.respond(header, Ok(self.get()))?;
+            }
+        } else if self.deployment.setter == Some(header.method_id) {
+            let mut current = self.value.lock().unwrap();
+            match self.skeleton.decode::<T>(data).map_err(|_| ReturnCode::MalformedMessage)
+                .and_then(|value| self.validate(&current, value))
+            {
+                Ok(value) => {
+                    // the value is stored also if notifying it fails, the setter is answered first
+                    let notified = self.update(&mut current, value);
+                    let stored = current.clone();
+                    drop(current);
+                    if expects_response {
+                        self.skeleton.respond(header, Ok(stored))?;
+                    }
+                    notified?;
+                }
+                Err(return_code) => {
+                    drop(current);
+                    if expects_response {
+                        self.skeleton.respond::<()>(header, Err(return_code))?
+                    }
+                }
+            }
+        } else {
+            return Ok(false);
+        }
+        Ok(true)
+    }
+
+    fn validate(&self, current: &T, value: T) -> Result<T, ReturnCode> {
+        match &self.validator {
+            Some(validator) => validator(current, value),
+            None => Ok(value),
+        }
+    }
+
+    /// Stores the value into the locked `current` one and notifies it if it differs.
+    fn update(&self, current: &mut T, value: T) -> Result<bool, ServiceError> {
+        if *current == value {
+            return Ok(false);
+        }
+        *current = value;
+        self.notify(current)?;
+        Ok(true)
+    }
+
+    fn notify(&self, value: &T) -> Result<(), ServiceError> {
+        match &self.deployment.notifier {
+            Some((notifier_id, _)) => self.skeleton.notify(*notifier_id, value),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{InstanceID, InterfaceVersion, ServiceID};
+
+    #[test]
+    fn field_cache_test() {
+        let value = watch::Sender::new(None);
+        let mut changes = value.subscribe();
+        assert_eq!(store(&value, None::<Result<u8, CodecError>>), Ok(false));
+        assert!(!changes.has_changed().unwrap());
+
+        assert_eq!(store(&value, Some(Ok(1u8))), Ok(true));
+        assert!(changes.has_changed().unwrap());
+        assert_eq!(*changes.borrow_and_update(), Some(1));
+        // a value that cannot be decoded keeps the last one
+        assert!(store(&value, Some(Err(CodecError::Unsupported("test")))).is_err());
+        assert_eq!(*value.borrow(), Some(1));
+        // each value received marks the receivers changed, also the same one
+        assert_eq!(store(&value, Some(Ok(1u8))), Ok(true));
+        assert!(changes.has_changed().unwrap());
+        changes.mark_unchanged();
+
+        let address = Address::new(ServiceID(1), InstanceID(1), InterfaceVersion::make_version(1, 0));
+        let availability = |instance, avail| Availability { service_id: ServiceID(1),
+            instance_id: InstanceID(instance), avail, version: InterfaceVersion::make_version(1, 0) };
+        clear_if_lost(&value, &address, &availability(1, true));
+        clear_if_lost(&value, &address, &availability(2, false));
+        assert_eq!(*value.borrow(), Some(1));
+        assert!(!changes.has_changed().unwrap());
+
+        clear_if_lost(&value, &address, &availability(1, false));
+        assert!(changes.has_changed().unwrap());
+        assert_eq!(*changes.borrow_and_update(), None);
+        // clearing an empty cache is no change
+        clear_if_lost(&value, &address, &availability(1, false));
+        assert!(!changes.has_changed().unwrap());
+    }
+}
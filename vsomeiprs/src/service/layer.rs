@@ -0,0 +1,131 @@
+// SPDX-License-Identifier: MPL-2.0
+//
+// Copyright (C) 2024 Alexander Seifarth
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Instant;
+use bytes::Bytes;
+use super::CallError;
+use crate::{MessageHeader, ReturnCode};
+
+/// Boxed future returned by the methods of a [Layer].
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// Serialized request passing the layers, the header's `client_id` and `session_id` of an
+/// outgoing call are not yet known.
+#[derive(Debug, Clone)]
+pub struct LayerRequest {
+    pub header: MessageHeader,
+    pub payload: Bytes,
+}
+
+/// A layer wraps the method calls of a [super::ServiceProxy] and the requests handled by a
+/// [super::Dispatcher], e.g. for logging, authorization, metrics or payload transformation.
+///
+/// A layer gets the serialized request and the rest of the chain, it may change the request,
+/// answer it without calling [Next::run()], and change the serialized response or the error.
+/// Both methods have pass-through default implementations, so a layer only needs to implement
+/// the side it cares about.
+///
+/// Layers are applied in the order they were added, the layer added first is the outermost one
+/// and sees the request first and the response last. Unlike an [crate::Interceptor] a layer
+/// sees a call as a whole and may await, but fire-and-forget calls of a proxy and notifications
+/// do not pass it. Fire-and-forget requests to a dispatcher do, their output is dropped.
+/// ```rust
+/// use vsomeiprs::ReturnCode;
+/// use bytes::Bytes;
+/// use vsomeiprs::service::{BoxFuture, Layer, LayerRequest, Next};
+///
+/// /// Rejects requests of clients other than the diagnosis client.
+/// struct Authorize;
+///
+/// impl Layer for Authorize {
+///     fn handle<'a>(&'a self, request: LayerRequest, next: Next<'a, ReturnCode>)
+///         -> BoxFuture<'a, Result<Bytes, ReturnCode>>
+///     {
+///         match request.header.client_id.0 {
+///             0x1001 => next.run(request),
+///             _ => Box::pin(async { Err(ReturnCode::NotOk) }),
+///         }
+///     }
+/// }
+/// ```
+pub trait Layer: Send + Sync {
+    /// Called for a request received by a [super::Dispatcher], returns the serialized output or
+    /// the return code of the error response.
+    fn handle<'a>(&'a self, request: LayerRequest, next: Next<'a, ReturnCode>)
+        -> BoxFuture<'a, Result<Bytes, ReturnCode>>
+    {
+        next.run(request)
+    }
+
+    /// Called for a method call of a [super::ServiceProxy], returns the payload of the response.
+    fn call<'a>(&'a self, request: LayerRequest, next: Next<'a, CallError>) -> BoxFuture<'a, Result<Bytes, CallError>> {
+        next.run(request)
+    }
+}
+
+type Endpoint<'a, E> = dyn Fn(LayerRequest) -> BoxFuture<'a, Result<Bytes, E>> + Send + Sync + 'a;
+
+type Apply<'a, E> = fn(&'a dyn Layer, LayerRequest, Next<'a, E>) -> BoxFuture<'a, Result<Bytes, E>>;
+
+/// The layers after the current one and the handler or transport at the end of the chain.
+pub struct Next<'a, E> {
+    layers: &'a [Arc<dyn Layer>],
+    apply: Apply<'a, E>,
+    endpoint: &'a Endpoint<'a, E>,
+}
+
+impl<'a, E> Next<'a, E> {
+    pub(crate) fn new(layers: &'a [Arc<dyn Layer>], apply: Apply<'a, E>, endpoint: &'a Endpoint<'a, E>) -> Self {
+        Next { layers, apply, endpoint }
+    }
+
+    /// Passes the request to the next layer or the end of the chain.
+    pub fn run(self, request: LayerRequest) -> BoxFuture<'a, Result<Bytes, E>> {
+        match self.layers.split_first() {
+            Some((layer, layers)) => (self.apply)(layer.as_ref(), request, Next { layers, ..self }),
+            None => (self.endpoint)(request),
+        }
+    }
+}
+
+/// A layer may pass a request more than once, e.g. to retry a call.
+impl<E> Clone for Next<'_, E> {
+    fn clone(&self) -> Self {
+        Next { layers: self.layers, apply: self.apply, endpoint: self.endpoint }
+    }
+}
+
+/// Layer that logs all calls and handled requests with their duration with `log::debug!`.
+#[derive(Default)]
+pub struct LogLayer;
+
+impl Layer for LogLayer {
+    fn handle<'a>(&'a self, request: LayerRequest, next: Next<'a, ReturnCode>)
+        -> BoxFuture<'a, Result<Bytes, ReturnCode>>
+    {
+        Box::pin(async move {
+            let header = request.header.clone();
+            let start = Instant::now();
+            let result = next.run(request).await;
+            log::debug!("handled {} in {:?}: {:?}", header, start.elapsed(), result);
+            result
+        })
+    }
+
+    fn call<'a>(&'a self, request: LayerRequest, next: Next<'a, CallError>) -> BoxFuture<'a, Result<Bytes, CallError>> {
+        Box::pin(async move {
+            let header = request.header.clone();
+            let start = Instant::now();
+            let result = next.run(request).await;
+            log::debug!("called {} in {:?}: {:?}", header, start.elapsed(), result);
+            result
+        })
+    }
+}
@@ -0,0 +1,171 @@
+// SPDX-License-Identifier: MPL-2.0
+//
+// Copyright (C) 2024 Alexander Seifarth
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use bytes::Bytes;
+use tokio::time::Instant;
+use super::{BoxFuture, Layer, LayerRequest, Next};
+use crate::{ClientID, ReturnCode};
+
+/// Handling of a request exceeding the rate of its client.
+#[derive(Eq, PartialEq, Debug, Clone, Copy, Default)]
+pub enum Overflow {
+    /// Answers the request with [ReturnCode::NotReady], drops a fire-and-forget request.
+    #[default]
+    Reject,
+    /// Holds the request back until the rate permits it, it occupies its slot of the
+    /// [super::Dispatcher] in the meantime.
+    Delay,
+}
+
+/// Requests a client may send: a burst of `burst` requests, refilled by one each `period`.
+#[derive(Eq, PartialEq, Debug, Clone, Copy)]
+pub struct Limit {
+    pub burst: u32,
+    pub period: Duration,
+}
+
+impl Limit {
+    pub fn new(burst: u32, period: Duration) -> Self {
+        Limit { burst: burst.max(1), period }
+    }
+
+    /// Limit of `rate` requests per second, also as burst.
+    pub fn per_second(rate: u32) -> Self {
+        let rate = rate.max(1);
+        Limit::new(rate, Duration::from_secs(1) / rate)
+    }
+
+    /// Returns how long a request at `now` has to wait, zero if it may pass. Takes the token of a
+    /// request that may pass or, if `reserve` is set, of the one after the wait. `next` is the
+    /// time the bucket would be full again.
+    fn acquire(&self, next: &mut Instant, now: Instant, reserve: bool) -> Duration {
+        let start = (*next).max(now);
+        let tolerance = self.period * self.burst.saturating_sub(1);
+        let wait = start.saturating_duration_since(now).saturating_sub(tolerance);
+        if wait.is_zero() || reserve {
+            *next = start + self.period;
+        }
+        wait
+    }
+}
+
+/// Layer limiting the rate of the requests of each client with a token bucket, so a misbehaving
+/// client cannot starve the others of a [super::Dispatcher].
+///
+/// Each client gets the default [Limit] unless a client specific one is set. The requests
+/// exceeding it are rejected or delayed, see [Overflow]. Fire-and-forget requests are limited
+/// as well, a rejected one is dropped silently as it has no response.
+/// ```rust,no_run
+/// use std::sync::Arc;
+/// use std::time::Duration;
+/// use vsomeiprs::service::{Dispatcher, Limit, Overflow, RateLimit, ServiceSkeleton};
+/// use vsomeiprs::ClientID;
+///
+/// fn limit(skeleton: ServiceSkeleton) -> Dispatcher {
+///     let rate_limit = RateLimit::new(Limit::per_second(10))
+///         .with_client_limit(ClientID(0x1001), Limit::new(50, Duration::from_millis(10)))
+///         .with_overflow(Overflow::Delay);
+///     Dispatcher::new(skeleton).with_layer(Arc::new(rate_limit))
+/// }
+/// ```
+#[derive(Debug)]
+pub struct RateLimit {
+    limit: Limit,
+    client_limits: HashMap<ClientID, Limit>,
+    overflow: Overflow,
+    buckets: Mutex<HashMap<ClientID, Instant>>,
+}
+
+impl RateLimit {
+    /// Rate limit of all clients rejecting the exceeding requests.
+    pub fn new(limit: Limit) -> Self {
+        RateLimit { limit, client_limits: HashMap::new(), overflow: Overflow::default(),
+                    buckets: Mutex::new(HashMap::new()) }
+    }
+
+    /// Sets the limit of the client, replacing the default one.
+    pub fn with_client_limit(mut self, client_id: ClientID, limit: Limit) -> Self {
+        self.client_limits.insert(client_id, limit);
+        self
+    }
+
+    pub fn with_overflow(mut self, overflow: Overflow) -> Self {
+        self.overflow = overflow;
+        self
+    }
+
+    /// Returns how long the request of the client at `now` is delayed, `None` if it is rejected.
+    fn acquire(&self, client_id: ClientID, now: Instant) -> Option<Duration> {
+        let limit = self.client_limits.get(&client_id).unwrap_or(&self.limit);
+        let mut buckets = self.buckets.lock().unwrap();
+        if !buckets.contains_key(&client_id) {
+            // a full bucket is the same as none, so the buckets of clients gone do not pile up
+            buckets.retain(|_, next| *next > now);
+        }
+        let next = buckets.entry(client_id).or_insert(now);
+        let wait = limit.acquire(next, now, self.overflow == Overflow::Delay);
+        match self.overflow {
+            Overflow::Reject if !wait.is_zero() => None,
+            _ => Some(wait),
+        }
+    }
+}
+
+impl Layer for RateLimit {
+    fn handle<'a>(&'a self, request: LayerRequest, next: Next<'a, ReturnCode>)
+        -> BoxFuture<'a, Result<Bytes, ReturnCode>>
+    {
+        match self.acquire(request.header.client_id, Instant::now()) {
+            Some(wait) if wait.is_zero() => next.run(request),
+            Some(wait) => Box::pin(async move {
+                log::debug!("Delaying {} by {:?}, rate limit exceeded", request.header, wait);
+                tokio::time::sleep(wait).await;
+                next.run(request).await
+            }),
+            None => {
+                log::debug!("Rejecting {}, rate limit exceeded", request.header);
+                Box::pin(async { Err(ReturnCode::NotReady) })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn rate_limit_test() {
+        let ms = Duration::from_millis;
+        let now = Instant::now();
+        let rate_limit = RateLimit::new(Limit::new(2, ms(100)))
+            .with_client_limit(ClientID(2), Limit::new(1, ms(10)));
+        assert_eq!(rate_limit.acquire(ClientID(1), now), Some(Duration::ZERO));
+        assert_eq!(rate_limit.acquire(ClientID(1), now), Some(Duration::ZERO));
+        assert_eq!(rate_limit.acquire(ClientID(1), now), None);
+        assert_eq!(rate_limit.acquire(ClientID(1), now + ms(50)), None);
+        assert_eq!(rate_limit.acquire(ClientID(1), now + ms(100)), Some(Duration::ZERO));
+        assert_eq!(rate_limit.acquire(ClientID(2), now), Some(Duration::ZERO));
+        assert_eq!(rate_limit.acquire(ClientID(2), now + ms(5)), None);
+        assert_eq!(rate_limit.acquire(ClientID(2), now + ms(10)), Some(Duration::ZERO));
+        assert_eq!(rate_limit.buckets.lock().unwrap().len(), 2);
+        // the buckets full again are evicted when a new client arrives
+        assert_eq!(rate_limit.acquire(ClientID(3), now + ms(300)), Some(Duration::ZERO));
+        assert_eq!(rate_limit.buckets.lock().unwrap().keys().collect::<Vec<_>>(), [&ClientID(3)]);
+
+        let rate_limit = RateLimit::new(Limit::new(2, ms(100))).with_overflow(Overflow::Delay);
+        assert_eq!(rate_limit.acquire(ClientID(1), now), Some(Duration::ZERO));
+        assert_eq!(rate_limit.acquire(ClientID(1), now), Some(Duration::ZERO));
+        assert_eq!(rate_limit.acquire(ClientID(1), now), Some(ms(100)));
+        assert_eq!(rate_limit.acquire(ClientID(1), now), Some(ms(200)));
+        assert_eq!(rate_limit.acquire(ClientID(1), now + ms(1000)), Some(Duration::ZERO));
+        assert_eq!(Limit::per_second(4), Limit::new(4, ms(250)));
+    }
+}
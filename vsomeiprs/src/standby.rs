@@ -0,0 +1,215 @@
+// SPDX-License-Identifier: MPL-2.0
+//
+// Copyright (C) 2024 Alexander Seifarth
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Role coordination of redundant (hot-standby) providers.
+//!
+//! vsomeip accepts several offers of the same service instance and routes requests to the first
+//! provider only, the others are standby without any indication of that. A [HotStandby]
+//! coordinator tells a provider its role with a heartbeat method convention:
+//! - every provider requests its own service instance and periodically calls the heartbeat
+//!   method ([HotStandby::heartbeat()]),
+//! - the heartbeat is answered with the token identifying the responding provider, i.e. by the
+//!   active provider, as vsomeip routes the requests to it,
+//! - a provider receiving its own token is [Role::Active], otherwise [Role::Standby]. Without a
+//!   heartbeat response for [MAX_MISSED_HEARTBEATS] periods the role is [Role::Unknown].
+//!
+//! Like the [crate::commonapi] types the coordinator is passive: the application passes all
+//! received messages to [HotStandby::handle()] and calls [HotStandby::heartbeat()] from a timer.
+//! ```rust,no_run
+//! use std::sync::Arc;
+//! use std::time::Duration;
+//! use vsomeiprs::commonapi::Address;
+//! use vsomeiprs::standby::HotStandby;
+//! use vsomeiprs::{InstanceID, InterfaceVersion, MethodID, ServiceID, VSomeipApplication};
+//!
+//! async fn run() {
+//!     let (app, mut recv) = VSomeipApplication::create("provider").unwrap();
+//!     let address = Address::new(ServiceID(0x1234), InstanceID(1), InterfaceVersion::make_version(1, 0));
+//!     let standby = HotStandby::new(Arc::new(app), address, MethodID(0x7fff), std::process::id() as u64);
+//!     standby.get_role_event().subscribe(|role| println!("role {:?}", role));
+//!     standby.start().expect("service offered");
+//!     let mut interval = tokio::time::interval(Duration::from_millis(500));
+//!     loop {
+//!         tokio::select! {
+//!             Some(msg) = recv.recv() => { standby.handle(&msg); }
+//!             _ = interval.tick() => standby.heartbeat(),
+//!         }
+//!     }
+//! }
+//! ```
+
+use std::sync::{Arc, Mutex};
+use bytes::Bytes;
+use super::commonapi::{Address, Event};
+use super::{MessageType, MethodID, Reliability, ReturnCode, SendError, SessionID, VSomeipApplication, VSomeipMessage};
+
+/// Number of heartbeat periods without response after which the role becomes [Role::Unknown].
+pub const MAX_MISSED_HEARTBEATS: u32 = 3;
+
+/// Role of a redundant provider.
+#[derive(Eq, PartialEq, Debug, Clone, Copy)]
+pub enum Role {
+    /// No heartbeat response (yet), e.g. while the service is not available.
+    Unknown,
+    /// This provider receives the requests of the service instance.
+    Active,
+    /// Another provider receives the requests, this one takes over when it disappears.
+    Standby,
+}
+
+struct State {
+    role: Role,
+    available: bool,
+    pending: Option<SessionID>,
+    missed: u32,
+}
+
+/// Coordinates the role of one of several providers of a service instance.
+/// The service is released and no longer offered when the coordinator is dropped.
+pub struct HotStandby {
+    app: Arc<VSomeipApplication>,
+    address: Address,
+    heartbeat_method: MethodID,
+    token: Bytes,
+    state: Mutex<State>,
+    role_event: Event<Role>,
+}
+
+impl HotStandby {
+    /// Creates the coordinator, `token` must be unique among the providers of the instance.
+    /// The heartbeat method must not be used by the service interface.
+    pub fn new(app: Arc<VSomeipApplication>, address: Address, heartbeat_method: MethodID, token: u64) -> Self {
+        HotStandby { app, address, heartbeat_method, token: Bytes::copy_from_slice(&token.to_be_bytes()),
+            state: Mutex::new(State { role: Role::Unknown, available: false, pending: None, missed: 0 }),
+            role_event: Event::default() }
+    }
+
+    /// Offers the service instance and requests it to observe its availability.
+    pub fn start(&self) -> Result<(), SendError> {
+        self.app.offer_service(self.address)?;
+        self.app.request_service(self.address);
+        Ok(())
+    }
+
+    pub fn get_role(&self) -> Role {
+        self.state.lock().unwrap().role
+    }
+
+    /// Event fired on each role change.
+    pub fn get_role_event(&self) -> &Event<Role> {
+        &self.role_event
+    }
+
+    /// Sends the next heartbeat request, to be called periodically.
+    pub fn heartbeat(&self) {
+        let mut state = self.state.lock().unwrap();
+        let mut changed = None;
+        if state.pending.take().is_some() {
+            state.missed += 1;
+            if state.missed >= MAX_MISSED_HEARTBEATS {
+                changed = update_role(&mut state, Role::Unknown);
+            }
+        }
+        if state.available {
+            // the state stays locked so the response cannot be handled before the session is stored
+            match self.app.send_request(self.address, self.heartbeat_method, Bytes::new(), Reliability::Unknown) {
+                Ok(session) => state.pending = Some(session),
+                Err(e) => log::warn!("Cannot send heartbeat: {}", e),
+            }
+        }
+        drop(state);
+        self.fire(changed);
+    }
+
+    /// Processes a message received by the application: answers heartbeat requests and evaluates
+    /// heartbeat responses. Returns whether the message belonged to the coordinator.
+    pub fn handle(&self, msg: &VSomeipMessage) -> bool {
+        match msg {
+            VSomeipMessage::ServiceAvailability { service_id, instance_id, avail, .. }
+                if *service_id == self.address.service_id && *instance_id == self.address.instance_id =>
+            {
+                let mut state = self.state.lock().unwrap();
+                state.available = *avail;
+                let mut changed = None;
+                if !*avail {
+                    state.pending = None;
+                    changed = update_role(&mut state, Role::Unknown);
+                }
+                drop(state);
+                self.fire(changed);
+                false
+            }
+            VSomeipMessage::Message(msg) => {
+                let header = msg.header();
+                if header.service_id != self.address.service_id || header.instance_id != self.address.instance_id
+                    || header.method_id != self.heartbeat_method {
+                    return false;
+                }
+                match msg {
+                    MessageType::Request { header, .. } => {
+                        if let Err(e) = self.app.send_response(header, ReturnCode::Ok, &self.token) {
+                            log::warn!("Cannot answer heartbeat: {}", e);
+                        }
+                    }
+                    MessageType::Response { header, data } => {
+                        let mut state = self.state.lock().unwrap();
+                        if state.pending == Some(header.session_id) {
+                            state.pending = None;
+                            state.missed = 0;
+                            let changed = update_role(&mut state, role_for(data.as_bytes_ref(), &self.token));
+                            drop(state);
+                            self.fire(changed);
+                        }
+                    }
+                    _ => {}
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Fires the role event outside of the state lock, so listeners may query the coordinator.
+    fn fire(&self, changed: Option<Role>) {
+        if let Some(role) = changed {
+            self.role_event.fire(&role);
+        }
+    }
+}
+
+/// Sets the role, returns it if it changed.
+fn update_role(state: &mut State, role: Role) -> Option<Role> {
+    if state.role == role {
+        return None;
+    }
+    state.role = role;
+    Some(role)
+}
+
+impl Drop for HotStandby {
+    fn drop(&mut self) {
+        self.app.stop_offer_service(self.address);
+        self.app.release_service(self.address);
+    }
+}
+
+/// Role derived from the token of the provider answering the heartbeat.
+fn role_for(responder: &Bytes, token: &Bytes) -> Role {
+    if responder == token { Role::Active } else { Role::Standby }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn role_test() {
+        let token = Bytes::copy_from_slice(&7u64.to_be_bytes());
+        assert_eq!(role_for(&Bytes::copy_from_slice(&7u64.to_be_bytes()), &token), Role::Active);
+        assert_eq!(role_for(&Bytes::copy_from_slice(&8u64.to_be_bytes()), &token), Role::Standby);
+    }
+}
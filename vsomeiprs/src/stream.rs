@@ -0,0 +1,72 @@
+// SPDX-License-Identifier: MPL-2.0
+//
+// Copyright (C) 2024 Alexander Seifarth
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use futures_core::Stream;
+use tokio::sync::mpsc::UnboundedReceiver;
+use super::{EventGroupID, InstanceID, MessageHeader, MessageType, MethodID, ServiceID, VSomeipApplication,
+            VSomeipMessage, VSomeipPayload};
+
+/// Event or field notification yielded by a [NotificationStream].
+#[derive(Debug)]
+pub struct Notification {
+    pub header: MessageHeader,
+    /// `true` for the initial value of a field sent after subscribing.
+    pub is_initial: bool,
+    pub data: VSomeipPayload,
+}
+
+/// Stream of the notifications of one event, returned by
+/// [VSomeipApplication::subscribe_stream()].
+///
+/// The notifications are delivered through a dedicated message handler, so they do not appear in
+/// the application's main channel while the stream exists. Dropping the stream unsubscribes and
+/// releases the event and unregisters the handler, whose context is freed right away as vsomeip
+/// does not call the handler anymore. The subscriptions of other events of the event group are
+/// kept.
+pub struct NotificationStream<'a> {
+    app: &'a VSomeipApplication,
+    service_id: ServiceID,
+    instance_id: InstanceID,
+    event_group_id: EventGroupID,
+    notifier_id: MethodID,
+    recv: UnboundedReceiver<VSomeipMessage>,
+}
+
+impl<'a> NotificationStream<'a> {
+    pub(crate) fn new(app: &'a VSomeipApplication, service_id: ServiceID, instance_id: InstanceID,
+                      event_group_id: EventGroupID, notifier_id: MethodID,
+                      recv: UnboundedReceiver<VSomeipMessage>) -> Self {
+        NotificationStream { app, service_id, instance_id, event_group_id, notifier_id, recv }
+    }
+}
+
+impl Stream for NotificationStream<'_> {
+    type Item = Notification;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            match self.recv.poll_recv(cx) {
+                Poll::Ready(Some(VSomeipMessage::Message(MessageType::Notification { header, is_initial, data }))) =>
+                    return Poll::Ready(Some(Notification { header, is_initial, data })),
+                // the handler also receives responses of a method sharing the notifier's id
+                Poll::Ready(Some(_)) => continue,
+                Poll::Ready(None) => return Poll::Ready(None),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+impl Drop for NotificationStream<'_> {
+    fn drop(&mut self) {
+        self.app.unsubscribe_event(self.service_id, self.instance_id, self.event_group_id, self.notifier_id);
+        self.app.release_event(self.service_id, self.instance_id, self.notifier_id);
+        self.app.unregister_message_handler(self.service_id, self.instance_id, self.notifier_id);
+    }
+}
@@ -0,0 +1,135 @@
+// SPDX-License-Identifier: MPL-2.0
+//
+// Copyright (C) 2024 Alexander Seifarth
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use super::{ClientID, EventGroupID, InstanceID, MethodID, Payload, ServiceID};
+
+/// A subscription handler is invoked when a client subscribes to or unsubscribes from an event
+/// group of an offered service instance, see
+/// [crate::VSomeipApplication::register_subscription_handler()].
+///
+/// It decides whether a subscription is accepted and provides the initial values sent to a new
+/// subscriber only, instead of notifying all subscribers with `force_notification`. The values are
+/// stored as the current values of the events before the subscription is accepted, vsomeip sends
+/// them to the new subscriber as initial notifications once the subscription is established.
+/// Subscribers already there are notified only if a value changed. vsomeip sends initial
+/// notifications of fields only, see [crate::EventKind::Field]. All methods have default
+/// implementations accepting every subscription without initial values.
+///
+/// The handler is called from the vsomeip dispatcher threads, so it must not block.
+pub trait SubscriptionHandler: Send + Sync {
+    /// Returns whether the subscription of the client is accepted.
+    fn on_subscribe(&self, _client_id: ClientID) -> bool {
+        true
+    }
+
+    /// Called when the client unsubscribed.
+    fn on_unsubscribe(&self, _client_id: ClientID) {}
+
+    /// Returns the current values of the events of the event group, each is sent as initial
+    /// notification to the accepted subscriber once its subscription is established.
+    fn initial_values(&self, _client_id: ClientID) -> Vec<(MethodID, Payload)> {
+        Vec::new()
+    }
+}
+
+/// Subscription handler accepting all subscriptions and sending the current value of a field to
+/// each new subscriber. The closure returns the value, `None` if there is none yet.
+/// ```rust,no_run
+/// use std::sync::{Arc, Mutex};
+/// use vsomeiprs::{EventGroupID, InitialValue, InstanceID, MethodID, Payload, ServiceID, VSomeipApplication};
+///
+/// fn provide(app: &VSomeipApplication, speed: Arc<Mutex<Option<u16>>>) {
+///     let value = move |_client| speed.lock().unwrap().map(|speed| Payload::from(speed.to_be_bytes().to_vec()));
+///     app.register_subscription_handler(ServiceID(0x1234), InstanceID(1), EventGroupID(1),
+///                                       Arc::new(InitialValue::new(MethodID(0x8001), value)));
+/// }
+/// ```
+pub struct InitialValue<F> {
+    notifier_id: MethodID,
+    value: F,
+}
+
+impl<F: Fn(ClientID) -> Option<Payload> + Send + Sync> InitialValue<F> {
+    pub fn new(notifier_id: MethodID, value: F) -> Self {
+        InitialValue { notifier_id, value }
+    }
+}
+
+impl<F: Fn(ClientID) -> Option<Payload> + Send + Sync> SubscriptionHandler for InitialValue<F> {
+    fn initial_values(&self, client_id: ClientID) -> Vec<(MethodID, Payload)> {
+        (self.value)(client_id).map(|payload| (self.notifier_id, payload)).into_iter().collect()
+    }
+}
+
+type EventGroupKey = (ServiceID, InstanceID, EventGroupID);
+
+/// Subscription handlers of the event groups of the offered service instances.
+#[derive(Default)]
+pub(crate) struct SubscriptionHandlers {
+    handlers: RwLock<HashMap<EventGroupKey, Arc<dyn SubscriptionHandler>>>,
+}
+
+impl SubscriptionHandlers {
+    pub(crate) fn insert(&self, service_id: ServiceID, instance_id: InstanceID, event_group_id: EventGroupID,
+                         handler: Arc<dyn SubscriptionHandler>)
+    {
+        self.handlers.write().unwrap().insert((service_id, instance_id, event_group_id), handler);
+    }
+
+    pub(crate) fn remove(&self, service_id: ServiceID, instance_id: InstanceID, event_group_id: EventGroupID) {
+        self.handlers.write().unwrap().remove(&(service_id, instance_id, event_group_id));
+    }
+
+    /// Returns the handler of the event group, the lock is not held while it is called.
+    pub(crate) fn get(&self, service_id: ServiceID, instance_id: InstanceID, event_group_id: EventGroupID)
+        -> Option<Arc<dyn SubscriptionHandler>>
+    {
+        self.handlers.read().unwrap().get(&(service_id, instance_id, event_group_id)).cloned()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    struct Allowed(ClientID);
+
+    impl SubscriptionHandler for Allowed {
+        fn on_subscribe(&self, client_id: ClientID) -> bool {
+            client_id == self.0
+        }
+    }
+
+    #[test]
+    fn subscription_handlers_test() {
+        let handlers = SubscriptionHandlers::default();
+        handlers.insert(ServiceID(1), InstanceID(1), EventGroupID(1), Arc::new(Allowed(ClientID(0x10))));
+        let handler = handlers.get(ServiceID(1), InstanceID(1), EventGroupID(1)).unwrap();
+        assert!(handler.on_subscribe(ClientID(0x10)));
+        assert!(!handler.on_subscribe(ClientID(0x11)));
+        assert!(handler.initial_values(ClientID(0x10)).is_empty());
+        assert!(handlers.get(ServiceID(1), InstanceID(1), EventGroupID(2)).is_none());
+        handlers.remove(ServiceID(1), InstanceID(1), EventGroupID(1));
+        assert!(handlers.get(ServiceID(1), InstanceID(1), EventGroupID(1)).is_none());
+    }
+
+    #[test]
+    fn initial_value_test() {
+        let handler = InitialValue::new(MethodID(0x8001), |client: ClientID| match client.0 {
+            0x10 => Some(Payload::from(vec![1, 2])),
+            _ => None,
+        });
+        assert!(handler.on_subscribe(ClientID(0x11)));
+        let values = handler.initial_values(ClientID(0x10));
+        assert_eq!(values.len(), 1);
+        assert_eq!(values[0].0, MethodID(0x8001));
+        assert_eq!(values[0].1.as_ref(), [1, 2]);
+        assert!(handler.initial_values(ClientID(0x11)).is_empty());
+    }
+}
@@ -0,0 +1,58 @@
+// SPDX-License-Identifier: MPL-2.0
+//
+// Copyright (C) 2024 Alexander Seifarth
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use super::{EventGroupID, InstanceID, MethodID, ServiceID, VSomeipApplication};
+
+/// Subscription of an event, returned by [VSomeipApplication::subscribe_scoped()].
+///
+/// Dropping the guard unsubscribes and releases the event, also when the consumer's task ends
+/// early or panics. The subscriptions of other events of the event group are kept.
+/// [SubscriptionGuard::release()] keeps the subscription.
+#[must_use = "dropping the guard unsubscribes the event"]
+pub struct SubscriptionGuard<'a> {
+    app: &'a VSomeipApplication,
+    service_id: ServiceID,
+    instance_id: InstanceID,
+    event_group_id: EventGroupID,
+    notifier_id: MethodID,
+}
+
+impl<'a> SubscriptionGuard<'a> {
+    pub(crate) fn new(app: &'a VSomeipApplication, service_id: ServiceID, instance_id: InstanceID,
+                      event_group_id: EventGroupID, notifier_id: MethodID) -> Self {
+        SubscriptionGuard { app, service_id, instance_id, event_group_id, notifier_id }
+    }
+
+    pub fn service_id(&self) -> ServiceID {
+        self.service_id
+    }
+
+    pub fn instance_id(&self) -> InstanceID {
+        self.instance_id
+    }
+
+    pub fn event_group_id(&self) -> EventGroupID {
+        self.event_group_id
+    }
+
+    pub fn notifier_id(&self) -> MethodID {
+        self.notifier_id
+    }
+
+    /// Drops the guard without unsubscribing, the event stays subscribed until
+    /// [VSomeipApplication::unsubscribe_event()] or until the application is dropped.
+    pub fn release(self) {
+        std::mem::forget(self);
+    }
+}
+
+impl Drop for SubscriptionGuard<'_> {
+    fn drop(&mut self) {
+        self.app.unsubscribe_event(self.service_id, self.instance_id, self.event_group_id, self.notifier_id);
+        self.app.release_event(self.service_id, self.instance_id, self.notifier_id);
+    }
+}
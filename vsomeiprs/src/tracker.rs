@@ -0,0 +1,266 @@
+// SPDX-License-Identifier: MPL-2.0
+//
+// Copyright (C) 2024 Alexander Seifarth
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+use tokio::sync::oneshot;
+use super::{MessageHeader, MessageType, MethodID, RequestError, Response, ServiceID, SessionID};
+
+/// Time a late response of a request cancelled with suppression is discarded for.
+const SUPPRESS_DURATION: Duration = Duration::from_secs(60);
+
+/// Identifies a request awaiting its response.
+#[derive(Eq, PartialEq, Hash, Debug, Clone, Copy)]
+pub struct RequestKey {
+    pub service_id: ServiceID,
+    pub method_id: MethodID,
+    pub session_id: SessionID,
+}
+
+impl RequestKey {
+    /// Returns the key of the request the header (of a request or its response) belongs to.
+    pub fn of(header: &MessageHeader) -> Self {
+        RequestKey { service_id: header.service_id, method_id: header.method_id, session_id: header.session_id }
+    }
+}
+
+struct Entry {
+    sender: oneshot::Sender<Result<Response, RequestError>>,
+    since: Instant,
+}
+
+#[derive(Default)]
+struct State {
+    entries: HashMap<RequestKey, Entry>,
+    /// Cancelled requests whose late response is discarded, with the time of cancellation.
+    suppressed: HashMap<RequestKey, Instant>,
+    max_age: Option<Duration>,
+}
+
+impl State {
+    /// Fails the entries older than `max_age` with [RequestError::Timeout].
+    fn expire(&mut self, max_age: Duration) -> usize {
+        let now = Instant::now();
+        let expired: Vec<_> = self.entries.iter()
+            .filter(|(_, entry)| now.duration_since(entry.since) >= max_age)
+            .map(|(key, _)| *key)
+            .collect();
+        for key in &expired {
+            if let Some(entry) = self.entries.remove(key) {
+                // the caller may have stopped waiting
+                let _ = entry.sender.send(Err(RequestError::Timeout));
+            }
+        }
+        expired.len()
+    }
+}
+
+/// Tracks requests awaiting their response and hands out a [PendingResponse] future for each.
+///
+/// The tracker is fed with the received messages by [RequestTracker::complete()]. The tracker of
+/// an application, see [crate::VSomeipApplication::request_tracker()], is fed by the application
+/// itself and used by [crate::VSomeipApplication::send_request_await()].
+///
+/// Entries are removed when answered, when their future is dropped and when they are older than
+/// the maximum age, see [RequestTracker::set_max_age()].
+/// ```rust
+/// use std::sync::Arc;
+/// use vsomeiprs::{RequestKey, RequestTracker, MethodID, ServiceID, SessionID};
+///
+/// let tracker = Arc::new(RequestTracker::new());
+/// let key = RequestKey { service_id: ServiceID(1), method_id: MethodID(1), session_id: SessionID(1) };
+/// let response = tracker.track(|| Ok(key)).unwrap();
+/// assert_eq!(tracker.len(), 1);
+/// drop(response);
+/// assert!(tracker.is_empty());
+/// ```
+#[derive(Default)]
+pub struct RequestTracker {
+    state: Mutex<State>,
+}
+
+impl RequestTracker {
+    pub fn new() -> Self {
+        RequestTracker::default()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Sends a request with `send` and tracks it under the returned key.
+    ///
+    /// The tracker is locked while sending so that the response cannot be handled before the
+    /// request is tracked, `send` must therefore not call into the tracker. Entries older than the
+    /// maximum age are expired before.
+    pub fn track<F>(self: &Arc<Self>, send: F) -> Result<PendingResponse, RequestError>
+        where F: FnOnce() -> Result<RequestKey, RequestError>
+    {
+        let mut state = self.lock();
+        if let Some(max_age) = state.max_age {
+            state.expire(max_age);
+        }
+        state.suppressed.retain(|_, since| since.elapsed() < SUPPRESS_DURATION);
+        let key = send()?;
+        let (sender, receiver) = oneshot::channel();
+        state.entries.insert(key, Entry { sender, since: Instant::now() });
+        Ok(PendingResponse { receiver, tracker: self.clone(), key })
+    }
+
+    /// Completes the pending request the message answers. Returns the message if no request is
+    /// waiting for it, unless it is the late response of a request cancelled with suppression.
+    pub fn complete(&self, msg: MessageType) -> Option<MessageType> {
+        let key = match &msg {
+            MessageType::Response { header, .. } | MessageType::Error { header, .. } => RequestKey::of(header),
+            _ => return Some(msg),
+        };
+        let entry = {
+            let mut state = self.lock();
+            if state.suppressed.remove(&key).is_some() {
+                return None;
+            }
+            state.entries.remove(&key)
+        };
+        let Some(entry) = entry else { return Some(msg) };
+        let result = match msg {
+            MessageType::Response { header, data } => Ok(Response { header, data }),
+            MessageType::Error { header, return_code, data } => Err(RequestError::Error { header, return_code, data }),
+            _ => unreachable!(),
+        };
+        // the caller may have stopped waiting
+        let _ = entry.sender.send(result);
+        None
+    }
+
+    /// Sets the age after which unanswered requests fail with [RequestError::Timeout], `None`
+    /// (the default) keeps them until answered or dropped. Expiry happens when tracking the next
+    /// request and by [RequestTracker::expire()].
+    pub fn set_max_age(&self, max_age: Option<Duration>) {
+        self.lock().max_age = max_age;
+    }
+
+    /// Fails the requests older than `max_age` with [RequestError::Timeout] and returns their
+    /// number.
+    pub fn expire(&self, max_age: Duration) -> usize {
+        self.lock().expire(max_age)
+    }
+
+    /// Fails all pending requests with [RequestError::Closed].
+    pub fn clear(&self) {
+        self.lock().entries.clear();
+    }
+
+    /// Returns the number of pending requests.
+    pub fn len(&self) -> usize {
+        self.lock().entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lock().entries.is_empty()
+    }
+}
+
+/// Future of the response to a request tracked by a [RequestTracker].
+///
+/// Dropping the future removes the request from the tracker, a late response is then returned by
+/// [RequestTracker::complete()] like an unrequested one.
+#[must_use = "the response is discarded if the future is dropped"]
+pub struct PendingResponse {
+    receiver: oneshot::Receiver<Result<Response, RequestError>>,
+    tracker: Arc<RequestTracker>,
+    key: RequestKey,
+}
+
+impl PendingResponse {
+    pub fn key(&self) -> RequestKey {
+        self.key
+    }
+
+    /// Stops waiting for the response. With `suppress_late` a response arriving later is
+    /// discarded by [RequestTracker::complete()] instead of being returned as unrequested.
+    pub fn cancel(self, suppress_late: bool) {
+        let mut state = self.tracker.lock();
+        if state.entries.remove(&self.key).is_some() && suppress_late {
+            state.suppressed.insert(self.key, Instant::now());
+        }
+    }
+}
+
+impl Future for PendingResponse {
+    type Output = Result<Response, RequestError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut self.receiver).poll(cx).map(|result| result.unwrap_or(Err(RequestError::Closed)))
+    }
+}
+
+impl Drop for PendingResponse {
+    fn drop(&mut self) {
+        self.tracker.lock().entries.remove(&self.key);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use bytes::Bytes;
+    use crate::{InstanceID, ReturnCode, VSomeipPayload};
+
+    fn header(session: u16) -> MessageHeader {
+        MessageHeader { session_id: SessionID(session), ..MessageHeader::new(ServiceID(1), InstanceID(1), MethodID(2)) }
+    }
+
+    #[tokio::test]
+    async fn complete_test() {
+        let tracker = Arc::new(RequestTracker::new());
+        let response = tracker.track(|| Ok(RequestKey::of(&header(7)))).unwrap();
+        assert!(matches!(tracker.track(|| Err(RequestError::Dropped)), Err(RequestError::Dropped)));
+
+        let other = MessageType::Response { header: header(8), data: VSomeipPayload::from(Bytes::new()) };
+        assert!(tracker.complete(other).is_some());
+        let error = MessageType::Error { header: header(7), return_code: ReturnCode::NotOk,
+            data: VSomeipPayload::from(Bytes::new()) };
+        assert!(tracker.complete(error).is_none());
+        assert!(matches!(response.await, Err(RequestError::Error { return_code: ReturnCode::NotOk, .. })));
+        assert!(tracker.is_empty());
+
+        let response = tracker.track(|| Ok(RequestKey::of(&header(9)))).unwrap();
+        tracker.clear();
+        assert!(matches!(response.await, Err(RequestError::Closed)));
+    }
+
+    #[tokio::test]
+    async fn expire_test() {
+        let tracker = Arc::new(RequestTracker::new());
+        let old = tracker.track(|| Ok(RequestKey::of(&header(1)))).unwrap();
+        assert_eq!(tracker.expire(Duration::from_secs(60)), 0);
+        tracker.set_max_age(Some(Duration::ZERO));
+        let new = tracker.track(|| Ok(RequestKey::of(&header(2)))).unwrap();
+        assert!(matches!(old.await, Err(RequestError::Timeout)));
+        assert_eq!(new.key().session_id, SessionID(2));
+        assert_eq!(tracker.len(), 1);
+        drop(new);
+        assert!(tracker.is_empty());
+    }
+
+    #[test]
+    fn cancel_test() {
+        let tracker = Arc::new(RequestTracker::new());
+        let response = |session| MessageType::Response { header: header(session),
+            data: VSomeipPayload::from(Bytes::new()) };
+        tracker.track(|| Ok(RequestKey::of(&header(1)))).unwrap().cancel(false);
+        tracker.track(|| Ok(RequestKey::of(&header(2)))).unwrap().cancel(true);
+        assert!(tracker.is_empty());
+        assert!(tracker.complete(response(1)).is_some());
+        assert!(tracker.complete(response(2)).is_none());
+        assert!(tracker.complete(response(2)).is_some());
+    }
+}
@@ -0,0 +1,122 @@
+// SPDX-License-Identifier: MPL-2.0
+//
+// Copyright (C) 2024 Alexander Seifarth
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::fmt::Debug;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+use super::{InstanceID, InterfaceVersion, MessageType, ServiceID, VSomeipMessage};
+
+/// Availability of a requested service, see [VSomeipMessage::ServiceAvailability].
+#[derive(Eq, PartialEq, Debug, Clone, Copy)]
+pub struct Availability {
+    pub service_id: ServiceID,
+    pub instance_id: InstanceID,
+    pub avail: bool,
+    pub version: InterfaceVersion,
+}
+
+/// Receivers of an application created with [crate::VSomeipApplication::create_typed()], one
+/// per kind of incoming traffic.
+///
+/// The receivers are independent, so provider and consumer logic can run in separate tasks. A
+/// receiver that is not needed can be dropped, its messages are discarded then.
+/// ```rust,no_run
+/// use vsomeiprs::{MessageType, ReturnCode, VSomeipApplication};
+///
+/// async fn run() {
+///     let (app, receivers) = VSomeipApplication::create_typed("provider").unwrap();
+///     let mut requests = receivers.requests;
+///     while let Some(MessageType::Request { header, data }) = requests.recv().await {
+///         let _ = app.send_response(&header, ReturnCode::Ok, data.into_bytes());
+///     }
+/// }
+/// ```
+pub struct TypedReceivers {
+    /// [MessageType::Request] and [MessageType::RequestNoReturn] messages.
+    pub requests: UnboundedReceiver<MessageType>,
+    /// [MessageType::Response] and [MessageType::Error] messages.
+    pub responses: UnboundedReceiver<MessageType>,
+    /// [MessageType::Notification] messages.
+    pub notifications: UnboundedReceiver<MessageType>,
+    pub availability: UnboundedReceiver<Availability>,
+    /// Registration state, `true` once the application is registered at the routing manager.
+    pub registration: UnboundedReceiver<bool>,
+    /// All other messages, e.g. [VSomeipMessage::RoutingState] or [VSomeipMessage::CallbackPanic].
+    pub other: UnboundedReceiver<VSomeipMessage>,
+}
+
+/// Senders of the [TypedReceivers], used as the sink of the application.
+pub(crate) struct TypedSenders {
+    requests: UnboundedSender<MessageType>,
+    responses: UnboundedSender<MessageType>,
+    notifications: UnboundedSender<MessageType>,
+    availability: UnboundedSender<Availability>,
+    registration: UnboundedSender<bool>,
+    other: UnboundedSender<VSomeipMessage>,
+}
+
+impl TypedSenders {
+    pub(crate) fn channels() -> (Self, TypedReceivers) {
+        let (requests, requests_recv) = unbounded_channel();
+        let (responses, responses_recv) = unbounded_channel();
+        let (notifications, notifications_recv) = unbounded_channel();
+        let (availability, availability_recv) = unbounded_channel();
+        let (registration, registration_recv) = unbounded_channel();
+        let (other, other_recv) = unbounded_channel();
+        (TypedSenders { requests, responses, notifications, availability, registration, other },
+         TypedReceivers { requests: requests_recv, responses: responses_recv, notifications: notifications_recv,
+             availability: availability_recv, registration: registration_recv, other: other_recv })
+    }
+
+    /// Delivers the message into its channel, it is dropped if the channel's receiver is closed.
+    pub(crate) fn send(&self, msg: VSomeipMessage) {
+        match msg {
+            VSomeipMessage::Message(msg @ (MessageType::Request { .. } | MessageType::RequestNoReturn { .. })) =>
+                deliver(&self.requests, msg),
+            VSomeipMessage::Message(msg @ (MessageType::Response { .. } | MessageType::Error { .. })) =>
+                deliver(&self.responses, msg),
+            VSomeipMessage::Message(msg @ MessageType::Notification { .. }) => deliver(&self.notifications, msg),
+            VSomeipMessage::ServiceAvailability { service_id, instance_id, avail, version } =>
+                deliver(&self.availability, Availability { service_id, instance_id, avail, version }),
+            VSomeipMessage::RegistrationState(registered) => deliver(&self.registration, registered),
+            msg => deliver(&self.other, msg),
+        }
+    }
+}
+
+fn deliver<T: Debug>(sender: &UnboundedSender<T>, msg: T) {
+    if let Err(e) = sender.send(msg) {
+        log::debug!("Receiver closed, dropping {:?}", e.0);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use bytes::Bytes;
+    use crate::{MessageHeader, MethodID, RoutingState, VSomeipPayload};
+
+    #[test]
+    fn typed_senders_test() {
+        let (senders, mut receivers) = TypedSenders::channels();
+        let header = MessageHeader::new(ServiceID(1), InstanceID(1), MethodID(1));
+        senders.send(VSomeipMessage::Message(MessageType::RequestNoReturn { header: header.clone(),
+            data: VSomeipPayload::from(Bytes::new()) }));
+        senders.send(VSomeipMessage::RegistrationState(true));
+        senders.send(VSomeipMessage::ServiceAvailability { service_id: ServiceID(1), instance_id: InstanceID(1),
+            avail: true, version: InterfaceVersion::make_any() });
+        drop(receivers.notifications);
+        senders.send(VSomeipMessage::Message(MessageType::Notification { header, is_initial: false,
+            data: VSomeipPayload::from(Bytes::new()) }));
+        senders.send(VSomeipMessage::RoutingState(RoutingState::Running));
+
+        assert!(matches!(receivers.requests.try_recv(), Ok(MessageType::RequestNoReturn { .. })));
+        assert!(receivers.responses.try_recv().is_err());
+        assert_eq!(receivers.registration.try_recv(), Ok(true));
+        assert!(matches!(receivers.availability.try_recv(), Ok(Availability { avail: true, .. })));
+        assert!(matches!(receivers.other.try_recv(), Ok(VSomeipMessage::RoutingState(RoutingState::Running))));
+    }
+}
@@ -6,15 +6,20 @@
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
 use std::fmt;
+use std::net::SocketAddr;
+use std::time::Duration;
+use bytes::Bytes;
 use super::VSomeipPayload;
 
 macro_rules! base_type {
     ($name:ident, $base_type:ty) => {
-        #[derive(Eq, PartialEq, Ord, PartialOrd, Debug, Copy, Clone)]
+        #[derive(Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Copy, Clone)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
         pub struct $name (pub $base_type);
 
         impl $name {
-            pub fn id(&self) -> $base_type { self.0 }
+            pub const fn new(id: $base_type) -> Self { Self(id) }
+            pub const fn id(&self) -> $base_type { self.0 }
         }
 
         impl From<$base_type> for $name {
@@ -57,9 +62,11 @@ base_type!(MinorVersion, u32);
 pub const ANY_MINOR_VERSION: MinorVersion = MinorVersion(0xffff_ffff);
 
 base_type!(ProtocolVersion, u8);
+pub const SOMEIP_PROTOCOL_VERSION: ProtocolVersion = ProtocolVersion(0x01);
 
 /// Version (major, minor) for service interfaces
-#[derive(Eq, PartialEq, Ord, PartialOrd, Debug, Clone, Copy)]
+#[derive(Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct InterfaceVersion {
     pub major: MajorVersion,
     pub minor: MinorVersion,
@@ -67,17 +74,17 @@ pub struct InterfaceVersion {
 
 impl InterfaceVersion {
     /// Returns ANY_MAJOR_VERSION.ANY_MINOR_VERSION.
-    pub fn make_any() -> Self {
+    pub const fn make_any() -> Self {
         InterfaceVersion{ major: ANY_MAJOR_VERSION, minor: ANY_MINOR_VERSION }
     }
 
     /// Returns the major.minor version.
-    pub fn make_version(major: u8, minor: u32) -> Self {
+    pub const fn make_version(major: u8, minor: u32) -> Self {
         InterfaceVersion{ major: MajorVersion(major), minor: MinorVersion(minor) }
     }
 
     /// Returns the major.ANY_MINOR_VERSION.
-    pub fn make_major(major: u8) -> Self {
+    pub const fn make_major(major: u8) -> Self {
         InterfaceVersion{ major: MajorVersion(major), minor: ANY_MINOR_VERSION }
     }
 }
@@ -98,7 +105,8 @@ impl fmt::Display for InterfaceVersion {
 
 /// Common elements of every SOME/IP message received or sent by vsomeip.
 /// Not all elements are always meaningful or required.
-#[derive(Eq, PartialEq, Ord, PartialOrd, Debug)]
+#[derive(Eq, PartialEq, Ord, PartialOrd, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MessageHeader {
     /// ID of the service interface (mandatory)
     pub service_id: ServiceID,
@@ -119,8 +127,177 @@ pub struct MessageHeader {
     /// In receive direction only the major version is indicated, because the minor version is not
     /// contained in SOME/IP messages directly.
     pub interface_version: InterfaceVersion,
+    /// SOME/IP protocol version as received. For messages to be sent vsomeip inserts it.
+    pub protocol_version: ProtocolVersion,
+    /// Message type byte as received. For messages to be sent the type is given by the send
+    /// function, except for [crate::VSomeipApplication::send_message()].
+    pub raw_message_type: u8,
+    /// Return code byte as received, also for values without [ReturnCode] mapping.
+    /// For messages to be sent the return code is given separately, except for
+    /// [crate::VSomeipApplication::send_message()].
+    pub raw_return_code: u8,
+    /// SOME/IP length field as received, i.e. the payload length + 8. For messages to be sent
+    /// vsomeip inserts it.
+    pub length: u32,
     /// Indicates whether the message was sent on reliable transport (TCP) or not (UDP).
     pub reliable: bool,
+    /// Sender of a received message. Not relevant in send-direction.
+    pub origin: Origin,
+}
+
+impl MessageHeader {
+    /// Returns a header for the given method with `UNKNOWN_CLIENT`, `NO_SESSION`, any interface
+    /// version, message type REQUEST, return code OK and unreliable transport.
+    pub fn new(service_id: ServiceID, instance_id: InstanceID, method_id: MethodID) -> Self {
+        MessageHeader { service_id, instance_id, method_id, client_id: UNKNOWN_CLIENT, session_id: NO_SESSION,
+            interface_version: InterfaceVersion::make_any(), protocol_version: SOMEIP_PROTOCOL_VERSION,
+            raw_message_type: u8::from(MessageKind::Request), raw_return_code: u8::from(ReturnCode::Ok), length: 0,
+            reliable: false, origin: Origin::Unknown }
+    }
+
+    /// Returns a builder for a header, e.g. for synthetic responses created by gateways.
+    pub fn builder() -> MessageHeaderBuilder {
+        MessageHeaderBuilder::default()
+    }
+}
+
+/// Builder for a [MessageHeader].
+/// Service, instance and method are mandatory, all other elements default to the values of
+/// [MessageHeader::new()].
+#[derive(Debug, Clone, Default)]
+pub struct MessageHeaderBuilder {
+    service_id: Option<ServiceID>,
+    instance_id: Option<InstanceID>,
+    method_id: Option<MethodID>,
+    client_id: Option<ClientID>,
+    session_id: Option<SessionID>,
+    interface_version: Option<InterfaceVersion>,
+    kind: Option<MessageKind>,
+    return_code: Option<ReturnCode>,
+    reliable: bool,
+}
+
+impl MessageHeaderBuilder {
+    pub fn service(mut self, service_id: ServiceID) -> Self {
+        self.service_id = Some(service_id);
+        self
+    }
+
+    pub fn instance(mut self, instance_id: InstanceID) -> Self {
+        self.instance_id = Some(instance_id);
+        self
+    }
+
+    pub fn method(mut self, method_id: MethodID) -> Self {
+        self.method_id = Some(method_id);
+        self
+    }
+
+    pub fn client(mut self, client_id: ClientID) -> Self {
+        self.client_id = Some(client_id);
+        self
+    }
+
+    pub fn session(mut self, session_id: SessionID) -> Self {
+        self.session_id = Some(session_id);
+        self
+    }
+
+    pub fn interface_version(mut self, version: InterfaceVersion) -> Self {
+        self.interface_version = Some(version);
+        self
+    }
+
+    pub fn reliable(mut self, reliable: bool) -> Self {
+        self.reliable = reliable;
+        self
+    }
+
+    pub fn message_kind(mut self, kind: MessageKind) -> Self {
+        self.kind = Some(kind);
+        self
+    }
+
+    pub fn return_code(mut self, return_code: ReturnCode) -> Self {
+        self.return_code = Some(return_code);
+        self
+    }
+
+    /// Returns the header.
+    /// Fails if service, instance or method are missing or wildcards - a message always
+    /// addresses one concrete method.
+    pub fn build(self) -> Result<MessageHeader, HeaderError> {
+        let service_id = match self.service_id {
+            None => return Err(HeaderError::MissingService),
+            Some(ANY_SERVICE) => return Err(HeaderError::Wildcard),
+            Some(id) => id,
+        };
+        let instance_id = match self.instance_id {
+            None => return Err(HeaderError::MissingInstance),
+            Some(ANY_INSTANCE) => return Err(HeaderError::Wildcard),
+            Some(id) => id,
+        };
+        let method_id = match self.method_id {
+            None => return Err(HeaderError::MissingMethod),
+            Some(ANY_METHOD) => return Err(HeaderError::Wildcard),
+            Some(id) => id,
+        };
+        let mut header = MessageHeader::new(service_id, instance_id, method_id);
+        header.client_id = self.client_id.unwrap_or(UNKNOWN_CLIENT);
+        header.session_id = self.session_id.unwrap_or(NO_SESSION);
+        header.interface_version = self.interface_version.unwrap_or_else(InterfaceVersion::make_any);
+        header.raw_message_type = u8::from(self.kind.unwrap_or(MessageKind::Request));
+        header.raw_return_code = u8::from(self.return_code.unwrap_or(ReturnCode::Ok));
+        header.reliable = self.reliable;
+        Ok(header)
+    }
+}
+
+/// errors of [MessageHeaderBuilder::build()]
+#[derive(Eq, PartialEq, Debug, Clone, Copy)]
+pub enum HeaderError {
+    MissingService,
+    MissingInstance,
+    MissingMethod,
+    /// Service, instance or method is a wildcard (ANY_...).
+    Wildcard,
+}
+
+impl fmt::Display for HeaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HeaderError::MissingService => write!(f, "service id missing"),
+            HeaderError::MissingInstance => write!(f, "instance id missing"),
+            HeaderError::MissingMethod => write!(f, "method id missing"),
+            HeaderError::Wildcard => write!(f, "wildcard id in message header"),
+        }
+    }
+}
+
+impl std::error::Error for HeaderError {}
+
+/// sender of a received message
+#[derive(Eq, PartialEq, Ord, PartialOrd, Debug, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Origin {
+    /// Sender is not known, e.g. for messages to be sent or if vsomeip did not report an IPv4
+    /// sender address (IPv6 peers, local applications connected via unix domain sockets).
+    #[default]
+    Unknown,
+    /// Message was sent by an application on the same host.
+    Local,
+    /// Message was received from the given remote endpoint. Only IPv4 endpoints are reported.
+    Remote(SocketAddr),
+}
+
+impl fmt::Display for Origin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Origin::Unknown => write!(f, "unknown"),
+            Origin::Local => write!(f, "local"),
+            Origin::Remote(addr) => write!(f, "{}", addr),
+        }
+    }
 }
 
 impl fmt::Display for MessageHeader {
@@ -145,6 +322,56 @@ pub enum MessageType {
     Notification{ header: MessageHeader, is_initial: bool, data: VSomeipPayload },
 }
 
+impl MessageType {
+    /// Returns the header of the message.
+    pub fn header(&self) -> &MessageHeader {
+        match self {
+            MessageType::Request{ header, .. } => header,
+            MessageType::RequestNoReturn{ header, .. } => header,
+            MessageType::Response{ header, .. } => header,
+            MessageType::Error{ header, .. } => header,
+            MessageType::Notification{ header, .. } => header,
+        }
+    }
+
+    /// Returns the payload of the message.
+    pub fn payload(&self) -> &VSomeipPayload {
+        match self {
+            MessageType::Request{ data, .. } => data,
+            MessageType::RequestNoReturn{ data, .. } => data,
+            MessageType::Response{ data, .. } => data,
+            MessageType::Error{ data, .. } => data,
+            MessageType::Notification{ data, .. } => data,
+        }
+    }
+
+    /// Returns the kind of the message.
+    pub fn kind(&self) -> MessageKind {
+        match self {
+            MessageType::Request{ .. } => MessageKind::Request,
+            MessageType::RequestNoReturn{ .. } => MessageKind::RequestNoReturn,
+            MessageType::Response{ .. } => MessageKind::Response,
+            MessageType::Error{ .. } => MessageKind::Error,
+            MessageType::Notification{ .. } => MessageKind::Notification,
+        }
+    }
+
+    /// Returns whether the message is a request, with or without return.
+    pub fn is_request(&self) -> bool {
+        matches!(self, MessageType::Request{ .. } | MessageType::RequestNoReturn{ .. })
+    }
+
+    /// Returns whether the message is a response or an error.
+    pub fn is_response(&self) -> bool {
+        matches!(self, MessageType::Response{ .. } | MessageType::Error{ .. })
+    }
+
+    /// Returns whether the message is an event notification.
+    pub fn is_notification(&self) -> bool {
+        matches!(self, MessageType::Notification{ .. })
+    }
+}
+
 impl fmt::Display for MessageType {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -162,8 +389,132 @@ impl fmt::Display for MessageType {
     }
 }
 
+/// Owned payload of a message to be sent.
+/// It can be created from `Bytes`, `Vec<u8>`, slices or a received [VSomeipPayload], so helpers
+/// can forward received data without caring about its origin.
+#[derive(Eq, PartialEq, Debug, Clone, Default)]
+pub struct Payload(Bytes);
+
+impl Payload {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn as_bytes(&self) -> &Bytes {
+        &self.0
+    }
+
+    pub fn into_bytes(self) -> Bytes {
+        self.0
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl From<Bytes> for Payload {
+    fn from(bytes: Bytes) -> Self {
+        Self(bytes)
+    }
+}
+
+impl From<&Bytes> for Payload {
+    fn from(bytes: &Bytes) -> Self {
+        Self(bytes.clone())
+    }
+}
+
+impl From<Vec<u8>> for Payload {
+    fn from(data: Vec<u8>) -> Self {
+        Self(Bytes::from(data))
+    }
+}
+
+impl From<&[u8]> for Payload {
+    fn from(data: &[u8]) -> Self {
+        Self(Bytes::copy_from_slice(data))
+    }
+}
+
+/// Shares the data, the vsomeip payload object is kept alive by the returned payload.
+impl From<&VSomeipPayload> for Payload {
+    fn from(payload: &VSomeipPayload) -> Self {
+        Self(payload.as_bytes_ref().clone())
+    }
+}
+
+/// Takes over the data without copying.
+impl From<VSomeipPayload> for Payload {
+    fn from(payload: VSomeipPayload) -> Self {
+        Self(payload.into_bytes())
+    }
+}
+
+impl AsRef<[u8]> for Payload {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+/// kind of a SOME/IP message, i.e. the message type without header and data
+#[derive(Eq, PartialEq, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MessageKind {
+    Request,
+    RequestNoReturn,
+    Response,
+    Error,
+    Notification,
+}
+
+impl MessageKind {
+    /// Converts a SOME/IP message type byte, `None` for the acknowledgement types (TP flag
+    /// cleared) and reserved values.
+    pub fn from_wire(value: u8) -> Option<Self> {
+        match value {
+            0x00 => Some(MessageKind::Request),
+            0x01 => Some(MessageKind::RequestNoReturn),
+            0x02 => Some(MessageKind::Notification),
+            0x80 => Some(MessageKind::Response),
+            0x81 => Some(MessageKind::Error),
+            _ => None,
+        }
+    }
+}
+
+impl From<MessageKind> for u8 {
+    /// Returns the SOME/IP wire value.
+    fn from(kind: MessageKind) -> u8 {
+        match kind {
+            MessageKind::Request => 0x00,
+            MessageKind::RequestNoReturn => 0x01,
+            MessageKind::Notification => 0x02,
+            MessageKind::Response => 0x80,
+            MessageKind::Error => 0x81,
+        }
+    }
+}
+
+impl fmt::Display for MessageKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MessageKind::Request => write!(f, "REQUEST"),
+            MessageKind::RequestNoReturn => write!(f, "REQUEST(FF)"),
+            MessageKind::Response => write!(f, "RESPONSE"),
+            MessageKind::Error => write!(f, "ERROR"),
+            MessageKind::Notification => write!(f, "NOTIFICATION"),
+        }
+    }
+}
+
 /// return codes corresponding to SOME/IP return code
-#[derive(Eq, PartialEq, Ord, PartialOrd, Debug)]
+#[derive(Eq, PartialEq, Ord, PartialOrd, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ReturnCode {
     Ok,
     NotOk,
@@ -176,10 +527,37 @@ pub enum ReturnCode {
     WrongInterfaceVersion,
     MalformedMessage,
     WrongMessageType,
+    /// Service/method specific error (0x20 - 0x5E), see [ReturnCode::application_specific()]
+    ApplicationSpecific(u8),
+    /// Value reserved by SOME/IP without meaning assigned (0x0B - 0x1F, 0x5F - 0xFE), e.g. sent by
+    /// a peer implementing a newer protocol version.
+    Reserved(u8),
     Unknown,
 }
 
 impl ReturnCode {
+    /// Converts a SOME/IP wire value, reserved values map to [ReturnCode::Reserved].
+    pub fn from_wire(value: u8) -> Self {
+        ReturnCode::try_from(value).unwrap_or(ReturnCode::Reserved(value))
+    }
+
+    /// Service/method specific error, fails if `code` is not in [APPLICATION_RETURN_CODES].
+    pub fn application_specific(code: u8) -> Result<Self, InvalidReturnCode> {
+        match code {
+            code if APPLICATION_RETURN_CODES.contains(&code) => Ok(ReturnCode::ApplicationSpecific(code)),
+            code => Err(InvalidReturnCode(code)),
+        }
+    }
+
+    /// Returns whether the value of [ReturnCode::ApplicationSpecific] or [ReturnCode::Reserved]
+    /// is in the range of its variant, the other return codes are always valid.
+    pub fn is_valid(&self) -> bool {
+        match self {
+            ReturnCode::ApplicationSpecific(code) => APPLICATION_RETURN_CODES.contains(code),
+            ReturnCode::Reserved(code) => matches!(code, 0x0b..=0x1f | 0x5f..=0xfe),
+            _ => true,
+        }
+    }
 
     /// Returns whether an application is allowed to send the return code in a response.
     pub fn can_be_sent(&self) -> bool {
@@ -208,13 +586,104 @@ impl fmt::Display for ReturnCode {
             ReturnCode::WrongInterfaceVersion => write!(f, "WRONG_INTERFACE_VERSION"),
             ReturnCode::MalformedMessage => write!(f, "MALFORMED_MESSAGE"),
             ReturnCode::WrongMessageType => write!(f, "WRONG_MESSAGE_TYPE"),
+            ReturnCode::ApplicationSpecific(code) => write!(f, "APPLICATION({:#04x})", code),
+            ReturnCode::Reserved(code) => write!(f, "RESERVED({:#04x})", code),
             ReturnCode::Unknown => write!(f, "UNKNOWN")
         }
     }
 }
 
 
-#[derive(Eq, PartialEq, Ord, PartialOrd, Debug)]
+/// routing state of the routing manager, see [crate::VSomeipApplication::set_routing_state()]
+#[derive(Eq, PartialEq, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RoutingState {
+    Running,
+    Suspended,
+    Resumed,
+    Shutdown,
+    Diagnosis,
+    DelayedResume,
+    Unknown,
+}
+
+impl fmt::Display for RoutingState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RoutingState::Running => write!(f, "RUNNING"),
+            RoutingState::Suspended => write!(f, "SUSPENDED"),
+            RoutingState::Resumed => write!(f, "RESUMED"),
+            RoutingState::Shutdown => write!(f, "SHUTDOWN"),
+            RoutingState::Diagnosis => write!(f, "DIAGNOSIS"),
+            RoutingState::DelayedResume => write!(f, "DELAYED_RESUME"),
+            RoutingState::Unknown => write!(f, "UNKNOWN"),
+        }
+    }
+}
+
+/// first and last return code reserved for service/method specific errors
+pub const APPLICATION_RETURN_CODES: std::ops::RangeInclusive<u8> = 0x20..=0x5e;
+
+impl From<ReturnCode> for u8 {
+    /// Returns the SOME/IP wire value, `0xff` for [ReturnCode::Unknown].
+    fn from(rc: ReturnCode) -> u8 {
+        match rc {
+            ReturnCode::Ok => 0x00,
+            ReturnCode::NotOk => 0x01,
+            ReturnCode::UnknownService => 0x02,
+            ReturnCode::UnknownMethod => 0x03,
+            ReturnCode::NotReady => 0x04,
+            ReturnCode::NotReachable => 0x05,
+            ReturnCode::Timeout => 0x06,
+            ReturnCode::WrongProtocolVersion => 0x07,
+            ReturnCode::WrongInterfaceVersion => 0x08,
+            ReturnCode::MalformedMessage => 0x09,
+            ReturnCode::WrongMessageType => 0x0a,
+            ReturnCode::ApplicationSpecific(code) => code,
+            ReturnCode::Reserved(code) => code,
+            ReturnCode::Unknown => 0xff,
+        }
+    }
+}
+
+impl TryFrom<u8> for ReturnCode {
+    type Error = InvalidReturnCode;
+
+    /// Converts a SOME/IP wire value. Values reserved by SOME/IP without meaning assigned
+    /// (0x0b - 0x1f, 0x5f - 0xfe) fail, see [ReturnCode::from_wire()] for a lossless conversion.
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0x00 => Ok(ReturnCode::Ok),
+            0x01 => Ok(ReturnCode::NotOk),
+            0x02 => Ok(ReturnCode::UnknownService),
+            0x03 => Ok(ReturnCode::UnknownMethod),
+            0x04 => Ok(ReturnCode::NotReady),
+            0x05 => Ok(ReturnCode::NotReachable),
+            0x06 => Ok(ReturnCode::Timeout),
+            0x07 => Ok(ReturnCode::WrongProtocolVersion),
+            0x08 => Ok(ReturnCode::WrongInterfaceVersion),
+            0x09 => Ok(ReturnCode::MalformedMessage),
+            0x0a => Ok(ReturnCode::WrongMessageType),
+            0xff => Ok(ReturnCode::Unknown),
+            code => ReturnCode::application_specific(code),
+        }
+    }
+}
+
+/// error of the `u8` to [ReturnCode] conversions carrying the value out of range
+#[derive(Eq, PartialEq, Debug, Clone, Copy)]
+pub struct InvalidReturnCode(pub u8);
+
+impl fmt::Display for InvalidReturnCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid return code {:#04x}", self.0)
+    }
+}
+
+impl std::error::Error for InvalidReturnCode {}
+
+#[derive(Eq, PartialEq, Ord, PartialOrd, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Reliability {
     Reliable,
     Unreliable,
@@ -222,6 +691,122 @@ pub enum Reliability {
     Unknown,
 }
 
+/// Kind of an offered or requested event.
+#[derive(Eq, PartialEq, Ord, PartialOrd, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum EventKind {
+    Event,
+    /// Event whose notifications are sent to single subscribers only, see
+    /// [crate::VSomeipApplication::notify_one()].
+    SelectiveEvent,
+    Field,
+}
+
+impl EventKind {
+    /// Returns [EventKind::Field] or [EventKind::Event].
+    pub fn from_is_field(is_field: bool) -> Self {
+        if is_field { EventKind::Field } else { EventKind::Event }
+    }
+}
+
+/// Description of an offered event, see [crate::VSomeipApplication::offer_event()] for the
+/// meaning of the fields.
+#[derive(Eq, PartialEq, Debug, Clone)]
+pub struct EventDescriptor {
+    pub notifier_id: MethodID,
+    pub event_groups: Vec<EventGroupID>,
+    pub kind: EventKind,
+    pub cycle: Option<Duration>,
+    pub change_resets_cycle: bool,
+    pub update_on_change: bool,
+    pub reliability: Reliability,
+}
+
+impl EventDescriptor {
+    /// Plain event without cycle, updated on change, with the configured transport.
+    pub fn new(notifier_id: MethodID, event_groups: Vec<EventGroupID>) -> Self {
+        EventDescriptor { notifier_id, event_groups, kind: EventKind::Event, cycle: None, change_resets_cycle: false,
+            update_on_change: true, reliability: Reliability::Unknown }
+    }
+
+    /// Field with the same defaults as [EventDescriptor::new()].
+    pub fn field(notifier_id: MethodID, event_groups: Vec<EventGroupID>) -> Self {
+        EventDescriptor { kind: EventKind::Field, ..Self::new(notifier_id, event_groups) }
+    }
+
+    /// Selective event with the same defaults as [EventDescriptor::new()].
+    pub fn selective(notifier_id: MethodID, event_groups: Vec<EventGroupID>) -> Self {
+        EventDescriptor { kind: EventKind::SelectiveEvent, ..Self::new(notifier_id, event_groups) }
+    }
+}
+
+/// Service instance with its interface version, accepted as `impl Into<ServiceHandle>` by the
+/// offer, request, subscribe and send methods of [crate::VSomeipApplication], e.g. as tuple
+/// `(service_id, instance_id, version)` or [crate::commonapi::Address].
+///
+/// Requests and subscriptions use the major version only, [InterfaceVersion::make_major()] leaves
+/// the minor version open.
+#[derive(Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ServiceHandle {
+    pub service_id: ServiceID,
+    pub instance_id: InstanceID,
+    pub version: InterfaceVersion,
+}
+
+impl ServiceHandle {
+    pub const fn new(service_id: ServiceID, instance_id: InstanceID, version: InterfaceVersion) -> Self {
+        ServiceHandle { service_id, instance_id, version }
+    }
+}
+
+impl From<(ServiceID, InstanceID, InterfaceVersion)> for ServiceHandle {
+    fn from((service_id, instance_id, version): (ServiceID, InstanceID, InterfaceVersion)) -> Self {
+        ServiceHandle { service_id, instance_id, version }
+    }
+}
+
+/// Service instance with the major version and any minor version.
+impl From<(ServiceID, InstanceID, MajorVersion)> for ServiceHandle {
+    fn from((service_id, instance_id, major): (ServiceID, InstanceID, MajorVersion)) -> Self {
+        ServiceHandle { service_id, instance_id, version: InterfaceVersion::make_major(major.id()) }
+    }
+}
+
+impl From<&ServiceDescriptor> for ServiceHandle {
+    fn from(descriptor: &ServiceDescriptor) -> Self {
+        ServiceHandle::new(descriptor.service_id, descriptor.instance_id, descriptor.version)
+    }
+}
+
+impl fmt::Display for ServiceHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{} v{}", self.service_id, self.instance_id, self.version)
+    }
+}
+
+/// Description of an offered service instance together with its events, see
+/// [crate::VSomeipApplication::offer_service_with_events()].
+#[derive(Eq, PartialEq, Debug, Clone)]
+pub struct ServiceDescriptor {
+    pub service_id: ServiceID,
+    pub instance_id: InstanceID,
+    pub version: InterfaceVersion,
+    pub events: Vec<EventDescriptor>,
+}
+
+impl ServiceDescriptor {
+    pub fn new(service_id: ServiceID, instance_id: InstanceID, version: InterfaceVersion) -> Self {
+        ServiceDescriptor { service_id, instance_id, version, events: Vec::new() }
+    }
+
+    /// Adds an event to the description.
+    pub fn with_event(mut self, event: EventDescriptor) -> Self {
+        self.events.push(event);
+        self
+    }
+}
+
 
 
 #[cfg(test)]
@@ -239,4 +824,112 @@ mod test {
         assert_eq!(ServiceID(2), ServiceID::from(2));
         assert_ne!(ServiceID(0x23), ServiceID::from(23));
     }
+
+    #[test]
+    fn service_id_hash_test() {
+        const TABLE: [(ServiceID, InstanceID); 2] = [(ServiceID::new(1), InstanceID::new(1)), (ServiceID(2), ANY_INSTANCE)];
+        let map: std::collections::HashMap<_, _> = TABLE.iter().map(|(s, i)| (*s, *i)).collect();
+        assert_eq!(map.get(&ServiceID(2)), Some(&ANY_INSTANCE));
+        const ID: u16 = ServiceID::new(0x42).id();
+        assert_eq!(ID, 0x42);
+    }
+
+    #[test]
+    fn return_code_u8_test() {
+        for value in 0..=u8::MAX {
+            if let Ok(rc) = ReturnCode::try_from(value) {
+                assert_eq!(u8::from(rc), value);
+            }
+        }
+        assert_eq!(ReturnCode::try_from(0x0a), Ok(ReturnCode::WrongMessageType));
+        assert_eq!(ReturnCode::try_from(0x20), Ok(ReturnCode::ApplicationSpecific(0x20)));
+        assert_eq!(ReturnCode::try_from(0x0b), Err(InvalidReturnCode(0x0b)));
+        assert_eq!(ReturnCode::try_from(0x5f), Err(InvalidReturnCode(0x5f)));
+        for value in 0..=u8::MAX {
+            assert_eq!(u8::from(ReturnCode::from_wire(value)), value);
+        }
+        assert_eq!(ReturnCode::from_wire(0x20), ReturnCode::ApplicationSpecific(0x20));
+        assert_eq!(ReturnCode::from_wire(0x5f), ReturnCode::Reserved(0x5f));
+    }
+
+    #[test]
+    fn return_code_range_test() {
+        assert_eq!(ReturnCode::application_specific(0x5e), Ok(ReturnCode::ApplicationSpecific(0x5e)));
+        assert_eq!(ReturnCode::application_specific(0x1f), Err(InvalidReturnCode(0x1f)));
+        assert_eq!(ReturnCode::application_specific(0x5f), Err(InvalidReturnCode(0x5f)));
+        assert!(ReturnCode::ApplicationSpecific(0x20).is_valid());
+        assert!(!ReturnCode::ApplicationSpecific(0x01).is_valid());
+        assert!(ReturnCode::Reserved(0x0b).is_valid());
+        assert!(!ReturnCode::Reserved(0x20).is_valid());
+        assert!(!ReturnCode::Reserved(0xff).is_valid());
+        for value in 0..=u8::MAX {
+            assert!(ReturnCode::from_wire(value).is_valid());
+        }
+    }
+
+    #[test]
+    fn message_type_accessor_test() {
+        let msg = MessageType::Notification{ header: MessageHeader::new(ServiceID(1), InstanceID(2), MethodID(0x8001)),
+            is_initial: false, data: VSomeipPayload::from(bytes::Bytes::from_static(&[1, 2])) };
+        assert_eq!(msg.header().method_id, MethodID(0x8001));
+        assert_eq!(msg.payload().as_bytes_ref().as_ref(), &[1, 2]);
+        assert_eq!(msg.kind(), MessageKind::Notification);
+        assert!(msg.is_notification());
+        assert!(!msg.is_request() && !msg.is_response());
+    }
+
+    #[test]
+    fn payload_test() {
+        let received = VSomeipPayload::from(Bytes::from_static(&[1, 2, 3]));
+        assert_eq!(Payload::from(&received), Payload::from(vec![1, 2, 3]));
+        // received data is shared, not copied
+        assert_eq!(Payload::from(&received).as_bytes().as_ptr(), received.as_bytes_ref().as_ptr());
+        assert_eq!(Payload::from(&[1u8, 2, 3][..]).into_bytes(), Bytes::from_static(&[1, 2, 3]));
+        assert!(Payload::new().is_empty());
+    }
+
+    #[test]
+    fn header_builder_test() {
+        let header = MessageHeader::builder()
+            .service(ServiceID(0x1234)).instance(InstanceID(1)).method(MethodID(0x8001))
+            .session(SessionID(7)).build().unwrap();
+        assert_eq!(header.client_id, UNKNOWN_CLIENT);
+        assert_eq!(header.session_id, SessionID(7));
+        assert_eq!(header.interface_version, InterfaceVersion::make_any());
+        assert_eq!(header.raw_message_type, 0x00);
+
+        let header = MessageHeader::builder()
+            .service(ServiceID(0x1234)).instance(InstanceID(1)).method(MethodID(0x8001))
+            .message_kind(MessageKind::Notification).return_code(ReturnCode::NotReady).build().unwrap();
+        assert_eq!(MessageKind::from_wire(header.raw_message_type), Some(MessageKind::Notification));
+        assert_eq!(header.raw_return_code, u8::from(ReturnCode::NotReady));
+        assert_eq!(MessageKind::from_wire(0x40), None);
+
+        assert_eq!(MessageHeader::builder().service(ServiceID(1)).instance(InstanceID(1)).build(),
+                   Err(HeaderError::MissingMethod));
+        assert_eq!(MessageHeader::builder().service(ServiceID(1)).instance(ANY_INSTANCE).method(MethodID(1)).build(),
+                   Err(HeaderError::Wildcard));
+    }
+
+    #[test]
+    fn service_handle_test() {
+        let service = ServiceHandle::new(ServiceID(0x1234), InstanceID(1), InterfaceVersion::make_version(1, 2));
+        assert_eq!(ServiceHandle::from((ServiceID(0x1234), InstanceID(1), InterfaceVersion::make_version(1, 2))),
+                   service);
+        assert_eq!(ServiceHandle::from((ServiceID(0x1234), InstanceID(1), MajorVersion(1))).version,
+                   InterfaceVersion::make_major(1));
+        let descriptor = ServiceDescriptor::new(ServiceID(0x1234), InstanceID(1), service.version);
+        assert_eq!(ServiceHandle::from(&descriptor), service);
+        assert_eq!(service.to_string(), "1234.0001 v1.2");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_test() {
+        assert_eq!(serde_json::to_string(&ServiceID(0x1234)).unwrap(), "4660");
+        let header = MessageHeader::new(ServiceID(1), InstanceID(2), MethodID(3));
+        let json = serde_json::to_string(&header).unwrap();
+        assert_eq!(serde_json::from_str::<MessageHeader>(&json).unwrap(), header);
+        assert_eq!(serde_json::from_str::<ReturnCode>("\"NotReady\"").unwrap(), ReturnCode::NotReady);
+    }
 }
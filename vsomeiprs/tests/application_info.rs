@@ -0,0 +1,106 @@
+// SPDX-License-Identifier: MPL-2.0
+//
+// Copyright (C) 2024 Alexander Seifarth
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::time::Duration;
+use bytes::Bytes;
+use tokio::time::timeout;
+use vsomeiprs::{ClientID, InstanceID, InterfaceVersion, MajorVersion, MessageType, MethodID, Reliability, ReturnCode,
+                ServiceID, TypedReceivers, VSomeipApplication, UNKNOWN_CLIENT};
+
+const SERVICE_ID: ServiceID = ServiceID(0x0033);
+const INSTANCE_ID: InstanceID = InstanceID(1);
+const METHOD_ID: MethodID = MethodID(0x0001);
+const MAJOR: u8 = 3;
+const MINOR: u32 = 7;
+
+/// Test: application-info
+///
+/// Creates three vsomeip applications:
+/// - routing: setup before the others, acts as routing manager host
+/// - provider: Offers a single interface and answers one request.
+/// - consumer: Requests the interface and waits until it is available in the offered major
+///             version. Then it calls its method once. The response must carry the client id of the
+///             consumer.
+///
+/// Only the routing application must report to host the routing manager. All applications must
+/// have a client id of their own and keep their names.
+///
+#[tokio::test]
+pub async fn main() {
+    let (rtmp, _rrecv) = setup_app("routing").await;
+    assert!(rtmp.is_routing_host());
+    assert_eq!(rtmp.name(), "routing");
+
+    let ph = tokio::spawn(provider());
+
+    let (client_id, provider_id) = match timeout(Duration::from_secs(100), consumer()).await {
+        Ok(client_id) => (client_id, ph.await.unwrap()),
+        Err(_) => panic!("Error - timeout waiting for consumer"),
+    };
+    assert_ne!(rtmp.client_id(), UNKNOWN_CLIENT);
+    assert_ne!(rtmp.client_id(), client_id);
+    assert_ne!(rtmp.client_id(), provider_id);
+    assert_ne!(client_id, provider_id);
+}
+
+async fn provider() -> ClientID {
+    let version = InterfaceVersion::make_version(MAJOR, MINOR);
+    let (papp, mut precv) = setup_app("provider").await;
+    assert!(!papp.is_routing_host());
+    assert_eq!(papp.name(), "provider");
+    papp.offer_service((SERVICE_ID, INSTANCE_ID, version)).unwrap();
+    match precv.requests.recv().await {
+        Some(MessageType::Request{ header, .. }) => {
+            papp.send_response(&header, ReturnCode::Ok, Bytes::new()).unwrap();
+        }
+        Some(m) => panic!("Unexpected message: {}", m),
+        None => panic!("provider request channel closed"),
+    }
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    papp.stop_offer_service((SERVICE_ID, INSTANCE_ID, version));
+    papp.client_id()
+}
+
+async fn consumer() -> ClientID {
+    let version = InterfaceVersion::make_version(MAJOR, MINOR);
+    let (capp, mut crecv) = setup_app("consumer").await;
+    assert!(!capp.is_routing_host());
+    assert_eq!(capp.name(), "consumer");
+    capp.request_service((SERVICE_ID, INSTANCE_ID, version));
+    loop {
+        match crecv.availability.recv().await {
+            Some(availability) if availability.service_id == SERVICE_ID && availability.avail => {
+                // the major version offered by the provider
+                assert_eq!(availability.version.major, version.major);
+                break;
+            }
+            Some(_) => {}
+            None => panic!("consumer availability channel closed"),
+        }
+    }
+    capp.send_request((SERVICE_ID, INSTANCE_ID, MajorVersion(MAJOR)), METHOD_ID, Bytes::new(),
+                      Reliability::Unreliable).unwrap();
+    match crecv.responses.recv().await {
+        Some(MessageType::Response{ header, .. }) => assert_eq!(header.client_id, capp.client_id()),
+        Some(m) => panic!("Unexpected message: {}", m),
+        None => panic!("consumer response channel closed"),
+    }
+    capp.release_service((SERVICE_ID, INSTANCE_ID, version));
+    capp.client_id()
+}
+
+async fn setup_app(name: &str) -> (VSomeipApplication, TypedReceivers) {
+    let (app, mut recv) = VSomeipApplication::create_typed(name).unwrap();
+    loop {
+        match recv.registration.recv().await {
+            Some(true) => break,
+            Some(false) => {}
+            None => panic!("Channel closed"),
+        }
+    }
+    (app, recv)
+}
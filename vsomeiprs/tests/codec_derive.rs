@@ -0,0 +1,205 @@
+// SPDX-License-Identifier: MPL-2.0
+//
+// Copyright (C) 2024 Alexander Seifarth
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+#![cfg(feature = "derive")]
+
+use vsomeiprs::codec::{self, ByteOrder, CodecError, DeploymentProfile, LengthWidth, SomeipDeserialize,
+                       SomeipSerialize};
+
+#[derive(SomeipSerialize, SomeipDeserialize, Debug, PartialEq, Clone, Copy)]
+#[repr(u8)]
+enum Gear {
+    Park = 0,
+    Drive = 4,
+}
+
+#[derive(SomeipSerialize, SomeipDeserialize, Debug, PartialEq)]
+#[repr(u16)]
+enum Mode {
+    Eco = 1,
+    Sport,
+    #[someip(unknown)]
+    Unknown(u16),
+    Comfort,
+}
+
+#[derive(SomeipSerialize, SomeipDeserialize, Debug, PartialEq)]
+#[someip(length_width = 2)]
+struct Position(i16, i16);
+
+#[derive(SomeipSerialize, SomeipDeserialize, Debug, PartialEq)]
+struct Status {
+    gear: Gear,
+    #[someip(align = 4)]
+    speed: u32,
+    position: Position,
+    #[someip(length_width = 1)]
+    name: String,
+    #[someip(length_width = 0)]
+    samples: Vec<u8>,
+}
+
+#[derive(SomeipSerialize, SomeipDeserialize, Debug, PartialEq)]
+#[someip(tlv)]
+struct Settings {
+    #[someip(id = 1)]
+    volume: u8,
+    #[someip(id = 2, length_width = 1)]
+    stations: Vec<u16>,
+    #[someip(id = 4)]
+    gear: Gear,
+    #[someip(id = 5, length_width = 1, encoding = "utf16be", bom = false)]
+    owner: String,
+    #[someip(id = 6)]
+    balance: Option<i8>,
+}
+
+#[derive(SomeipSerialize, SomeipDeserialize, Debug, PartialEq)]
+#[someip(length_width = 1, selector_width = 1)]
+enum Source {
+    Off,
+    Tuner(u16),
+    #[someip(selector = 7)]
+    Media(Vec<u8>),
+}
+
+#[derive(SomeipSerialize, SomeipDeserialize, Debug, PartialEq)]
+#[someip(bitfield = 16)]
+struct Doors {
+    front_left: bool,
+    front_right: bool,
+    #[someip(bits = 3, offset = 4)]
+    window: u8,
+    #[someip(bits = 8, offset = 8)]
+    counter: u16,
+}
+
+#[derive(SomeipSerialize, SomeipDeserialize, Debug, PartialEq)]
+#[someip(tlv)]
+struct Body {
+    #[someip(id = 1)]
+    doors: Doors,
+}
+
+#[derive(SomeipSerialize, SomeipDeserialize, Debug, PartialEq)]
+struct Label {
+    #[someip(length_width = 1, encoding = "utf16le", terminator = false)]
+    text: String,
+}
+
+#[derive(SomeipSerialize, SomeipDeserialize, Debug, PartialEq)]
+struct Sample {
+    id: u16,
+    flag: bool,
+    #[someip(bom = false)]
+    name: String,
+    values: Vec<u16>,
+}
+
+#[derive(SomeipSerialize, SomeipDeserialize, Debug, PartialEq)]
+#[someip(byte_order = "little_endian")]
+struct Counters {
+    total: u32,
+    #[someip(byte_order = "big_endian", length_width = 1)]
+    recent: Vec<u16>,
+}
+
+#[derive(SomeipSerialize, SomeipDeserialize, Debug, PartialEq)]
+#[someip(tlv)]
+struct Record {
+    #[someip(id = 1)]
+    sample: Sample,
+}
+
+/// Test: codec-derive
+///
+/// Serializes a struct with derived implementations and checks the wire format and the round
+/// trip, no vsomeip application involved.
+#[test]
+pub fn main() {
+    let modes = [Mode::Sport, Mode::Comfort, Mode::Unknown(9)];
+    let payload = codec::encode(&modes).unwrap();
+    assert_eq!(payload.as_ref(), [0, 2, 0, 4, 0, 9]);
+    assert_eq!(codec::decode::<[Mode; 3]>(&payload).unwrap(), modes);
+    assert_eq!(codec::decode::<Mode>(&[0, 1]).unwrap(), Mode::Eco);
+
+    let status = Status { gear: Gear::Drive, speed: 0x1234, position: Position(1, -1), name: "a".to_string(),
+        samples: vec![1, 2] };
+    let payload = codec::encode(&status).unwrap();
+    assert_eq!(payload.as_ref(), [
+        4, 0, 0, 0,                 // gear, padding
+        0, 0, 0x12, 0x34,           // speed
+        0, 4, 0, 1, 0xff, 0xff,     // position with length field
+        5, 0xef, 0xbb, 0xbf, b'a', 0, // name
+        1, 2,                       // samples up to the end
+    ]);
+    assert_eq!(codec::decode::<Status>(&payload).unwrap(), status);
+
+    assert_eq!(codec::decode::<Gear>(&[1]),
+               Err(CodecError::InvalidValue { type_name: "Gear", value: 1 }));
+
+    let settings = Settings { volume: 7, stations: vec![0x0102], gear: Gear::Park, owner: "a".to_string(),
+        balance: None };
+    let payload = codec::encode(&settings).unwrap();
+    assert_eq!(payload.as_ref(), [0x00, 0x01, 7, 0x50, 0x02, 2, 1, 2, 0x00, 0x04, 0, 0x50, 0x05, 4, 0, b'a', 0, 0]);
+    // unknown member 3 of a newer version is skipped, the members may come in any order
+    let extended = [0x50, 0x05, 4, 0, b'a', 0, 0, 0x00, 0x04, 0, 0x50, 0x02, 2, 1, 2, 0x20, 0x03, 0, 0, 0, 9,
+        0x00, 0x01, 7];
+    assert_eq!(codec::decode::<Settings>(&extended).unwrap(), settings);
+    assert_eq!(codec::decode::<Settings>(&payload[..3]),
+               Err(CodecError::MissingMember { type_name: "Settings", data_id: 2 }));
+
+    let settings = Settings { balance: Some(-1), ..settings };
+    let payload = codec::encode(&settings).unwrap();
+    assert_eq!(payload[payload.len() - 3..], [0x00, 0x06, 0xff]);
+    assert_eq!(codec::decode::<Settings>(&payload).unwrap(), settings);
+
+    let sources = vec![Source::Off, Source::Tuner(0x0102), Source::Media(vec![9])];
+    let payload = codec::encode(&sources).unwrap();
+    assert_eq!(payload.as_ref(), [0, 0, 0, 13, 0, 1, 2, 2, 1, 2, 5, 7, 0, 0, 0, 1, 9]);
+    assert_eq!(codec::decode::<Vec<Source>>(&payload).unwrap(), sources);
+    assert_eq!(codec::decode::<Source>(&[0, 0]), Err(CodecError::InvalidValue { type_name: "Source", value: 0 }));
+
+    let body = Body { doors: Doors { front_left: true, front_right: false, window: 5, counter: 0xab } };
+    let payload = codec::encode(&body).unwrap();
+    // the bitfield is a base type of 16 bit
+    assert_eq!(payload.as_ref(), [0x10, 0x01, 0xab, 0x51]);
+    assert_eq!(codec::decode::<Body>(&payload).unwrap(), body);
+    let doors = Doors { front_left: false, front_right: false, window: 8, counter: 0 };
+    assert_eq!(codec::encode(&doors), Err(CodecError::InvalidValue { type_name: "Doors", value: 8 }));
+
+    let label = Label { text: "a".to_string() };
+    let payload = codec::encode(&label).unwrap();
+    assert_eq!(payload.as_ref(), [4, 0xff, 0xfe, b'a', 0]);
+    assert_eq!(codec::decode::<Label>(&payload).unwrap(), label);
+
+    let profile = DeploymentProfile { struct_length: LengthWidth::U8, string_length: LengthWidth::U8,
+        array_length: LengthWidth::U16, alignment: 2, byte_order: ByteOrder::LittleEndian, ..Default::default() };
+    let sample = Sample { id: 0x0102, flag: true, name: "a".to_string(), values: vec![3] };
+    let payload = codec::encode_with(&sample, &profile).unwrap();
+    assert_eq!(payload.as_ref(), [
+        13, 0, 2, 1,                // length of the struct, padding, id
+        1, 0, 2, b'a', 0,           // flag, padding, name
+        0, 2, 0, 3, 0,              // padding, values
+    ]);
+    assert_eq!(codec::decode_with::<Sample>(&payload, &profile).unwrap(), sample);
+    let counters = Counters { total: 0x01020304, recent: vec![0x0506] };
+    let payload = codec::encode(&counters).unwrap();
+    assert_eq!(payload.as_ref(), [4, 3, 2, 1, 2, 5, 6]);
+    assert_eq!(codec::decode::<Counters>(&payload).unwrap(), counters);
+    // the byte order of the type takes precedence over the profile's
+    assert_eq!(codec::encode_with(&counters, &profile).unwrap().as_ref(), [8, 0, 4, 3, 2, 1, 2, 5, 6]);
+
+    // the length field of the TLV member replaces the one of the profile
+    let record = Record { sample };
+    let payload = codec::encode_with(&record, &profile).unwrap();
+    assert_eq!(payload.as_ref(), [
+        19, 0x01, 0x70, 13, 0, 0, 0, // length of the struct, tag, length of the member
+        0, 2, 1, 1, 0, 2, b'a', 0, 0, 2, 0, 3, 0,
+    ]);
+    assert_eq!(codec::decode_with::<Record>(&payload, &profile).unwrap(), record);
+}
@@ -0,0 +1,128 @@
+// SPDX-License-Identifier: MPL-2.0
+//
+// Copyright (C) 2024 Alexander Seifarth
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::sync::Arc;
+use std::time::Duration;
+use bytes::Bytes;
+use tokio::sync::mpsc::UnboundedReceiver;
+use tokio::time::timeout;
+use vsomeiprs::{EventGroupID, EventKind, InstanceID, InterfaceVersion, MajorVersion, MessageType, MethodID,
+                Reliability, ReturnCode, ServiceID, VSomeipApplication, VSomeipMessage};
+
+const SERVICE_ID: ServiceID = ServiceID(0x002f);
+const INSTANCE_ID: InstanceID = InstanceID(1);
+const METHOD_ID: MethodID = MethodID(0x0001);
+const NOTIFIER_ID: MethodID = MethodID(0x8001);
+const EVENT_GROUP: EventGroupID = EventGroupID(1);
+const MAJOR: u8 = 1;
+const THREADS: u16 = 4;
+const ITERATIONS: u16 = 100;
+
+/// Test: concurrency
+///
+/// Creates three vsomeip applications:
+/// - routing: setup before the others, acts as routing manager host
+/// - provider: Offers a service with one event and answers its requests. Several threads notify
+///             the event while others register and unregister dedicated message handlers.
+/// - consumer: Subscribes the event, several threads send requests at the same time. Completes
+///             when all responses are received.
+///
+/// The test exercises the application's thread safety, it is run under ThreadSanitizer by the
+/// `tsan` job of the CI workflow:
+/// ```text
+/// RUSTFLAGS=-Zsanitizer=thread cargo +nightly test -Zbuild-std --target x86_64-unknown-linux-gnu \
+///     --test concurrency
+/// ```
+///
+#[tokio::test(flavor = "multi_thread")]
+pub async fn main() {
+    let (_rtmp, _rrecv) = setup_app("routing").await;
+
+    let (papp, precv) = setup_app("provider").await;
+    let papp = Arc::new(papp);
+    papp.offer_event(SERVICE_ID, INSTANCE_ID, NOTIFIER_ID, vec![EVENT_GROUP], EventKind::Event, None, false, false,
+                     Reliability::Unreliable).unwrap();
+    papp.offer_service((SERVICE_ID, INSTANCE_ID, InterfaceVersion::make_version(MAJOR, 0))).unwrap();
+    let ph = tokio::spawn(provider(papp.clone(), precv));
+
+    match timeout(Duration::from_secs(100), consumer(papp)).await {
+        Ok(_) => {}
+        Err(_) => panic!("Error - timeout waiting for consumer"),
+    }
+    ph.abort();
+}
+
+async fn provider(papp: Arc<VSomeipApplication>, mut precv: UnboundedReceiver<VSomeipMessage>) {
+    while let Some(msg) = precv.recv().await {
+        match msg {
+            VSomeipMessage::CallbackPanic{ callback, message } => panic!("{} panicked: {}", callback, message),
+            VSomeipMessage::Message(MessageType::Request{ header, data }) => {
+                papp.send_response(&header, ReturnCode::Ok, data.into_bytes()).unwrap();
+            }
+            _ => {}
+        }
+    }
+}
+
+async fn consumer(papp: Arc<VSomeipApplication>) {
+    let (capp, mut crecv) = setup_app("consumer").await;
+    let capp = Arc::new(capp);
+    capp.request_service((SERVICE_ID, INSTANCE_ID, InterfaceVersion::make_version(MAJOR, 0)));
+    capp.request_event(SERVICE_ID, INSTANCE_ID, NOTIFIER_ID, vec![EVENT_GROUP], false, Reliability::Unreliable);
+    capp.wait_for_service((SERVICE_ID, INSTANCE_ID, MajorVersion(MAJOR)), Duration::from_secs(10)).await.unwrap();
+    capp.subscribe((SERVICE_ID, INSTANCE_ID, MajorVersion(MAJOR)), EVENT_GROUP, NOTIFIER_ID).unwrap();
+
+    let workers = {
+        let capp = capp.clone();
+        tokio::task::spawn_blocking(move || std::thread::scope(|scope| {
+            for thread in 0..THREADS {
+                scope.spawn(|| for i in 0..ITERATIONS {
+                    papp.notify(SERVICE_ID, INSTANCE_ID, NOTIFIER_ID, Bytes::from(i.to_be_bytes().to_vec()), true)
+                        .unwrap();
+                });
+                scope.spawn(|| for i in 0..ITERATIONS {
+                    capp.send_request((SERVICE_ID, INSTANCE_ID, MajorVersion(MAJOR)), METHOD_ID,
+                                      Bytes::from(i.to_be_bytes().to_vec()), Reliability::Unreliable).unwrap();
+                });
+                let papp = &papp;
+                scope.spawn(move || for _ in 0..ITERATIONS {
+                    let method_id = MethodID(0x0100 + thread);
+                    let _dedicated = papp.register_message_handler(SERVICE_ID, INSTANCE_ID, method_id).unwrap();
+                    assert!(papp.unregister_message_handler(SERVICE_ID, INSTANCE_ID, method_id));
+                });
+            }
+        }))
+    };
+
+    let mut responses = 0;
+    while responses < THREADS * ITERATIONS {
+        match crecv.recv().await {
+            Some(VSomeipMessage::CallbackPanic{ callback, message }) => panic!("{} panicked: {}", callback, message),
+            Some(VSomeipMessage::Message(MessageType::Response{ header, .. })) => {
+                assert_eq!(header.method_id, METHOD_ID);
+                responses += 1;
+            }
+            Some(VSomeipMessage::Message(MessageType::Error{ header, return_code, .. })) =>
+                panic!("Unexpected error {:?} for session {}", return_code, header.session_id),
+            Some(_) => {}
+            None => panic!("consumer vsomeip channel closed"),
+        }
+    }
+    workers.await.unwrap();
+}
+
+async fn setup_app(name: &str) -> (VSomeipApplication, UnboundedReceiver<VSomeipMessage>) {
+    let (app, mut recv) = VSomeipApplication::create(name).unwrap();
+    loop {
+        match recv.recv().await {
+            Some(VSomeipMessage::RegistrationState(true)) => break,
+            Some(_) => {}
+            None => panic!("Channel closed"),
+        }
+    }
+    (app, recv)
+}
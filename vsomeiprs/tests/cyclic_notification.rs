@@ -0,0 +1,101 @@
+// SPDX-License-Identifier: MPL-2.0
+//
+// Copyright (C) 2024 Alexander Seifarth
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::timeout;
+use vsomeiprs::{CyclicNotification, EventGroupID, EventKind, InstanceID, InterfaceVersion, MajorVersion, MessageType,
+                MethodID, Payload, Reliability, ServiceID, TypedReceivers, VSomeipApplication};
+
+const SERVICE_ID: ServiceID = ServiceID(0x0032);
+const INSTANCE_ID: InstanceID = InstanceID(1);
+const NOTIFIER_ID: MethodID = MethodID(0x8001);
+const EVENT_GROUP: EventGroupID = EventGroupID(1);
+const MAJOR: u8 = 1;
+const MINOR: u32 = 0;
+const MAX_COUNT_NOTIFICATION: u32 = 50;
+
+/// Test: cyclic-notification
+///
+/// Creates three vsomeip applications:
+/// - routing: setup before the others, acts as routing manager host
+/// - provider: Offers a single service interface with one event and notifies an increasing counter
+///             every 100ms by a CyclicNotification, which ends after 50 notifications.
+/// - consumer: Requests the interface of the provider and subscribes to the event. The counter
+///             must increase with each notification received. Completes when the final
+///             notification is received.
+///
+#[tokio::test]
+pub async fn main() {
+    let (_rtmp, _rrecv) = setup_app("routing").await;
+
+    let ph = tokio::spawn(provider());
+
+    match timeout(Duration::from_secs(100), consumer()).await {
+        Ok(_) => {}
+        Err(_) => panic!("Error - timeout waiting for consumer"),
+    }
+    let _ = ph.await;
+}
+
+async fn provider() {
+    let version = InterfaceVersion::make_version(MAJOR, MINOR);
+    let (papp, _precv) = setup_app("provider").await;
+    let papp = Arc::new(papp);
+    papp.offer_event_seg(SERVICE_ID, INSTANCE_ID, NOTIFIER_ID, EVENT_GROUP, EventKind::Event, None, false, false,
+                         Reliability::Unknown).unwrap();
+    papp.offer_service((SERVICE_ID, INSTANCE_ID, version)).unwrap();
+
+    let mut counter = 0u32;
+    let notifier = CyclicNotification::new(SERVICE_ID, INSTANCE_ID, NOTIFIER_ID, Duration::from_millis(100))
+        .start(papp.clone(), move || {
+            counter += 1;
+            (counter <= MAX_COUNT_NOTIFICATION).then(|| Payload::from(counter.to_be_bytes().to_vec()))
+        })
+        .unwrap();
+    notifier.finished().await;
+
+    tokio::time::sleep(Duration::from_secs(2)).await;
+    papp.stop_offer_event(SERVICE_ID, INSTANCE_ID, NOTIFIER_ID);
+    papp.stop_offer_service((SERVICE_ID, INSTANCE_ID, version));
+}
+
+async fn consumer() {
+    let version = InterfaceVersion::make_version(MAJOR, MINOR);
+    let (capp, mut crecv) = setup_app("consumer").await;
+    capp.request_service((SERVICE_ID, INSTANCE_ID, version));
+    capp.request_event_seg(SERVICE_ID, INSTANCE_ID, NOTIFIER_ID, EVENT_GROUP, false, Reliability::Unknown);
+    capp.wait_for_service((SERVICE_ID, INSTANCE_ID, version), Duration::from_secs(10)).await.unwrap();
+    capp.subscribe((SERVICE_ID, INSTANCE_ID, MajorVersion(MAJOR)), EVENT_GROUP, NOTIFIER_ID).unwrap();
+
+    let mut last = 0u32;
+    while last < MAX_COUNT_NOTIFICATION {
+        match crecv.notifications.recv().await {
+            Some(MessageType::Notification{ header, data, .. }) if header.method_id == NOTIFIER_ID => {
+                let counter = u32::from_be_bytes(data.as_bytes_ref().as_ref().try_into().unwrap());
+                assert!(counter > last, "notification {} after {}", counter, last);
+                last = counter;
+            }
+            Some(_) => {}
+            None => panic!("consumer notification channel closed"),
+        }
+    }
+    capp.release_event(SERVICE_ID, INSTANCE_ID, NOTIFIER_ID);
+    capp.release_service((SERVICE_ID, INSTANCE_ID, version));
+}
+
+async fn setup_app(name: &str) -> (VSomeipApplication, TypedReceivers) {
+    let (app, mut recv) = VSomeipApplication::create_typed(name).unwrap();
+    loop {
+        match recv.registration.recv().await {
+            Some(true) => break,
+            Some(false) => {}
+            None => panic!("Channel closed"),
+        }
+    }
+    (app, recv)
+}
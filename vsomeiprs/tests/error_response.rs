@@ -0,0 +1,130 @@
+// SPDX-License-Identifier: MPL-2.0
+//
+// Copyright (C) 2024 Alexander Seifarth
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::timeout;
+use vsomeiprs::commonapi::Address;
+use vsomeiprs::service::{AccessControl, CallError, ClientClass, Dispatcher, Limit, RateLimit, ServiceProxy,
+                         ServiceSkeleton};
+use vsomeiprs::{InstanceID, InterfaceVersion, MethodID, Reliability, ReturnCode, ServiceID, TypedReceivers,
+                VSomeipApplication};
+
+const SERVICE_ID: ServiceID = ServiceID(0x002e);
+const PLAIN: InstanceID = InstanceID(1);
+const QUEUE_LIMITED: InstanceID = InstanceID(2);
+const PROTECTED: InstanceID = InstanceID(3);
+const RATE_LIMITED: InstanceID = InstanceID(4);
+const KNOWN_METHOD: MethodID = MethodID(0x0001);
+const UNKNOWN_METHOD: MethodID = MethodID(0x0002);
+const SLOW_METHOD: MethodID = MethodID(0x0003);
+
+/// Test: error-response
+///
+/// Creates six vsomeip applications:
+/// - routing: setup before the others, acts as routing manager host
+/// - provider: Offers the service with a Dispatcher handling one of its methods.
+/// - provider_queue: Offers a second instance whose Dispatcher handles a slow method one request
+///                   at a time and lets no further request wait.
+/// - provider_acl: Offers a third instance whose AccessControl grants no client the handled method.
+/// - provider_rate: Offers a fourth instance whose RateLimit permits one request per minute.
+/// - consumer: Calls the handled method and a method without handler through a ServiceProxy.
+///             The call of the latter must fail with CallError::UnknownMethod. Then it calls the
+///             slow method three times at once, at least one call must fail with
+///             CallError::NotReady. The call of the third instance must be refused with
+///             CallError::Failed(ReturnCode::NotOk). The second call of the fourth instance
+///             must be rejected with CallError::NotReady.
+///
+#[tokio::test]
+pub async fn main() {
+    let (_rtmp, _rrecv) = setup_app("routing").await;
+
+    let ph = tokio::spawn(provider("provider", PLAIN, |dispatcher| dispatcher));
+    let qh = tokio::spawn(provider("provider_queue", QUEUE_LIMITED, |dispatcher| {
+        dispatcher.with_queue_limit(0).on_request(SLOW_METHOD, |_header, _payload| async {
+            tokio::time::sleep(Duration::from_millis(500)).await;
+            Ok(())
+        })
+    }));
+    let ah = tokio::spawn(provider("provider_acl", PROTECTED, |dispatcher| {
+        dispatcher.with_layer(Arc::new(AccessControl::new().allow_method(KNOWN_METHOD, ClientClass::Ids(vec![]))))
+    }));
+    let rh = tokio::spawn(provider("provider_rate", RATE_LIMITED, |dispatcher| {
+        dispatcher.with_layer(Arc::new(RateLimit::new(Limit::new(1, Duration::from_secs(60)))))
+    }));
+
+    match timeout(Duration::from_secs(100), consumer()).await {
+        Ok(_) => {}
+        Err(_) => panic!("Error - timeout waiting for consumer"),
+    }
+    ph.abort();
+    qh.abort();
+    ah.abort();
+    rh.abort();
+}
+
+fn address(instance_id: InstanceID) -> Address {
+    Address::new(SERVICE_ID, instance_id, InterfaceVersion::make_version(1, 0))
+}
+
+async fn provider(name: &str, instance_id: InstanceID, configure: impl FnOnce(Dispatcher) -> Dispatcher) {
+    let (papp, precv) = setup_app(name).await;
+    let skeleton = ServiceSkeleton::new(Arc::new(papp), address(instance_id));
+    skeleton.offer(vec![]).unwrap();
+    configure(Dispatcher::new(skeleton))
+        .on_request(KNOWN_METHOD, |_header, _payload| async { Ok(7u8) })
+        .run(precv.requests)
+        .await;
+}
+
+async fn consumer() {
+    let (capp, _crecv) = setup_app("consumer").await;
+    let capp = Arc::new(capp);
+    let proxy = |instance_id| {
+        let proxy = ServiceProxy::new(capp.clone(), address(instance_id));
+        proxy.request_service();
+        proxy
+    };
+
+    let plain = proxy(PLAIN);
+    capp.wait_for_service(address(PLAIN), Duration::from_secs(10)).await.unwrap();
+    let known: Result<u8, CallError> = plain.call(KNOWN_METHOD, &(), Reliability::Unreliable).await;
+    assert_eq!(known.unwrap(), 7);
+    let unknown: Result<(), CallError> = plain.call(UNKNOWN_METHOD, &(), Reliability::Unreliable).await;
+    assert!(matches!(unknown, Err(CallError::UnknownMethod)), "unexpected {:?}", unknown);
+
+    let queue_limited = proxy(QUEUE_LIMITED);
+    capp.wait_for_service(address(QUEUE_LIMITED), Duration::from_secs(10)).await.unwrap();
+    let slow = || queue_limited.call::<_, ()>(SLOW_METHOD, &(), Reliability::Unreliable);
+    let (first, second, third) = tokio::join!(slow(), slow(), slow());
+    let results = [first, second, third];
+    assert!(results.iter().any(|result| matches!(result, Err(CallError::NotReady))), "unexpected {:?}", results);
+
+    let protected = proxy(PROTECTED);
+    capp.wait_for_service(address(PROTECTED), Duration::from_secs(10)).await.unwrap();
+    let denied: Result<u8, CallError> = protected.call(KNOWN_METHOD, &(), Reliability::Unreliable).await;
+    assert!(matches!(denied, Err(CallError::Failed(ReturnCode::NotOk))), "unexpected {:?}", denied);
+
+    let rate_limited = proxy(RATE_LIMITED);
+    capp.wait_for_service(address(RATE_LIMITED), Duration::from_secs(10)).await.unwrap();
+    let first: Result<u8, CallError> = rate_limited.call(KNOWN_METHOD, &(), Reliability::Unreliable).await;
+    assert_eq!(first.unwrap(), 7);
+    let second: Result<u8, CallError> = rate_limited.call(KNOWN_METHOD, &(), Reliability::Unreliable).await;
+    assert!(matches!(second, Err(CallError::NotReady)), "unexpected {:?}", second);
+}
+
+async fn setup_app(name: &str) -> (VSomeipApplication, TypedReceivers) {
+    let (app, mut recv) = VSomeipApplication::create_typed(name).unwrap();
+    loop {
+        match recv.registration.recv().await {
+            Some(true) => break,
+            Some(false) => {}
+            None => panic!("Channel closed"),
+        }
+    }
+    (app, recv)
+}
@@ -0,0 +1,105 @@
+// SPDX-License-Identifier: MPL-2.0
+//
+// Copyright (C) 2024 Alexander Seifarth
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::sync::Arc;
+use std::time::Duration;
+use bytes::Bytes;
+use tokio::sync::mpsc::UnboundedReceiver;
+use tokio::time::timeout;
+use vsomeiprs::commonapi::FailoverProxy;
+use vsomeiprs::{InstanceID, InterfaceVersion, MessageType, MethodID, Reliability, ReturnCode, ServiceID,
+                VSomeipApplication, VSomeipMessage};
+
+const SERVICE_ID: ServiceID = ServiceID(0x002d);
+const PREFERRED: InstanceID = InstanceID(1);
+const BACKUP: InstanceID = InstanceID(2);
+const METHOD_ID: MethodID = MethodID(0x0001);
+
+/// Test: failover
+///
+/// Creates three vsomeip applications:
+/// - routing: setup before the others, acts as routing manager host
+/// - provider: Offers two instances of the service and answers requests with the instance id.
+///             After the first answer of the preferred instance it stops offering it.
+/// - consumer: Uses a failover proxy preferring instance 1 and calls the method until it is
+///             answered by instance 2.
+///
+#[tokio::test]
+pub async fn main() {
+    let (_rtmp, _crecv) = setup_app("routing").await;
+
+    let ph = tokio::spawn(provider());
+
+    match timeout(Duration::from_secs(100), consumer()).await {
+        Ok(_) => {}
+        Err(_) => panic!("Error - timeout waiting for consumer"),
+    }
+    ph.abort();
+}
+
+async fn provider() {
+    let version = InterfaceVersion::make_version(1, 0);
+    let (papp, mut precv) = setup_app("provider").await;
+    papp.offer_service((SERVICE_ID, PREFERRED, version)).unwrap();
+    papp.offer_service((SERVICE_ID, BACKUP, version)).unwrap();
+    while let Some(msg) = precv.recv().await {
+        if let VSomeipMessage::Message(MessageType::Request{ header, .. }) = msg {
+            papp.send_response(&header, ReturnCode::Ok, header.instance_id.id().to_be_bytes().to_vec()).unwrap();
+            if header.instance_id == PREFERRED {
+                papp.stop_offer_service((SERVICE_ID, PREFERRED, version));
+                assert_eq!(papp.offered_services().iter().map(|s| s.instance_id).collect::<Vec<_>>(), vec![BACKUP]);
+            }
+        }
+    }
+}
+
+async fn consumer() {
+    let (capp, mut crecv) = setup_app("consumer").await;
+    let proxy = FailoverProxy::new(Arc::new(capp), SERVICE_ID, &[PREFERRED, BACKUP],
+                                   InterfaceVersion::make_version(1, 0));
+    let (sender, mut responses) = tokio::sync::mpsc::unbounded_channel();
+    let mut answered_by = Vec::new();
+    let mut interval = tokio::time::interval(Duration::from_millis(200));
+    while answered_by.last() != Some(&BACKUP) {
+        tokio::select! {
+            msgo = crecv.recv() => {
+                match msgo {
+                    Some(msg) => { proxy.handle(&msg); }
+                    None => panic!("consumer vsomeip channel closed"),
+                }
+            }
+            Some(instance) = responses.recv() => answered_by.push(instance),
+            _ = interval.tick() => {
+                if proxy.is_available() {
+                    let sender = sender.clone();
+                    proxy.call_method(METHOD_ID, &Bytes::new(), Reliability::Unreliable, move |result| {
+                        if let Ok(response) = result {
+                            let _ = sender.send(InstanceID(u16::from_be_bytes([response[0], response[1]])));
+                        }
+                    });
+                }
+            }
+        }
+    }
+    assert_eq!(answered_by.first(), Some(&PREFERRED));
+}
+
+async fn setup_app(name: &str) -> (VSomeipApplication, UnboundedReceiver<VSomeipMessage>) {
+    let (app, mut recv) = VSomeipApplication::create(name).unwrap();
+    loop {
+        tokio::select! {
+            msg = recv.recv() => {
+                match msg {
+                    Some(VSomeipMessage::RegistrationState(true)) => {break;},
+                    None => { panic!("Channel closed") }
+                    _ => {}
+                }
+            }
+        }
+    }
+    (app, recv)
+}
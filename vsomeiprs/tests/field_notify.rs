@@ -7,7 +7,7 @@
 
 use std::time::Duration;
 use bytes::{Buf, BufMut, BytesMut};
-use vsomeiprs::{EventGroupID, InstanceID, InterfaceVersion, MajorVersion, MessageType, MethodID, ServiceID, VSomeipApplication, VSomeipMessage};
+use vsomeiprs::{EventGroupID, EventKind, InstanceID, InterfaceVersion, MajorVersion, MessageType, MethodID, Reliability, ServiceID, VSomeipApplication, VSomeipMessage};
 use tokio::sync::mpsc::UnboundedReceiver;
 use tokio::time;
 use tokio::time::timeout;
@@ -50,8 +50,9 @@ async fn provider() {
 
     // create the provider app before fork ensure that it has the routing manager
     let (papp, mut precv) = setup_app("provider").await;
-    papp.offer_event_seg(SERVICE_ID, INSTANCE_ID, NOTIFIER_ID, EVENT_GROUP, true, None, true, true);
-    papp.offer_service(SERVICE_ID, INSTANCE_ID, version);
+    papp.offer_event_seg(SERVICE_ID, INSTANCE_ID, NOTIFIER_ID, EVENT_GROUP, EventKind::Field, None, true, true,
+                         Reliability::Unknown).unwrap();
+    papp.offer_service((SERVICE_ID, INSTANCE_ID, version)).unwrap();
 
     let mut interval = time::interval(Duration::from_millis(100));
     loop {
@@ -64,7 +65,7 @@ async fn provider() {
                     let mut pl = BytesMut::with_capacity(4);
                     pl.put_u32(counter);
                     // println!("sending: {}", counter);
-                    papp.notify(SERVICE_ID, INSTANCE_ID, NOTIFIER_ID, &pl.freeze(), true);
+                    papp.notify(SERVICE_ID, INSTANCE_ID, NOTIFIER_ID, pl.freeze(), true).unwrap();
                 }
             },
             _ = precv.recv() => { /*println!("Message {:?}", msg);*/ }
@@ -72,7 +73,7 @@ async fn provider() {
     }
     tokio::time::sleep(Duration::from_secs(2)).await;
     papp.stop_offer_event(SERVICE_ID, INSTANCE_ID, NOTIFIER_ID);
-    papp.stop_offer_service(SERVICE_ID, INSTANCE_ID, version);
+    papp.stop_offer_service((SERVICE_ID, INSTANCE_ID, version));
 }
 
 async fn consumer() -> (u32, u32) {
@@ -81,8 +82,8 @@ async fn consumer() -> (u32, u32) {
     let mut notific_counter = 0u32;
 
     let (capp, mut crecv) = setup_app("consumer").await;
-    capp.request_service(SERVICE_ID, INSTANCE_ID, version);
-    capp.request_event_seg(SERVICE_ID, INSTANCE_ID, NOTIFIER_ID, EVENT_GROUP, true);
+    capp.request_service((SERVICE_ID, INSTANCE_ID, version));
+    capp.request_event_seg(SERVICE_ID, INSTANCE_ID, NOTIFIER_ID, EVENT_GROUP, true, Reliability::Unknown);
     loop {
         tokio::select! {
             msgo = crecv.recv() => {
@@ -93,11 +94,16 @@ async fn consumer() -> (u32, u32) {
                                 panic!("Registration lost to vsomeip")
                             }
                         }
-                        VSomeipMessage::ServiceAvailability{ service_id, instance_id, avail } => {
+                        VSomeipMessage::RoutingState(_) => {}
+                        VSomeipMessage::CallbackPanic{ callback, message } => panic!("{} panicked: {}", callback, message),
+                        VSomeipMessage::ProtocolAnomaly{ message_type, .. } => panic!("unknown message type {}", message_type),
+                        VSomeipMessage::SubscriptionStatus{ .. } => {}
+                        VSomeipMessage::ServiceAvailability{ service_id, instance_id, avail, .. } => {
                             // println!("Service {:04x}.{:04x} available: {}", service_id, instance_id, avail);
-                            if service_id == SERVICE_ID.id() && instance_id == INSTANCE_ID.id() && avail {
+                            if service_id == SERVICE_ID && instance_id == INSTANCE_ID && avail {
                                 // println!("Subscribing");
-                                capp.subscribe(SERVICE_ID, INSTANCE_ID, EVENT_GROUP, NOTIFIER_ID, MajorVersion(MAJOR));
+                                capp.subscribe((SERVICE_ID, INSTANCE_ID, MajorVersion(MAJOR)), EVENT_GROUP, NOTIFIER_ID)
+                                    .unwrap();
                             }
                         }
                         VSomeipMessage::Message(m) => {
@@ -128,7 +134,7 @@ async fn consumer() -> (u32, u32) {
         }
     }
     capp.release_event(SERVICE_ID, INSTANCE_ID, NOTIFIER_ID);
-    capp.release_service(SERVICE_ID, INSTANCE_ID, version);
+    capp.release_service((SERVICE_ID, INSTANCE_ID, version));
     (notific_counter, counter)
 }
 
@@ -0,0 +1,89 @@
+// SPDX-License-Identifier: MPL-2.0
+//
+// Copyright (C) 2024 Alexander Seifarth
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::timeout;
+use vsomeiprs::commonapi::Address;
+use vsomeiprs::service::{Field, FieldDeployment, FieldProvider, ServiceProxy, ServiceSkeleton};
+use vsomeiprs::{InstanceID, InterfaceVersion, MethodID, Reliability, ReturnCode, ServiceID, TypedReceivers,
+                VSomeipApplication};
+
+const SERVICE_ID: ServiceID = ServiceID(0x0030);
+const INSTANCE_ID: InstanceID = InstanceID(1);
+const GETTER: MethodID = MethodID(0x0010);
+const SETTER: MethodID = MethodID(0x0011);
+
+/// Test: field-provider
+///
+/// Creates three vsomeip applications:
+/// - routing: setup before the others, acts as routing manager host
+/// - provider: Offers the service with a FieldProvider whose validator accepts values up to 10.
+/// - consumer: Writes an accepted and a rejected value and reads the field by requests without
+///             return, then reads the field by its getter. The accepted value must have been
+///             stored, but none of the requests without return must have been answered.
+///
+#[tokio::test]
+pub async fn main() {
+    let (_rtmp, _rrecv) = setup_app("routing").await;
+
+    let ph = tokio::spawn(provider());
+
+    match timeout(Duration::from_secs(100), consumer()).await {
+        Ok(_) => {}
+        Err(_) => panic!("Error - timeout waiting for consumer"),
+    }
+    ph.abort();
+}
+
+fn address() -> Address {
+    Address::new(SERVICE_ID, INSTANCE_ID, InterfaceVersion::make_version(1, 0))
+}
+
+fn deployment() -> FieldDeployment {
+    FieldDeployment { getter: Some(GETTER), setter: Some(SETTER), notifier: None, reliable: false, cycle: None }
+}
+
+async fn provider() {
+    let (papp, mut precv) = setup_app("provider").await;
+    let skeleton = ServiceSkeleton::new(Arc::new(papp), address());
+    skeleton.offer(vec![]).unwrap();
+    let field = FieldProvider::new(skeleton, deployment(), 0u8)
+        .with_validator(|_current, value| if value <= 10 { Ok(value) } else { Err(ReturnCode::NotOk) });
+    while let Some(msg) = precv.requests.recv().await {
+        field.handle(&msg).unwrap();
+    }
+}
+
+async fn consumer() {
+    let (capp, mut crecv) = setup_app("consumer").await;
+    let capp = Arc::new(capp);
+    let proxy = ServiceProxy::new(capp.clone(), address());
+    proxy.request_service();
+    capp.wait_for_service(address(), Duration::from_secs(10)).await.unwrap();
+
+    proxy.call_no_return(SETTER, &5u8, Reliability::Unreliable).unwrap();
+    proxy.call_no_return(SETTER, &20u8, Reliability::Unreliable).unwrap();
+    proxy.call_no_return(GETTER, &(), Reliability::Unreliable).unwrap();
+
+    // the requests are handled in order, the getter is answered after the ones without return
+    let field = Field::<u8>::new(proxy, deployment());
+    assert_eq!(field.get().await.unwrap(), 5);
+    assert!(crecv.responses.try_recv().is_err(), "request without return answered");
+}
+
+async fn setup_app(name: &str) -> (VSomeipApplication, TypedReceivers) {
+    let (app, mut recv) = VSomeipApplication::create_typed(name).unwrap();
+    loop {
+        match recv.registration.recv().await {
+            Some(true) => break,
+            Some(false) => {}
+            None => panic!("Channel closed"),
+        }
+    }
+    (app, recv)
+}
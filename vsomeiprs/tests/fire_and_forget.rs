@@ -0,0 +1,112 @@
+// SPDX-License-Identifier: MPL-2.0
+//
+// Copyright (C) 2024 Alexander Seifarth
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::timeout;
+use vsomeiprs::{service_interface, InstanceID, MethodID, Reliability, ReturnCode, TypedReceivers,
+                VSomeipApplication};
+
+const INSTANCE_ID: InstanceID = InstanceID(1);
+
+service_interface! {
+    pub interface Counter {
+        service_id: 0x0031,
+        version: (1, 0),
+        proxy: CounterProxy,
+        stub: CounterStub,
+        skeleton: CounterSkeleton,
+        event: CounterEvent,
+        methods {
+            fn add(value: u8) -> u8 = 0x0001;
+        }
+        fields {
+            Total(u8) = 0x8001 in [1] { get get_total = 0x0010; set set_total = 0x0011; }
+        }
+    }
+}
+
+#[derive(Default)]
+struct CounterService {
+    total: Mutex<u8>,
+}
+
+impl CounterStub for CounterService {
+    async fn add(&self, value: u8) -> Result<u8, ReturnCode> {
+        let mut total = self.total.lock().unwrap();
+        *total += value;
+        Ok(*total)
+    }
+
+    async fn get_total(&self) -> Result<u8, ReturnCode> {
+        Ok(*self.total.lock().unwrap())
+    }
+
+    async fn set_total(&self, value: u8) -> Result<u8, ReturnCode> {
+        *self.total.lock().unwrap() = value;
+        Ok(value)
+    }
+}
+
+/// Test: fire-and-forget
+///
+/// Creates three vsomeip applications:
+/// - routing: setup before the others, acts as routing manager host
+/// - provider: Offers the service of a service_interface! skeleton.
+/// - consumer: Sends requests without return to the method, the getter and the setter, then calls
+///             the getter. The stub must have processed the requests without return, but none of
+///             them must have been answered.
+///
+#[tokio::test]
+pub async fn main() {
+    let (_rtmp, _rrecv) = setup_app("routing").await;
+
+    let ph = tokio::spawn(provider());
+
+    match timeout(Duration::from_secs(100), consumer()).await {
+        Ok(_) => {}
+        Err(_) => panic!("Error - timeout waiting for consumer"),
+    }
+    ph.abort();
+}
+
+async fn provider() {
+    let (papp, mut precv) = setup_app("provider").await;
+    let skeleton = CounterSkeleton::new(Arc::new(papp), INSTANCE_ID);
+    skeleton.offer().unwrap();
+    skeleton.run(&CounterService::default(), &mut precv.requests).await;
+}
+
+async fn consumer() {
+    let (capp, mut crecv) = setup_app("consumer").await;
+    let capp = Arc::new(capp);
+    let proxy = CounterProxy::new(capp.clone(), INSTANCE_ID);
+    proxy.proxy().request_service();
+    capp.wait_for_service(*proxy.proxy().address(), Duration::from_secs(10)).await.unwrap();
+
+    let fire_and_forget = proxy.proxy();
+    fire_and_forget.call_no_return(MethodID(0x0001), &(2u8,), Reliability::Unreliable).unwrap();
+    fire_and_forget.call_no_return(MethodID(0x0011), &(5u8,), Reliability::Unreliable).unwrap();
+    fire_and_forget.call_no_return(MethodID(0x0010), &(), Reliability::Unreliable).unwrap();
+    fire_and_forget.call_no_return(MethodID(0x0001), &(3u8,), Reliability::Unreliable).unwrap();
+
+    // the requests are processed in order, the last one is answered after the ones without return
+    assert_eq!(proxy.get_total().await.unwrap(), 8);
+    assert!(crecv.responses.try_recv().is_err(), "request without return answered");
+}
+
+async fn setup_app(name: &str) -> (VSomeipApplication, TypedReceivers) {
+    let (app, mut recv) = VSomeipApplication::create_typed(name).unwrap();
+    loop {
+        match recv.registration.recv().await {
+            Some(true) => break,
+            Some(false) => {}
+            None => panic!("Channel closed"),
+        }
+    }
+    (app, recv)
+}
@@ -0,0 +1,98 @@
+// SPDX-License-Identifier: MPL-2.0
+//
+// Copyright (C) 2024 Alexander Seifarth
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc::UnboundedReceiver;
+use tokio::time::timeout;
+use vsomeiprs::commonapi::Address;
+use vsomeiprs::standby::{HotStandby, Role};
+use vsomeiprs::{InstanceID, InterfaceVersion, MethodID, ServiceID, VSomeipApplication, VSomeipMessage};
+
+const SERVICE_ID: ServiceID = ServiceID(0x002c);
+const INSTANCE_ID: InstanceID = InstanceID(1);
+const HEARTBEAT_METHOD: MethodID = MethodID(0x7fff);
+
+/// Test: hot-standby
+///
+/// Creates three vsomeip applications:
+/// - routing: setup before the others, acts as routing manager host
+/// - primary: Offers the service first and must become active.
+/// - secondary: Offers the same instance after the primary is active and must become standby.
+///              When the primary is stopped the secondary must take over and become active.
+///
+#[tokio::test]
+pub async fn main() {
+    let (_rtmp, _crecv) = setup_app("routing").await;
+    let address = Address::new(SERVICE_ID, INSTANCE_ID, InterfaceVersion::make_version(1, 0));
+
+    let (primary, mut precv) = setup_app("primary").await;
+    let primary = HotStandby::new(Arc::new(primary), address, HEARTBEAT_METHOD, 1);
+    primary.start().unwrap();
+    match timeout(Duration::from_secs(30), wait_for_role(&primary, &mut precv, Role::Active)).await {
+        Ok(_) => {}
+        Err(_) => panic!("Error - timeout waiting for the primary to become active"),
+    }
+
+    let (secondary, mut srecv) = setup_app("secondary").await;
+    let secondary = HotStandby::new(Arc::new(secondary), address, HEARTBEAT_METHOD, 2);
+    secondary.start().unwrap();
+    let run_primary = async {
+        loop {
+            tokio::select! {
+                Some(msg) = precv.recv() => { primary.handle(&msg); }
+                _ = tokio::time::sleep(Duration::from_millis(100)) => primary.heartbeat(),
+            }
+        }
+    };
+    tokio::select! {
+        _ = run_primary => {}
+        r = timeout(Duration::from_secs(30), wait_for_role(&secondary, &mut srecv, Role::Standby)) => {
+            if r.is_err() { panic!("Error - timeout waiting for the secondary to become standby") }
+        }
+    }
+    assert_eq!(primary.get_role(), Role::Active);
+
+    // failover: stopping the primary withdraws its offer
+    drop(primary);
+    drop(precv);
+    match timeout(Duration::from_secs(30), wait_for_role(&secondary, &mut srecv, Role::Active)).await {
+        Ok(_) => {}
+        Err(_) => panic!("Error - timeout waiting for the secondary to take over"),
+    }
+}
+
+async fn wait_for_role(standby: &HotStandby, recv: &mut UnboundedReceiver<VSomeipMessage>, role: Role) {
+    let mut interval = tokio::time::interval(Duration::from_millis(100));
+    while standby.get_role() != role {
+        tokio::select! {
+            msgo = recv.recv() => {
+                match msgo {
+                    Some(msg) => { standby.handle(&msg); }
+                    None => panic!("vsomeip channel closed"),
+                }
+            }
+            _ = interval.tick() => standby.heartbeat(),
+        }
+    }
+}
+
+async fn setup_app(name: &str) -> (VSomeipApplication, UnboundedReceiver<VSomeipMessage>) {
+    let (app, mut recv) = VSomeipApplication::create(name).unwrap();
+    loop {
+        tokio::select! {
+            msg = recv.recv() => {
+                match msg {
+                    Some(VSomeipMessage::RegistrationState(true)) => {break;},
+                    None => { panic!("Channel closed") }
+                    _ => {}
+                }
+            }
+        }
+    }
+    (app, recv)
+}
@@ -0,0 +1,86 @@
+// SPDX-License-Identifier: MPL-2.0
+//
+// Copyright (C) 2024 Alexander Seifarth
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::timeout;
+use vsomeiprs::{EventGroupID, EventKind, InitialValue, InstanceID, InterfaceVersion, MajorVersion, MessageType,
+                MethodID, Payload, Reliability, ServiceID, TypedReceivers, VSomeipApplication};
+
+const SERVICE_ID: ServiceID = ServiceID(0x0036);
+const INSTANCE_ID: InstanceID = InstanceID(1);
+const NOTIFIER_ID: MethodID = MethodID(0x8001);
+const EVENT_GROUP: EventGroupID = EventGroupID(1);
+const MAJOR: u8 = 1;
+const MINOR: u32 = 0;
+const VALUE: [u8; 2] = [0x12, 0x34];
+
+/// Test: initial-value
+///
+/// Creates three vsomeip applications:
+/// - routing: setup before the others, acts as routing manager host
+/// - provider: Offers a single interface with one field, but never notifies it. Its subscription
+///             handler provides the value of the field to new subscribers.
+/// - consumer: Requests the interface of the provider and subscribes to the field. It must receive
+///             the value of the subscription handler as initial notification.
+///
+#[tokio::test]
+pub async fn main() {
+    let (_rtmp, _rrecv) = setup_app("routing").await;
+
+    let ph = tokio::spawn(provider());
+
+    match timeout(Duration::from_secs(100), consumer()).await {
+        Ok(_) => {}
+        Err(_) => panic!("Error - timeout waiting for consumer"),
+    }
+    ph.abort();
+}
+
+async fn provider() {
+    let version = InterfaceVersion::make_version(MAJOR, MINOR);
+    let (papp, mut precv) = setup_app("provider").await;
+    papp.offer_event_seg(SERVICE_ID, INSTANCE_ID, NOTIFIER_ID, EVENT_GROUP, EventKind::Field, None, false, true,
+                         Reliability::Unknown).unwrap();
+    papp.register_subscription_handler(SERVICE_ID, INSTANCE_ID, EVENT_GROUP,
+        Arc::new(InitialValue::new(NOTIFIER_ID, |_client| Some(Payload::from(VALUE.to_vec())))));
+    papp.offer_service((SERVICE_ID, INSTANCE_ID, version)).unwrap();
+    while precv.requests.recv().await.is_some() {}
+}
+
+async fn consumer() {
+    let version = InterfaceVersion::make_version(MAJOR, MINOR);
+    let (capp, mut crecv) = setup_app("consumer").await;
+    capp.request_service((SERVICE_ID, INSTANCE_ID, version));
+    capp.request_event_seg(SERVICE_ID, INSTANCE_ID, NOTIFIER_ID, EVENT_GROUP, true, Reliability::Unknown);
+    capp.wait_for_service((SERVICE_ID, INSTANCE_ID, version), Duration::from_secs(10)).await.unwrap();
+    capp.subscribe((SERVICE_ID, INSTANCE_ID, MajorVersion(MAJOR)), EVENT_GROUP, NOTIFIER_ID).unwrap();
+
+    match crecv.notifications.recv().await {
+        Some(MessageType::Notification{ header, is_initial, data }) => {
+            assert_eq!(header.method_id, NOTIFIER_ID);
+            assert!(is_initial);
+            assert_eq!(data.as_bytes_ref().as_ref(), VALUE);
+        }
+        Some(m) => panic!("Unexpected message: {}", m),
+        None => panic!("consumer notification channel closed"),
+    }
+    capp.release_event(SERVICE_ID, INSTANCE_ID, NOTIFIER_ID);
+    capp.release_service((SERVICE_ID, INSTANCE_ID, version));
+}
+
+async fn setup_app(name: &str) -> (VSomeipApplication, TypedReceivers) {
+    let (app, mut recv) = VSomeipApplication::create_typed(name).unwrap();
+    loop {
+        match recv.registration.recv().await {
+            Some(true) => break,
+            Some(false) => {}
+            None => panic!("Channel closed"),
+        }
+    }
+    (app, recv)
+}
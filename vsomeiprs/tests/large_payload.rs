@@ -0,0 +1,171 @@
+// SPDX-License-Identifier: MPL-2.0
+//
+// Copyright (C) 2024 Alexander Seifarth
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::time::Duration;
+use bytes::{Bytes, BytesMut};
+use tokio::sync::mpsc::UnboundedReceiver;
+use tokio::time;
+use tokio::time::timeout;
+use vsomeiprs::{Configuration, EventGroupID, EventKind, InstanceID, InterfaceVersion, MajorVersion, MessageType, MethodID,
+                Reliability, ReturnCode, ServiceConfig, ServiceID, TpConfig, VSomeipApplication, VSomeipMessage};
+
+const SERVICE_ID: ServiceID = ServiceID(0x0815);
+const INSTANCE_ID: InstanceID = InstanceID(7);
+const METHOD_ID: MethodID = MethodID(0x0001);
+const NOTIFIER_ID: MethodID = MethodID(0x8001);
+const EVENT_GROUP: EventGroupID = EventGroupID(1);
+const MAJOR: u8 = 1;
+const MINOR: u32 = 0;
+const PAYLOAD_SIZE: usize = 20_000;
+const MAX_COUNT_REQUESTS: u32 = 10;
+
+/// Test: large-payload
+///
+/// Applies a configuration with SOME/IP-TP settings for one method and one event and transfers
+/// payloads far beyond the UDP MTU (~1400 bytes) between three vsomeip applications:
+/// - routing: setup before the others, acts as routing manager host
+/// - provider: Offers the service and a field, answers requests with the reversed request payload
+///             and notifies a large payload once the consumer subscribed.
+/// - consumer: Sends large requests and checks the responses, then waits for the notification.
+///
+/// All applications run in this process and talk via the local routing manager, so the payloads
+/// never go over UDP and SOME/IP-TP segmentation is not exercised. The test only checks that
+/// vsomeip accepts the configuration and that large payloads arrive complete.
+///
+#[tokio::test]
+pub async fn main() {
+    let config_path = std::env::temp_dir().join("vsomeiprs-large-payload.json");
+    let (name, value) = Configuration::new()
+        .logging("fatal", false)
+        .max_payload_size_local(4 * PAYLOAD_SIZE as u32)
+        .max_payload_size_unreliable(4 * PAYLOAD_SIZE as u32)
+        .service(ServiceConfig::new(SERVICE_ID, INSTANCE_ID)
+            .unreliable(30510)
+            .tp_client_to_service(TpConfig::new(METHOD_ID))
+            .tp_service_to_client(TpConfig::new(METHOD_ID)
+                .max_segment_length(1392)
+                .separation_time(Duration::from_micros(100)))
+            .tp_service_to_client(TpConfig::new(NOTIFIER_ID)))
+        .apply(&config_path, None)
+        .expect("Failed to write vsomeip configuration");
+    // the test runs on a single thread, vsomeip has not started any yet
+    std::env::set_var(name, value);
+
+    let (_rtmp, _crecv) = setup_app("routing").await;
+
+    let ph = tokio::spawn(provider());
+
+    match timeout(Duration::from_secs(100), consumer()).await {
+        Ok(_) => {}
+        Err(_) => panic!("Error - timeout waiting for consumer"),
+    }
+    let _ = ph.await;
+}
+
+fn make_payload(seed: u32) -> Bytes {
+    let mut pl = BytesMut::with_capacity(PAYLOAD_SIZE);
+    pl.extend((0..PAYLOAD_SIZE).map(|i| (i as u32).wrapping_mul(31).wrapping_add(seed) as u8));
+    pl.freeze()
+}
+
+async fn provider() {
+    let version = InterfaceVersion::make_version(MAJOR, MINOR);
+    let (papp, mut precv) = setup_app("provider").await;
+    papp.offer_event_seg(SERVICE_ID, INSTANCE_ID, NOTIFIER_ID, EVENT_GROUP, EventKind::Field, None, false, true,
+                         Reliability::Unreliable).unwrap();
+    papp.offer_service((SERVICE_ID, INSTANCE_ID, version)).unwrap();
+    papp.notify(SERVICE_ID, INSTANCE_ID, NOTIFIER_ID, make_payload(0xff), true).unwrap();
+
+    let mut handled = 0u32;
+    while handled < MAX_COUNT_REQUESTS {
+        match precv.recv().await {
+            Some(VSomeipMessage::Message(MessageType::Request{ header, data })) => {
+                let mut response = data.as_bytes_ref().to_vec();
+                assert_eq!(response.len(), PAYLOAD_SIZE);
+                response.reverse();
+                papp.send_response(&header, ReturnCode::Ok, Bytes::from(response)).unwrap();
+                handled += 1;
+            }
+            Some(_) => {}
+            None => panic!("provider vsomeip channel closed"),
+        }
+    }
+    tokio::time::sleep(Duration::from_secs(2)).await;
+    papp.stop_offer_event(SERVICE_ID, INSTANCE_ID, NOTIFIER_ID);
+    papp.stop_offer_service((SERVICE_ID, INSTANCE_ID, version));
+}
+
+async fn consumer() {
+    let version = InterfaceVersion::make_version(MAJOR, MINOR);
+    let mut interval = time::interval(Duration::from_millis(100));
+    let (capp, mut crecv) = setup_app("consumer").await;
+    let mut available = false;
+    let mut sent = 0u32;
+    let mut responses = 0u32;
+    let mut notified = false;
+    capp.request_service((SERVICE_ID, INSTANCE_ID, version));
+    capp.request_event_seg(SERVICE_ID, INSTANCE_ID, NOTIFIER_ID, EVENT_GROUP, true, Reliability::Unreliable);
+    while responses < MAX_COUNT_REQUESTS || !notified {
+        tokio::select! {
+            _ = interval.tick() => {
+                if available && sent < MAX_COUNT_REQUESTS {
+                    capp.send_request((SERVICE_ID, INSTANCE_ID, MajorVersion(MAJOR)), METHOD_ID, make_payload(sent),
+                                      Reliability::Unreliable).unwrap();
+                    sent += 1;
+                }
+            }
+            msgo = crecv.recv() => {
+                match msgo {
+                    Some(VSomeipMessage::ServiceAvailability{ service_id, instance_id, avail, .. }) => {
+                        if service_id == SERVICE_ID && instance_id == INSTANCE_ID {
+                            available = avail;
+                            if avail {
+                                capp.subscribe((SERVICE_ID, INSTANCE_ID, MajorVersion(MAJOR)), EVENT_GROUP, NOTIFIER_ID)
+                                    .unwrap();
+                            }
+                        }
+                    }
+                    Some(VSomeipMessage::Message(MessageType::Response{ header, data })) => {
+                        assert_eq!(header.method_id, METHOD_ID);
+                        let payload = data.as_bytes_ref();
+                        assert_eq!(payload.len(), PAYLOAD_SIZE);
+                        assert!(payload.iter().rev().zip(make_payload(responses).iter()).all(|(a, b)| a == b));
+                        responses += 1;
+                    }
+                    Some(VSomeipMessage::Message(MessageType::Notification{ header, data, .. })) => {
+                        assert_eq!(header.method_id, NOTIFIER_ID);
+                        assert_eq!(data.as_bytes_ref(), &make_payload(0xff));
+                        notified = true;
+                    }
+                    Some(VSomeipMessage::Message(MessageType::Error{ return_code, .. })) => {
+                        panic!("Unexpected error {}", return_code)
+                    }
+                    Some(_) => {}
+                    None => panic!("consumer vsomeip channel closed"),
+                }
+            }
+        }
+    }
+    capp.release_event(SERVICE_ID, INSTANCE_ID, NOTIFIER_ID);
+    capp.release_service((SERVICE_ID, INSTANCE_ID, version));
+}
+
+async fn setup_app(name: &str) -> (VSomeipApplication, UnboundedReceiver<VSomeipMessage>) {
+    let (app, mut recv) = VSomeipApplication::create(name).unwrap();
+    loop {
+        tokio::select! {
+            msg = recv.recv() => {
+                match msg {
+                    Some(VSomeipMessage::RegistrationState(true)) => {break;},
+                    None => { panic!("Channel closed") }
+                    _ => {}
+                }
+            }
+        }
+    }
+    (app, recv)
+}
@@ -0,0 +1,122 @@
+// SPDX-License-Identifier: MPL-2.0
+//
+// Copyright (C) 2024 Alexander Seifarth
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::time::Duration;
+use bytes::Bytes;
+use tokio::sync::mpsc::UnboundedReceiver;
+use tokio::time::timeout;
+use vsomeiprs::{InstanceID, InterfaceVersion, MajorVersion, MessageType, MethodID, Reliability, ReturnCode, ServiceID,
+                VSomeipApplication, VSomeipError, VSomeipMessage, ANY_INSTANCE, ANY_METHOD, ANY_SERVICE};
+
+const SERVICE_ID: ServiceID = ServiceID(0x002b);
+const INSTANCE_ID: InstanceID = InstanceID(7);
+const DEDICATED_METHOD: MethodID = MethodID(0x0001);
+const DEFAULT_METHOD: MethodID = MethodID(0x0002);
+const MAJOR: u8 = 1;
+const MINOR: u32 = 0;
+
+/// Test: message-handler
+///
+/// Creates three vsomeip applications:
+/// - routing: setup before the others, acts as routing manager host
+/// - provider: Offers a single interface and registers a dedicated message handler for one of
+///             its methods. Requests to that method must only arrive on the dedicated receiver,
+///             requests to the other method only on the application's main receiver.
+/// - consumer: Requests the interface and calls both methods once.
+///
+#[tokio::test]
+pub async fn main() {
+    let (_rtmp, _crecv) = setup_app("routing").await;
+
+    let ph = tokio::spawn(provider());
+
+    match timeout(Duration::from_secs(100), consumer()).await {
+        Ok(_) => {}
+        Err(_) => panic!("Error - timeout waiting for consumer"),
+    }
+    let _ = ph.await;
+}
+
+async fn provider() {
+    let version = InterfaceVersion::make_version(MAJOR, MINOR);
+    let (papp, mut precv) = setup_app("provider").await;
+    assert_eq!(papp.register_message_handler(ANY_SERVICE, ANY_INSTANCE, ANY_METHOD).err(),
+               Some(VSomeipError::ReservedHandler));
+    let mut dedicated = papp.register_message_handler(SERVICE_ID, INSTANCE_ID, DEDICATED_METHOD).unwrap();
+    papp.offer_service((SERVICE_ID, INSTANCE_ID, version)).unwrap();
+    let mut pending = 2;
+    while pending > 0 {
+        tokio::select! {
+            msgo = dedicated.recv() => {
+                match msgo {
+                    Some(VSomeipMessage::Message(MessageType::Request{ header, .. })) => {
+                        assert_eq!(header.method_id, DEDICATED_METHOD);
+                        papp.send_response(&header, ReturnCode::Ok, Bytes::new()).unwrap();
+                        pending -= 1;
+                    }
+                    Some(m) => panic!("Unexpected message on dedicated handler: {:?}", m),
+                    None => panic!("dedicated channel closed"),
+                }
+            }
+            msgo = precv.recv() => {
+                match msgo {
+                    Some(VSomeipMessage::Message(MessageType::Request{ header, .. })) => {
+                        assert_eq!(header.method_id, DEFAULT_METHOD);
+                        papp.send_response(&header, ReturnCode::Ok, Bytes::new()).unwrap();
+                        pending -= 1;
+                    }
+                    Some(VSomeipMessage::Message(m)) => panic!("Unexpected message: {}", m),
+                    Some(_) => {}
+                    None => panic!("provider vsomeip channel closed"),
+                }
+            }
+        }
+    }
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    papp.stop_offer_service((SERVICE_ID, INSTANCE_ID, version));
+}
+
+async fn consumer() {
+    let version = InterfaceVersion::make_version(MAJOR, MINOR);
+    let (capp, mut crecv) = setup_app("consumer").await;
+    capp.request_service((SERVICE_ID, INSTANCE_ID, version));
+    let mut responses = 0;
+    while responses < 2 {
+        match crecv.recv().await {
+            Some(VSomeipMessage::ServiceAvailability{ service_id, instance_id, avail: true, .. })
+                if service_id == SERVICE_ID && instance_id == INSTANCE_ID => {
+                for method in [DEDICATED_METHOD, DEFAULT_METHOD] {
+                    capp.send_request((SERVICE_ID, INSTANCE_ID, MajorVersion(MAJOR)), method, Bytes::new(),
+                                      Reliability::Unreliable).unwrap();
+                }
+            }
+            Some(VSomeipMessage::Message(MessageType::Response{ header, .. })) => {
+                assert_eq!(header.service_id, SERVICE_ID);
+                responses += 1;
+            }
+            Some(_) => {}
+            None => panic!("consumer vsomeip channel closed"),
+        }
+    }
+    capp.release_service((SERVICE_ID, INSTANCE_ID, version));
+}
+
+async fn setup_app(name: &str) -> (VSomeipApplication, UnboundedReceiver<VSomeipMessage>) {
+    let (app, mut recv) = VSomeipApplication::create(name).unwrap();
+    loop {
+        tokio::select! {
+            msg = recv.recv() => {
+                match msg {
+                    Some(VSomeipMessage::RegistrationState(true)) => {break;},
+                    None => { panic!("Channel closed") }
+                    _ => {}
+                }
+            }
+        }
+    }
+    (app, recv)
+}
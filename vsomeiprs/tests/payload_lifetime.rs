@@ -0,0 +1,106 @@
+// SPDX-License-Identifier: MPL-2.0
+//
+// Copyright (C) 2024 Alexander Seifarth
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::time::Duration;
+use bytes::Bytes;
+use tokio::sync::mpsc::UnboundedReceiver;
+use tokio::time::timeout;
+use vsomeiprs::{InstanceID, InterfaceVersion, MajorVersion, MessageType, MethodID, Reliability, ReturnCode, ServiceID, VSomeipApplication, VSomeipMessage};
+
+const SERVICE_ID: ServiceID = ServiceID(0x002c);
+const INSTANCE_ID: InstanceID = InstanceID(7);
+const METHOD_ID: MethodID = MethodID(0x0001);
+const MAJOR: u8 = 1;
+const MINOR: u32 = 0;
+const RESPONSE: [u8; 4] = [0xde, 0xad, 0xbe, 0xef];
+
+/// Test: payload-lifetime
+///
+/// Creates three vsomeip applications:
+/// - routing: setup before the others, acts as routing manager host
+/// - provider: Offers a single interface and answers requests with a fixed payload.
+/// - consumer: Requests the interface and calls the method once. It keeps the received payload
+///             as `Bytes` (and a clone of it) and checks the data again after the message and the
+///             application are gone.
+///
+#[tokio::test]
+pub async fn main() {
+    let (_rtmp, _crecv) = setup_app("routing").await;
+
+    let ph = tokio::spawn(provider());
+
+    let (payload, copy) = match timeout(Duration::from_secs(100), consumer()).await {
+        Ok(payloads) => payloads,
+        Err(_) => panic!("Error - timeout waiting for consumer"),
+    };
+    let _ = ph.await;
+    assert_eq!(payload.as_ref(), RESPONSE);
+    assert_eq!(copy.as_ref(), RESPONSE);
+    drop(payload);
+    assert_eq!(copy.as_ref(), RESPONSE);
+}
+
+async fn provider() {
+    let version = InterfaceVersion::make_version(MAJOR, MINOR);
+    let (papp, mut precv) = setup_app("provider").await;
+    papp.offer_service((SERVICE_ID, INSTANCE_ID, version)).unwrap();
+    loop {
+        match precv.recv().await {
+            Some(VSomeipMessage::Message(MessageType::Request{ header, .. })) => {
+                papp.send_response(&header, ReturnCode::Ok, Bytes::from_static(&RESPONSE)).unwrap();
+                break;
+            }
+            Some(VSomeipMessage::Message(m)) => panic!("Unexpected message: {}", m),
+            Some(_) => {}
+            None => panic!("provider vsomeip channel closed"),
+        }
+    }
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    papp.stop_offer_service((SERVICE_ID, INSTANCE_ID, version));
+}
+
+async fn consumer() -> (Bytes, Bytes) {
+    let version = InterfaceVersion::make_version(MAJOR, MINOR);
+    let (capp, mut crecv) = setup_app("consumer").await;
+    capp.request_service((SERVICE_ID, INSTANCE_ID, version));
+    let payload = loop {
+        match crecv.recv().await {
+            Some(VSomeipMessage::ServiceAvailability{ service_id, instance_id, avail: true, .. })
+                if service_id == SERVICE_ID && instance_id == INSTANCE_ID => {
+                capp.send_request((SERVICE_ID, INSTANCE_ID, MajorVersion(MAJOR)), METHOD_ID, Bytes::new(),
+                                  Reliability::Unreliable).unwrap();
+            }
+            Some(VSomeipMessage::Message(MessageType::Response{ data, .. })) => {
+                // shares the vsomeip payload object instead of copying it
+                break data.into_bytes();
+            }
+            Some(_) => {}
+            None => panic!("consumer vsomeip channel closed"),
+        }
+    };
+    capp.release_service((SERVICE_ID, INSTANCE_ID, version));
+    let copy = payload.clone();
+    drop(crecv);
+    drop(capp);
+    (payload, copy)
+}
+
+async fn setup_app(name: &str) -> (VSomeipApplication, UnboundedReceiver<VSomeipMessage>) {
+    let (app, mut recv) = VSomeipApplication::create(name).unwrap();
+    loop {
+        tokio::select! {
+            msg = recv.recv() => {
+                match msg {
+                    Some(VSomeipMessage::RegistrationState(true)) => {break;},
+                    None => { panic!("Channel closed") }
+                    _ => {}
+                }
+            }
+        }
+    }
+    (app, recv)
+}
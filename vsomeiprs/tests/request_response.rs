@@ -12,7 +12,7 @@ use bytes::{Buf, BufMut, BytesMut};
 use tokio::sync::mpsc::UnboundedReceiver;
 use tokio::time;
 use tokio::time::timeout;
-use vsomeiprs::{InstanceID, InterfaceVersion, MajorVersion, MessageType, MethodID, ReturnCode, ServiceID, VSomeipApplication, VSomeipMessage};
+use vsomeiprs::{InstanceID, InterfaceVersion, MajorVersion, MessageType, MethodID, Reliability, ReturnCode, ServiceID, SessionID, VSomeipApplication, VSomeipMessage};
 
 const SERVICE_ID: ServiceID = ServiceID(0x002a);
 const INSTANCE_ID: InstanceID = InstanceID(101);
@@ -46,13 +46,17 @@ pub async fn main() {
 async fn provider() {
     let version = InterfaceVersion::make_version(MAJOR, MINOR);
     let (papp, mut precv) = setup_app("provider").await;
-    papp.offer_service(SERVICE_ID, INSTANCE_ID, version);
+    papp.offer_service((SERVICE_ID, INSTANCE_ID, version)).unwrap();
     loop {
         tokio::select! {
             msgo = precv.recv() => {
                 if let Some(msg) = msgo {
                     match msg {
                         VSomeipMessage::RegistrationState(rs) => { assert!(rs) }
+                        VSomeipMessage::RoutingState(_) => {}
+                        VSomeipMessage::CallbackPanic{ callback, message } => panic!("{} panicked: {}", callback, message),
+                        VSomeipMessage::ProtocolAnomaly{ message_type, .. } => panic!("unknown message type {}", message_type),
+                        VSomeipMessage::SubscriptionStatus{ .. } => {}
                         VSomeipMessage::ServiceAvailability{ .. } => {}
                         VSomeipMessage::Message(m) => {
                             // println!("P: {}", m);
@@ -67,7 +71,7 @@ async fn provider() {
                                     let input = payload.get_u32();
                                     let mut resp_pl = BytesMut::with_capacity(4);
                                     resp_pl.put_u32( input.bitxor(0x12345678u32) );
-                                    papp.send_response(&header, ReturnCode::Ok, &resp_pl.freeze());
+                                    papp.send_response(&header, ReturnCode::Ok, resp_pl.freeze()).unwrap();
 
                                     if input == MAX_COUNT_REQUESTS { break }
                                 }
@@ -84,7 +88,7 @@ async fn provider() {
             }
         }
     }
-    papp.stop_offer_service(SERVICE_ID, INSTANCE_ID, version);
+    papp.stop_offer_service((SERVICE_ID, INSTANCE_ID, version));
 }
 
 async fn consumer() {
@@ -93,17 +97,17 @@ async fn consumer() {
     let (capp, mut crecv) = setup_app("consumer").await;
     let mut available = false;
     let mut counter:u32 = 0;
-    let mut session_map = HashMap::<u16,u32>::new();
-    capp.request_service(SERVICE_ID, INSTANCE_ID, version);
+    let mut session_map = HashMap::<SessionID,u32>::new();
+    capp.request_service((SERVICE_ID, INSTANCE_ID, version));
     loop {
         tokio::select!{
             _ = interval.tick() => {
                 if available && counter <= MAX_COUNT_REQUESTS {
                    let mut pl = BytesMut::with_capacity(4);
                     pl.put_u32(counter);
-                    let session = capp.send_request(SERVICE_ID, INSTANCE_ID, METHOD_ID,
-                                                   MajorVersion(MAJOR), &pl.freeze(), false);
-                    session_map.insert(session.id(), counter);
+                    let session = capp.send_request((SERVICE_ID, INSTANCE_ID, MajorVersion(MAJOR)), METHOD_ID,
+                                                    pl.freeze(), Reliability::Unreliable).unwrap();
+                    session_map.insert(session, counter);
                     counter += 1
                 }
             }
@@ -111,8 +115,12 @@ async fn consumer() {
                 if let Some(msg) = msgo {
                     match msg {
                         VSomeipMessage::RegistrationState(rs) => { assert!(rs) }
-                        VSomeipMessage::ServiceAvailability{ service_id, instance_id, avail } => {
-                            if service_id == SERVICE_ID.id() && instance_id == INSTANCE_ID.id() {
+                        VSomeipMessage::RoutingState(_) => {}
+                        VSomeipMessage::CallbackPanic{ callback, message } => panic!("{} panicked: {}", callback, message),
+                        VSomeipMessage::ProtocolAnomaly{ message_type, .. } => panic!("unknown message type {}", message_type),
+                        VSomeipMessage::SubscriptionStatus{ .. } => {}
+                        VSomeipMessage::ServiceAvailability{ service_id, instance_id, avail, .. } => {
+                            if service_id == SERVICE_ID && instance_id == INSTANCE_ID {
                                 available = avail;
                             }
                         }
@@ -130,7 +138,7 @@ async fn consumer() {
                                     assert_eq!(payload.len(), 4);
                                     let input = payload.get_u32().bitxor(0x12345678);
                                     assert_eq!(
-                                        session_map.get(&header.session_id.id()), Some(&input));
+                                        session_map.get(&header.session_id), Some(&input));
                                     if input >= MAX_COUNT_REQUESTS { break }
                                 }
                                 MessageType::Error{ .. } => { panic!("Unexpected Error") }
@@ -0,0 +1,64 @@
+// SPDX-License-Identifier: MPL-2.0
+//
+// Copyright (C) 2024 Alexander Seifarth
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::time::Duration;
+use tokio::sync::mpsc::UnboundedReceiver;
+use tokio::time::timeout;
+use vsomeiprs::{InstanceID, InterfaceVersion, ServiceID, VSomeipApplication, VSomeipMessage};
+
+const SERVICE_ID: ServiceID = ServiceID(0x0034);
+const INSTANCE_ID: InstanceID = InstanceID(1);
+const MAJOR: u8 = 1;
+const MINOR: u32 = 0;
+
+/// Test: shutdown
+///
+/// Creates three vsomeip applications:
+/// - routing: setup before the others, acts as routing manager host
+/// - provider: Offers a single interface, then shuts down once the consumer saw it available.
+///             Its channel must close after the shutdown.
+/// - consumer: Requests the interface of the provider and waits until it is available and
+///             unavailable again, as the provider stops offering it on shutdown.
+///
+#[tokio::test]
+pub async fn main() {
+    let (_rtmp, _rrecv) = setup_app("routing").await;
+
+    let version = InterfaceVersion::make_version(MAJOR, MINOR);
+    let (capp, _crecv) = setup_app("consumer").await;
+    let mut available = capp.watch_availability((SERVICE_ID, INSTANCE_ID, version));
+
+    let (papp, mut precv) = setup_app("provider").await;
+    papp.offer_service((SERVICE_ID, INSTANCE_ID, version)).unwrap();
+
+    let result = timeout(Duration::from_secs(100), async {
+        available.wait_for(|avail| *avail).await.unwrap();
+        papp.shutdown().await;
+        // the channel closes once the application is gone
+        while precv.recv().await.is_some() {}
+        available.wait_for(|avail| !*avail).await.unwrap();
+    }).await;
+    if result.is_err() {
+        panic!("Error - timeout waiting for shutdown");
+    }
+}
+
+async fn setup_app(name: &str) -> (VSomeipApplication, UnboundedReceiver<VSomeipMessage>) {
+    let (app, mut recv) = VSomeipApplication::create(name).unwrap();
+    loop {
+        tokio::select! {
+            msg = recv.recv() => {
+                match msg {
+                    Some(VSomeipMessage::RegistrationState(true)) => {break;},
+                    None => { panic!("Channel closed") }
+                    _ => {}
+                }
+            }
+        }
+    }
+    (app, recv)
+}
@@ -0,0 +1,114 @@
+// SPDX-License-Identifier: MPL-2.0
+//
+// Copyright (C) 2024 Alexander Seifarth
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc::UnboundedReceiver;
+use tokio::time::timeout;
+use vsomeiprs::{ClientID, EventGroupID, EventKind, InstanceID, InterfaceVersion, MajorVersion, MethodID, Reliability,
+                ServiceID, SubscriptionHandler, VSomeipApplication, VSomeipMessage};
+
+const SERVICE_ID: ServiceID = ServiceID(0x0035);
+const INSTANCE_ID: InstanceID = InstanceID(1);
+const ACCEPTED_EVENT: MethodID = MethodID(0x8001);
+const ACCEPTED_GROUP: EventGroupID = EventGroupID(1);
+const REFUSED_EVENT: MethodID = MethodID(0x8002);
+const REFUSED_GROUP: EventGroupID = EventGroupID(2);
+const MAJOR: u8 = 1;
+const MINOR: u32 = 0;
+
+/// Test: subscription-status
+///
+/// Creates three vsomeip applications:
+/// - routing: setup before the others, acts as routing manager host
+/// - provider: Offers a single interface with an event in each of two event groups and refuses
+///             all subscriptions to the second one.
+/// - consumer: Registers for the subscription status of both event groups and subscribes to them.
+///             The subscription to the first event group must be reported accepted, the one to
+///             the second event group refused.
+///
+#[tokio::test]
+pub async fn main() {
+    let (_rtmp, _rrecv) = setup_app("routing").await;
+
+    let ph = tokio::spawn(provider());
+
+    match timeout(Duration::from_secs(100), consumer()).await {
+        Ok(_) => {}
+        Err(_) => panic!("Error - timeout waiting for consumer"),
+    }
+    ph.abort();
+}
+
+struct Refuse;
+
+impl SubscriptionHandler for Refuse {
+    fn on_subscribe(&self, _client_id: ClientID) -> bool {
+        false
+    }
+}
+
+async fn provider() {
+    let version = InterfaceVersion::make_version(MAJOR, MINOR);
+    let (papp, mut precv) = setup_app("provider").await;
+    papp.offer_event_seg(SERVICE_ID, INSTANCE_ID, ACCEPTED_EVENT, ACCEPTED_GROUP, EventKind::Event, None, false,
+                         true, Reliability::Unknown).unwrap();
+    papp.offer_event_seg(SERVICE_ID, INSTANCE_ID, REFUSED_EVENT, REFUSED_GROUP, EventKind::Event, None, false,
+                         true, Reliability::Unknown).unwrap();
+    papp.register_subscription_handler(SERVICE_ID, INSTANCE_ID, REFUSED_GROUP, Arc::new(Refuse));
+    papp.offer_service((SERVICE_ID, INSTANCE_ID, version)).unwrap();
+    while precv.recv().await.is_some() {}
+}
+
+async fn consumer() {
+    let version = InterfaceVersion::make_version(MAJOR, MINOR);
+    let (capp, mut crecv) = setup_app("consumer").await;
+    capp.request_service((SERVICE_ID, INSTANCE_ID, version));
+    capp.request_event_seg(SERVICE_ID, INSTANCE_ID, ACCEPTED_EVENT, ACCEPTED_GROUP, false, Reliability::Unknown);
+    capp.request_event_seg(SERVICE_ID, INSTANCE_ID, REFUSED_EVENT, REFUSED_GROUP, false, Reliability::Unknown);
+    capp.register_subscription_status_handler(SERVICE_ID, INSTANCE_ID, ACCEPTED_GROUP, ACCEPTED_EVENT);
+    capp.register_subscription_status_handler(SERVICE_ID, INSTANCE_ID, REFUSED_GROUP, REFUSED_EVENT);
+    capp.wait_for_service((SERVICE_ID, INSTANCE_ID, version), Duration::from_secs(10)).await.unwrap();
+    capp.subscribe((SERVICE_ID, INSTANCE_ID, MajorVersion(MAJOR)), ACCEPTED_GROUP, ACCEPTED_EVENT).unwrap();
+    capp.subscribe((SERVICE_ID, INSTANCE_ID, MajorVersion(MAJOR)), REFUSED_GROUP, REFUSED_EVENT).unwrap();
+
+    let (mut accepted, mut refused) = (None, None);
+    while accepted.is_none() || refused.is_none() {
+        match crecv.recv().await {
+            Some(VSomeipMessage::SubscriptionStatus{ service_id, instance_id, event_group_id, accepted: status, .. })
+                if service_id == SERVICE_ID && instance_id == INSTANCE_ID => {
+                match event_group_id {
+                    ACCEPTED_GROUP => accepted = Some(status),
+                    REFUSED_GROUP => refused = Some(status),
+                    _ => panic!("Unexpected event group {}", event_group_id),
+                }
+            }
+            Some(VSomeipMessage::CallbackPanic{ callback, message }) => panic!("{} panicked: {}", callback, message),
+            Some(_) => {}
+            None => panic!("consumer vsomeip channel closed"),
+        }
+    }
+    assert_eq!(accepted, Some(true));
+    assert_eq!(refused, Some(false));
+    capp.release_service((SERVICE_ID, INSTANCE_ID, version));
+}
+
+async fn setup_app(name: &str) -> (VSomeipApplication, UnboundedReceiver<VSomeipMessage>) {
+    let (app, mut recv) = VSomeipApplication::create(name).unwrap();
+    loop {
+        tokio::select! {
+            msg = recv.recv() => {
+                match msg {
+                    Some(VSomeipMessage::RegistrationState(true)) => {break;},
+                    None => { panic!("Channel closed") }
+                    _ => {}
+                }
+            }
+        }
+    }
+    (app, recv)
+}
@@ -9,22 +9,37 @@
 
 #include <cassert>
 #include <iostream>
+#include <system_error>
+#include <utility>
 
-std::shared_ptr<application> application::create(std::string const& name) {
+create_result_ce application::create(std::string const& name, std::string const& configuration,
+                                     std::shared_ptr<application>& result) {
     auto runtime = vsomeip::runtime::get();
-    assert(runtime);
-    auto application= runtime->create_application(name);
+    if (!runtime) {
+        std::cerr << "FAILED to get vsomeip::runtime [" << name << "]\n";
+        return CR_NO_RUNTIME;
+    }
+    auto application = configuration.empty() ? runtime->create_application(name)
+                                             : runtime->create_application(name, configuration);
     if (!application) {
         std::cerr << "FAILED to create vsomeip::application object [" << name << "]\n";
-        return nullptr;
+        return CR_CREATE_FAILED;
     }
     if (!application->init()) {
         std::cerr << "FAILED to initialize vsomeip::application [" << name << "]\n";
-        return nullptr;
+        runtime->remove_application(name);
+        return CR_INIT_FAILED;
     }
     auto af = std::make_shared<::application>(runtime, application);
-    af->start();
-    return af;
+    af->setup_routing_ready_handler();
+    try {
+        af->start();
+    } catch (std::system_error const& e) {
+        std::cerr << "FAILED to start vsomeip::application [" << name << "]: " << e.what() << "\n";
+        return CR_START_FAILED;
+    }
+    result = std::move(af);
+    return CR_OK;
 }
 
 application::application(
@@ -34,6 +49,8 @@ application::application(
         , _application{ std::move(application) }
         , _dispatch_thread{}
         , _state_connected{false}
+        , _routing_state{vsomeip::routing_state_e::RS_UNKNOWN}
+        , _routing_ready{false}
 {}
 
 application::~application() {
@@ -54,6 +71,14 @@ std::string const& application::name() const {
     return _application->get_name();
 }
 
+bool application::is_routing() const {
+    return _application->is_routing();
+}
+
+vsomeip::client_t application::client() const {
+    return _application->get_client();
+}
+
 std::shared_ptr<vsomeip::payload> application::create_payload_empty() const {
     return _runtime->create_payload();
 }
@@ -132,6 +157,51 @@ void application::unsubscribe(
     _application->unsubscribe(service, instance, event_group);
 }
 
+void application::unsubscribe(
+        vsomeip::service_t service,
+        vsomeip::instance_t instance,
+        vsomeip::eventgroup_t event_group,
+        vsomeip::event_t event)
+{
+    _application->unsubscribe(service, instance, event_group, event);
+}
+
+void application::setup_subscription_status_handler(
+        vsomeip::service_t service,
+        vsomeip::instance_t instance,
+        vsomeip::eventgroup_t event_group,
+        vsomeip::event_t event,
+        vsomeip::subscription_status_handler_t callback)
+{
+    _application->register_subscription_status_handler(service, instance, event_group, event, std::move(callback));
+}
+
+void application::clear_subscription_status_handler(
+        vsomeip::service_t service,
+        vsomeip::instance_t instance,
+        vsomeip::eventgroup_t event_group,
+        vsomeip::event_t event)
+{
+    _application->unregister_subscription_status_handler(service, instance, event_group, event);
+}
+
+void application::setup_subscription_handler(
+        vsomeip::service_t service,
+        vsomeip::instance_t instance,
+        vsomeip::eventgroup_t event_group,
+        vsomeip::subscription_handler_sec_t callback)
+{
+    _application->register_subscription_handler(service, instance, event_group, std::move(callback));
+}
+
+void application::clear_subscription_handler(
+        vsomeip::service_t service,
+        vsomeip::instance_t instance,
+        vsomeip::eventgroup_t event_group)
+{
+    _application->unregister_subscription_handler(service, instance, event_group);
+}
+
 void application::offer_service(
         vsomeip::service_t service,
         vsomeip::instance_t instance,
@@ -166,6 +236,8 @@ void application::offer_event(vsomeip::service_t service, vsomeip::instance_t in
 void application::stop_offer_event(vsomeip::service_t service, vsomeip::instance_t instance, vsomeip::event_t event)
 {
     _application->stop_offer_event(service, instance, event);
+    std::lock_guard<std::mutex> lock{_notify_payloads_mutex};
+    _notify_payloads.erase(std::make_tuple(service, instance, event));
 }
 
 void application::notify(vsomeip::service_t service, vsomeip::instance_t instance, vsomeip::event_t event,
@@ -175,6 +247,27 @@ void application::notify(vsomeip::service_t service, vsomeip::instance_t instanc
     _application->notify(service, instance, event, payload, force);
 }
 
+void application::notify_one(vsomeip::service_t service, vsomeip::instance_t instance, vsomeip::event_t event,
+                             vsomeip::client_t client, bool force, uint8_t const* data, uint32_t data_len)
+{
+    auto payload = _runtime->create_payload(data, data_len);
+    _application->notify_one(service, instance, event, payload, client, force);
+}
+
+void application::fast_notify(vsomeip::service_t service, vsomeip::instance_t instance, vsomeip::event_t event,
+                              bool force, uint8_t const* data, uint32_t data_len)
+{
+    // vsomeip copies the payload data into the event during notify(), so the payload object can be
+    // reused for the next notification once notify() returned. The lock serializes the users of a slot.
+    std::lock_guard<std::mutex> lock{_notify_payloads_mutex};
+    auto& payload = _notify_payloads[std::make_tuple(service, instance, event)];
+    if (!payload) {
+        payload = _runtime->create_payload();
+    }
+    payload->set_data(data, data_len);
+    _application->notify(service, instance, event, payload, force);
+}
+
 void application::setup_state_handler(on_state_callback_t callback) {
     _application->register_state_handler(
     [c = std::move(callback)](vsomeip::state_type_e state) {
@@ -182,6 +275,41 @@ void application::setup_state_handler(on_state_callback_t callback) {
     );
 }
 
+void application::setup_routing_state_handler(on_routing_state_callback_t callback) {
+    _application->register_routing_state_handler(
+    [this, c = std::move(callback)](vsomeip::routing_state_e state) {
+                _routing_state = state;
+                c(state); }
+    );
+}
+
+void application::clear_main_handlers() {
+    _application->unregister_state_handler();
+    _application->unregister_message_handler(vsomeip::ANY_SERVICE, vsomeip::ANY_INSTANCE, vsomeip::ANY_METHOD);
+    // vsomeip cannot unregister the routing state handler, it is replaced by one only tracking the state
+    _application->register_routing_state_handler(
+    [this](vsomeip::routing_state_e state) { _routing_state = state; }
+    );
+}
+
+void application::set_routing_state(vsomeip::routing_state_e state) {
+    // vsomeip does not provide a getter, so the last requested/reported state is kept here
+    _routing_state = state;
+    _application->set_routing_state(state);
+}
+
+vsomeip::routing_state_e application::routing_state() const {
+    return _routing_state;
+}
+
+void application::setup_routing_ready_handler() {
+    _application->register_routing_ready_handler([this]() { _routing_ready = true; });
+}
+
+bool application::is_routing_ready() const {
+    return _routing_ready;
+}
+
 void application::setup_avail_handler(on_avail_callback_t callback) {
     _application->register_availability_handler(
     vsomeip::ANY_SERVICE, vsomeip::ANY_INSTANCE,
@@ -203,14 +331,133 @@ void application::setup_avail_handler(vsomeip::service_t service, vsomeip::insta
 void application::clear_avail_handler(vsomeip::service_t service, vsomeip::instance_t instance, vsomeip::major_version_t  major)
 {
     _application->unregister_availability_handler(service, instance, major);
+    std::lock_guard<std::mutex> lock{_versions_mutex};
+    for (auto it = _versions.begin(); it != _versions.end(); ) {
+        auto [s, i, m] = it->first;
+        bool handled = s == service && (i == instance || instance == vsomeip::ANY_INSTANCE) && m == major;
+        it = handled ? _versions.erase(it) : std::next(it);
+    }
+}
+
+bool application::is_available(vsomeip::service_t service, vsomeip::instance_t instance,
+                               vsomeip::major_version_t major, vsomeip::minor_version_t minor) const
+{
+    return _application->is_available(service, instance, major, minor);
+}
+
+std::pair<vsomeip::major_version_t, vsomeip::minor_version_t> application::changed_version(
+        vsomeip::service_t service, vsomeip::instance_t instance, vsomeip::major_version_t major, bool avail)
+{
+    // vsomeip does not report the version in availability callbacks, so compare the versions
+    // available now with those available at the last callback of the handler
+    vsomeip::available_t available;
+    _application->are_available(available, service, instance, major);
+    auto current = std::move(available[service][instance]);
+
+    std::lock_guard<std::mutex> lock{_versions_mutex};
+    auto key = std::make_tuple(service, instance, major);
+    auto& known = _versions[key];
+    auto const& before = avail ? known : current;
+    auto const& after = avail ? current : known;
+    std::pair<vsomeip::major_version_t, vsomeip::minor_version_t> changed{vsomeip::ANY_MAJOR, vsomeip::ANY_MINOR};
+    for (auto const& [available_major, minor] : after) {
+        auto it = before.find(available_major);
+        if (it == before.end() || it->second != minor) {
+            changed = {available_major, minor};
+            break;
+        }
+    }
+    if (current.empty()) {
+        _versions.erase(key);
+    } else {
+        known = std::move(current);
+    }
+    return changed;
 }
 
 void application::setup_msg_handler(on_msg_callback_t callback) {
     _application->register_message_handler(
     vsomeip::ANY_SERVICE, vsomeip::ANY_INSTANCE, vsomeip::ANY_METHOD,
-    [c = std::move(callback)](std::shared_ptr<vsomeip::message> const& msg) {
-                c(msg);
+    [this, c = std::move(callback)](std::shared_ptr<vsomeip::message> const& msg) {
+                // vsomeip invokes all matching handlers, messages with a dedicated handler
+                // must not show up here as well
+                if (!has_msg_handler(msg)) {
+                    c(msg);
+                }
+        });
+}
+
+void application::msg_handler_slot::disable() {
+    std::lock_guard<std::mutex> lock{mutex};
+    enabled = false;
+}
+
+void application::setup_msg_handler(vsomeip::service_t service, vsomeip::instance_t instance,
+                                    vsomeip::method_t method, on_msg_callback_t callback)
+{
+    auto slot = std::make_shared<msg_handler_slot>();
+    std::shared_ptr<msg_handler_slot> previous;
+    {
+        std::lock_guard<std::mutex> lock{_msg_handlers_mutex};
+        previous = std::exchange(_msg_handlers[std::make_tuple(service, instance, method)], slot);
+    }
+    _application->register_message_handler(service, instance, method,
+    [slot, c = std::move(callback)](std::shared_ptr<vsomeip::message> const& msg) {
+                std::lock_guard<std::mutex> lock{slot->mutex};
+                if (slot->enabled) {
+                    c(msg);
+                }
         });
+    if (previous) {
+        previous->disable();
+    }
+}
+
+void application::clear_msg_handler(vsomeip::service_t service, vsomeip::instance_t instance,
+                                    vsomeip::method_t method)
+{
+    _application->unregister_message_handler(service, instance, method);
+    std::shared_ptr<msg_handler_slot> slot;
+    {
+        std::lock_guard<std::mutex> lock{_msg_handlers_mutex};
+        auto it = _msg_handlers.find(std::make_tuple(service, instance, method));
+        if (it == _msg_handlers.end()) {
+            return;
+        }
+        slot = std::move(it->second);
+        _msg_handlers.erase(it);
+    }
+    slot->disable();
+}
+
+void application::clear_all_handlers() {
+    _application->clear_all_handler();
+    setup_routing_ready_handler();
+    decltype(_msg_handlers) slots;
+    {
+        std::lock_guard<std::mutex> lock{_msg_handlers_mutex};
+        slots.swap(_msg_handlers);
+    }
+    for (auto& [key, slot] : slots) {
+        slot->disable();
+    }
+}
+
+bool application::has_msg_handler(std::shared_ptr<vsomeip::message> const& msg) {
+    std::lock_guard<std::mutex> lock{_msg_handlers_mutex};
+    if (_msg_handlers.empty()) {
+        return false;
+    }
+    for (auto service : {msg->get_service(), vsomeip::ANY_SERVICE}) {
+        for (auto instance : {msg->get_instance(), vsomeip::ANY_INSTANCE}) {
+            for (auto method : {msg->get_method(), vsomeip::ANY_METHOD}) {
+                if (_msg_handlers.count(std::make_tuple(service, instance, method)) > 0) {
+                    return true;
+                }
+            }
+        }
+    }
+    return false;
 }
 
 vsomeip::session_t
@@ -256,11 +503,28 @@ void application::send_error(service_id service, instance_id instance, method_id
     msg->set_client(client);
     msg->set_session(session);
     msg->set_interface_version(major);
-    msg->set_message_type(vsomeip::message_type_e::MT_RESPONSE);
+    msg->set_message_type(vsomeip::message_type_e::MT_ERROR);
     msg->set_return_code(rc);
     _application->send(msg);
 }
 
+void application::send_message(message_header const& header, uint8_t const* data, uint32_t data_len)
+{
+    auto payload = _runtime->create_payload(data, data_len);
+    auto msg = _runtime->create_message(header.is_reliable);
+    msg->set_service(header.service);
+    msg->set_instance(header.instance);
+    msg->set_method(header.method);
+    msg->set_client(header.client);
+    msg->set_session(header.session);
+    msg->set_interface_version(header.if_version);
+    // message type and return code are passed through as given, the C enums carry the wire values
+    msg->set_message_type(static_cast<vsomeip::message_type_e>(header.message_type));
+    msg->set_return_code(static_cast<vsomeip::return_code_e>(header.return_code));
+    msg->set_payload(payload);
+    _application->send(msg);
+}
+
 std::shared_ptr<vsomeip::message> application::create_message() {
     return _runtime->create_message();
 }
@@ -12,44 +12,116 @@
 
 #include <vsomeip/vsomeip.hpp>
 
+#include <atomic>
 #include <memory>
+#include <mutex>
+#include <map>
+#include <set>
 #include <thread>
+#include <tuple>
 
 class application {
     std::shared_ptr<vsomeip::runtime> _runtime;
     std::shared_ptr<vsomeip::application> _application;
     std::thread _dispatch_thread;
     bool _state_connected;
+    std::atomic<vsomeip::routing_state_e> _routing_state;
+    std::atomic<bool> _routing_ready;
+
+    // dedicated message handler of a (service, instance, method) combination, disabled when it is
+    // replaced or removed: vsomeip may still dispatch queued copies of the handler afterwards
+    struct msg_handler_slot {
+        std::mutex mutex;
+        bool enabled{true};
+
+        // waits for a running call of the handler, the handler is not called anymore afterwards
+        void disable();
+    };
+
+    // (service, instance, method) combinations with a dedicated message handler
+    std::mutex _msg_handlers_mutex;
+    std::map<std::tuple<vsomeip::service_t, vsomeip::instance_t, vsomeip::method_t>,
+             std::shared_ptr<msg_handler_slot>> _msg_handlers;
+
+    // reusable payload objects per notifier for fast_notify
+    std::mutex _notify_payloads_mutex;
+    std::map<std::tuple<vsomeip::service_t, vsomeip::instance_t, vsomeip::event_t>,
+             std::shared_ptr<vsomeip::payload>> _notify_payloads;
+
+    // versions of the service instances available at the last callback of the availability
+    // handler of (service, instance, requested major)
+    std::mutex _versions_mutex;
+    std::map<std::tuple<vsomeip::service_t, vsomeip::instance_t, vsomeip::major_version_t>,
+             std::map<vsomeip::major_version_t, vsomeip::minor_version_t>> _versions;
 
     using on_state_callback_t = std::function<void(state_type_ce)>;
+    using on_routing_state_callback_t = std::function<void(vsomeip::routing_state_e)>;
     using on_avail_callback_t = std::function<void(vsomeip::service_t, vsomeip::instance_t, bool)>;
     using on_msg_callback_t = std::function<void (const std::shared_ptr< vsomeip::message > &)>;
 
     void start();
     void stop();
+    // tracks whether vsomeip reported the routing to be ready, registered again by clear_all_handlers()
+    void setup_routing_ready_handler();
+    bool has_msg_handler(std::shared_ptr<vsomeip::message> const& msg);
 
 public:
     application(std::shared_ptr<vsomeip::runtime> runtime, std::shared_ptr<vsomeip::application> application);
     application(application const&) = delete;
     ~application();
 
+    // an empty `configuration` uses vsomeip's default configuration lookup
     [[nodiscard]]
-    static std::shared_ptr<application> create(std::string const& name);
+    static create_result_ce create(std::string const& name, std::string const& configuration,
+                                   std::shared_ptr<application>& result);
 
     void setup_state_handler(on_state_callback_t callback);
     void setup_avail_handler(on_avail_callback_t callback);
+    void setup_routing_state_handler(on_routing_state_callback_t callback);
     void setup_msg_handler(on_msg_callback_t callback);
+    void setup_msg_handler(vsomeip::service_t service, vsomeip::instance_t instance, vsomeip::method_t method,
+                           on_msg_callback_t callback);
+    void clear_msg_handler(vsomeip::service_t service, vsomeip::instance_t instance, vsomeip::method_t method);
+    void clear_all_handlers();
+    // unregisters the state, routing state and main message handlers, the routing state is still tracked
+    void clear_main_handlers();
 
     void setup_avail_handler(vsomeip::service_t service, vsomeip::instance_t instance, vsomeip::major_version_t  major,
                              on_avail_callback_t callback);
     void clear_avail_handler(vsomeip::service_t service, vsomeip::instance_t instance, vsomeip::major_version_t  major);
 
+    [[nodiscard]]
+    bool is_available(vsomeip::service_t service, vsomeip::instance_t instance, vsomeip::major_version_t major,
+                      vsomeip::minor_version_t minor) const;
+
+    // returns the version of the service instance that became available or unavailable for the
+    // availability handler of `major`, ANY_MAJOR and ANY_MINOR if none changed
+    [[nodiscard]]
+    std::pair<vsomeip::major_version_t, vsomeip::minor_version_t> changed_version(vsomeip::service_t service,
+                                                                                 vsomeip::instance_t instance,
+                                                                                 vsomeip::major_version_t major,
+                                                                                 bool avail);
+
+    void set_routing_state(vsomeip::routing_state_e state);
+
+    [[nodiscard]]
+    vsomeip::routing_state_e routing_state() const;
+
+    [[nodiscard]]
+    bool is_routing_ready() const;
+
     [[nodiscard]]
     std::shared_ptr<vsomeip::runtime>& runtime();
 
     [[nodiscard]]
     std::string const& name() const;
 
+    [[nodiscard]]
+    bool is_routing() const;
+
+    [[nodiscard]]
+    vsomeip::client_t client() const;
+
     [[nodiscard]]
     std::shared_ptr<vsomeip::payload> create_payload_empty() const;
 
@@ -82,6 +154,20 @@ public:
 
     void unsubscribe(vsomeip::service_t service, vsomeip::instance_t instance, vsomeip::eventgroup_t event_group);
 
+    void unsubscribe(vsomeip::service_t service, vsomeip::instance_t instance, vsomeip::eventgroup_t event_group,
+                     vsomeip::event_t event);
+
+    void setup_subscription_status_handler(vsomeip::service_t service, vsomeip::instance_t instance,
+                                           vsomeip::eventgroup_t event_group, vsomeip::event_t event,
+                                           vsomeip::subscription_status_handler_t callback);
+    void clear_subscription_status_handler(vsomeip::service_t service, vsomeip::instance_t instance,
+                                           vsomeip::eventgroup_t event_group, vsomeip::event_t event);
+
+    void setup_subscription_handler(vsomeip::service_t service, vsomeip::instance_t instance,
+                                    vsomeip::eventgroup_t event_group, vsomeip::subscription_handler_sec_t callback);
+    void clear_subscription_handler(vsomeip::service_t service, vsomeip::instance_t instance,
+                                    vsomeip::eventgroup_t event_group);
+
     void offer_service(vsomeip::service_t service, vsomeip::instance_t instance,
                        vsomeip::major_version_t major = vsomeip::DEFAULT_MAJOR,
                        vsomeip::minor_version_t minor = vsomeip::DEFAULT_MINOR);
@@ -104,6 +190,12 @@ public:
     void notify(vsomeip::service_t service, vsomeip::instance_t instance, vsomeip::event_t event,
                 bool force, uint8_t const* data, uint32_t data_len);
 
+    void notify_one(vsomeip::service_t service, vsomeip::instance_t instance, vsomeip::event_t event,
+                    vsomeip::client_t client, bool force, uint8_t const* data, uint32_t data_len);
+
+    void fast_notify(vsomeip::service_t service, vsomeip::instance_t instance, vsomeip::event_t event,
+                     bool force, uint8_t const* data, uint32_t data_len);
+
     vsomeip::session_t send_request(vsomeip::service_t service, vsomeip::instance_t instance, vsomeip::method_t method,
                       major_version major, uint8_t const* data, uint32_t data_len, bool reliable);
 
@@ -113,6 +205,8 @@ public:
 
     void send_error(service_id service, instance_id instance, method_id method, client_id client, session_id session,
                     major_version major, bool reliable, vsomeip::return_code_e rc);
+
+    void send_message(message_header const& header, uint8_t const* data, uint32_t data_len);
 };
 
 #endif // APPLICATION_H_
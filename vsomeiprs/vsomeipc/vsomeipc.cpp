@@ -8,17 +8,40 @@
 #include "vsomeipc.h"
 #include "application.h"
 
+#include <arpa/inet.h>
+
 #include <cassert>
+#include <exception>
 #include <iostream>
 #include <optional>
 #include <thread>
 
-application_t create_application(const char* name) {
-    auto af = application::create(name);
-    if (af) {
-        return new std::shared_ptr<application>(af);
+// Runs a call into vsomeip. Exceptions must not cross the C interface, they are reported as SR_FAILED.
+template<typename F>
+static send_result_ce checked(char const* call, F&& f) {
+    try {
+        f();
+        return SR_OK;
+    } catch (std::exception const& e) {
+        std::cerr << "FAILED to " << call << ": " << e.what() << "\n";
+        return SR_FAILED;
     }
-    return nullptr;
+}
+
+create_result_ce create_application(const char* name, application_t* app) {
+    assert(app);
+    std::shared_ptr<application> af;
+    auto result = application::create(name, "", af);
+    *app = result == CR_OK ? new std::shared_ptr<application>(af) : nullptr;
+    return result;
+}
+
+create_result_ce create_application_with_configuration(const char* name, const char* path, application_t* app) {
+    assert(app && path);
+    std::shared_ptr<application> af;
+    auto result = application::create(name, path, af);
+    *app = result == CR_OK ? new std::shared_ptr<application>(af) : nullptr;
+    return result;
 }
 
 void application_delete(application_t app) {
@@ -32,7 +55,18 @@ char const* application_get_name(application_t app) {
     return (*app)->name().c_str();
 }
 
+bool application_is_routing(application_t app) {
+    assert(app && *app);
+    return (*app)->is_routing();
+}
+
+client_id application_get_client(application_t app) {
+    assert(app && *app);
+    return (*app)->client();
+}
+
 struct message_header make_message_header(std::shared_ptr<vsomeip::message> const& msg) {
+    auto sec_client = msg->get_sec_client();
     struct message_header hdr {
             .service = msg->get_service(),
             .instance = msg->get_instance(),
@@ -43,14 +77,26 @@ struct message_header make_message_header(std::shared_ptr<vsomeip::message> cons
             .if_version = msg->get_interface_version(),
             .message_type = (message_type) msg->get_message_type(),
             .return_code = (return_code) msg->get_return_code(),
+            .length = msg->get_length(),
             .is_initial = msg->is_initial(),
             .is_reliable = msg->is_reliable(),
-            .data = msg->get_payload() ? msg->get_payload()->get_data() : nullptr,
-            .data_size = msg->get_length(),
+            .remote_address = ntohl(sec_client.host),
+            .remote_port = ntohs(sec_client.port),
     };
     return hdr;
 }
 
+static auto make_msg_callback(message_handler_t msg_handler, void const* object) {
+    return [msg_handler, object](std::shared_ptr<vsomeip::message> const& msg) {
+        // the payload is handed over by reference (shared_ptr), Rust releases it with payload_destroy
+        auto const& payload = msg->get_payload();
+        msg_handler(
+            make_message_header(msg),
+            payload && payload->get_length() > 0 ? new std::shared_ptr<vsomeip::payload>(payload) : nullptr,
+            object );
+    };
+}
+
 void application_register_handlers(
         application_t app,
         state_handler_t state_handler,
@@ -64,16 +110,113 @@ void application_register_handlers(
         );
     }
     if (msg_handler) {
-        (*app)->setup_msg_handler(
-                [msg_handler, object](std::shared_ptr<vsomeip::message> const& msg) {
-                    msg_handler(
-                        make_message_header(msg),
-                        new std::shared_ptr<vsomeip::payload>(msg->get_payload()),
-                        object );
-        });
+        (*app)->setup_msg_handler(make_msg_callback(msg_handler, object));
+    }
+}
+
+void application_register_message_handler(application_t app, service_id service, instance_id instance,
+                                          method_id method, message_handler_t msg_handler, void const* object)
+{
+    assert(app && *app);
+    assert(msg_handler);
+    (*app)->setup_msg_handler(service, instance, method, make_msg_callback(msg_handler, object));
+}
+
+static vsomeip::event_type_e from(event_type_ce event_type) {
+    switch(event_type) {
+        case ET_EVENT: return vsomeip::event_type_e::ET_EVENT;
+        case ET_SELECTIVE_EVENT: return vsomeip::event_type_e::ET_SELECTIVE_EVENT;
+        case ET_FIELD: return vsomeip::event_type_e::ET_FIELD;
+        default: {
+            std::cerr << "Invalid event type from Rust-FFI: 0x" << std::hex << (int)event_type << "\n";
+            exit(1);
+        }
+    }
+}
+
+static vsomeip::reliability_type_e from(reliability_type_ce reliability) {
+    switch(reliability) {
+        case RT_RELIABLE: return vsomeip::reliability_type_e::RT_RELIABLE;
+        case RT_UNRELIABLE: return vsomeip::reliability_type_e::RT_UNRELIABLE;
+        case RT_BOTH: return vsomeip::reliability_type_e::RT_BOTH;
+        case RT_UNKNOWN: return vsomeip::reliability_type_e::RT_UNKNOWN;
+        default: {
+            std::cerr << "Invalid reliability from Rust-FFI: 0x" << std::hex << (int)reliability << "\n";
+            exit(1);
+        }
     }
 }
 
+static vsomeip::routing_state_e from(routing_state_ce state) {
+    switch(state) {
+        case RS_RUNNING: return vsomeip::routing_state_e::RS_RUNNING;
+        case RS_SUSPENDED: return vsomeip::routing_state_e::RS_SUSPENDED;
+        case RS_RESUMED: return vsomeip::routing_state_e::RS_RESUMED;
+        case RS_SHUTDOWN: return vsomeip::routing_state_e::RS_SHUTDOWN;
+        case RS_DIAGNOSIS: return vsomeip::routing_state_e::RS_DIAGNOSIS;
+        case RS_DELAYED_RESUME: return vsomeip::routing_state_e::RS_DELAYED_RESUME;
+        case RS_UNKNOWN: return vsomeip::routing_state_e::RS_UNKNOWN;
+        default: {
+            std::cerr << "Invalid routing_state from Rust-FFI: 0x" << std::hex << (int)state << "\n";
+            exit(1);
+        }
+    }
+}
+
+static routing_state_ce to(vsomeip::routing_state_e state) {
+    switch(state) {
+        case vsomeip::routing_state_e::RS_RUNNING: return RS_RUNNING;
+        case vsomeip::routing_state_e::RS_SUSPENDED: return RS_SUSPENDED;
+        case vsomeip::routing_state_e::RS_RESUMED: return RS_RESUMED;
+        case vsomeip::routing_state_e::RS_SHUTDOWN: return RS_SHUTDOWN;
+        case vsomeip::routing_state_e::RS_DIAGNOSIS: return RS_DIAGNOSIS;
+        case vsomeip::routing_state_e::RS_DELAYED_RESUME: return RS_DELAYED_RESUME;
+        default: return RS_UNKNOWN;
+    }
+}
+
+void application_register_routing_state_handler(application_t app, routing_state_handler_t handler,
+                                                 void const* object)
+{
+    assert(app && *app);
+    assert(handler);
+    (*app)->setup_routing_state_handler(
+        [handler, object](vsomeip::routing_state_e state) { handler(to(state), object); }
+    );
+}
+
+void application_set_routing_state(application_t app, routing_state_ce state) {
+    assert(app && *app);
+    (*app)->set_routing_state(from(state));
+}
+
+routing_state_ce application_get_routing_state(application_t app) {
+    assert(app && *app);
+    return to((*app)->routing_state());
+}
+
+bool application_is_routing_ready(application_t app) {
+    assert(app && *app);
+    return (*app)->is_routing_ready();
+}
+
+void application_unregister_message_handler(application_t app, service_id service, instance_id instance,
+                                            method_id method)
+{
+    assert(app && *app);
+    (*app)->clear_msg_handler(service, instance, method);
+}
+
+void application_clear_all_handlers(application_t app) {
+    assert(app && *app);
+    (*app)->clear_all_handlers();
+}
+
+void application_unregister_main_handlers(application_t app) {
+    assert(app && *app);
+    (*app)->clear_main_handlers();
+}
+
 payload_t application_payload_create(application_t app, uint8_t const* data, uint32_t size) {
     assert(app && *app);
     auto pl = (*app)->create_payload(data, size);
@@ -128,10 +271,9 @@ static vsomeip::return_code_e from(return_code rt) {
         case E_MALFORMED_MESSAGE: return vsomeip::return_code_e::E_MALFORMED_MESSAGE;
         case E_WRONG_MESSAGE_TYPE: return vsomeip::return_code_e::E_WRONG_MESSAGE_TYPE;
         case E_UNKNOWN: return vsomeip::return_code_e::E_UNKNOWN;
-        default: {
-            std::cerr << "Invalid return_code from Rust-FFI: 0x" << std::hex << (int)rt << "\n";
-            exit(1);
-        }
+        default:
+            // service/method specific and reserved return codes are passed through
+            return static_cast<vsomeip::return_code_e>(rt);
     }
 }
 
@@ -175,9 +317,15 @@ void application_request_service(application_t app,
                                  void const* object)
 {
     assert(app && *app);
+    // the handler is owned by the vsomeip application which is owned by *app
+    auto* a = app->get();
     (*app)->setup_avail_handler(service, instance, major,
-        [avail_handler, object](vsomeip::service_t svc, vsomeip::instance_t inst, bool avail) {
-            avail_handler(svc, inst, avail ? AS_AVAILABLE : AS_UNAVAILABLE, object);}
+        [a, avail_handler, object, major, minor](vsomeip::service_t svc, vsomeip::instance_t inst, bool avail) {
+            auto changed = a->changed_version(svc, inst, major, avail);
+            if (changed.first == vsomeip::ANY_MAJOR) {
+                changed = {major, minor};
+            }
+            avail_handler(svc, inst, avail ? AS_AVAILABLE : AS_UNAVAILABLE, changed.first, changed.second, object);}
     );
     (*app)->request_service(service, instance, major, minor);
 }
@@ -188,11 +336,18 @@ void application_release_service(application_t app, service_id service, instance
     (*app)->release_service(service, instance);
 }
 
-void application_offer_service(application_t app, service_id service, instance_id instance,
-                               major_version major, minor_version  minor)
+bool application_is_available(application_t app, service_id service, instance_id instance,
+                              major_version major, minor_version minor)
 {
     assert(app && *app);
-    (*app)->offer_service(service, instance, major, minor);
+    return (*app)->is_available(service, instance, major, minor);
+}
+
+send_result_ce application_offer_service(application_t app, service_id service, instance_id instance,
+                                         major_version major, minor_version  minor)
+{
+    assert(app && *app);
+    return checked("offer service", [&] { (*app)->offer_service(service, instance, major, minor); });
 }
 
 void application_stop_offer_service(application_t app, service_id  service, instance_id instance,
@@ -202,9 +357,11 @@ void application_stop_offer_service(application_t app, service_id  service, inst
     (*app)->stop_offer_service(service, instance, major, minor);
 }
 
-void application_offer_event(application_t app, service_id service, instance_id instance, notifier_id notifier,
-                             eventgroup_id const* event_groups, uint32_t event_groups_size, bool is_field,
-                             uint32_t cycle, bool change_resets_cycle, bool update_on_change)
+send_result_ce application_offer_event(application_t app, service_id service, instance_id instance,
+                                       notifier_id notifier, eventgroup_id const* event_groups,
+                                       uint32_t event_groups_size, event_type_ce event_type, uint32_t cycle,
+                                       bool change_resets_cycle, bool update_on_change,
+                                       reliability_type_ce reliability)
 {
     assert(app && *app);
     assert(event_groups != nullptr);
@@ -212,9 +369,33 @@ void application_offer_event(application_t app, service_id service, instance_id
     for(int i = 0; i < event_groups_size; ++i) {
         event_groups_set.emplace(event_groups[i]);
     }
-    (*app)->offer_event(service, instance, notifier, event_groups_set,
-                        is_field ? vsomeip::event_type_e::ET_FIELD : vsomeip::event_type_e::ET_EVENT,
-                        std::chrono::milliseconds(cycle),change_resets_cycle, update_on_change);
+    return checked("offer event", [&] {
+        (*app)->offer_event(service, instance, notifier, event_groups_set, from(event_type),
+                            std::chrono::milliseconds(cycle),change_resets_cycle, update_on_change, nullptr,
+                            from(reliability));
+    });
+}
+
+send_result_ce application_offer_service_with_events(application_t app, service_id service, instance_id instance,
+                                                     major_version major, minor_version minor,
+                                                     struct event_descriptor const* events, uint32_t events_size)
+{
+    assert(app && *app);
+    assert(events != nullptr || events_size == 0);
+    return checked("offer service with events", [&] {
+        // events first, so they are known when the service offer is announced
+        for(uint32_t i = 0; i < events_size; ++i) {
+            auto const& event = events[i];
+            assert(event.event_groups != nullptr);
+            std::set<vsomeip::eventgroup_t> event_groups_set{event.event_groups,
+                                                             event.event_groups + event.event_groups_size};
+            (*app)->offer_event(service, instance, event.notifier, event_groups_set,
+                                from(event.event_type),
+                                std::chrono::milliseconds(event.cycle), event.change_resets_cycle,
+                                event.update_on_change, nullptr, from(event.reliability));
+        }
+        (*app)->offer_service(service, instance, major, minor);
+    });
 }
 
 void application_stop_offer_event(application_t app, service_id service, instance_id instance, notifier_id notifier)
@@ -224,7 +405,8 @@ void application_stop_offer_event(application_t app, service_id service, instanc
 }
 
 void application_request_event(application_t app, service_id service, instance_id instance, notifier_id notifier,
-                               eventgroup_id const* event_groups, uint32_t event_groups_size, bool is_field)
+                               eventgroup_id const* event_groups, uint32_t event_groups_size,
+                               event_type_ce event_type, reliability_type_ce reliability)
 {
     assert(app && *app);
     assert(event_groups != nullptr);
@@ -232,8 +414,7 @@ void application_request_event(application_t app, service_id service, instance_i
     for(int i = 0; i < event_groups_size; ++i) {
         event_groups_set.emplace(event_groups[i]);
     }
-    (*app)->request_event(service, instance, notifier, event_groups_set,
-                          is_field ? vsomeip::event_type_e::ET_FIELD : vsomeip::event_type_e::ET_EVENT);
+    (*app)->request_event(service, instance, notifier, event_groups_set, from(event_type), from(reliability));
 }
 
 void application_release_event(application_t app, service_id service, instance_id instance, notifier_id notifier)
@@ -242,11 +423,11 @@ void application_release_event(application_t app, service_id service, instance_i
     (*app)->release_event(service, instance, notifier);
 }
 
-void application_subscribe_event(application_t app, service_id service, instance_id instance, eventgroup_id eg,
-                                 notifier_id event, major_version version)
+send_result_ce application_subscribe_event(application_t app, service_id service, instance_id instance,
+                                           eventgroup_id eg, notifier_id event, major_version version)
 {
     assert(app && *app);
-    (*app)->subscribe(service, instance, eg, version, event);
+    return checked("subscribe", [&] { (*app)->subscribe(service, instance, eg, version, event); });
 }
 
 void application_unsubscribe_event(application_t app, service_id service, instance_id instance, eventgroup_id eg)
@@ -255,33 +436,128 @@ void application_unsubscribe_event(application_t app, service_id service, instan
     (*app)->unsubscribe(service, instance, eg);
 }
 
-void application_notify(application_t app, service_id service, instance_id instance, notifier_id notifier,
-                                   bool force_send, uint8_t const* data, uint32_t data_len)
+void application_unsubscribe_single_event(application_t app, service_id service, instance_id instance,
+                                          eventgroup_id eg, notifier_id event)
+{
+    assert(app && *app);
+    (*app)->unsubscribe(service, instance, eg, event);
+}
+
+void application_register_subscription_status_handler(application_t app, service_id service, instance_id instance,
+                                                      eventgroup_id eg, notifier_id event,
+                                                      subscription_status_handler_t handler, void const* object)
+{
+    assert(app && *app);
+    (*app)->setup_subscription_status_handler(service, instance, eg, event,
+        [handler, object](vsomeip::service_t svc, vsomeip::instance_t inst, vsomeip::eventgroup_t group,
+                          vsomeip::event_t evt, uint16_t error) {
+            handler(svc, inst, group, evt, error, object);
+        });
+}
+
+void application_unregister_subscription_status_handler(application_t app, service_id service, instance_id instance,
+                                                        eventgroup_id eg, notifier_id event)
+{
+    assert(app && *app);
+    (*app)->clear_subscription_status_handler(service, instance, eg, event);
+}
+
+void application_register_subscription_handler(application_t app, service_id service, instance_id instance,
+                                               eventgroup_id eg, subscription_handler_t handler, void const* object)
+{
+    assert(app && *app);
+    (*app)->setup_subscription_handler(service, instance, eg,
+        [handler, object, service, instance, eg](vsomeip::client_t client, auto const* /* sec_client */,
+                                                 std::string const& /* env */, bool subscribed) {
+            return handler(service, instance, eg, client, subscribed, object);
+        });
+}
+
+void application_unregister_subscription_handler(application_t app, service_id service, instance_id instance,
+                                                 eventgroup_id eg)
+{
+    assert(app && *app);
+    (*app)->clear_subscription_handler(service, instance, eg);
+}
+
+send_result_ce application_notify(application_t app, service_id service, instance_id instance, notifier_id notifier,
+                                  bool force_send, uint8_t const* data, uint32_t data_len)
+{
+    assert(app && *app);
+    return checked("notify", [&] { (*app)->notify(service, instance, notifier, force_send, data, data_len); });
+}
+
+send_result_ce application_notify_one(application_t app, service_id service, instance_id instance,
+                                      notifier_id notifier, client_id client, bool force_send,
+                                      uint8_t const* data, uint32_t data_len)
+{
+    assert(app && *app);
+    return checked("notify one", [&] {
+        (*app)->notify_one(service, instance, notifier, client, force_send, data, data_len);
+    });
+}
+
+send_result_ce application_fast_notify(application_t app, service_id service, instance_id instance,
+                                       notifier_id notifier, bool force_send, uint8_t const* data, uint32_t data_len)
+{
+    assert(app && *app);
+    return checked("fast notify", [&] { (*app)->fast_notify(service, instance, notifier, force_send, data, data_len); });
+}
+
+send_result_ce application_notify_batch(application_t app, service_id service, instance_id instance, bool force_send,
+                                        struct notification const* notifications, uint32_t notifications_size,
+                                        uint32_t* sent)
+{
+    assert(app && *app);
+    assert(notifications != nullptr || notifications_size == 0);
+    assert(sent);
+    *sent = 0;
+    return checked("notify batch", [&] {
+        for(; *sent < notifications_size; ++*sent) {
+            auto const& notification = notifications[*sent];
+            (*app)->notify(service, instance, notification.notifier, force_send, notification.data,
+                           notification.data_len);
+        }
+    });
+}
+
+send_result_ce application_send_request(application_t app, service_id service, instance_id instance,
+                                        method_id method, major_version major, bool reliable,
+                                        uint8_t const* data, uint32_t data_len, session_id* session)
 {
     assert(app && *app);
-    (*app)->notify(service, instance, notifier, force_send, data, data_len);
+    assert(session);
+    return checked("send request", [&] {
+        *session = (*app)->send_request(service, instance, method, major, data, data_len, reliable);
+    });
 }
 
-session_id application_send_request(application_t app, service_id service, instance_id instance, method_id method,
-                              major_version major, bool reliable, uint8_t const* data, uint32_t data_len)
+send_result_ce application_send_response(application_t app, service_id service, instance_id instance,
+                                         method_id method, client_id client, session_id session, major_version major,
+                                         bool reliable, enum return_code rc, uint8_t const* data, uint32_t data_len)
 {
     assert(app && *app);
-    return (*app)->send_request(service, instance, method, major, data, data_len, reliable);
+    return checked("send response", [&] {
+        (*app)->send_response(service, instance, method, client, session, major, reliable,from(rc), data, data_len);
+    });
 }
 
-void application_send_response(application_t app, service_id service, instance_id instance, method_id method,
-                               client_id client, session_id session, major_version major, bool reliable,
-                               enum return_code rc, uint8_t const* data, uint32_t data_len)
+send_result_ce application_send_error(application_t app, service_id service, instance_id instance, method_id method,
+                                      client_id client, session_id session, major_version major, bool reliable,
+                                      enum return_code rc)
 {
     assert(app && *app);
-    (*app)->send_response(service, instance, method, client, session, major, reliable,from(rc), data, data_len);
+    return checked("send error", [&] {
+        (*app)->send_error(service, instance, method, client, session, major, reliable, from(rc));
+    });
 }
 
-void application_send_error(application_t app, service_id service, instance_id instance, method_id method,
-                            client_id client, session_id session, major_version major, bool reliable, enum return_code rc)
+send_result_ce application_send_message(application_t app, struct message_header const* header,
+                                        uint8_t const* data, uint32_t data_len)
 {
     assert(app && *app);
-    (*app)->send_error(service, instance, method, client, session, major, reliable, from(rc));
+    assert(header);
+    return checked("send message", [&] { (*app)->send_message(*header, data, data_len); });
 }
 
 PayloadInfo payload_get_info(payload_t pl) {
@@ -13,6 +13,42 @@ enum state_type_ce {
     REGISTERED = 1,
 };
 
+enum routing_state_ce {
+    RS_RUNNING = 0x00,
+    RS_SUSPENDED = 0x01,
+    RS_RESUMED = 0x02,
+    RS_SHUTDOWN = 0x03,
+    RS_DIAGNOSIS = 0x04,
+    RS_DELAYED_RESUME = 0x05,
+    RS_UNKNOWN = 0xFF,
+};
+
+enum reliability_type_ce {
+    RT_RELIABLE = 0x01,
+    RT_UNRELIABLE = 0x02,
+    RT_BOTH = 0x03,
+    RT_UNKNOWN = 0xFF,
+};
+
+enum event_type_ce {
+    ET_EVENT = 0x00,
+    ET_SELECTIVE_EVENT = 0x01,
+    ET_FIELD = 0x02,
+};
+
+enum create_result_ce {
+    CR_OK = 0,
+    CR_NO_RUNTIME = 1,
+    CR_CREATE_FAILED = 2,
+    CR_INIT_FAILED = 3,
+    CR_START_FAILED = 4,
+};
+
+enum send_result_ce {
+    SR_OK = 0,
+    SR_FAILED = 1,
+};
+
 enum availability_state_e {
     AS_UNAVAILABLE = 0,
     AS_AVAILABLE = 1,
@@ -98,7 +134,14 @@ extern "C" {
 #endif
 
     typedef void (*state_handler_t)(enum state_type_ce state, void const* target);
-    typedef void (*availability_handler_t)(service_id svc_id, instance_id inst_id, enum availability_state_e avail, void const* target);
+    typedef void (*routing_state_handler_t)(enum routing_state_ce state, void const* target);
+    typedef void (*availability_handler_t)(service_id svc_id, instance_id inst_id, enum availability_state_e avail,
+                                           major_version major, minor_version minor, void const* target);
+    typedef void (*subscription_status_handler_t)(service_id svc_id, instance_id inst_id, eventgroup_id eg,
+                                                  notifier_id event, uint16_t error, void const* target);
+    // returns whether the subscription of the client is accepted, the result is ignored for unsubscriptions
+    typedef bool (*subscription_handler_t)(service_id svc_id, instance_id inst_id, eventgroup_id eg, client_id client,
+                                           bool subscribed, void const* target);
 
     struct message_header {
         service_id service;
@@ -110,58 +153,128 @@ extern "C" {
         interface_version if_version;
         enum message_type message_type;
         enum return_code return_code;
+        // SOME/IP length field (payload length + 8), ignored when sending
+        uint32_t length;
         bool is_initial;
         bool is_reliable;
-        uint8_t const* data;
-        uint32_t data_size;
+        // sender endpoint (host byte order), IPv4 only as vsomeip's security client carries no IPv6
+        // address. Loopback for local applications via TCP, unset if vsomeip reports no address.
+        uint32_t remote_address;
+        uint16_t remote_port;
     };
 
     typedef void (*message_handler_t)(struct message_header header, payload_t payload, void const* target);
 
     // application handling
-    application_t create_application(const char* name);
+    enum create_result_ce create_application(const char* name, application_t* app);
+    // like create_application but reads the configuration file (or directory) `path`
+    enum create_result_ce create_application_with_configuration(const char* name, const char* path,
+                                                                application_t* app);
     void application_register_handlers(application_t app,
                                        state_handler_t state_handler,
                                        message_handler_t msg_handler,
                                        void const* object);
+    // once registering another handler for the combination, unregistering it or clearing all handlers
+    // returned, the dedicated handler is not called anymore and its `object` may be released
+    void application_register_message_handler(application_t app, service_id service, instance_id instance,
+                                              method_id method, message_handler_t msg_handler, void const* object);
+    void application_register_routing_state_handler(application_t app, routing_state_handler_t handler,
+                                                    void const* object);
+    void application_set_routing_state(application_t app, enum routing_state_ce state);
+    enum routing_state_ce application_get_routing_state(application_t app);
+    // whether vsomeip reported the routing to be ready since the application was created
+    bool application_is_routing_ready(application_t app);
+    void application_unregister_message_handler(application_t app, service_id service, instance_id instance,
+                                                method_id method);
+    void application_clear_all_handlers(application_t app);
+    // unregisters the handlers of application_register_handlers and application_register_routing_state_handler
+    void application_unregister_main_handlers(application_t app);
     void application_delete(application_t app);
     char const* application_get_name(application_t app);
-
-    session_id send_request(application_t app, uint8_t const* data, uint32_t data_len);
-
+    bool application_is_routing(application_t app);
+    client_id application_get_client(application_t app);
 
     void application_request_service(application_t app, service_id service, instance_id instance,
                                      major_version major, minor_version minor,
                                      availability_handler_t avail_handler, void const* object);
     void application_release_service(application_t app, service_id service, instance_id instance, major_version major);
-    void application_offer_service(application_t app, service_id service, instance_id instance,
-                                   major_version major, minor_version  minor);
+    bool application_is_available(application_t app, service_id service, instance_id instance,
+                                  major_version major, minor_version minor);
+    enum send_result_ce application_offer_service(application_t app, service_id service, instance_id instance,
+                                                  major_version major, minor_version  minor);
     void application_stop_offer_service(application_t app, service_id  service, instance_id instance,
                                         major_version major, minor_version minor);
-    void application_offer_event(application_t app, service_id service, instance_id instance, notifier_id notifier,
-            eventgroup_id const* event_groups, uint32_t event_groups_size, bool is_field,
-            uint32_t cycle, bool change_resets_cycle, bool update_on_change);
+    enum send_result_ce application_offer_event(application_t app, service_id service, instance_id instance,
+            notifier_id notifier, eventgroup_id const* event_groups, uint32_t event_groups_size,
+            enum event_type_ce event_type,
+            uint32_t cycle, bool change_resets_cycle, bool update_on_change, enum reliability_type_ce reliability);
     void application_stop_offer_event(application_t app, service_id service, instance_id instance, notifier_id notifier);
+
+    struct event_descriptor {
+        notifier_id notifier;
+        eventgroup_id const* event_groups;
+        uint32_t event_groups_size;
+        enum event_type_ce event_type;
+        uint32_t cycle;
+        bool change_resets_cycle;
+        bool update_on_change;
+        enum reliability_type_ce reliability;
+    };
+
+    enum send_result_ce application_offer_service_with_events(application_t app, service_id service,
+            instance_id instance, major_version major, minor_version minor,
+            struct event_descriptor const* events, uint32_t events_size);
     void application_request_event(application_t app, service_id service, instance_id instance, notifier_id notifier,
-                                   eventgroup_id const* event_groups, uint32_t event_groups_size, bool is_field);
+                                   eventgroup_id const* event_groups, uint32_t event_groups_size,
+                                   enum event_type_ce event_type, enum reliability_type_ce reliability);
     void application_release_event(application_t app, service_id service, instance_id instance, notifier_id notifier);
-    void application_subscribe_event(application_t app, service_id service, instance_id instance, eventgroup_id eg,
-                                     notifier_id event, major_version version);
+    enum send_result_ce application_subscribe_event(application_t app, service_id service, instance_id instance,
+                                                    eventgroup_id eg, notifier_id event, major_version version);
     void application_unsubscribe_event(application_t app, service_id service, instance_id instance, eventgroup_id eg);
+    void application_unsubscribe_single_event(application_t app, service_id service, instance_id instance,
+                                              eventgroup_id eg, notifier_id event);
+    void application_register_subscription_status_handler(application_t app, service_id service, instance_id instance,
+                                                          eventgroup_id eg, notifier_id event,
+                                                          subscription_status_handler_t handler, void const* object);
+    void application_unregister_subscription_status_handler(application_t app, service_id service,
+                                                            instance_id instance, eventgroup_id eg, notifier_id event);
+    void application_register_subscription_handler(application_t app, service_id service, instance_id instance,
+                                                   eventgroup_id eg, subscription_handler_t handler,
+                                                   void const* object);
+    void application_unregister_subscription_handler(application_t app, service_id service, instance_id instance,
+                                                     eventgroup_id eg);
 
     //    void subscribe_with_debounce(vsomeip::service_t service, vsomeip::instance_t instance,
     //                                 vsomeip::eventgroup_t event_group, vsomeip::major_version_t major,
     //                                 vsomeip::event_t event, vsomeip::debounce_filter_t const& filter);
 
-    void application_notify(application_t app, service_id service, instance_id instance, notifier_id notifier,
-                            bool force_send, uint8_t const* data, uint32_t data_len);
-    session_id application_send_request(application_t app, service_id service, instance_id instance, method_id method,
-                            major_version major, bool reliable, uint8_t const* data, uint32_t data_len);
-    void application_send_response(application_t app, service_id service, instance_id instance, method_id method,
-                                   client_id client, session_id session, major_version major, bool reliable,
-                                   enum return_code rc, uint8_t const* data, uint32_t data_len);
-    void application_send_error(application_t app, service_id service, instance_id instance, method_id method,
-                                client_id client, session_id session, major_version major, bool reliable,  enum return_code rc);
+    enum send_result_ce application_notify(application_t app, service_id service, instance_id instance,
+            notifier_id notifier, bool force_send, uint8_t const* data, uint32_t data_len);
+    enum send_result_ce application_notify_one(application_t app, service_id service, instance_id instance,
+            notifier_id notifier, client_id client, bool force_send, uint8_t const* data, uint32_t data_len);
+    enum send_result_ce application_fast_notify(application_t app, service_id service, instance_id instance,
+            notifier_id notifier, bool force_send, uint8_t const* data, uint32_t data_len);
+
+    struct notification {
+        notifier_id notifier;
+        uint8_t const* data;
+        uint32_t data_len;
+    };
+
+    // `sent` is set to the number of notifications passed to vsomeip, also if it fails on one of them
+    enum send_result_ce application_notify_batch(application_t app, service_id service, instance_id instance,
+            bool force_send, struct notification const* notifications, uint32_t notifications_size, uint32_t* sent);
+    enum send_result_ce application_send_request(application_t app, service_id service, instance_id instance,
+            method_id method, major_version major, bool reliable, uint8_t const* data, uint32_t data_len,
+            session_id* session);
+    enum send_result_ce application_send_response(application_t app, service_id service, instance_id instance,
+            method_id method, client_id client, session_id session, major_version major, bool reliable,
+            enum return_code rc, uint8_t const* data, uint32_t data_len);
+    enum send_result_ce application_send_error(application_t app, service_id service, instance_id instance,
+            method_id method, client_id client, session_id session, major_version major, bool reliable,
+            enum return_code rc);
+    enum send_result_ce application_send_message(application_t app, struct message_header const* header,
+            uint8_t const* data, uint32_t data_len);
 
 
 // payload handling